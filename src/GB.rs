@@ -1,27 +1,35 @@
-pub mod cpu;
-pub mod ppu;
-pub mod apu;
-pub mod cartridge;
-pub mod joypad;
-pub mod memory;
-pub mod bus;
-pub mod types;
-pub mod traits;
-pub mod utils;
-mod timer;
-mod interrupt;
-pub mod dma;
-pub mod addresses;
+pub(crate) mod cpu;
+pub(crate) mod ppu;
+pub(crate) mod apu;
+pub(crate) mod cartridge;
+pub(crate) mod joypad;
+pub(crate) mod memory;
+pub(crate) mod bus;
+pub(crate) mod types;
+pub(crate) mod traits;
+pub(crate) mod utils;
+pub(crate) mod timer;
+pub(crate) mod serial;
+pub(crate) mod dma;
+#[allow(dead_code)] // Full memory map, not every address is referenced
+pub(crate) mod addresses;
+pub(crate) mod BIOS;
+pub(crate) mod model;
+pub(crate) mod save_state;
+pub(crate) mod rewind;
+pub(crate) mod disasm;
+pub(crate) mod asm;
 
 use crate::GB::cartridge::addresses as cartridge_addresses;
 use crate::GB::joypad::{JoypadButton, JoypadButtonsBits, JoypadDPadBits};
-use crate::GB::bus::{MmioContextRead, MmioContextWrite};
+use crate::GB::bus::{BusDevice, MmioContextRead, MmioContextWrite};
 use traits::Tick;
 use crate::GB::cpu::registers::interrupt_registers::InterruptFlagsMask;
-use crate::GB::memory::vram::VRAM;
+use crate::GB::disasm::DisassembledInstruction;
 use crate::GB::ppu::lcd::LCD;
 use crate::GB::ppu::PPU;
 use crate::GB::ppu::tile::GbColor;
+use crate::GB::save_state::{SaveState, SaveStateError, StateReader, StateWriter};
 use crate::GB::types::address::Address;
 use crate::GB::types::Byte;
 
@@ -33,24 +41,24 @@ fn debug_print(args: std::fmt::Arguments) {
 
 #[cfg(not(feature = "debug"))]
 #[inline]
+#[allow(dead_code)]
 fn debug_print(_args: std::fmt::Arguments) {
     // Do nothing
 }
 
-pub const SYSTEM_FREQUENCY_CLOCK: u64 = 1_048_576;
-pub const CYCLES_PER_FRAME: u64 = SYSTEM_FREQUENCY_CLOCK / 60;
-pub const FRAME_TIME: f64 = 1_f64 / 60_f64;
-
 macro_rules! gb_bus_ctx_mut {
     ($gb:ident) => {
         MmioContextWrite {
             cpu_mmio: &mut $gb.cpu_ctx.mmio,
+            bios_mmio: &mut $gb.bios,
             rom_mmio: &mut $gb.cartridge,
             ppu_mmio: &mut $gb.ppu_ctx.mmio,
             apu_mmio: &mut $gb.apu_ctx.mmio,
             dma_mmio: &mut $gb.dma_ctx.mmio,
             oam_mmio: &mut $gb.oam_memory,
             wram_mmio: &mut $gb.wram,
+            timer_mmio: &mut $gb.timer_ctx.mmio,
+            serial_mmio: &mut $gb.serial_ctx.mmio,
             joypad: &mut $gb.joypad,
         }
     };
@@ -60,53 +68,73 @@ macro_rules! gb_bus_ctx {
     ($gb:ident) => {
         MmioContextRead {
             cpu_mmio: &$gb.cpu_ctx.mmio,
+            bios_mmio: &$gb.bios,
             rom_mmio: &$gb.cartridge,
             ppu_mmio: &$gb.ppu_ctx.mmio,
             apu_mmio: &$gb.apu_ctx.mmio,
             dma_mmio: &$gb.dma_ctx.mmio,
             oam_mmio: &$gb.oam_memory,
             wram_mmio: &$gb.wram,
+            timer_mmio: &$gb.timer_ctx.mmio,
+            serial_mmio: &$gb.serial_ctx.mmio,
             joypad: &$gb.joypad,
         }
     };
 }
 
+/// What happened while running the system with `run_frame`, `run_cycles` or `step_instruction`
+#[derive(Debug, Clone, Default)]
+pub struct RunSummary {
+    pub cycles: u64, // T-Cycles executed
+    pub frame_completed: bool, // PPU entered VBlank
+    pub instructions: u64, // Instructions retired, interrupt dispatches excluded
+    pub interrupts: Vec<cpu::InterruptType>, // Interrupts dispatched, in order
+    pub last_instruction: Option<cpu::RetiredInstruction>, // Last instruction (or interrupt dispatch) retired, with its PC
+    pub lockup: Option<cpu::CpuLockup>, // CPU is hung on an illegal opcode
+    pub autosave_error: Option<std::io::ErrorKind>, // Cartridge RAM autosave failed, it is retried at the next period
+}
+
 // #[derive()]
 pub struct GB {
-    is_booting: bool,
     bus: bus::Bus,
-    pub wram: memory::wram::WRAM,
-    pub oam_memory: memory::oam_memory::OamMemory,
-    // pub bios: BIOS, // todo!("Add Bios")
+    wram: memory::wram::WRAM,
+    oam_memory: memory::oam_memory::OamMemory,
+    bios: BIOS::BIOS,
+    model: model::GbModel,
     cpu_ctx: cpu::CpuCtx,
     ppu_ctx: ppu::PpuCtx,
     dma_ctx: dma::DmaCtx,
     apu_ctx: apu::ApuCtx,
+    timer_ctx: timer::TimerCtx,
+    serial_ctx: serial::SerialCtx,
     joypad: joypad::Joypad,
     cartridge: Option<cartridge::Cartridge>,
+    rumble: bool, // Last rumble motor status notified to the host
+    rumble_callback: Option<Box<dyn FnMut(bool)>>,
     cycles: u64, // Number to cycle needed to complete current CPU instruction. cpu.cycle() is skipped if different from 0
     cycles_overflows: u64, // Number of time cycles has overflowed
+    autosave_due: bool, // Autosave period elapsed, cartridge RAM is flushed at the end of the current run
 }
 
 impl GB {
     pub const SYSTEM_FREQUENCY_CLOCK: u32 = 4_194_304;
+    /// T-Cycles between checks for unsaved cartridge RAM (5 seconds of emulated time)
+    pub const AUTOSAVE_PERIOD: u64 = 5 * Self::SYSTEM_FREQUENCY_CLOCK as u64;
+    pub const SCREEN_WIDTH: usize = PPU::SCREEN_COLUMNS as usize;
+    pub const SCREEN_HEIGHT: usize = PPU::SCREEN_LINES as usize;
+    pub const DEFAULT_AUDIO_SAMPLE_RATE: u32 = apu::APU::DEFAULT_SAMPLE_RATE;
+    /// T-Cycles of a whole frame: 154 lines of 456 dots
+    pub const FRAME_T_CYCLES: u64 = 154 * 456;
+    pub const SAVE_STATE_MAGIC: &'static [Byte; 4] = b"YAES";
+    pub const SAVE_STATE_VERSION: u16 = 4;
 
-    pub fn new(bios: Option<String>) -> Self {
-        // Todo!("Add Bios")
-        // let mut rom = BIOS::new();
-        let mut is_booting = false;
-
-        // match bios {
-        //     None => {}
-        //     Some(bios) => {
-        //         rom.load_bios(&bios);
-        //         is_booting = true;
-        //     }
-        // }
-
-        Self {
-            is_booting,
+    /// Build a new GB. If a BIOS is given the system boots from it starting at PC $0000,
+    /// otherwise it starts from the cartridge entry point. Fails if the given BIOS can't be loaded.
+    pub fn new(bios: Option<String>) -> Result<Self, std::io::Error> {
+        let mut gb = Self {
             bus: bus::Bus::new(),
+            bios: BIOS::BIOS::new(),
+            model: model::GbModel::default(),
             cpu_ctx: cpu::CpuCtx {
                 cpu: cpu::CPU::new(),
                 mmio: cpu::cpu_mmio::CpuMmio::new()
@@ -127,10 +155,30 @@ impl GB {
             oam_memory: memory::oam_memory::OamMemory::new(),
             wram: memory::wram::WRAM::new(),
             cartridge: None,
+            timer_ctx: timer::TimerCtx {
+                timer: timer::Timer::new(),
+                mmio: timer::TimerRegisters::new(),
+            },
+            serial_ctx: serial::SerialCtx {
+                serial: serial::Serial::new(),
+                mmio: serial::serial_mmio::SerialMmio::new(),
+            },
             joypad: joypad::Joypad::new(),
+            rumble: false,
+            rumble_callback: None,
             cycles: 0,
             cycles_overflows: 0,
+            autosave_due: false,
+        };
+
+        if let Some(bios) = bios {
+            gb.load_bios(&bios)?;
+            gb.boot();
+        }
+        if !gb.is_booting() {
+            gb.apply_post_boot_state();
         }
+        Ok(gb)
     }
 
     // fn with_bus<T>(&mut self, f: impl FnOnce(&mut MmioContext) -> T) -> T {
@@ -143,16 +191,73 @@ impl GB {
     //     f(&mut ctx)
     // }
 
+    pub fn load_bios(&mut self, path: &String) -> Result<(), std::io::Error> {
+        self.bios.load_bios(path)
+    }
+
+    /// Map the boot ROM over $0000-$00FF and restart from it. Does nothing if no BIOS has been loaded.
     pub fn boot(&mut self) {
-        // self.is_booting = true;
-        // self.memory.borrow_mut().boot_load(&self.bios);
-        // self.cpu.registers.set_pc(0);
+        if self.bios.is_loaded() {
+            self.bios.set_mapped(true);
+            self.cpu_ctx.cpu.registers.set_pc(BIOS::BIOS::BIOS_START_ADDRESS.as_u16());
+        }
+    }
+
+    pub fn is_booting(&self) -> bool {
+        self.bios.is_mapped()
     }
 
+    pub fn model(&self) -> model::GbModel {
+        self.model
+    }
+
+    /// Change the emulated model. Post-boot state is applied again if boot ROM is not running.
+    pub fn set_model(&mut self, model: model::GbModel) {
+        self.model = model;
+        if !self.is_booting() {
+            self.apply_post_boot_state();
+        }
+    }
+
+    /// Set registers and I/O as the boot ROM of the current model would have left them
+    fn apply_post_boot_state(&mut self) {
+        let state = self.model.post_boot_state();
+        let header_checksum = self.cartridge.as_ref().map(|c| c.header().rom_checksum());
+
+        let registers = &mut self.cpu_ctx.cpu.registers;
+        registers.set_a(state.a);
+        registers.set_f(state.f(header_checksum));
+        registers.set_b(state.b);
+        registers.set_c(state.c);
+        registers.set_d(state.d);
+        registers.set_e(state.e);
+        registers.set_h(state.h);
+        registers.set_l(state.l);
+        registers.set_sp(state.sp);
+        registers.set_pc(cartridge_addresses::ENTRY_POINT as u16);
+
+        self.timer_ctx.mmio.set_div_counter(state.div_counter);
+
+        let ppu_mmio = &mut self.ppu_ctx.mmio;
+        ppu_mmio.write(ppu::ppu_mmio::PpuMmio::LCDC_ADDRESS, state.lcdc);
+        ppu_mmio.set_stat(state.stat);
+        ppu_mmio.write(ppu::ppu_mmio::PpuMmio::BGP_ADDRESS, state.bgp);
+        ppu_mmio.write(ppu::ppu_mmio::PpuMmio::OBP0_ADDRESS, state.obp0);
+        ppu_mmio.write(ppu::ppu_mmio::PpuMmio::OBP1_ADDRESS, state.obp1);
+
+        let apu_start = apu::apu_mmio::ApuMmio::APU_REGISTERS_RANGE.start().as_u16();
+        for (i, value) in state.apu.iter().enumerate() {
+            self.apu_ctx.mmio.set_register(Address(apu_start + i as u16), *value);
+        }
+    }
+
+    /// Insert the cartridge, loading its battery save if present. The previous cartridge is ejected (and saved) first.
     pub fn insert_cartridge(&mut self, path: &String) -> Result<(), std::io::Error> {
+        self.eject_cartridge()?;
         let cartridge = cartridge::Cartridge::new((*path).clone());
         match cartridge {
-            Ok(c) => {
+            Ok(mut c) => {
+                c.load_battery_ram()?;
                 self.cartridge = Option::from(c);
                 Ok(())
             }
@@ -163,8 +268,127 @@ impl GB {
         }
     }
 
+    /// Remove the cartridge, flushing its battery-backed RAM to the save file
+    pub fn eject_cartridge(&mut self) -> Result<(), std::io::Error> {
+        self.save_cartridge_ram()?;
+        self.cartridge = None;
+        Ok(())
+    }
+
+    /// Flush battery-backed RAM of the inserted cartridge to its save file
+    pub fn save_cartridge_ram(&mut self) -> Result<(), std::io::Error> {
+        match self.cartridge.as_mut() {
+            Some(cartridge) => cartridge.save_battery_ram(),
+            None => Ok(()),
+        }
+    }
+
+    /// Snapshot of the whole machine, to be restored with `load_state`.
+    /// Host resources (boot ROM and cartridge ROM data, link cable, callbacks, audio samples not taken yet)
+    /// are not part of it.
+    pub fn save_state(&self) -> Vec<Byte> {
+        let mut state = StateWriter::new();
+        state.write_bytes(Self::SAVE_STATE_MAGIC);
+        state.write_u16(Self::SAVE_STATE_VERSION);
+        self.model.save_state(&mut state);
+        state.write_u64(self.cycles);
+        state.write_u64(self.cycles_overflows);
+        self.bios.save_state(&mut state);
+        self.cpu_ctx.cpu.save_state(&mut state);
+        self.cpu_ctx.mmio.save_state(&mut state);
+        self.ppu_ctx.ppu.save_state(&mut state);
+        self.ppu_ctx.lcd.save_state(&mut state);
+        self.ppu_ctx.mmio.save_state(&mut state);
+        self.apu_ctx.apu.save_state(&mut state);
+        self.apu_ctx.mmio.save_state(&mut state);
+        self.dma_ctx.dma.save_state(&mut state);
+        self.dma_ctx.mmio.save_state(&mut state);
+        self.timer_ctx.mmio.save_state(&mut state);
+        self.serial_ctx.serial.save_state(&mut state);
+        self.serial_ctx.mmio.save_state(&mut state);
+        self.wram.save_state(&mut state);
+        self.oam_memory.save_state(&mut state);
+        self.joypad.save_state(&mut state);
+        state.write_bool(self.cartridge.is_some());
+        if let Some(cartridge) = self.cartridge.as_ref() {
+            cartridge.save_state(&mut state);
+        }
+        state.into_vec()
+    }
+
+    /// Restore a snapshot taken by `save_state`. The inserted cartridge must be the same one of the snapshot.
+    /// On error the system is left untouched.
+    pub fn load_state(&mut self, data: &[Byte]) -> Result<(), SaveStateError> {
+        let backup = self.save_state();
+        let result = self.load_state_unchecked(data);
+        if result.is_err() {
+            self.load_state_unchecked(&backup).expect("Backup save state must be valid");
+        }
+        result
+    }
+
+    fn load_state_unchecked(&mut self, data: &[Byte]) -> Result<(), SaveStateError> {
+        let mut state = StateReader::new(data);
+        if state.read_bytes().ok() != Some(Self::SAVE_STATE_MAGIC.as_slice()) {
+            return Err(SaveStateError::InvalidMagic);
+        }
+        let version = state.read_u16()?;
+        if version != Self::SAVE_STATE_VERSION {
+            return Err(SaveStateError::UnsupportedVersion(version));
+        }
+        self.model.load_state(&mut state)?;
+        self.cycles = state.read_u64()?;
+        self.cycles_overflows = state.read_u64()?;
+        self.bios.load_state(&mut state)?;
+        self.cpu_ctx.cpu.load_state(&mut state)?;
+        self.cpu_ctx.mmio.load_state(&mut state)?;
+        self.ppu_ctx.ppu.load_state(&mut state)?;
+        self.ppu_ctx.lcd.load_state(&mut state)?;
+        self.ppu_ctx.mmio.load_state(&mut state)?;
+        self.apu_ctx.apu.load_state(&mut state)?;
+        self.apu_ctx.mmio.load_state(&mut state)?;
+        self.dma_ctx.dma.load_state(&mut state)?;
+        self.dma_ctx.mmio.load_state(&mut state)?;
+        self.timer_ctx.mmio.load_state(&mut state)?;
+        self.serial_ctx.serial.load_state(&mut state)?;
+        self.serial_ctx.mmio.load_state(&mut state)?;
+        self.wram.load_state(&mut state)?;
+        self.oam_memory.load_state(&mut state)?;
+        self.joypad.load_state(&mut state)?;
+        let has_cartridge = state.read_bool()?;
+        match self.cartridge.as_mut() {
+            Some(cartridge) if has_cartridge => cartridge.load_state(&mut state)?,
+            None if !has_cartridge => {}
+            _ => return Err(SaveStateError::CartridgeMismatch),
+        }
+        if !state.is_empty() {
+            return Err(SaveStateError::InvalidValue("trailing data"));
+        }
+        Ok(())
+    }
+
+    /// Periodic save, to not lose progress on crash: once every `AUTOSAVE_PERIOD` written cartridge RAM is flushed.
+    /// Nothing is done until the period elapses, so hosts ticking by hand can call it as often as they like
+    /// (`run_frame`, `run_cycles` and `step_instruction` already do it, keeping disk writes out of `tick`).
+    pub fn autosave(&mut self) -> Result<(), std::io::Error> {
+        if !std::mem::take(&mut self.autosave_due) {
+            return Ok(());
+        }
+        match self.cartridge.as_mut().filter(|c| c.is_dirty()) {
+            Some(cartridge) => cartridge.save_battery_ram(),
+            None => Ok(()),
+        }
+    }
+
+    fn autosave_summarized(&mut self, summary: &mut RunSummary) {
+        if let Err(err) = self.autosave() {
+            summary.autosave_error = Some(err.kind());
+        }
+    }
+
     /**
-    A single T-Cycle ticking
+    A single T-Cycle ticking.
+    Cartridge RAM is not autosaved here: hosts ticking by hand call `autosave` from time to time
     */
     pub fn tick(&mut self) {
         // let time = Instant::now();
@@ -174,11 +398,20 @@ impl GB {
         // Get IRQ statuses before tick the system
         let old_stat_irq = ctx.ppu_mmio.irq();
 
-        // Tick every component
+        // Tick every component - Timer, PPU and LCD are stopped too while CPU is in STOP mode
+        let stopped = self.cpu_ctx.cpu.is_stopped();
+        if !stopped {
+            self.timer_ctx.timer.tick(&mut self.bus, &mut ctx);
+        }
+        self.serial_ctx.serial.tick(&mut self.bus, &mut ctx);
         self.apu_ctx.apu.tick(&mut self.bus, &mut ctx);
-        self.ppu_ctx.ppu.tick(&mut self.bus, &mut ctx);
+        if !stopped {
+            self.ppu_ctx.ppu.tick(&mut self.bus, &mut ctx);
+        }
         self.dma_ctx.dma.tick(&mut self.bus, &mut ctx);
-        self.ppu_ctx.lcd.tick(&mut self.bus, &mut ctx);
+        if !stopped {
+            self.ppu_ctx.lcd.tick(&mut self.bus, &mut ctx);
+        }
         self.cpu_ctx.cpu.tick(&mut self.bus, &mut ctx);
 
         // Get IRQ statuses after ticked the system and check for interrupt enabling rising-edge/falling-edge
@@ -187,6 +420,15 @@ impl GB {
             ctx.cpu_mmio.interrupt_registers_mut().set_if_bit(InterruptFlagsMask::LCD);
         }
 
+        // Notify the host when cartridge rumble motor is switched on/off
+        let rumble = self.cartridge.as_ref().is_some_and(|c| c.rumble());
+        if rumble != self.rumble {
+            self.rumble = rumble;
+            if let Some(callback) = self.rumble_callback.as_mut() {
+                callback(rumble);
+            }
+        }
+
         // if self.cpu.dma_transfer {
         //     self.dma_transfer();
         // }
@@ -196,6 +438,78 @@ impl GB {
         if self.cycles == 0 {
             self.cycles_overflows = self.cycles_overflows.wrapping_add(1);
         }
+        if self.cycles.is_multiple_of(Self::AUTOSAVE_PERIOD) {
+            self.autosave_due = true;
+        }
+    }
+
+    /// Run 'till the end of the current frame, that is when the PPU enters VBlank.
+    /// If the LCD is off (or the system is stopped) a frame worth of T-Cycles is run.
+    pub fn run_frame(&mut self) -> RunSummary {
+        let mut summary = RunSummary::default();
+        loop {
+            self.tick_summarized(&mut summary);
+            if summary.frame_completed {
+                break;
+            }
+            let ppu_running = self.ppu_ctx.mmio.lcdc_view().lcd_enabled && !self.cpu_ctx.cpu.is_stopped();
+            if !ppu_running && summary.cycles >= Self::FRAME_T_CYCLES {
+                break;
+            }
+        }
+        self.autosave_summarized(&mut summary);
+        summary
+    }
+
+    /// Run the given number of T-Cycles
+    pub fn run_cycles(&mut self, t_cycles: u64) -> RunSummary {
+        let mut summary = RunSummary::default();
+        for _ in 0..t_cycles {
+            self.tick_summarized(&mut summary);
+        }
+        self.autosave_summarized(&mut summary);
+        summary
+    }
+
+    /// Run 'till the CPU starts the next instruction (or interrupt dispatch), retiring the running one.
+    /// While CPU is halted, stopped or locked a single M-Cycle is run.
+    pub fn step_instruction(&mut self) -> RunSummary {
+        let mut summary = RunSummary::default();
+        loop {
+            self.tick_summarized(&mut summary);
+            let cpu = &self.cpu_ctx.cpu;
+            if cpu.is_instruction_start() {
+                break;
+            }
+            if (cpu.is_halted() || cpu.is_stopped() || cpu.is_locked()) && cpu.instruction_t_cycle() == 0 {
+                break;
+            }
+        }
+        self.autosave_summarized(&mut summary);
+        summary
+    }
+
+    fn tick_summarized(&mut self, summary: &mut RunSummary) {
+        let running = self.cpu_ctx.cpu.running_instruction();
+        self.tick();
+        summary.cycles += 1;
+        summary.frame_completed |= self.is_vblank_edge();
+
+        let cpu = &self.cpu_ctx.cpu;
+        if cpu.is_instruction_start() {
+            if let Some(retired) = running {
+                if retired.interrupt.is_none() {
+                    summary.instructions += 1;
+                }
+                summary.last_instruction = Some(retired);
+            }
+            if let Some(interrupt) = cpu.maneging_interrupt() {
+                summary.interrupts.push(interrupt);
+            }
+        }
+        if summary.lockup.is_none() {
+            summary.lockup = cpu.lockup();
+        }
     }
 
     pub fn press_dpad(&mut self, dpad: JoypadDPadBits, pressed: bool) {
@@ -214,29 +528,72 @@ impl GB {
         )
     }
 
+    pub fn connect_link_cable(&mut self, link_cable: Box<dyn serial::LinkCable>) {
+        self.serial_ctx.serial.connect(link_cable);
+    }
+
+    pub fn disconnect_link_cable(&mut self) {
+        self.serial_ctx.serial.disconnect();
+    }
+
+    /// The callback is invoked with the new motor status every time a rumble cartridge switches it on/off
+    pub fn set_rumble_callback(&mut self, callback: Box<dyn FnMut(bool)>) {
+        self.rumble_callback = Some(callback);
+    }
+
+    pub fn clear_rumble_callback(&mut self) {
+        self.rumble_callback = None;
+    }
+
+    pub fn is_rumbling(&self) -> bool {
+        self.rumble
+    }
+
+    /// Record the CPU bus activity of the last `capacity` M-Cycles (see `cpu::bus_trace::BusTrace`)
+    pub fn enable_bus_trace(&mut self, capacity: usize) {
+        self.cpu_ctx.cpu.enable_bus_trace(capacity);
+    }
+
+    pub fn disable_bus_trace(&mut self) {
+        self.cpu_ctx.cpu.disable_bus_trace();
+    }
+
+    pub fn bus_trace(&self) -> Option<&cpu::bus_trace::BusTrace> {
+        self.cpu_ctx.cpu.bus_trace()
+    }
+
     pub fn set_use_boot(&mut self, use_boot: bool) {
-        self.is_booting = use_boot;
-        self.cpu_ctx.cpu.registers.set_pc(cartridge_addresses::ENTRY_POINT as u16);
+        if use_boot && self.bios.is_loaded() {
+            self.boot();
+        } else {
+            // Skip boot, starting from the cartridge entry point
+            self.bios.set_mapped(false);
+            self.apply_post_boot_state();
+        }
     }
     
-    pub fn cpu(&self) -> &cpu::CPU {
+    #[cfg(test)]
+    pub(crate) fn cpu(&self) -> &cpu::CPU {
         &self.cpu_ctx.cpu
     }
-    
-    pub fn ppu(&self) -> &ppu::PpuCtx {
-        &self.ppu_ctx
+
+    pub fn cpu_registers(&self) -> cpu::CpuRegisters {
+        self.cpu_ctx.cpu.registers_view()
     }
 
-    pub fn oam_memory(&self) -> &memory::OamMemory {
-        &self.oam_memory
+    /// True in the T-Cycle the CPU starts a new instruction (or interrupt dispatch)
+    pub fn is_instruction_start(&self) -> bool {
+        self.cpu_ctx.cpu.is_instruction_start()
     }
-    
-    pub fn vram(&self) -> &VRAM {
-        self.ppu_ctx.mmio.vram()
+
+    /// Interrupt the CPU is dispatching instead of running an instruction
+    pub fn dispatching_interrupt(&self) -> Option<cpu::InterruptType> {
+        self.cpu_ctx.cpu.maneging_interrupt()
     }
 
-    pub fn joypad(&self) -> &joypad::Joypad {
-        &self.joypad
+    /// Illegal opcode the CPU is hung on, if any
+    pub fn cpu_lockup(&self) -> Option<cpu::CpuLockup> {
+        self.cpu_ctx.cpu.lockup()
     }
 
     pub fn joypad_view(&self) -> joypad::JoypadInputs {
@@ -247,9 +604,29 @@ impl GB {
         self.cartridge.as_ref()
     }
 
-    // pub fn get_bios(&self) -> &BIOS {
-    //     &self.bios
-    // }
+    /// True in the T-Cycle the PPU enters VBlank after the last visible line, that is on a frame boundary
+    pub fn is_vblank_edge(&self) -> bool {
+        let mmio = &self.ppu_ctx.mmio;
+        mmio.ppu_mode() == ppu::ppu_mode::PpuMode::VBlank
+            && mmio.prev_ppu_mode() != mmio.ppu_mode()
+            && mmio.ly() == PPU::SCREEN_LINES as u8
+    }
+
+    pub fn audio_sample_rate(&self) -> u32 {
+        self.apu_ctx.apu.sample_rate()
+    }
+
+    /// Produce audio at the given rate (Hz), `DEFAULT_AUDIO_SAMPLE_RATE` by default.
+    /// Samples not taken yet are dropped.
+    pub fn set_audio_sample_rate(&mut self, sample_rate: u32) {
+        self.apu_ctx.apu.set_sample_rate(sample_rate);
+    }
+
+    /// Take the audio produced since the last call, from the oldest sample.
+    /// Up to a second of audio is kept: older samples are dropped if not taken in time.
+    pub fn take_audio_samples(&mut self) -> Vec<apu::AudioSample> {
+        self.apu_ctx.apu.take_samples()
+    }
 
     pub fn frame(&self) -> &[GbColor; PPU::SCREEN_PIXELS as usize] {
         if self.ppu_ctx.mmio.lcdc_view().lcd_enabled {
@@ -267,10 +644,15 @@ impl GB {
         let mut ctx = gb_bus_ctx_mut!(self);
         self.bus.write(&mut ctx, address, data)
     }
+
+    /// Decode the instruction at the given address as currently mapped on the bus
+    pub fn disassemble(&self, address: Address) -> DisassembledInstruction {
+        disasm::decode_at(|address| self.read(Address(address)), address.0)
+    }
 }
 
 impl Default for GB {
     fn default() -> Self {
-       Self::new(None)
+        Self::new(None).expect("No BIOS to load")
     }
 }
@@ -1,32 +1,119 @@
 use std::fs::File;
 use std::io::Read;
+use crate::GB::bus::BusDevice;
 use crate::GB::memory::{Length, Memory};
+use crate::GB::types::address::{Address, AddressRangeInclusive};
+use crate::GB::types::Byte;
+use crate::GB::save_state::{SaveState, SaveStateError, StateReader, StateWriter};
+
+#[cfg(test)]
+mod test {
+    use crate::GB::BIOS::BIOS;
+    use crate::GB::types::address::Address;
+    use crate::GB::GB;
+
+    fn gb_with_bios(program: &[u8]) -> GB {
+        let mut data = vec![0u8; BIOS::BIOS_SIZE];
+        data[..program.len()].copy_from_slice(program);
+        let mut gb = GB::new(None).unwrap();
+        gb.bios.load_bios_data(data);
+        gb.boot();
+        gb
+    }
+
+    #[test]
+    fn bios_is_mapped_while_booting() {
+        let gb = gb_with_bios(&[0x31, 0xFE, 0xFF]);
+        assert!(gb.is_booting());
+        assert_eq!(gb.cpu().registers.get_pc(), 0x0000);
+        assert_eq!(gb.read(Address(0x0000)), 0x31);
+        assert_eq!(gb.read(Address(0x0002)), 0xFF);
+        assert_eq!(gb.read(BIOS::BANK_REGISTER_ADDRESS), 0xFF);
+    }
+
+    #[test]
+    fn bios_is_unmapped_by_bank_register() {
+        // LD A, $01 - LDH ($50), A
+        let mut gb = gb_with_bios(&[0x3E, 0x01, 0xE0, 0x50]);
+        for _ in 0..(6 * 4) {
+            gb.tick();
+        }
+        assert!(!gb.is_booting());
+        // Without cartridge, ROM area is open bus
+        assert_eq!(gb.read(Address(0x0000)), 0xFF);
+
+        // Boot ROM can't be mapped again by software
+        gb.write(BIOS::BANK_REGISTER_ADDRESS, 0x00);
+        assert!(!gb.is_booting());
+    }
+
+    #[test]
+    fn bios_stays_mapped_on_zero_write() {
+        let mut gb = gb_with_bios(&[0x00]);
+        gb.write(BIOS::BANK_REGISTER_ADDRESS, 0x00);
+        assert!(gb.is_booting());
+        gb.write(BIOS::BANK_REGISTER_ADDRESS, 0x01);
+        assert!(!gb.is_booting());
+    }
+
+    #[test]
+    fn missing_bios_is_reported() {
+        assert!(GB::new(Some(String::from("does/not/exist.bin"))).is_err());
+    }
+
+    #[test]
+    fn skip_boot_starts_from_entry_point() {
+        let mut gb = gb_with_bios(&[0x00]);
+        gb.set_use_boot(false);
+        assert!(!gb.is_booting());
+        assert_eq!(gb.cpu().registers.get_pc(), 0x0100);
+    }
+}
 
 pub struct BIOS {
-    #[cfg(test)]
-    pub memory: Memory<u8>,
-    #[cfg(not(test))]
     memory: Memory<u8>,
-    bios: String,
+    loaded: bool,
+    mapped: bool, // Boot ROM overlays cartridge ROM 'till a write on BANK register
 }
 
 impl BIOS {
-    pub  fn new() -> Self {
-        Self { memory: Memory::<u8>::new(0, 256), bios: String::from("") }
-    }
+    pub const BIOS_SIZE: usize = 256;
+    pub const BIOS_START_ADDRESS: Address = Address(0x0000);
+    pub const BIOS_END_ADDRESS: Address = Address(0x00FF);
+    pub const BIOS_ADDRESS_RANGE: AddressRangeInclusive = Self::BIOS_START_ADDRESS..=Self::BIOS_END_ADDRESS;
+    pub const BANK_REGISTER_ADDRESS: Address = Address(0xFF50); // Any non-zero write unmaps the boot ROM
 
-    pub fn read(&self, address: u16) -> u8 {
-        self.memory[address as usize]
+    pub  fn new() -> Self {
+        Self { memory: Memory::<u8>::new(0, Self::BIOS_SIZE), loaded: false, mapped: false }
     }
 
     pub fn load_bios(&mut self, path: &String) -> Result<(), std::io::Error> {
         let mut file = File::open(path)?;
-        let mut buffer = vec![0u8; 256];
+        let mut buffer = vec![0u8; Self::BIOS_SIZE];
         file.read_exact(&mut buffer)?;
-        self.memory = Memory { memory: buffer };
-        self.bios = path.clone();
+        self.load_bios_data(buffer);
         Ok(())
     }
+
+    pub fn load_bios_data(&mut self, data: Vec<u8>) {
+        self.memory = Memory::new_from_vec(data);
+        self.loaded = true;
+    }
+
+    #[inline]
+    pub fn is_loaded(&self) -> bool {
+        self.loaded
+    }
+
+    #[inline]
+    pub fn is_mapped(&self) -> bool {
+        self.mapped
+    }
+
+    #[inline]
+    pub fn set_mapped(&mut self, mapped: bool) {
+        self.mapped = mapped;
+    }
 }
 
 impl Length for BIOS {
@@ -34,3 +121,49 @@ impl Length for BIOS {
         self.memory.len()
     }
 }
+
+impl BusDevice for BIOS {
+    fn read(&self, address: Address) -> Byte {
+        match address {
+            address if Self::BIOS_ADDRESS_RANGE.contains(&address) => {
+                self.memory[address.as_index() - Self::BIOS_START_ADDRESS.as_index()]
+            }
+            Self::BANK_REGISTER_ADDRESS => 0xFF,
+            _ => unreachable!(),
+        }
+    }
+
+    fn write(&mut self, address: Address, data: Byte) {
+        match address {
+            Self::BANK_REGISTER_ADDRESS => {
+                // Once unmapped, boot ROM can't be mapped again
+                if data != 0 {
+                    self.mapped = false;
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl Default for BIOS {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SaveState for BIOS {
+    /// Boot ROM content is a host resource: only the mapping is part of the state
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_bool(self.mapped);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        let mapped = state.read_bool()?;
+        if mapped && !self.loaded {
+            return Err(SaveStateError::InvalidValue("boot ROM mapping without a loaded boot ROM"));
+        }
+        self.mapped = mapped;
+        Ok(())
+    }
+}
@@ -1,16 +1,19 @@
+use std::collections::VecDeque;
 use crate::GB::apu::apu_mmio::ApuMmio;
-use crate::GB::bus::{Bus, BusDevice, MmioContextWrite, MmioDevice};
+use crate::GB::bus::{Bus, BusDevice, MmioContextWrite};
 use crate::GB::traits::Tick;
 use crate::GB::types::address::{Address, AddressRangeInclusive};
 use crate::GB::types::Byte;
+use crate::GB::save_state::{SaveState, SaveStateError, StateReader, StateWriter};
+use crate::GB::GB;
 
 mod channels;
 pub mod constants;
+#[allow(dead_code)] // Full APU register map, not every register is referenced
 pub mod mmio;
 pub mod apu_mmio;
 
 type AudioVolume = u8;
-type AudioPeriod = u16;
 
 trait ApuBusChannel: BusDevice {
     fn tick(&mut self, cycles: u32);
@@ -18,23 +21,77 @@ trait ApuBusChannel: BusDevice {
     fn output(&self) -> AudioVolume; // Signal intensity from 0 to 15 (4-bit)
 }
 
+/// Stereo sample of the mixed APU output, both sides in -1.0..=1.0 range
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct AudioSample {
+    pub left: f32,
+    pub right: f32,
+}
+
 pub struct APU {
     noise: channels::noise_channel::NoiseChannel,
     div: u16,
+    sample_rate: u32, // Hz
+    sample_clock: u64, // Accumulates the sample rate every T-Cycle: a sample is due when it reaches the system clock
+    samples: VecDeque<AudioSample>, // Produced samples not taken by the host yet, up to a second of audio
 }
 
 impl APU {
+    pub const DEFAULT_SAMPLE_RATE: u32 = 48_000;
+
     pub fn new() -> APU {
         Self {
             noise: channels::noise_channel::NoiseChannel::new(),
             div: u16::MAX,
+            sample_rate: Self::DEFAULT_SAMPLE_RATE,
+            sample_clock: 0,
+            samples: VecDeque::with_capacity(Self::DEFAULT_SAMPLE_RATE as usize),
         }
     }
+
+    #[inline]
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Change the rate samples are produced at, dropping the ones not taken yet
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.sample_rate = sample_rate.clamp(1, GB::SYSTEM_FREQUENCY_CLOCK);
+        self.sample_clock = 0;
+        self.samples.clear();
+    }
+
+    /// Take the produced samples, from the oldest
+    pub fn take_samples(&mut self) -> Vec<AudioSample> {
+        self.samples.drain(..).collect()
+    }
 }
 
 impl Tick for APU {
-    fn tick(&mut self, bus: &mut Bus, ctx: &mut MmioContextWrite) {
-        // todo!()
+    fn tick(&mut self, _bus: &mut Bus, ctx: &mut MmioContextWrite) {
+        ctx.apu_mmio.tick_channels();
+
+        self.sample_clock += self.sample_rate as u64;
+        if self.sample_clock >= GB::SYSTEM_FREQUENCY_CLOCK as u64 {
+            self.sample_clock -= GB::SYSTEM_FREQUENCY_CLOCK as u64;
+            if self.samples.len() >= self.sample_rate as usize {
+                self.samples.pop_front();
+            }
+            self.samples.push_back(ctx.apu_mmio.mix());
+        }
+    }
+}
+
+impl SaveState for APU {
+    fn save_state(&self, state: &mut StateWriter) {
+        self.noise.save_state(state);
+        state.write_u16(self.div);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        self.noise.load_state(state)?;
+        self.div = state.read_u16()?;
+        Ok(())
     }
 }
 
@@ -42,3 +99,64 @@ pub struct ApuCtx {
     pub apu: APU,
     pub mmio: ApuMmio
 }
+
+#[cfg(test)]
+mod test {
+    use crate::GB::apu::apu_mmio::ApuMmio;
+    use crate::GB::apu::channels::NoiseChannel;
+    use crate::GB::apu::AudioSample;
+    use crate::GB::GB;
+
+    fn gb_with_noise(panning: u8) -> GB {
+        let mut gb = GB::new(None).unwrap();
+        gb.write(ApuMmio::APU_NR52_MASTER_CONTROL_ADDRESS, 0x80);
+        gb.write(ApuMmio::APU_NR50_MASTER_VOLUME_ADDRESS, 0x77);
+        gb.write(ApuMmio::APU_NR51_SOUND_PANNING_ADDRESS, panning);
+        gb.write(NoiseChannel::APU_NR42_CHANNEL_VOLUME_ADDRESS, 0xF0); // Max volume, no envelope
+        gb.write(NoiseChannel::APU_NR43_CHANNEL_FREQUENCY_ADDRESS, 0x00); // LFSR clocked every 8 T-Cycles
+        gb.write(NoiseChannel::APU_NR44_CHANNEL_CONTROL_ADDRESS, 0x80); // Trigger
+        gb.take_audio_samples();
+        gb
+    }
+
+    #[test]
+    fn samples_are_produced_at_sample_rate() {
+        let mut gb = GB::new(None).unwrap();
+        assert_eq!(gb.audio_sample_rate(), GB::DEFAULT_AUDIO_SAMPLE_RATE);
+        gb.set_audio_sample_rate(32_000);
+        gb.run_cycles(GB::SYSTEM_FREQUENCY_CLOCK as u64 / 4);
+        assert_eq!(gb.take_audio_samples().len(), 8_000);
+        assert!(gb.take_audio_samples().is_empty());
+
+        // Only the last second of audio is kept
+        gb.run_cycles(GB::SYSTEM_FREQUENCY_CLOCK as u64 * 2);
+        assert_eq!(gb.take_audio_samples().len(), 32_000);
+    }
+
+    #[test]
+    fn apu_off_is_silent() {
+        let mut gb = gb_with_noise(0xFF);
+        gb.write(ApuMmio::APU_NR52_MASTER_CONTROL_ADDRESS, 0x00);
+        gb.run_cycles(10_000);
+        let samples = gb.take_audio_samples();
+        assert!(!samples.is_empty());
+        assert!(samples.iter().all(|sample| *sample == AudioSample::default()));
+    }
+
+    #[test]
+    fn noise_channel_is_panned() {
+        let mut gb = gb_with_noise(0x80); // Noise on left only
+        gb.run_cycles(10_000);
+        let samples = gb.take_audio_samples();
+        assert!(samples.iter().all(|sample| sample.right == 0.0));
+        assert!(samples.iter().all(|sample| (-1.0..=1.0).contains(&sample.left)));
+        // LFSR output toggles between DAC extremes
+        assert!(samples.iter().any(|sample| sample.left > 0.0));
+        assert!(samples.iter().any(|sample| sample.left < 0.0));
+
+        // Switching DAC off silences the channel
+        gb.write(NoiseChannel::APU_NR42_CHANNEL_VOLUME_ADDRESS, 0x00);
+        gb.run_cycles(1_000);
+        assert!(gb.take_audio_samples().iter().all(|sample| *sample == AudioSample::default()));
+    }
+}
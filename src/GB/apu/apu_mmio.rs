@@ -2,13 +2,18 @@ use crate::GB::bus::BusDevice;
 use crate::GB::types::address::{Address, AddressRangeInclusive};
 use crate::GB::types::Byte;
 use super::channels;
+use super::{ApuBusChannel, AudioSample, AudioVolume};
+use crate::GB::save_state::{SaveState, SaveStateError, StateReader, StateWriter};
 
 pub struct ApuMmio {
     // Todo: add APU memory-mapped mapped elements
+    #[allow(dead_code)]
     sqr0: channels::PulseChannel,
+    #[allow(dead_code)]
     sqr1: channels::PulseChannel,
     wave: channels::WaveChannel,
     noise: channels::NoiseChannel,
+    registers: [Byte; 0x17], // Raw NR10-NR52 registers, 'till channels handle their own ones
 }
 
 impl ApuMmio {
@@ -17,6 +22,15 @@ impl ApuMmio {
     pub const APU_NR52_MASTER_CONTROL_ADDRESS: Address = Address(0xFF26);
     pub const APU_REGISTERS_RANGE: AddressRangeInclusive = channels::PulseChannel::APU_NR10_CHANNEL_SWEEP_ADDRESS..=Self::APU_NR52_MASTER_CONTROL_ADDRESS;
     pub const APU_WAVE_RANGE: AddressRangeInclusive = channels::WaveChannel::APU_WAVE_PATTERN_RANGE;
+
+    // Bits always read as 1 for each NR10-NR52 register (write-only and unused bits)
+    const REGISTERS_READ_MASK: [Byte; 0x17] = [
+        0x80, 0x3F, 0x00, 0xFF, 0xBF, // NR10-NR14
+        0xFF, 0x3F, 0x00, 0xFF, 0xBF, // NR20-NR24
+        0x7F, 0xFF, 0x9F, 0xFF, 0xBF, // NR30-NR34
+        0xFF, 0xFF, 0x00, 0x00, 0xBF, // NR40-NR44
+        0x00, 0x00, 0x70,             // NR50-NR52
+    ];
 }
 
 impl ApuMmio {
@@ -26,6 +40,65 @@ impl ApuMmio {
             sqr1: channels::PulseChannel::new(false),
             wave: channels::WaveChannel::new(),
             noise: channels::NoiseChannel::new(),
+            registers: [0; 0x17],
+        }
+    }
+
+    /// Set a raw NR10-NR52 register value, without any side effect
+    pub fn set_register(&mut self, address: Address, value: Byte) {
+        let index = address.as_index() - Self::APU_REGISTERS_RANGE.start().as_index();
+        self.registers[index] = value;
+    }
+
+    #[inline]
+    fn register(&self, address: Address) -> Byte {
+        self.registers[address.as_index() - Self::APU_REGISTERS_RANGE.start().as_index()]
+    }
+
+    #[inline]
+    pub fn is_enabled(&self) -> bool {
+        (self.register(Self::APU_NR52_MASTER_CONTROL_ADDRESS) & 0b1000_0000) != 0
+    }
+
+    /// Advance channels by a T-Cycle
+    pub fn tick_channels(&mut self) {
+        if self.is_enabled() {
+            self.noise.tick(1);
+        }
+    }
+
+    /// Digital output of channels 1-4, None if the channel DAC is off.
+    /// Pulse and wave channels are not emulated yet: their DACs are always off.
+    fn channel_outputs(&self) -> [Option<AudioVolume>; 4] {
+        let noise = self.noise.dac_enabled().then(|| self.noise.output());
+        [None, None, None, noise]
+    }
+
+    /// Mix channels as the DACs, NR51 panning and NR50 master volume do. Silent while APU is off (NR52).
+    pub fn mix(&self) -> AudioSample {
+        if !self.is_enabled() {
+            return AudioSample::default();
+        }
+        let panning = self.register(Self::APU_NR51_SOUND_PANNING_ADDRESS);
+        let (mut left, mut right) = (0.0, 0.0);
+        for (channel, output) in self.channel_outputs().iter().enumerate() {
+            if let Some(output) = output {
+                // DAC maps digital 0-15 to analog 1.0 to -1.0
+                let analog = 1.0 - *output as f32 / 7.5;
+                if (panning & (0b0001_0000 << channel)) != 0 {
+                    left += analog;
+                }
+                if (panning & (0b0000_0001 << channel)) != 0 {
+                    right += analog;
+                }
+            }
+        }
+        let master_volume = self.register(Self::APU_NR50_MASTER_VOLUME_ADDRESS);
+        let left_volume = (((master_volume >> 4) & 0b111) + 1) as f32 / 8.0;
+        let right_volume = ((master_volume & 0b111) + 1) as f32 / 8.0;
+        AudioSample {
+            left: left / 4.0 * left_volume,
+            right: right / 4.0 * right_volume,
         }
     }
 }
@@ -33,10 +106,37 @@ impl ApuMmio {
 impl BusDevice for ApuMmio {
     fn read(&self, address: Address) -> Byte {
         // TODO: Add all APU addresses
-        0xFF
+        match address {
+            address if Self::APU_REGISTERS_RANGE.contains(&address) => {
+                let index = address.as_index() - Self::APU_REGISTERS_RANGE.start().as_index();
+                self.registers[index] | Self::REGISTERS_READ_MASK[index]
+            }
+            _ => 0xFF,
+        }
     }
 
     fn write(&mut self, address: Address, data: Byte) {
         // TODO: Add all APU addresses
+        if Self::APU_REGISTERS_RANGE.contains(&address) {
+            self.set_register(address, data);
+        }
+        if channels::NoiseChannel::APU_NOISE_CHANNEL_RANGE.contains(&address) {
+            self.noise.write(address, data);
+        }
+    }
+}
+
+impl SaveState for ApuMmio {
+    fn save_state(&self, state: &mut StateWriter) {
+        // Pulse channels don't hold any state yet
+        self.wave.save_state(state);
+        self.noise.save_state(state);
+        state.write_bytes(&self.registers);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        self.wave.load_state(state)?;
+        self.noise.load_state(state)?;
+        state.read_bytes_into(&mut self.registers, "APU registers")
     }
 }
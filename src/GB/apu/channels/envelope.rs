@@ -1,6 +1,7 @@
 use crate::GB::apu::constants::{FRAME_SEQUENCER_FREQUENCY, FRAME_SEQUENCER_TICKS};
 use std::cmp::{max, min};
 use super::super::AudioVolume;
+use crate::GB::save_state::{SaveState, SaveStateError, StateReader, StateWriter};
 
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
 #[repr(u8)]
@@ -15,7 +16,9 @@ pub struct Envelope {
 }
 
 impl Envelope {
+    #[allow(dead_code)]
     const ENVELOPE_TICKS: u16 = (FRAME_SEQUENCER_TICKS / 4) as u16;
+    #[allow(dead_code)]
     const ENVELOPE_FREQUENCY: u16 = (FRAME_SEQUENCER_FREQUENCY / 4) as u16; // Hz
     pub fn new() -> Envelope {
         Envelope {
@@ -29,6 +32,7 @@ impl Envelope {
         self.direction = direction;
     }
 
+    #[allow(dead_code)]
     pub fn tick(&mut self) {
         match self.direction {
             EnvelopeDirection::Down => self.volume = self.volume.saturating_sub(1),
@@ -41,3 +45,16 @@ impl Envelope {
         self.volume
     }
 }
+
+impl SaveState for Envelope {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_u8(self.volume);
+        state.write_bool(self.direction == EnvelopeDirection::Up);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        self.volume = state.read_u8()?;
+        self.direction = if state.read_bool()? { EnvelopeDirection::Up } else { EnvelopeDirection::Down };
+        Ok(())
+    }
+}
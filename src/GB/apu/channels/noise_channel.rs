@@ -1,8 +1,9 @@
 use crate::GB::bus::BusDevice;
 use crate::GB::types::address::{Address, AddressRangeInclusive};
 use crate::GB::types::Byte;
+use crate::GB::save_state::{SaveState, SaveStateError, StateReader, StateWriter};
 use crate::GB::apu::channels::envelope::{Envelope, EnvelopeDirection};
-use crate::GB::apu::{mmio, ApuBusChannel, AudioPeriod, AudioVolume};
+use crate::GB::apu::{mmio, ApuBusChannel, AudioVolume};
 use crate::{default_enum_u8_bit_ops, mask_flag_enum_default_impl};
 
 #[derive(Debug, Clone, Copy)]
@@ -50,6 +51,8 @@ pub struct NoiseChannel {
     nr44: u8, // Control
     lfsr: u16,
     envelope: Envelope,
+    enabled: bool, // Triggered with DAC on
+    timer: u32, // T-Cycles since the last LFSR clock
 }
 
 /**
@@ -61,11 +64,14 @@ impl NoiseChannel {
     const LFSR_SHORT_BIT: u8 = 6; // After the right shift
     const LFSR_BIT_SET_MASK: u16 = 1 << 14; // After the right shift
     const LFSR_SHORT_BIT_SET_MASK: u16 = 1 << 6; // After the right shift
+    #[allow(dead_code)]
     const LFSR_OUTPUT_BIT: u16 = 0;
     const LFSR_OUTPUT_MASK: u16 = 1;
 
     pub const APU_NR41_CHANNEL_TIMER_ADDRESS: Address = Address(0xFF20);
+    #[allow(dead_code)]
     pub const APU_NR42_CHANNEL_VOLUME_ADDRESS: Address = Address(0xFF21);
+    #[allow(dead_code)]
     pub const APU_NR43_CHANNEL_FREQUENCY_ADDRESS: Address = Address(0xFF22);
     pub const APU_NR44_CHANNEL_CONTROL_ADDRESS: Address = Address(0xFF23);
     pub const APU_NOISE_CHANNEL_START_ADDRESS: Address = Self::APU_NR41_CHANNEL_TIMER_ADDRESS;
@@ -82,15 +88,39 @@ impl NoiseChannel {
             nr44: 0,
             lfsr: 0,
             envelope: Envelope::new(),
+            enabled: false,
+            timer: 0,
         }
     }
 
+    /// DAC is on if any of NR42 volume/envelope direction bits is set
+    #[inline]
+    pub fn dac_enabled(&self) -> bool {
+        (self.nr42 & (NoiseNR42Masks::Volume as u8 | NoiseNR42Masks::EnvDir as u8)) != 0
+    }
+
+    #[inline]
+    #[allow(dead_code)]
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// T-Cycles between LFSR clocks: divisor (8 for 0, else 16 * code) shifted left by NR43 clock shift
+    fn period(&self) -> u32 {
+        let divisor = match self.nr43 & 0b0000_0111 {
+            0 => 8,
+            code => 16 * code as u32,
+        };
+        divisor << (self.nr43 >> 4)
+    }
+
     #[inline]
     pub fn short_mode(&self) -> bool {
         (self.nr43 & NoiseNR43Masks::LfsrWidth) != 0
     }
 
     #[inline]
+    #[allow(dead_code)]
     pub fn short_mode_as_u8(&self) -> u8 {
         self.nr43 & NoiseNR43Masks::LfsrWidth
     }
@@ -122,6 +152,8 @@ impl NoiseChannel {
     }
 
     fn trigger(&mut self) {
+        self.enabled = self.dac_enabled();
+        self.timer = 0;
         self.lfsr = 0x7FFF;
         self.envelope
             .trigger(self.volume(), self.envelope_direction());
@@ -135,6 +167,9 @@ impl NoiseChannel {
     #[inline]
     fn set_nr42(&mut self, val: Byte) {
         self.nr42 = val;
+        if !self.dac_enabled() {
+            self.enabled = false;
+        }
     }
 
     #[inline]
@@ -175,17 +210,49 @@ impl BusDevice for NoiseChannel {
 
 impl ApuBusChannel for NoiseChannel {
     fn tick(&mut self, cycles: u32) {
-        todo!()
+        // Clock shifts 14 and 15 don't clock the LFSR at all
+        if !self.enabled || (self.nr43 >> 4) >= 14 {
+            return;
+        }
+        self.timer += cycles;
+        let period = self.period();
+        while self.timer >= period {
+            self.timer -= period;
+            self.shift();
+        }
     }
 
     fn sample(&self) -> u8 {
-        todo!()
+        self.lfsr_output_bit()
     }
 
     fn output(&self) -> AudioVolume {
-        if (self.lfsr & Self::LFSR_BIT_SET_MASK) != 0 {
-            return self.envelope.volume();
+        if self.enabled {
+            return self.sample() * self.envelope.volume();
         }
         0
     }
 }
+
+impl SaveState for NoiseChannel {
+    fn save_state(&self, state: &mut StateWriter) {
+        for register in [self.nr41, self.nr42, self.nr43, self.nr44] {
+            state.write_u8(register);
+        }
+        state.write_u16(self.lfsr);
+        self.envelope.save_state(state);
+        state.write_bool(self.enabled);
+        state.write_u32(self.timer);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        for register in [&mut self.nr41, &mut self.nr42, &mut self.nr43, &mut self.nr44] {
+            *register = state.read_u8()?;
+        }
+        self.lfsr = state.read_u16()?;
+        self.envelope.load_state(state)?;
+        self.enabled = state.read_bool()?;
+        self.timer = state.read_u32()?;
+        Ok(())
+    }
+}
@@ -7,15 +7,25 @@ pub struct PulseChannel {
 
 impl PulseChannel {
     pub const APU_NR10_CHANNEL_SWEEP_ADDRESS: Address = Address(0xFF10);
+    #[allow(dead_code)]
     pub const APU_NR11_CHANNEL_TIMER_ADDRESS: Address = Address(0xFF11);
+    #[allow(dead_code)]
     pub const APU_NR12_CHANNEL_VOLUME_ADDRESS: Address = Address(0xFF12);
+    #[allow(dead_code)]
     pub const APU_NR13_CHANNEL_FREQUENCY_ADDRESS: Address = Address(0xFF13);
+    #[allow(dead_code)]
     pub const APU_NR14_CHANNEL_CONTROL_ADDRESS: Address = Address(0xFF14);
+    #[allow(dead_code)]
     pub const APU_PULSE_CHANNEL_1_RANGE: AddressRangeInclusive = Self::APU_NR10_CHANNEL_SWEEP_ADDRESS..=Self::APU_NR14_CHANNEL_CONTROL_ADDRESS;
+    #[allow(dead_code)]
     pub const APU_NR21_CHANNEL_TIMER_ADDRESS: Address = Address(0xFF16);
+    #[allow(dead_code)]
     pub const APU_NR22_CHANNEL_VOLUME_ADDRESS: Address = Address(0xFF17);
+    #[allow(dead_code)]
     pub const APU_NR23_CHANNEL_FREQUENCY_ADDRESS: Address = Address(0xFF18);
+    #[allow(dead_code)]
     pub const APU_NR24_CHANNEL_CONTROL_ADDRESS: Address = Address(0xFF19);
+    #[allow(dead_code)]
     pub const APU_PULSE_CHANNEL_2_RANGE: AddressRangeInclusive = Self::APU_NR21_CHANNEL_TIMER_ADDRESS..=Self::APU_NR24_CHANNEL_CONTROL_ADDRESS;
 }
 
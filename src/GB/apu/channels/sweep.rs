@@ -1,10 +1,12 @@
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
 #[repr(u8)]
+#[allow(dead_code)]
 pub enum SweepDirection {
     Down = 0,
     Up = 1,
 }
 
+#[allow(dead_code)]
 pub struct Sweep {
     period: u8,
     negate: bool,
@@ -14,6 +16,7 @@ pub struct Sweep {
 }
 
 impl Sweep {
+    #[allow(dead_code)]
     pub fn new() -> Self {
         Self {
             period: 0,
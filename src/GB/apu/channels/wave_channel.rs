@@ -1,4 +1,5 @@
 use crate::GB::types::address::{Address, AddressRangeInclusive};
+use crate::GB::save_state::{SaveState, SaveStateError, StateReader, StateWriter};
 
 pub struct WaveChannel {
     nr30: u8,  // DAC Enabled
@@ -10,10 +11,15 @@ pub struct WaveChannel {
 }
 
 impl WaveChannel {
+    #[allow(dead_code)]
     pub const APU_NR30_CHANNEL_DAC_ADDRESS: Address = Address(0xFF1A);
+    #[allow(dead_code)]
     pub const APU_NR31_CHANNEL_TIMER_ADDRESS: Address = Address(0xFF1B);
+    #[allow(dead_code)]
     pub const APU_NR32_CHANNEL_OUTPUT_LEVEL_ADDRESS: Address = Address(0xFF1C);
+    #[allow(dead_code)]
     pub const APU_NR34_CHANNEL_PERIOD_LOW_ADDRESS: Address = Address(0xFF1D);
+    #[allow(dead_code)]
     pub const APU_NR34_CHANNEL_PERIOD_HIGH_ADDRESS: Address = Address(0xFF1E);
     pub const APU_WAVE_PATTERN_START_ADDRESS: Address = Address(0xFF30);
     pub const APU_WAVE_PATTERN_END_ADDRESS: Address = Address(0xFF3F);
@@ -33,52 +39,78 @@ impl WaveChannel {
     }
 
     #[inline]
+    #[allow(dead_code)]
     pub fn nr30(&self) -> u8 {
         self.nr30
     }
 
     #[inline]
+    #[allow(dead_code)]
     pub fn nr31(&self) -> u8 {
         self.nr31
     }
 
     #[inline]
+    #[allow(dead_code)]
     pub fn nr32(&self) -> u8 {
         self.nr32
     }
 
     #[inline]
+    #[allow(dead_code)]
     pub fn nr33(&self) -> u8 {
         self.nr33
     }
 
     #[inline]
+    #[allow(dead_code)]
     pub fn nr34(&self) -> u8 {
         self.nr34
     }
 
     #[inline]
+    #[allow(dead_code)]
     pub fn set_nr30(&mut self, value: u8) {
         self.nr34 = value;
     }
 
     #[inline]
+    #[allow(dead_code)]
     pub fn set_nr31(&mut self, value: u8) {
         self.nr34 = value;
     }
 
     #[inline]
+    #[allow(dead_code)]
     pub fn set_nr32(&mut self, value: u8) {
         self.nr34 = value;
     }
 
     #[inline]
+    #[allow(dead_code)]
     pub fn set_nr33(&mut self, value: u8) {
         self.nr34 = value;
     }
 
     #[inline]
+    #[allow(dead_code)]
     pub fn set_nr34(&mut self, value: u8) {
         self.nr34 = value;
     }
 }
+
+impl SaveState for WaveChannel {
+    fn save_state(&self, state: &mut StateWriter) {
+        for register in [self.nr30, self.nr31, self.nr32, self.nr33, self.nr34] {
+            state.write_u8(register);
+        }
+        state.write_bytes(&self.ram);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        for register in [&mut self.nr30, &mut self.nr31, &mut self.nr32, &mut self.nr33, &mut self.nr34] {
+            *register = state.read_u8()?;
+        }
+        state.read_bytes_into(&mut self.ram, "wave RAM")
+    }
+}
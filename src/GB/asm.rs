@@ -0,0 +1,437 @@
+use std::collections::HashMap;
+use crate::GB::cpu::instructions::{OPCODES, OPCODES_CB};
+use crate::GB::types::Byte;
+
+/// Errors on source assembling
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AsmErrorKind {
+    /// No instruction with the given mnemonic
+    UnknownMnemonic(String),
+    /// Operands don't match any encoding of the instruction
+    InvalidOperands(String),
+    /// Expression can't be parsed
+    InvalidExpression(String),
+    UndefinedLabel(String),
+    DuplicateLabel(String),
+    /// Value doesn't fit the operand size (value)
+    OutOfRange(i64),
+}
+
+/// Assembling error, with the 1-based source line where it occurred
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AsmError {
+    pub line: usize,
+    pub kind: AsmErrorKind,
+}
+
+impl std::fmt::Display for AsmError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Line {}: ", self.line)?;
+        match &self.kind {
+            AsmErrorKind::UnknownMnemonic(mnemonic) => write!(f, "unknown mnemonic \"{}\"", mnemonic),
+            AsmErrorKind::InvalidOperands(instruction) => write!(f, "invalid operands for \"{}\"", instruction),
+            AsmErrorKind::InvalidExpression(expression) => write!(f, "invalid expression \"{}\"", expression),
+            AsmErrorKind::UndefinedLabel(label) => write!(f, "undefined label \"{}\"", label),
+            AsmErrorKind::DuplicateLabel(label) => write!(f, "label \"{}\" already defined", label),
+            AsmErrorKind::OutOfRange(value) => write!(f, "value {} out of range", value),
+        }
+    }
+}
+
+impl std::error::Error for AsmError {}
+
+/// Assembled program: code placed at `origin` and the address of every label
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Program {
+    pub origin: u16,
+    pub bytes: Vec<Byte>,
+    pub labels: HashMap<String, u16>,
+}
+
+impl Program {
+    pub fn label(&self, label: &str) -> Option<u16> {
+        self.labels.get(label).copied()
+    }
+}
+
+/// Encoding of an instruction, from its opcode tables name (e.g. "LD A, [HL+]", "JR NZ, e8")
+struct Encoding {
+    operands: Vec<&'static str>,
+    prefixed: bool,
+    opcode: u8,
+    size: u8,
+}
+
+lazy_static! {
+    /// Encodings by mnemonic, built from the same tables used by the CPU and the disassembler
+    static ref ENCODINGS: HashMap<&'static str, Vec<Encoding>> = {
+        let mut encodings: HashMap<&'static str, Vec<Encoding>> = HashMap::new();
+        let instructions = OPCODES.iter().flatten().filter(|instruction| instruction.opcode != 0xCB).map(|instruction| (instruction, false))
+            .chain(OPCODES_CB.iter().flatten().map(|instruction| (instruction, true)));
+        for (instruction, prefixed) in instructions {
+            let (mnemonic, operands) = instruction.name.split_once(' ').unwrap_or((instruction.name, ""));
+            encodings.entry(mnemonic).or_default().push(Encoding {
+                operands: operands.split(", ").filter(|operand| !operand.is_empty()).collect(),
+                prefixed,
+                opcode: instruction.opcode,
+                size: instruction.size.max(if prefixed { 2 } else { 1 }),
+            });
+        }
+        encodings
+    };
+}
+
+const REGISTERS: [&str; 12] = ["A", "B", "C", "D", "E", "H", "L", "AF", "BC", "DE", "HL", "SP"];
+const CONDITIONS: [&str; 3] = ["Z", "NZ", "NC"]; // C is a register too
+/// Mnemonics whose `A` destination can be omitted (`xor b` is `xor a, b`)
+const IMPLICIT_A_MNEMONICS: [&str; 8] = ["ADD", "ADC", "SUB", "SBC", "AND", "XOR", "OR", "CP"];
+
+/// Assemble SM83 source in RGBDS syntax placing it at the given address.
+/// Source holds one instruction or directive per line (`db`, `dw`, `ds count[, fill]`), optionally preceded by
+/// a `label:` (`.label:` is local to the previous global label) and followed by a `;` comment.
+/// Expressions are numbers (`$FF`, `0xFF`, `%1010`, `&17` octal, decimal) and labels, joined by `+` and `-`.
+pub fn assemble(source: &str, origin: u16) -> Result<Program, AsmError> {
+    let mut program = Program { origin, ..Default::default() };
+    // First pass only places labels: unresolved labels are allowed and encodings don't depend on them
+    assemble_pass(source, &mut program, false)?;
+    program.bytes.clear();
+    assemble_pass(source, &mut program, true)?;
+    Ok(program)
+}
+
+fn assemble_pass(source: &str, program: &mut Program, resolve: bool) -> Result<(), AsmError> {
+    let mut scope = String::new();
+    for (i, line) in source.lines().enumerate() {
+        let error = |kind| AsmError { line: i + 1, kind };
+        let mut line = line.split(';').next().unwrap_or("").trim();
+        let address = program.origin.wrapping_add(program.bytes.len() as u16);
+
+        // Label definition
+        if let Some((label, rest)) = line.split_once(':').filter(|(label, _)| is_identifier(label.trim())) {
+            let label = scoped_label(&mut scope, label.trim(), true);
+            if !resolve && program.labels.insert(label.clone(), address).is_some() {
+                return Err(error(AsmErrorKind::DuplicateLabel(label)));
+            }
+            line = rest.trim_start_matches(':').trim();
+        }
+        if line.is_empty() {
+            continue;
+        }
+
+        let context = Context { labels: &program.labels, scope: &scope, address, resolve };
+        let bytes = assemble_line(line, &context).map_err(error)?;
+        program.bytes.extend_from_slice(&bytes);
+    }
+    Ok(())
+}
+
+/// Full name of a label: local ones (starting with '.') are appended to the current global label
+fn scoped_label(scope: &mut String, label: &str, definition: bool) -> String {
+    if label.starts_with('.') {
+        return format!("{}{}", scope, label);
+    }
+    if definition {
+        *scope = label.to_string();
+    }
+    label.to_string()
+}
+
+fn is_identifier(name: &str) -> bool {
+    let name = name.strip_prefix('.').unwrap_or(name);
+    name.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+        && !REGISTERS.contains(&name.to_uppercase().as_str())
+}
+
+/// State needed to evaluate a line
+struct Context<'a> {
+    labels: &'a HashMap<String, u16>,
+    scope: &'a str,
+    address: u16, // Address of the line
+    resolve: bool, // Labels must be defined
+}
+
+impl Context<'_> {
+    /// Value of an expression, None if it refers to a label not defined yet (only before resolving)
+    fn evaluate(&self, expression: &str) -> Result<Option<i64>, AsmErrorKind> {
+        let invalid = || AsmErrorKind::InvalidExpression(expression.to_string());
+        let mut value = 0i64;
+        let mut known = true;
+        let mut rest = expression.trim();
+        let mut sign = 1;
+        if let Some(stripped) = rest.strip_prefix('-') {
+            sign = -1;
+            rest = stripped.trim_start();
+        }
+        loop {
+            let end = rest.find(['+', '-']).unwrap_or(rest.len());
+            let term = rest[..end].trim();
+            match self.term(term)? {
+                Some(term) => value += sign * term,
+                None => known = false,
+            }
+            if end == rest.len() {
+                break;
+            }
+            sign = if rest.as_bytes()[end] == b'+' { 1 } else { -1 };
+            rest = &rest[end + 1..];
+            if rest.trim().is_empty() {
+                return Err(invalid());
+            }
+        }
+        Ok(known.then_some(value))
+    }
+
+    fn term(&self, term: &str) -> Result<Option<i64>, AsmErrorKind> {
+        let invalid = || AsmErrorKind::InvalidExpression(term.to_string());
+        let (digits, radix) = match term.as_bytes().first() {
+            Some(b'$') => (&term[1..], 16),
+            Some(b'%') => (&term[1..], 2),
+            Some(b'&') => (&term[1..], 8),
+            Some(b'0') if term.starts_with("0x") || term.starts_with("0X") => (&term[2..], 16),
+            Some(c) if c.is_ascii_digit() => (term, 10),
+            _ if term == "@" => return Ok(Some(self.address as i64)),
+            _ if is_identifier(term) => {
+                let label = scoped_label(&mut self.scope.to_string(), term, false);
+                return match self.labels.get(&label) {
+                    Some(address) => Ok(Some(*address as i64)),
+                    None if self.resolve => Err(AsmErrorKind::UndefinedLabel(label)),
+                    None => Ok(None),
+                };
+            }
+            _ => return Err(invalid()),
+        };
+        i64::from_str_radix(&digits.replace('_', ""), radix).map(Some).map_err(|_| invalid())
+    }
+
+    /// Known value of an expression in the given range, 0 if still unknown
+    fn value_in(&self, expression: &str, range: std::ops::RangeInclusive<i64>) -> Result<i64, AsmErrorKind> {
+        match self.evaluate(expression)? {
+            Some(value) if range.contains(&value) => Ok(value),
+            Some(value) => Err(AsmErrorKind::OutOfRange(value)),
+            None => Ok(0),
+        }
+    }
+}
+
+fn assemble_line(line: &str, context: &Context) -> Result<Vec<Byte>, AsmErrorKind> {
+    let (mnemonic, operands) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    let mnemonic = mnemonic.to_uppercase();
+    let mut operands: Vec<&str> = operands.split(',').map(|operand| operand.trim()).filter(|operand| !operand.is_empty()).collect();
+
+    match mnemonic.as_str() {
+        "DB" => {
+            return operands.iter()
+                .map(|operand| context.value_in(operand, -0x80..=0xFF).map(|value| value as u8))
+                .collect();
+        }
+        "DW" => {
+            return operands.iter()
+                .map(|operand| context.value_in(operand, -0x8000..=0xFFFF).map(|value| (value as u16).to_le_bytes()))
+                .collect::<Result<Vec<[u8; 2]>, _>>()
+                .map(|words| words.concat());
+        }
+        "DS" => {
+            let count = match operands.first() {
+                Some(count) => context.evaluate(count)?.ok_or_else(|| AsmErrorKind::UndefinedLabel(count.to_string()))?,
+                None => return Err(AsmErrorKind::InvalidOperands(line.to_string())),
+            };
+            let count = usize::try_from(count).map_err(|_| AsmErrorKind::OutOfRange(count))?;
+            let fill = operands.get(1).map(|fill| context.value_in(fill, -0x80..=0xFF)).transpose()?.unwrap_or(0);
+            return Ok(vec![fill as u8; count]);
+        }
+        // RGBDS encodes STOP with a $00 padding byte
+        "STOP" if operands.is_empty() => operands.push("0"),
+        _ => {}
+    }
+
+    let mut mnemonics = vec![mnemonic.clone()];
+    if mnemonic == "LD" {
+        mnemonics.push("LDH".to_string()); // `ld a, [c]` alias
+    }
+    if IMPLICIT_A_MNEMONICS.contains(&mnemonic.as_str()) && operands.len() == 1 {
+        operands.insert(0, "a");
+    }
+
+    let mut known_mnemonic = false;
+    for mnemonic in mnemonics.iter() {
+        let Some(encodings) = ENCODINGS.get(mnemonic.as_str()) else {
+            continue;
+        };
+        known_mnemonic = true;
+        for encoding in encodings.iter().filter(|encoding| encoding.operands.len() == operands.len()) {
+            if let Some(bytes) = encode(encoding, &operands, context)? {
+                return Ok(bytes);
+            }
+        }
+    }
+    if known_mnemonic {
+        Err(AsmErrorKind::InvalidOperands(line.to_string()))
+    } else {
+        Err(AsmErrorKind::UnknownMnemonic(mnemonic))
+    }
+}
+
+/// Normalized fixed operand (register, condition or register indirect), None for expressions
+fn fixed_operand(operand: &str) -> Option<String> {
+    let operand: String = operand.to_uppercase().chars().filter(|c| !c.is_whitespace()).collect();
+    let operand = match operand.as_str() {
+        "[HLI]" => "[HL+]".to_string(),
+        "[HLD]" => "[HL-]".to_string(),
+        "[$FF00+C]" => "[C]".to_string(),
+        _ => operand,
+    };
+    let register = operand.strip_prefix('[').and_then(|operand| operand.strip_suffix(']')).unwrap_or(&operand);
+    let register = register.trim_end_matches(['+', '-']);
+    (REGISTERS.contains(&register) || CONDITIONS.contains(&register)).then_some(operand)
+}
+
+/// Bytes of the instruction if operands match the encoding
+fn encode(encoding: &Encoding, operands: &[&str], context: &Context) -> Result<Option<Vec<Byte>>, AsmErrorKind> {
+    let mut bytes = Vec::with_capacity(encoding.size as usize);
+    if encoding.prefixed {
+        bytes.push(0xCB);
+    }
+    bytes.push(encoding.opcode);
+    let next_address = context.address as i64 + encoding.size as i64;
+
+    for (pattern, operand) in encoding.operands.iter().zip(operands) {
+        let fixed = fixed_operand(operand);
+        let indirect = operand.strip_prefix('[').and_then(|operand| operand.strip_suffix(']'));
+        // Neither a register, a memory operand nor an `sp + e8` offset
+        let expression = fixed.is_none() && indirect.is_none() && !operand.to_uppercase().starts_with("SP");
+        match *pattern {
+            "imm8" if expression => bytes.push(context.value_in(operand, -0x80..=0xFF)? as u8),
+            "imm16" if expression => bytes.extend_from_slice(&(context.value_in(operand, -0x8000..=0xFFFF)? as u16).to_le_bytes()),
+            "e8" if expression && encoding.opcode != 0xE8 => {
+                // Relative jump, written as the absolute target
+                let offset = match context.evaluate(operand)? {
+                    Some(target) => target - next_address,
+                    None => 0,
+                };
+                if !(-0x80..=0x7F).contains(&offset) {
+                    return Err(AsmErrorKind::OutOfRange(offset));
+                }
+                bytes.push(offset as u8);
+            }
+            "e8" if expression => bytes.push(context.value_in(operand, -0x80..=0x7F)? as u8),
+            "[imm16]" | "[a16]" => match indirect {
+                Some(address) if fixed.is_none() => bytes.extend_from_slice(&(context.value_in(address, 0..=0xFFFF)? as u16).to_le_bytes()),
+                _ => return Ok(None),
+            },
+            "[imm8]" => match indirect {
+                Some(address) if fixed.is_none() => {
+                    let address = context.value_in(address, 0..=0xFFFF)?;
+                    match address {
+                        0xFF00..=0xFFFF | 0x00..=0xFF => bytes.push(address as u8),
+                        _ => return Err(AsmErrorKind::OutOfRange(address)),
+                    }
+                }
+                _ => return Ok(None),
+            },
+            "SP + e8" => {
+                let operand: String = operand.to_uppercase().chars().filter(|c| !c.is_whitespace()).collect();
+                let Some(offset) = operand.strip_prefix("SP") else {
+                    return Ok(None);
+                };
+                let offset = if offset.is_empty() { "0" } else { offset.strip_prefix('+').unwrap_or(offset) };
+                bytes.push(context.value_in(offset, -0x80..=0x7F)? as u8);
+            }
+            pattern if pattern.starts_with('$') || pattern.as_bytes()[0].is_ascii_digit() => {
+                // RST vector or bit number, constants required
+                let expected = context.term(pattern)?;
+                if fixed.is_some() || context.evaluate(operand)? != expected {
+                    return Ok(None);
+                }
+            }
+            pattern => {
+                if fixed.as_deref() != Some(pattern) {
+                    return Ok(None);
+                }
+            }
+        }
+    }
+    Ok(Some(bytes))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::GB::asm::{assemble, AsmError, AsmErrorKind};
+    use crate::GB::cpu::instructions::{OPCODES, OPCODES_CB};
+    use crate::GB::disasm::decode;
+
+    fn bytes(source: &str) -> Vec<u8> {
+        assemble(source, 0x0150).unwrap().bytes
+    }
+
+    #[test]
+    fn assemble_instructions() {
+        assert_eq!(bytes("nop"), vec![0x00]);
+        assert_eq!(bytes("ld a, $42"), vec![0x3E, 0x42]);
+        assert_eq!(bytes("LD BC, 0x1234"), vec![0x01, 0x34, 0x12]);
+        assert_eq!(bytes("ld a, [hl+]\nld [hld], a"), vec![0x2A, 0x32]);
+        assert_eq!(bytes("ld [$C000], a\nld a, [$FF44]"), vec![0xEA, 0x00, 0xC0, 0xFA, 0x44, 0xFF]);
+        assert_eq!(bytes("ldh [$FF40], a\nldh a, [$44]\nld a, [c]\nldh [$FF00+c], a\nldh [$FF00 + $80], a"), vec![0xE0, 0x40, 0xF0, 0x44, 0xF2, 0xE2, 0xE0, 0x80]);
+        assert_eq!(bytes("ld hl, sp - 2\nld hl, sp+5\nadd sp, -1"), vec![0xF8, 0xFE, 0xF8, 0x05, 0xE8, 0xFF]);
+        assert_eq!(bytes("xor a\ncp a, %1010\nadd hl, de"), vec![0xAF, 0xFE, 0x0A, 0x19]);
+        assert_eq!(bytes("bit 7, [hl]\nswap a\nset 0, b"), vec![0xCB, 0x7E, 0xCB, 0x37, 0xCB, 0xC0]);
+        assert_eq!(bytes("rst $38\nstop\nhalt\njp hl"), vec![0xFF, 0x10, 0x00, 0x76, 0xE9]);
+        assert_eq!(bytes("ld [$D000], sp\nret nc\njp c, $0150"), vec![0x08, 0x00, 0xD0, 0xD0, 0xDA, 0x50, 0x01]);
+    }
+
+    #[test]
+    fn assemble_labels_and_directives() {
+        let program = assemble("
+            Main:
+                ld hl, Data      ; Forward reference
+            .loop:
+                dec a
+                jr nz, .loop
+                call Sub
+                jr Main
+            Sub: ret
+            Data::
+                db $01, 2, -1
+                dw Data + 1, $BEEF
+                ds 3, $FF
+        ", 0x0150).unwrap();
+        assert_eq!(program.label("Main"), Some(0x0150));
+        assert_eq!(program.label("Main.loop"), Some(0x0153));
+        assert_eq!(program.label("Data"), Some(0x015C));
+        assert_eq!(program.bytes, vec![
+            0x21, 0x5C, 0x01, // ld hl, Data
+            0x3D,             // dec a
+            0x20, 0xFD,       // jr nz, .loop
+            0xCD, 0x5B, 0x01, // call Sub
+            0x18, 0xF5,       // jr Main
+            0xC9,             // ret
+            0x01, 0x02, 0xFF,
+            0x5D, 0x01, 0xEF, 0xBE,
+            0xFF, 0xFF, 0xFF,
+        ]);
+    }
+
+    #[test]
+    fn report_errors() {
+        let error = |source: &str| assemble(source, 0).unwrap_err();
+        assert_eq!(error("nop\nfoo a"), AsmError { line: 2, kind: AsmErrorKind::UnknownMnemonic("FOO".to_string()) });
+        assert_eq!(error("ld a, [bc+]").kind, AsmErrorKind::InvalidOperands("ld a, [bc+]".to_string()));
+        assert_eq!(error("ld [hl], [hl]").kind, AsmErrorKind::InvalidOperands("ld [hl], [hl]".to_string()));
+        assert_eq!(error("jp Nowhere").kind, AsmErrorKind::UndefinedLabel("Nowhere".to_string()));
+        assert_eq!(error("x:\nx:").kind, AsmErrorKind::DuplicateLabel("x".to_string()));
+        assert_eq!(error("ld a, $100").kind, AsmErrorKind::OutOfRange(0x100));
+        assert_eq!(error("jr $0200").kind, AsmErrorKind::OutOfRange(0x1FE));
+        assert_eq!(error("bit 8, a").kind, AsmErrorKind::InvalidOperands("bit 8, a".to_string()));
+    }
+
+    #[test]
+    fn disassembly_assembles_back() {
+        let instructions = OPCODES.iter().flatten().filter(|instruction| instruction.opcode != 0xCB).map(|instruction| vec![instruction.opcode])
+            .chain(OPCODES_CB.iter().flatten().map(|instruction| vec![0xCB, instruction.opcode]));
+        for mut bytes in instructions {
+            bytes.extend_from_slice(&[0x00, 0x12][..decode(&[bytes[0], 0, 0], 0).unwrap().size() as usize - bytes.len()]);
+            let instruction = decode(&bytes, 0x4000).unwrap();
+            assert_eq!(assemble(&instruction.to_string(), 0x4000).map(|program| program.bytes), Ok(bytes), "{}", instruction);
+        }
+    }
+}
@@ -1,10 +1,11 @@
 mod bus_device;
 
-pub(crate) use bus_device::{BusDevice, MmioDevice, MemoryDevice};
+pub(crate) use bus_device::BusDevice;
 use crate::GB::memory::wram::WRAM;
 use crate::GB::memory::hram::HRAM;
 use crate::GB::cpu::registers::interrupt_registers::InterruptRegisters;
 use crate::GB::apu::apu_mmio::ApuMmio;
+use crate::GB::BIOS::BIOS;
 use crate::GB::cartridge::Cartridge;
 use crate::GB::cpu::cpu_mmio::CpuMmio;
 use crate::GB::dma::DMA;
@@ -13,28 +14,37 @@ use crate::GB::joypad::Joypad;
 use crate::GB::memory::oam_memory::OamMemory;
 use crate::GB::memory::vram::VRAM;
 use crate::GB::ppu::ppu_mmio::PpuMmio;
+use crate::GB::serial::Serial;
+use crate::GB::serial::serial_mmio::SerialMmio;
+use crate::GB::timer::TimerRegisters;
 use crate::GB::types::address::Address;
 use crate::GB::types::Byte;
 
 pub struct MmioContextWrite<'a> {
     pub cpu_mmio: &'a mut CpuMmio,
+    pub bios_mmio: &'a mut BIOS,
     pub rom_mmio: &'a mut Option<Cartridge>,
     pub ppu_mmio: &'a mut PpuMmio,
     pub apu_mmio: &'a mut ApuMmio,
     pub dma_mmio: &'a mut DmaMmio,
     pub oam_mmio: &'a mut OamMemory,
     pub wram_mmio: &'a mut WRAM,
+    pub timer_mmio: &'a mut TimerRegisters,
+    pub serial_mmio: &'a mut SerialMmio,
     pub joypad: &'a mut Joypad,
 }
 
 pub struct MmioContextRead<'a> {
     pub cpu_mmio: &'a CpuMmio,
+    pub bios_mmio: &'a BIOS,
     pub rom_mmio: &'a Option<Cartridge>,
     pub ppu_mmio: &'a PpuMmio,
     pub apu_mmio: &'a ApuMmio,
     pub dma_mmio: &'a DmaMmio,
     pub oam_mmio: &'a OamMemory,
     pub wram_mmio: &'a WRAM,
+    pub timer_mmio: &'a TimerRegisters,
+    pub serial_mmio: &'a SerialMmio,
     pub joypad: &'a Joypad,
 }
 
@@ -42,12 +52,15 @@ impl<'a> MmioContextWrite<'a> {
     pub fn as_read(&'a self) -> MmioContextRead<'a> {
         MmioContextRead {
             cpu_mmio: &self.cpu_mmio,
+            bios_mmio: &self.bios_mmio,
             rom_mmio: &self.rom_mmio,
             ppu_mmio: &self.ppu_mmio,
             apu_mmio: &self.apu_mmio,
             dma_mmio: &self.dma_mmio,
             oam_mmio: &self.oam_mmio,
             wram_mmio: &self.wram_mmio,
+            timer_mmio: &self.timer_mmio,
+            serial_mmio: &self.serial_mmio,
             joypad: &self.joypad,
         }
     }
@@ -59,6 +72,12 @@ impl<'a> From<&'a MmioContextWrite<'a>> for MmioContextRead<'a> {
     }
 }
 
+/// Memory accesses driven by the CPU
+pub trait MemoryBus {
+    fn read(&self, ctx: &MmioContextRead, address: Address) -> Byte;
+    fn write(&mut self, ctx: &mut MmioContextWrite, address: Address, data: Byte);
+}
+
 pub struct Bus {}
 
 impl Bus {
@@ -70,6 +89,9 @@ impl Bus {
 impl Bus {
     pub fn read(&self, ctx: &MmioContextRead, address: Address) -> Byte {
         match address {
+            address if ctx.bios_mmio.is_mapped() && BIOS::BIOS_ADDRESS_RANGE.contains(&address) => {
+                ctx.bios_mmio.read(address)
+            }
             address if Cartridge::CART_ROM_RANGE_ADDRESS.contains(&address) => {
                 match ctx.rom_mmio.as_ref() {
                     None => {
@@ -120,6 +142,15 @@ impl Bus {
             Joypad::JOYPAD_REGISTER_ADDRESS => {
                 ctx.joypad.read(address)
             }
+            BIOS::BANK_REGISTER_ADDRESS => {
+                ctx.bios_mmio.read(address)
+            }
+            address if TimerRegisters::TIMER_REGISTERS_RANGE.contains(&address) => {
+                ctx.timer_mmio.read(address)
+            }
+            address if Serial::SERIAL_REGISTERS_RANGE.contains(&address) => {
+                ctx.serial_mmio.read(address)
+            }
             _ => 0xFF//todo!("Implement all other ranges"),
         }
     }
@@ -172,11 +203,30 @@ impl Bus {
             Joypad::JOYPAD_REGISTER_ADDRESS => {
                 ctx.joypad.write(address, data)
             }
+            BIOS::BANK_REGISTER_ADDRESS => {
+                ctx.bios_mmio.write(address, data)
+            }
+            address if TimerRegisters::TIMER_REGISTERS_RANGE.contains(&address) => {
+                ctx.timer_mmio.write(address, data)
+            }
+            address if Serial::SERIAL_REGISTERS_RANGE.contains(&address) => {
+                ctx.serial_mmio.write(address, data)
+            }
             _ => ()//todo!("Implement all other ranges"),
         }
     }
 }
 
+impl MemoryBus for Bus {
+    fn read(&self, ctx: &MmioContextRead, address: Address) -> Byte {
+        Bus::read(self, ctx, address)
+    }
+
+    fn write(&mut self, ctx: &mut MmioContextWrite, address: Address, data: Byte) {
+        Bus::write(self, ctx, address, data)
+    }
+}
+
 impl Default for Bus {
     fn default() -> Self {
         Self::new()
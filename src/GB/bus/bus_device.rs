@@ -5,6 +5,3 @@ pub trait BusDevice {
     fn read(&self, address: Address) -> Byte;
     fn write(&mut self, address: Address, data: Byte);
 }
-
-pub trait MmioDevice: BusDevice {}
-pub trait MemoryDevice: BusDevice {}
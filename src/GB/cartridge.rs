@@ -1,3 +1,4 @@
+#[allow(dead_code)] // Full header and MBC register map, not every address is referenced
 pub mod addresses;
 
 #[cfg(test)]
@@ -7,21 +8,21 @@ pub mod controller;
 
 use std::io::prelude::*;
 use std::fs::File;
+use std::path::PathBuf;
 use crate::GB::cartridge::header::RomHeader;
 // use crate::GB::memory::addresses::{EXTERNAL_RAM_ADDRESS, EXTERNAL_RAM_LAST_ADDRESS, ROM_BANK_0_ADDRESS, ROM_BANK_0_LAST_ADDRESS, ROM_BANK_1_ADDRESS, ROM_BANK_1_LAST_ADDRESS};
 use controller::{CartridgeControllerType, RomController};
 use crate::GB::bus::BusDevice;
+use crate::GB::save_state::{SaveState, SaveStateError, StateReader, StateWriter};
 use crate::GB::types::address::{Address, AddressRangeInclusive};
 use crate::GB::types::Byte;
 
 pub struct Cartridge {
     rom: Box<dyn RomController>,
     rom_path: String,
+    dirty: bool, // Battery-backed RAM written since last save
 }
 
-/// Alias name for cartridge type, as it is commonly known as ROM
-pub type ROM = Cartridge;
-
 impl Cartridge {
     pub const CART_ROM_START_ADDRESS: Address = Address(0x0);
     pub const CART_ROM_END_ADDRESS: Address = Address(0x7FFF);
@@ -31,19 +32,53 @@ impl Cartridge {
     pub const CART_RAM_RANGE_ADDRESS: AddressRangeInclusive = Self::CART_RAM_START_ADDRESS..=Self::CART_RAM_END_ADDRESS;
     pub const ROM_BANK_SIZE: usize = 0x4000;
     pub const RAM_BANK_SIZE: usize = 0x2000;
+    pub const SAVE_EXTENSION: &'static str = "sav";
 
     pub fn new(file: String) -> Result<Self, std::io::Error> {
         let mut f = File::open(&file)?;
         let mut buffer = Vec::new();
         f.read_to_end(&mut buffer)?;
+        Self::new_from_vec(buffer, file)
+    }
 
-        // TODO: maybe a header should built there and then use it to build a rom item
-        // let cartridge_type: CartridgeControllerType = Self::get_cartridge_type(buffer[addresses::CARTRIDGE_TYPE]);
-        let rom = controller::Mbc1::new(f)?; // TODO: move to match sentence
+    /// Build the cartridge from a ROM dump, choosing the controller declared in its header
+    pub fn new_from_vec(data: Vec<Byte>, rom_path: String) -> Result<Self, std::io::Error> {
+        let header = RomHeader::from_rom(&data)?;
+        let controller_type = header.rom_controller_type();
+        let controller_byte = data[RomHeader::HEADER_CONTROLLER_TYPE_ADDRESS.as_usize()];
+        let rom: Box<dyn RomController> = match controller_type {
+            CartridgeControllerType::RomOnly
+            | CartridgeControllerType::RomRam => Box::new(controller::RomOnly::new(data, false)?),
+            CartridgeControllerType::RomRamBattery => Box::new(controller::RomOnly::new(data, true)?),
+            CartridgeControllerType::Mbc1
+            | CartridgeControllerType::Mbc1Ram
+            | CartridgeControllerType::Mbc1RamBattery => Box::new(controller::Mbc1::new(data)?),
+            CartridgeControllerType::Mbc2 => Box::new(controller::Mbc2::new(data, false)?),
+            CartridgeControllerType::Mbc2Battery => Box::new(controller::Mbc2::new(data, true)?),
+            CartridgeControllerType::Mbc3
+            | CartridgeControllerType::Mbc3Ram => Box::new(controller::Mbc3::new(data, false, false)?),
+            CartridgeControllerType::Mbc3RamBattery => Box::new(controller::Mbc3::new(data, false, true)?),
+            CartridgeControllerType::Mbc3TimerBattery
+            | CartridgeControllerType::Mbc3TimerRamBattery => Box::new(controller::Mbc3::new(data, true, true)?),
+            CartridgeControllerType::Mbc5
+            | CartridgeControllerType::Mbc5Ram => Box::new(controller::Mbc5::new(data, false, false)?),
+            CartridgeControllerType::Mbc5RamBattery => Box::new(controller::Mbc5::new(data, false, true)?),
+            CartridgeControllerType::Mbc5Rumble
+            | CartridgeControllerType::Mbc5RumbleRam => Box::new(controller::Mbc5::new(data, true, false)?),
+            CartridgeControllerType::Mbc5RumbleRamBattery => Box::new(controller::Mbc5::new(data, true, true)?),
+            _ => {
+                let controller_name: &str = controller_type.into();
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::Unsupported,
+                    format!("Unsupported cartridge controller {} (${:02X})", controller_name, controller_byte),
+                ));
+            }
+        };
 
         Ok(Self {
-            rom: Box::new(rom),
-            rom_path: file,
+            rom,
+            rom_path,
+            dirty: false,
         })
     }
 
@@ -60,6 +95,21 @@ impl Cartridge {
         self.header().rom_controller_type()
     }
 
+    /// Same as `cart_type`, kept for existing callers
+    pub fn get_cart_type(&self) -> CartridgeControllerType {
+        self.cart_type()
+    }
+
+    /// ROM size in bytes, as declared in the header
+    pub fn get_rom_size(&self) -> usize {
+        self.header().rom_banks() * Self::ROM_BANK_SIZE
+    }
+
+    /// External RAM size in bytes, as declared in the header
+    pub fn get_ram_size(&self) -> usize {
+        self.header().ram_banks() * Self::RAM_BANK_SIZE
+    }
+
     pub fn rom_bank(&self) -> u16 {
         self.rom.high_rom_bank_addressed()
     }
@@ -67,6 +117,62 @@ impl Cartridge {
     pub fn ram_bank(&self) -> u16 {
         self.rom.ram_bank_addressed()
     }
+
+    pub fn rumble(&self) -> bool {
+        self.rom.rumble()
+    }
+
+    pub fn has_battery(&self) -> bool {
+        self.rom.has_battery()
+    }
+
+    /// True if battery-backed RAM has been written since it was last saved or loaded
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Path of the battery save file, that is the ROM path with `.sav` extension.
+    /// None if the cartridge has no battery or it isn't backed by a file.
+    pub fn save_path(&self) -> Option<PathBuf> {
+        if !self.has_battery() || self.rom_path.is_empty() {
+            return None;
+        }
+        Some(PathBuf::from(&self.rom_path).with_extension(Self::SAVE_EXTENSION))
+    }
+
+    pub fn export_battery_ram(&self) -> Vec<Byte> {
+        self.rom.export_battery_ram()
+    }
+
+    pub fn import_battery_ram(&mut self, data: &[Byte]) {
+        self.rom.import_battery_ram(data);
+        self.dirty = false;
+    }
+
+    /// Load battery-backed RAM from the save file, if any
+    pub fn load_battery_ram(&mut self) -> Result<(), std::io::Error> {
+        let Some(path) = self.save_path() else {
+            return Ok(());
+        };
+        match std::fs::read(path) {
+            Ok(data) => {
+                self.import_battery_ram(&data);
+                Ok(())
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Write battery-backed RAM to the save file
+    pub fn save_battery_ram(&mut self) -> Result<(), std::io::Error> {
+        let Some(path) = self.save_path() else {
+            return Ok(());
+        };
+        std::fs::write(path, self.export_battery_ram())?;
+        self.dirty = false;
+        Ok(())
+    }
 }
 
 impl BusDevice for Cartridge {
@@ -75,10 +181,35 @@ impl BusDevice for Cartridge {
     }
 
     fn write(&mut self, address: Address, data: Byte) {
+        if self.has_battery() && self.rom.stores_ram_write(address) {
+            self.dirty = true;
+        }
         self.rom.write(address, data);
     }
 }
 
+impl SaveState for Cartridge {
+    /// Cartridge identity (title and global checksum) is stored before the controller state,
+    /// so that a state can't be restored on a different game
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_bytes(self.title().as_bytes());
+        state.write_u16(self.header().global_checksum());
+        self.rom.save_state(state);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        let title = state.read_bytes()?;
+        let global_checksum = state.read_u16()?;
+        if title != self.title().as_bytes() || global_checksum != self.header().global_checksum() {
+            return Err(SaveStateError::CartridgeMismatch);
+        }
+        self.rom.load_state(state)?;
+        // Restored RAM differs from the one on disk
+        self.dirty = self.has_battery();
+        Ok(())
+    }
+}
+
 impl std::fmt::Display for Cartridge {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
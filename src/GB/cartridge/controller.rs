@@ -1,10 +1,23 @@
 pub mod mbc1;
+pub mod mbc2;
+pub mod mbc3;
+pub mod mbc5;
+pub mod rom_only;
 
 pub use mbc1::Mbc1;
+pub use mbc2::Mbc2;
+pub use mbc3::Mbc3;
+pub use mbc5::Mbc5;
+pub use rom_only::RomOnly;
 use crate::GB::bus::BusDevice;
 use crate::GB::cartridge::header::RomHeader;
+use crate::GB::save_state::SaveState;
+use crate::GB::types::address::Address;
+use crate::GB::types::Byte;
 
-pub trait RomController: BusDevice {
+/// Save states cover bank registers and RAM (and any other controller state), but not ROM data
+pub trait RomController: BusDevice + SaveState {
+    #[allow(dead_code)]
     fn load(&mut self, rom_path: &str) -> Result<(), std::io::Error>;
     fn header(&self) -> &RomHeader;
 
@@ -15,9 +28,30 @@ pub trait RomController: BusDevice {
     /// This should return the effective addressed RAM bank exposed to the GB system
     fn ram_bank_addressed(&self) -> u16;
     /// This should return the internal value of ROM bank register stored in the cartridge
+    #[allow(dead_code)]
     fn rom_bank_register(&self) -> u16;
     /// This should return the internal value of RAM bank register stored in the cartridge
+    #[allow(dead_code)]
     fn ram_bank_register(&self) -> u16;
+    /// This should return true while the cartridge rumble motor is powered
+    fn rumble(&self) -> bool {
+        false
+    }
+    /// This should return true if cartridge RAM (and RTC) is kept alive by a battery
+    fn has_battery(&self) -> bool {
+        false
+    }
+    /// This should return true if a write at the given address would be stored in RAM (or RTC) by the controller
+    /// in its current state, that is not dropped because RAM is disabled or missing
+    fn stores_ram_write(&self, _address: Address) -> bool {
+        false
+    }
+    /// This should return the battery-backed state to persist (RAM followed by any extra data, like RTC)
+    fn export_battery_ram(&self) -> Vec<Byte> {
+        Vec::new()
+    }
+    /// This should restore a state previously returned by `export_battery_ram`
+    fn import_battery_ram(&mut self, _data: &[Byte]) {}
 }
 
 #[derive(Copy, Clone, Debug, Ord, PartialOrd, Eq, PartialEq)]
@@ -132,9 +166,3 @@ impl From<CartridgeControllerType> for String {
         s.to_string()
     }
 }
-
-#[derive(Clone, Debug)]
-#[repr(u8)]
-pub enum CartridgeController {
-    Mbc1(Mbc1),
-}
@@ -1,16 +1,13 @@
 use std::fs::File;
-use std::io::{Read, Seek};
 use crate::GB::bus::BusDevice;
 use crate::GB::cartridge::Cartridge;
-use super::{RomController};
+use super::{CartridgeControllerType, RomController};
 use crate::GB::cartridge::header::RomHeader;
 use crate::GB::memory::Memory;
+use crate::GB::save_state::{SaveState, SaveStateError, StateReader, StateWriter};
 use crate::GB::types::address::{Address, AddressRangeInclusive};
 use crate::GB::types::Byte;
 
-const MBC1_ROM_BANKS: usize = 80;
-pub const MBC1_RAM_BANKS: usize = 4;
-
 #[derive(Copy, Clone, Debug)]
 enum Mbc1BankMode {
     Simple,
@@ -37,8 +34,6 @@ pub struct Mbc1 {
 }
 
 impl Mbc1 {
-    pub const MBC1_MAX_ROM_BANKS: usize = 80;
-    pub const MBC1_MAX_RAM_BANKS: usize = 4;
     pub const MBC1_RAM_ENABLE_VALUE: u8 = 0xA;
     pub const MBC1_ROM_BANK_0_START: Address = Address(0x0);
     pub const MBC1_ROM_BANK_0_END: Address = Address(0x3FFF);
@@ -64,13 +59,8 @@ impl Mbc1 {
 }
 
 impl Mbc1 {
-    pub fn new(mut file: File) -> Result<Self, std::io::Error> {
-        file.rewind()?;
-        let mut rom: Vec<Byte> = Vec::new();
-        file.read_to_end(&mut rom)?;
-        let header = RomHeader::new(
-            rom[RomHeader::HEADER_START_ADDRESS.as_usize()..=RomHeader::HEADER_END_ADDRESS.as_usize()].as_array().unwrap()
-        );
+    pub fn new(rom: Vec<Byte>) -> Result<Self, std::io::Error> {
+        let header = RomHeader::from_rom(&rom)?;
 
         Ok(Self {
             rom_bank: 0,
@@ -87,6 +77,7 @@ impl Mbc1 {
     /// If the lower 5 bits are equal to 0 the selected bank is the following one (e.g. rom bank value of 0x20 selects rom bank 0x21).
     ///
     /// Idx ∈ [0, 0x7F]
+    #[allow(dead_code)]
     pub fn rom_bank(&self) -> u8 {
         let base_bank = self.rom_bank & Mbc1Mask::ROM_BANK;
         match self.banking_mode {
@@ -166,7 +157,9 @@ impl BusDevice for Mbc1 {
                 self.banking_mode = if (data & Mbc1Mask::ROM_BANK_MODE) != 0 { Mbc1BankMode::Advanced } else { Mbc1BankMode::Simple };
             }
             address if Self::MBC1_RAM_BANK_0_RANGE.contains(&address) => {
-                // TODO: allowing writing only if ram is enabled
+                if !self.ram_enabled {
+                    return;
+                }
                 let ram_bank_idx = address.as_usize() - Self::MBC1_RAM_BANK_0_START.as_usize();
                 let ram_bank;
                 match self.banking_mode {
@@ -242,4 +235,40 @@ impl RomController for Mbc1 {
     fn ram_bank_register(&self) -> u16 {
         self.ram_bank as u16
     }
+
+    #[inline]
+    fn has_battery(&self) -> bool {
+        self.header.rom_controller_type() == CartridgeControllerType::Mbc1RamBattery
+    }
+
+    fn stores_ram_write(&self, address: Address) -> bool {
+        Self::MBC1_RAM_BANK_0_RANGE.contains(&address) && self.ram_enabled
+    }
+
+    fn export_battery_ram(&self) -> Vec<Byte> {
+        self.ram.clone()
+    }
+
+    fn import_battery_ram(&mut self, data: &[Byte]) {
+        let len = data.len().min(self.ram.len());
+        self.ram[..len].copy_from_slice(&data[..len]);
+    }
+}
+
+impl SaveState for Mbc1 {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_u8(self.rom_bank);
+        state.write_u8(self.ram_bank);
+        state.write_bool(self.ram_enabled);
+        state.write_bool(matches!(self.banking_mode, Mbc1BankMode::Advanced));
+        state.write_bytes(&self.ram);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        self.rom_bank = state.read_u8()? & Mbc1Mask::ROM_BANK;
+        self.ram_bank = state.read_u8()? & Mbc1Mask::RAM_BANK;
+        self.ram_enabled = state.read_bool()?;
+        self.banking_mode = if state.read_bool()? { Mbc1BankMode::Advanced } else { Mbc1BankMode::Simple };
+        state.read_bytes_into(&mut self.ram, "cartridge RAM")
+    }
 }
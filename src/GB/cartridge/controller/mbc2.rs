@@ -0,0 +1,178 @@
+use crate::GB::bus::BusDevice;
+use crate::GB::cartridge::Cartridge;
+use super::RomController;
+use crate::GB::cartridge::header::RomHeader;
+use crate::GB::types::address::{Address, AddressRangeInclusive};
+use crate::GB::save_state::{SaveState, SaveStateError, StateReader, StateWriter};
+use crate::GB::types::Byte;
+
+struct Mbc2Mask {}
+impl Mbc2Mask {
+    const ROM_BANK: u8 = 0b_0000_1111;
+    const ENABLE_RAM: u8 = 0b_0000_1111;
+    const REGISTER_SELECT: u16 = 0x0100; // Address bit 8 selects between RAM enable and ROM bank registers
+    const RAM_NIBBLE: u8 = 0b_0000_1111;
+    const RAM_UNUSED_BITS: u8 = 0b_1111_0000; // Upper nibble is not wired and reads as 1
+}
+
+#[derive(Clone, Debug)]
+pub struct Mbc2 {
+    header: RomHeader,
+    rom_bank: u8,
+    ram_enabled: bool,
+    rom: Vec<Byte>,
+    ram: [Byte; Mbc2::MBC2_RAM_SIZE], // Built-in 512x4-bit RAM, only lower nibble is stored
+    battery: bool,
+}
+
+impl Mbc2 {
+    pub const MBC2_RAM_SIZE: usize = 512;
+    pub const MBC2_RAM_ENABLE_VALUE: u8 = 0xA;
+    pub const MBC2_ROM_BANK_0_START: Address = Address(0x0);
+    pub const MBC2_ROM_BANK_0_END: Address = Address(0x3FFF);
+    pub const MBC2_ROM_BANK_0_RANGE: AddressRangeInclusive = Self::MBC2_ROM_BANK_0_START..=Self::MBC2_ROM_BANK_0_END;
+    pub const MBC2_ROM_BANK_1_START: Address = Address(0x4000);
+    pub const MBC2_ROM_BANK_1_END: Address = Address(0x7FFF);
+    pub const MBC2_ROM_BANK_1_RANGE: AddressRangeInclusive = Self::MBC2_ROM_BANK_1_START..=Self::MBC2_ROM_BANK_1_END;
+    pub const MBC2_RAM_START: Address = Address(0xA000);
+    pub const MBC2_RAM_END: Address = Address(0xBFFF);
+    pub const MBC2_RAM_RANGE: AddressRangeInclusive = Self::MBC2_RAM_START..=Self::MBC2_RAM_END;
+    pub const MBC2_REGISTERS_START: Address = Address(0x0);
+    pub const MBC2_REGISTERS_END: Address = Address(0x3FFF);
+    pub const MBC2_REGISTERS_RANGE: AddressRangeInclusive = Self::MBC2_REGISTERS_START..=Self::MBC2_REGISTERS_END;
+}
+
+impl Mbc2 {
+    pub fn new(rom: Vec<Byte>, battery: bool) -> Result<Self, std::io::Error> {
+        let header = RomHeader::from_rom(&rom)?;
+
+        Ok(Self {
+            header,
+            rom_bank: 1,
+            ram_enabled: false,
+            rom,
+            ram: [0; Self::MBC2_RAM_SIZE],
+            battery,
+        })
+    }
+
+    #[inline]
+    fn ram_index(address: Address) -> usize {
+        // 512 bytes mirrored over the whole $A000-$BFFF area
+        (address.as_usize() - Self::MBC2_RAM_START.as_usize()) % Self::MBC2_RAM_SIZE
+    }
+}
+
+impl BusDevice for Mbc2 {
+    fn read(&self, address: Address) -> Byte {
+        match address {
+            address if Self::MBC2_ROM_BANK_0_RANGE.contains(&address) => {
+                *self.rom.get(address.as_usize()).unwrap_or(&0xFF)
+            }
+            address if Self::MBC2_ROM_BANK_1_RANGE.contains(&address) => {
+                let bank_idx = address.as_usize() - Self::MBC2_ROM_BANK_1_START.as_usize();
+                let offset = Cartridge::ROM_BANK_SIZE * self.rom_bank as usize + bank_idx;
+                self.rom[offset % self.rom.len()]
+            }
+            address if Self::MBC2_RAM_RANGE.contains(&address) => {
+                if !self.ram_enabled {
+                    return 0xFF;
+                }
+                Mbc2Mask::RAM_UNUSED_BITS | self.ram[Self::ram_index(address)]
+            }
+            _ => unreachable!()
+        }
+    }
+
+    fn write(&mut self, address: Address, data: Byte) {
+        match address {
+            address if Self::MBC2_REGISTERS_RANGE.contains(&address) => {
+                if address.0 & Mbc2Mask::REGISTER_SELECT == 0 {
+                    self.ram_enabled = (data & Mbc2Mask::ENABLE_RAM) == Self::MBC2_RAM_ENABLE_VALUE;
+                } else {
+                    let bank = data & Mbc2Mask::ROM_BANK;
+                    self.rom_bank = if bank == 0 { 1 } else { bank };
+                }
+            }
+            address if Self::MBC2_ROM_BANK_1_RANGE.contains(&address) => {}
+            address if Self::MBC2_RAM_RANGE.contains(&address) => {
+                if self.ram_enabled {
+                    self.ram[Self::ram_index(address)] = data & Mbc2Mask::RAM_NIBBLE;
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl RomController for Mbc2 {
+    fn load(&mut self, rom_path: &str) -> Result<(), std::io::Error> {
+        std::fs::File::open(rom_path).map(|_| ())
+    }
+
+    fn header(&self) -> &RomHeader {
+        &self.header
+    }
+
+    #[inline]
+    fn low_rom_bank_addressed(&self) -> u16 {
+        0
+    }
+
+    #[inline]
+    /// Return the ROM bank mapped on $4000-$7FFF. Idx ∈ [1, 0xF]
+    fn high_rom_bank_addressed(&self) -> u16 {
+        self.rom_bank as u16 % self.header.rom_banks() as u16
+    }
+
+    #[inline]
+    /// MBC2 has a single built-in RAM bank
+    fn ram_bank_addressed(&self) -> u16 {
+        0
+    }
+
+    #[inline]
+    fn rom_bank_register(&self) -> u16 {
+        self.rom_bank as u16
+    }
+
+    #[inline]
+    fn ram_bank_register(&self) -> u16 {
+        0
+    }
+
+    #[inline]
+    fn has_battery(&self) -> bool {
+        self.battery
+    }
+
+    fn stores_ram_write(&self, address: Address) -> bool {
+        Self::MBC2_RAM_RANGE.contains(&address) && self.ram_enabled
+    }
+
+    fn export_battery_ram(&self) -> Vec<Byte> {
+        self.ram.to_vec()
+    }
+
+    fn import_battery_ram(&mut self, data: &[Byte]) {
+        for (cell, byte) in self.ram.iter_mut().zip(data) {
+            *cell = byte & Mbc2Mask::RAM_NIBBLE;
+        }
+    }
+}
+
+impl SaveState for Mbc2 {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_u8(self.rom_bank);
+        state.write_bool(self.ram_enabled);
+        state.write_bytes(&self.ram);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        self.rom_bank = state.read_u8()? & Mbc2Mask::ROM_BANK;
+        self.ram_enabled = state.read_bool()?;
+        state.read_bytes_into(&mut self.ram, "cartridge RAM")?;
+        self.ram.iter_mut().for_each(|nibble| *nibble &= Mbc2Mask::RAM_NIBBLE);
+        Ok(())
+    }
+}
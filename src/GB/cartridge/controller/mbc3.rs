@@ -0,0 +1,395 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::GB::bus::BusDevice;
+use crate::GB::cartridge::Cartridge;
+use super::RomController;
+use crate::GB::cartridge::header::RomHeader;
+use crate::GB::types::address::{Address, AddressRangeInclusive};
+use crate::GB::save_state::{SaveState, SaveStateError, StateReader, StateWriter};
+use crate::GB::types::Byte;
+
+/// Time source used by MBC3 RTC, in seconds
+pub trait RtcClock {
+    fn now(&self) -> u64;
+}
+
+/// Wall-clock time source
+pub struct SystemClock;
+
+impl RtcClock for SystemClock {
+    fn now(&self) -> u64 {
+        SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+    }
+}
+
+struct Mbc3Mask {}
+impl Mbc3Mask {
+    const ROM_BANK: u8 = 0b_0111_1111;
+    const RAM_BANK: u8 = 0b_0000_0011;
+    const ENABLE_RAM: u8 = 0b_0000_1111;
+    const RTC_SECONDS: u8 = 0b_0011_1111;
+    const RTC_MINUTES: u8 = 0b_0011_1111;
+    const RTC_HOURS: u8 = 0b_0001_1111;
+    const RTC_DAY_HIGH: u8 = 0b_0000_0001;
+    const RTC_HALT: u8 = 0b_0100_0000;
+    const RTC_DAY_CARRY: u8 = 0b_1000_0000;
+}
+
+/// RTC registers - S, M, H, DL, DH
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct RtcRegisters {
+    pub seconds: u8,
+    pub minutes: u8,
+    pub hours: u8,
+    pub days: u16, // 9-bit day counter
+    pub halt: bool,
+    pub day_carry: bool,
+}
+
+impl RtcRegisters {
+    const SECONDS_PER_DAY: u64 = 86400;
+    const SAVE_SIZE: usize = 5 * 4;
+    const MAX_DAYS: u64 = 512;
+
+    fn advance(&mut self, seconds: u64) {
+        let total = self.seconds as u64
+            + self.minutes as u64 * 60
+            + self.hours as u64 * 3600
+            + self.days as u64 * Self::SECONDS_PER_DAY
+            + seconds;
+        let days = total / Self::SECONDS_PER_DAY;
+        if days >= Self::MAX_DAYS {
+            // Carry stays set 'till software resets it
+            self.day_carry = true;
+        }
+        self.days = (days % Self::MAX_DAYS) as u16;
+        self.hours = ((total % Self::SECONDS_PER_DAY) / 3600) as u8;
+        self.minutes = ((total % 3600) / 60) as u8;
+        self.seconds = (total % 60) as u8;
+    }
+
+    /// Registers as saved after RAM in battery files: S, M, H, DL, DH as 32-bit little endian values
+    fn to_save_bytes(self) -> [Byte; Self::SAVE_SIZE] {
+        let mut bytes = [0; Self::SAVE_SIZE];
+        for (i, register) in (Mbc3::RTC_SECONDS_REGISTER..=Mbc3::RTC_DAY_HIGH_REGISTER).enumerate() {
+            bytes[i * 4] = self.read(register);
+        }
+        bytes
+    }
+
+    fn from_save_bytes(bytes: &[Byte]) -> Self {
+        let mut registers = Self::default();
+        for (i, register) in (Mbc3::RTC_SECONDS_REGISTER..=Mbc3::RTC_DAY_HIGH_REGISTER).enumerate() {
+            registers.write(register, bytes[i * 4]);
+        }
+        registers
+    }
+
+    fn read(&self, register: u8) -> Byte {
+        match register {
+            Mbc3::RTC_SECONDS_REGISTER => self.seconds,
+            Mbc3::RTC_MINUTES_REGISTER => self.minutes,
+            Mbc3::RTC_HOURS_REGISTER => self.hours,
+            Mbc3::RTC_DAY_LOW_REGISTER => self.days as u8,
+            Mbc3::RTC_DAY_HIGH_REGISTER => {
+                ((self.days >> 8) as u8 & Mbc3Mask::RTC_DAY_HIGH)
+                    | if self.halt { Mbc3Mask::RTC_HALT } else { 0 }
+                    | if self.day_carry { Mbc3Mask::RTC_DAY_CARRY } else { 0 }
+            }
+            _ => 0xFF,
+        }
+    }
+
+    fn write(&mut self, register: u8, data: Byte) {
+        match register {
+            Mbc3::RTC_SECONDS_REGISTER => self.seconds = data & Mbc3Mask::RTC_SECONDS,
+            Mbc3::RTC_MINUTES_REGISTER => self.minutes = data & Mbc3Mask::RTC_MINUTES,
+            Mbc3::RTC_HOURS_REGISTER => self.hours = data & Mbc3Mask::RTC_HOURS,
+            Mbc3::RTC_DAY_LOW_REGISTER => self.days = (self.days & 0x100) | data as u16,
+            Mbc3::RTC_DAY_HIGH_REGISTER => {
+                self.days = (self.days & 0xFF) | (((data & Mbc3Mask::RTC_DAY_HIGH) as u16) << 8);
+                self.halt = data & Mbc3Mask::RTC_HALT != 0;
+                self.day_carry = data & Mbc3Mask::RTC_DAY_CARRY != 0;
+            }
+            _ => {}
+        }
+    }
+}
+
+pub struct Mbc3 {
+    header: RomHeader,
+    rom_bank: u8,
+    ram_bank: u8, // RAM bank ($00-$03) or RTC register ($08-$0C) mapped on $A000-$BFFF
+    ram_enabled: bool,
+    rom: Vec<Byte>,
+    ram: Vec<Byte>,
+    battery: bool,
+    timer: bool,
+    clock: Box<dyn RtcClock>,
+    rtc: RtcRegisters,
+    rtc_latched: RtcRegisters,
+    rtc_last_update: u64, // Clock time of the last RTC update
+    latch_value: Byte, // Last value written on latch register, $00 followed by $01 latches RTC
+}
+
+impl Mbc3 {
+    pub const MBC3_RAM_ENABLE_VALUE: u8 = 0xA;
+    pub const MBC3_ROM_BANK_0_START: Address = Address(0x0);
+    pub const MBC3_ROM_BANK_0_END: Address = Address(0x3FFF);
+    pub const MBC3_ROM_BANK_0_RANGE: AddressRangeInclusive = Self::MBC3_ROM_BANK_0_START..=Self::MBC3_ROM_BANK_0_END;
+    pub const MBC3_ROM_BANK_1_START: Address = Address(0x4000);
+    pub const MBC3_ROM_BANK_1_END: Address = Address(0x7FFF);
+    pub const MBC3_ROM_BANK_1_RANGE: AddressRangeInclusive = Self::MBC3_ROM_BANK_1_START..=Self::MBC3_ROM_BANK_1_END;
+    pub const MBC3_RAM_BANK_START: Address = Address(0xA000);
+    pub const MBC3_RAM_BANK_END: Address = Address(0xBFFF);
+    pub const MBC3_RAM_BANK_RANGE: AddressRangeInclusive = Self::MBC3_RAM_BANK_START..=Self::MBC3_RAM_BANK_END;
+    pub const MBC3_RAM_ENABLE_START: Address = Address(0x0);
+    pub const MBC3_RAM_ENABLE_END: Address = Address(0x1FFF);
+    pub const MBC3_RAM_ENABLE_RANGE: AddressRangeInclusive = Self::MBC3_RAM_ENABLE_START..=Self::MBC3_RAM_ENABLE_END;
+    pub const MBC3_ROM_BANK_SELECTOR_START: Address = Address(0x2000);
+    pub const MBC3_ROM_BANK_SELECTOR_END: Address = Address(0x3FFF);
+    pub const MBC3_ROM_BANK_SELECTOR_RANGE: AddressRangeInclusive = Self::MBC3_ROM_BANK_SELECTOR_START..=Self::MBC3_ROM_BANK_SELECTOR_END;
+    pub const MBC3_RAM_BANK_SELECTOR_START: Address = Address(0x4000);
+    pub const MBC3_RAM_BANK_SELECTOR_END: Address = Address(0x5FFF);
+    pub const MBC3_RAM_BANK_SELECTOR_RANGE: AddressRangeInclusive = Self::MBC3_RAM_BANK_SELECTOR_START..=Self::MBC3_RAM_BANK_SELECTOR_END;
+    pub const MBC3_LATCH_CLOCK_START: Address = Address(0x6000);
+    pub const MBC3_LATCH_CLOCK_END: Address = Address(0x7FFF);
+    pub const MBC3_LATCH_CLOCK_RANGE: AddressRangeInclusive = Self::MBC3_LATCH_CLOCK_START..=Self::MBC3_LATCH_CLOCK_END;
+    pub const RTC_SECONDS_REGISTER: u8 = 0x08;
+    pub const RTC_MINUTES_REGISTER: u8 = 0x09;
+    pub const RTC_HOURS_REGISTER: u8 = 0x0A;
+    pub const RTC_DAY_LOW_REGISTER: u8 = 0x0B;
+    pub const RTC_DAY_HIGH_REGISTER: u8 = 0x0C;
+    /// RTC data appended to RAM in battery files: live and latched registers plus a 64-bit UNIX timestamp
+    pub const RTC_SAVE_SIZE: usize = 2 * RtcRegisters::SAVE_SIZE + 8;
+}
+
+impl Mbc3 {
+    pub fn new(rom: Vec<Byte>, timer: bool, battery: bool) -> Result<Self, std::io::Error> {
+        Self::new_with_clock(rom, timer, battery, Box::new(SystemClock))
+    }
+
+    pub fn new_with_clock(rom: Vec<Byte>, timer: bool, battery: bool, clock: Box<dyn RtcClock>) -> Result<Self, std::io::Error> {
+        let header = RomHeader::from_rom(&rom)?;
+        let rtc_last_update = clock.now();
+
+        Ok(Self {
+            rom_bank: 1,
+            ram_bank: 0,
+            ram_enabled: false,
+            rom,
+            ram: vec![0; Cartridge::RAM_BANK_SIZE * header.ram_banks()],
+            battery,
+            timer,
+            clock,
+            rtc: RtcRegisters::default(),
+            rtc_latched: RtcRegisters::default(),
+            rtc_last_update,
+            latch_value: 0xFF,
+            header,
+        })
+    }
+
+    /// Bring RTC registers up to date with the clock source
+    fn update_rtc(&mut self) {
+        let now = self.clock.now();
+        if !self.rtc.halt {
+            self.rtc.advance(now.saturating_sub(self.rtc_last_update));
+        }
+        self.rtc_last_update = now;
+    }
+
+    fn rtc_selected(&self) -> bool {
+        (Self::RTC_SECONDS_REGISTER..=Self::RTC_DAY_HIGH_REGISTER).contains(&self.ram_bank)
+    }
+
+    fn ram_index(&self, address: Address) -> Option<usize> {
+        if self.ram.is_empty() {
+            return None;
+        }
+        let bank = (self.ram_bank & Mbc3Mask::RAM_BANK) as usize;
+        let index = bank * Cartridge::RAM_BANK_SIZE + address.as_usize() - Self::MBC3_RAM_BANK_START.as_usize();
+        Some(index % self.ram.len())
+    }
+}
+
+impl BusDevice for Mbc3 {
+    fn read(&self, address: Address) -> Byte {
+        match address {
+            address if Self::MBC3_ROM_BANK_0_RANGE.contains(&address) => {
+                *self.rom.get(address.as_usize()).unwrap_or(&0xFF)
+            }
+            address if Self::MBC3_ROM_BANK_1_RANGE.contains(&address) => {
+                let bank_idx = address.as_usize() - Self::MBC3_ROM_BANK_1_START.as_usize();
+                let offset = Cartridge::ROM_BANK_SIZE * self.rom_bank as usize + bank_idx;
+                self.rom[offset % self.rom.len()]
+            }
+            address if Self::MBC3_RAM_BANK_RANGE.contains(&address) => {
+                if !self.ram_enabled {
+                    return 0xFF;
+                }
+                if self.rtc_selected() {
+                    return if self.timer { self.rtc_latched.read(self.ram_bank) } else { 0xFF };
+                }
+                match self.ram_index(address) {
+                    Some(index) if self.ram_bank <= Mbc3Mask::RAM_BANK => self.ram[index],
+                    _ => 0xFF,
+                }
+            }
+            _ => unreachable!()
+        }
+    }
+
+    fn write(&mut self, address: Address, data: Byte) {
+        match address {
+            address if Self::MBC3_RAM_ENABLE_RANGE.contains(&address) => {
+                self.ram_enabled = (data & Mbc3Mask::ENABLE_RAM) == Self::MBC3_RAM_ENABLE_VALUE;
+            }
+            address if Self::MBC3_ROM_BANK_SELECTOR_RANGE.contains(&address) => {
+                // 7-bit ROM bank, bank 0 can't be mapped on high area
+                let bank = data & Mbc3Mask::ROM_BANK;
+                self.rom_bank = if bank == 0 { 1 } else { bank };
+            }
+            address if Self::MBC3_RAM_BANK_SELECTOR_RANGE.contains(&address) => {
+                self.ram_bank = data;
+            }
+            address if Self::MBC3_LATCH_CLOCK_RANGE.contains(&address) => {
+                if self.timer && self.latch_value == 0x00 && data == 0x01 {
+                    self.update_rtc();
+                    self.rtc_latched = self.rtc;
+                }
+                self.latch_value = data;
+            }
+            address if Self::MBC3_RAM_BANK_RANGE.contains(&address) => {
+                if !self.ram_enabled {
+                    return;
+                }
+                if self.rtc_selected() {
+                    if self.timer {
+                        self.update_rtc();
+                        self.rtc.write(self.ram_bank, data);
+                        // Written value is immediately visible on the latched registers too
+                        self.rtc_latched.write(self.ram_bank, data);
+                    }
+                    return;
+                }
+                if self.ram_bank <= Mbc3Mask::RAM_BANK {
+                    if let Some(index) = self.ram_index(address) {
+                        self.ram[index] = data;
+                    }
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl RomController for Mbc3 {
+    fn load(&mut self, rom_path: &str) -> Result<(), std::io::Error> {
+        std::fs::File::open(rom_path).map(|_| ())
+    }
+
+    fn header(&self) -> &RomHeader {
+        &self.header
+    }
+
+    #[inline]
+    fn low_rom_bank_addressed(&self) -> u16 {
+        0
+    }
+
+    #[inline]
+    /// Return the ROM bank mapped on $4000-$7FFF. Idx ∈ [1, 0x7F]
+    fn high_rom_bank_addressed(&self) -> u16 {
+        self.rom_bank as u16
+    }
+
+    #[inline]
+    /// Return the index of addressed RAM bank. Idx ∈ [0, 3]
+    fn ram_bank_addressed(&self) -> u16 {
+        (self.ram_bank & Mbc3Mask::RAM_BANK) as u16
+    }
+
+    #[inline]
+    fn rom_bank_register(&self) -> u16 {
+        self.rom_bank as u16
+    }
+
+    #[inline]
+    /// Return the value of RAM/RTC select register. Value ∈ [0, 3] ∪ [8, 0xC]
+    fn ram_bank_register(&self) -> u16 {
+        self.ram_bank as u16
+    }
+
+    #[inline]
+    fn has_battery(&self) -> bool {
+        self.battery
+    }
+
+    fn stores_ram_write(&self, address: Address) -> bool {
+        if !Self::MBC3_RAM_BANK_RANGE.contains(&address) || !self.ram_enabled {
+            return false;
+        }
+        if self.rtc_selected() {
+            return self.timer;
+        }
+        self.ram_bank <= Mbc3Mask::RAM_BANK && self.ram_index(address).is_some()
+    }
+
+    fn export_battery_ram(&self) -> Vec<Byte> {
+        let mut data = self.ram.clone();
+        if self.timer {
+            // Bring live registers up to date without touching the controller state
+            let mut rtc = self.rtc;
+            let now = self.clock.now();
+            if !rtc.halt {
+                rtc.advance(now.saturating_sub(self.rtc_last_update));
+            }
+            data.extend_from_slice(&rtc.to_save_bytes());
+            data.extend_from_slice(&self.rtc_latched.to_save_bytes());
+            data.extend_from_slice(&now.to_le_bytes());
+        }
+        data
+    }
+
+    fn import_battery_ram(&mut self, data: &[Byte]) {
+        let len = data.len().min(self.ram.len());
+        self.ram[..len].copy_from_slice(&data[..len]);
+        if self.timer && data.len() >= self.ram.len() + Self::RTC_SAVE_SIZE {
+            let rtc_data = &data[self.ram.len()..];
+            self.rtc = RtcRegisters::from_save_bytes(&rtc_data[..RtcRegisters::SAVE_SIZE]);
+            self.rtc_latched = RtcRegisters::from_save_bytes(&rtc_data[RtcRegisters::SAVE_SIZE..2 * RtcRegisters::SAVE_SIZE]);
+            let timestamp = &rtc_data[2 * RtcRegisters::SAVE_SIZE..Self::RTC_SAVE_SIZE];
+            // Time elapsed while the save was on disk is accounted on the next RTC update
+            self.rtc_last_update = u64::from_le_bytes(timestamp.try_into().unwrap());
+        }
+    }
+}
+
+impl SaveState for Mbc3 {
+    /// RTC is stored with the clock time of its last update, so time elapsed after saving is accounted on restore
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_u8(self.rom_bank);
+        state.write_u8(self.ram_bank);
+        state.write_bool(self.ram_enabled);
+        state.write_u8(self.latch_value);
+        state.write_bytes(&self.ram);
+        state.write_bytes(&self.rtc.to_save_bytes());
+        state.write_bytes(&self.rtc_latched.to_save_bytes());
+        state.write_u64(self.rtc_last_update);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        self.rom_bank = state.read_u8()? & Mbc3Mask::ROM_BANK;
+        self.ram_bank = state.read_u8()?;
+        self.ram_enabled = state.read_bool()?;
+        self.latch_value = state.read_u8()?;
+        state.read_bytes_into(&mut self.ram, "cartridge RAM")?;
+        let mut rtc = [0; RtcRegisters::SAVE_SIZE];
+        state.read_bytes_into(&mut rtc, "RTC registers")?;
+        self.rtc = RtcRegisters::from_save_bytes(&rtc);
+        state.read_bytes_into(&mut rtc, "RTC registers")?;
+        self.rtc_latched = RtcRegisters::from_save_bytes(&rtc);
+        self.rtc_last_update = state.read_u64()?;
+        Ok(())
+    }
+}
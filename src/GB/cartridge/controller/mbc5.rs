@@ -0,0 +1,219 @@
+use crate::GB::bus::BusDevice;
+use crate::GB::cartridge::Cartridge;
+use super::RomController;
+use crate::GB::cartridge::header::RomHeader;
+use crate::GB::types::address::{Address, AddressRangeInclusive};
+use crate::GB::save_state::{SaveState, SaveStateError, StateReader, StateWriter};
+use crate::GB::types::Byte;
+
+struct Mbc5Mask {}
+impl Mbc5Mask {
+    const ROM_BANK: u16 = 0x1FF;
+    const ROM_BANK_HIGH: u8 = 0b_0000_0001;
+    const RAM_BANK: u8 = 0b_0000_1111;
+    const RUMBLE_RAM_BANK: u8 = 0b_0000_0111; // On rumble carts bit 3 drives the motor
+    const RUMBLE: u8 = 0b_0000_1000;
+}
+
+#[derive(Clone, Debug)]
+pub struct Mbc5 {
+    header: RomHeader,
+    rom_bank: u16, // 9-bit ROM bank
+    ram_bank: u8,
+    ram_enabled: bool,
+    rom: Vec<Byte>,
+    ram: Vec<Byte>,
+    battery: bool,
+    rumble: Option<bool>, // None if the cartridge has no rumble motor
+}
+
+impl Mbc5 {
+    pub const MBC5_RAM_ENABLE_VALUE: u8 = 0xA;
+    pub const MBC5_ROM_BANK_0_START: Address = Address(0x0);
+    pub const MBC5_ROM_BANK_0_END: Address = Address(0x3FFF);
+    pub const MBC5_ROM_BANK_0_RANGE: AddressRangeInclusive = Self::MBC5_ROM_BANK_0_START..=Self::MBC5_ROM_BANK_0_END;
+    pub const MBC5_ROM_BANK_1_START: Address = Address(0x4000);
+    pub const MBC5_ROM_BANK_1_END: Address = Address(0x7FFF);
+    pub const MBC5_ROM_BANK_1_RANGE: AddressRangeInclusive = Self::MBC5_ROM_BANK_1_START..=Self::MBC5_ROM_BANK_1_END;
+    pub const MBC5_RAM_BANK_START: Address = Address(0xA000);
+    pub const MBC5_RAM_BANK_END: Address = Address(0xBFFF);
+    pub const MBC5_RAM_BANK_RANGE: AddressRangeInclusive = Self::MBC5_RAM_BANK_START..=Self::MBC5_RAM_BANK_END;
+    pub const MBC5_RAM_ENABLE_START: Address = Address(0x0);
+    pub const MBC5_RAM_ENABLE_END: Address = Address(0x1FFF);
+    pub const MBC5_RAM_ENABLE_RANGE: AddressRangeInclusive = Self::MBC5_RAM_ENABLE_START..=Self::MBC5_RAM_ENABLE_END;
+    pub const MBC5_ROM_BANK_LOW_SELECTOR_START: Address = Address(0x2000);
+    pub const MBC5_ROM_BANK_LOW_SELECTOR_END: Address = Address(0x2FFF);
+    pub const MBC5_ROM_BANK_LOW_SELECTOR_RANGE: AddressRangeInclusive = Self::MBC5_ROM_BANK_LOW_SELECTOR_START..=Self::MBC5_ROM_BANK_LOW_SELECTOR_END;
+    pub const MBC5_ROM_BANK_HIGH_SELECTOR_START: Address = Address(0x3000);
+    pub const MBC5_ROM_BANK_HIGH_SELECTOR_END: Address = Address(0x3FFF);
+    pub const MBC5_ROM_BANK_HIGH_SELECTOR_RANGE: AddressRangeInclusive = Self::MBC5_ROM_BANK_HIGH_SELECTOR_START..=Self::MBC5_ROM_BANK_HIGH_SELECTOR_END;
+    pub const MBC5_RAM_BANK_SELECTOR_START: Address = Address(0x4000);
+    pub const MBC5_RAM_BANK_SELECTOR_END: Address = Address(0x5FFF);
+    pub const MBC5_RAM_BANK_SELECTOR_RANGE: AddressRangeInclusive = Self::MBC5_RAM_BANK_SELECTOR_START..=Self::MBC5_RAM_BANK_SELECTOR_END;
+    pub const MBC5_UNUSED_START: Address = Address(0x6000);
+    pub const MBC5_UNUSED_END: Address = Address(0x7FFF);
+    pub const MBC5_UNUSED_RANGE: AddressRangeInclusive = Self::MBC5_UNUSED_START..=Self::MBC5_UNUSED_END;
+}
+
+impl Mbc5 {
+    pub fn new(rom: Vec<Byte>, rumble: bool, battery: bool) -> Result<Self, std::io::Error> {
+        let header = RomHeader::from_rom(&rom)?;
+
+        Ok(Self {
+            rom_bank: 1,
+            ram_bank: 0,
+            ram_enabled: false,
+            rom,
+            ram: vec![0; Cartridge::RAM_BANK_SIZE * header.ram_banks()],
+            battery,
+            rumble: if rumble { Some(false) } else { None },
+            header,
+        })
+    }
+
+    fn ram_index(&self, address: Address) -> Option<usize> {
+        if self.ram.is_empty() {
+            return None;
+        }
+        let index = self.ram_bank as usize * Cartridge::RAM_BANK_SIZE + address.as_usize() - Self::MBC5_RAM_BANK_START.as_usize();
+        Some(index % self.ram.len())
+    }
+}
+
+impl BusDevice for Mbc5 {
+    fn read(&self, address: Address) -> Byte {
+        match address {
+            address if Self::MBC5_ROM_BANK_0_RANGE.contains(&address) => {
+                *self.rom.get(address.as_usize()).unwrap_or(&0xFF)
+            }
+            address if Self::MBC5_ROM_BANK_1_RANGE.contains(&address) => {
+                let bank_idx = address.as_usize() - Self::MBC5_ROM_BANK_1_START.as_usize();
+                let offset = Cartridge::ROM_BANK_SIZE * self.rom_bank as usize + bank_idx;
+                self.rom[offset % self.rom.len()]
+            }
+            address if Self::MBC5_RAM_BANK_RANGE.contains(&address) => {
+                match self.ram_index(address) {
+                    Some(index) if self.ram_enabled => self.ram[index],
+                    _ => 0xFF,
+                }
+            }
+            _ => unreachable!()
+        }
+    }
+
+    fn write(&mut self, address: Address, data: Byte) {
+        match address {
+            address if Self::MBC5_RAM_ENABLE_RANGE.contains(&address) => {
+                // Unlike MBC1 the whole byte is checked
+                self.ram_enabled = data == Self::MBC5_RAM_ENABLE_VALUE;
+            }
+            address if Self::MBC5_ROM_BANK_LOW_SELECTOR_RANGE.contains(&address) => {
+                // Bank 0 can be mapped on high area too
+                self.rom_bank = (self.rom_bank & 0x100) | data as u16;
+            }
+            address if Self::MBC5_ROM_BANK_HIGH_SELECTOR_RANGE.contains(&address) => {
+                self.rom_bank = (self.rom_bank & 0xFF) | (((data & Mbc5Mask::ROM_BANK_HIGH) as u16) << 8);
+            }
+            address if Self::MBC5_RAM_BANK_SELECTOR_RANGE.contains(&address) => {
+                match self.rumble.as_mut() {
+                    Some(rumble) => {
+                        *rumble = data & Mbc5Mask::RUMBLE != 0;
+                        self.ram_bank = data & Mbc5Mask::RUMBLE_RAM_BANK;
+                    }
+                    None => self.ram_bank = data & Mbc5Mask::RAM_BANK,
+                }
+            }
+            address if Self::MBC5_UNUSED_RANGE.contains(&address) => {}
+            address if Self::MBC5_RAM_BANK_RANGE.contains(&address) => {
+                if !self.ram_enabled {
+                    return;
+                }
+                if let Some(index) = self.ram_index(address) {
+                    self.ram[index] = data;
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl RomController for Mbc5 {
+    fn load(&mut self, rom_path: &str) -> Result<(), std::io::Error> {
+        std::fs::File::open(rom_path).map(|_| ())
+    }
+
+    fn header(&self) -> &RomHeader {
+        &self.header
+    }
+
+    #[inline]
+    fn low_rom_bank_addressed(&self) -> u16 {
+        0
+    }
+
+    #[inline]
+    /// Return the ROM bank mapped on $4000-$7FFF. Idx ∈ [0, 0x1FF]
+    fn high_rom_bank_addressed(&self) -> u16 {
+        self.rom_bank % self.header.rom_banks() as u16
+    }
+
+    #[inline]
+    /// Return the index of addressed RAM bank. Idx ∈ [0, 0xF]
+    fn ram_bank_addressed(&self) -> u16 {
+        self.ram_bank as u16
+    }
+
+    #[inline]
+    fn rom_bank_register(&self) -> u16 {
+        self.rom_bank
+    }
+
+    #[inline]
+    fn ram_bank_register(&self) -> u16 {
+        self.ram_bank as u16
+    }
+
+    #[inline]
+    fn rumble(&self) -> bool {
+        self.rumble.unwrap_or(false)
+    }
+
+    #[inline]
+    fn has_battery(&self) -> bool {
+        self.battery
+    }
+
+    fn stores_ram_write(&self, address: Address) -> bool {
+        Self::MBC5_RAM_BANK_RANGE.contains(&address) && self.ram_enabled && self.ram_index(address).is_some()
+    }
+
+    fn export_battery_ram(&self) -> Vec<Byte> {
+        self.ram.clone()
+    }
+
+    fn import_battery_ram(&mut self, data: &[Byte]) {
+        let len = data.len().min(self.ram.len());
+        self.ram[..len].copy_from_slice(&data[..len]);
+    }
+}
+
+impl SaveState for Mbc5 {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_u16(self.rom_bank);
+        state.write_u8(self.ram_bank);
+        state.write_bool(self.ram_enabled);
+        state.write_bool(self.rumble.unwrap_or(false));
+        state.write_bytes(&self.ram);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        self.rom_bank = state.read_u16()? & Mbc5Mask::ROM_BANK;
+        self.ram_bank = state.read_u8()? & Mbc5Mask::RAM_BANK;
+        self.ram_enabled = state.read_bool()?;
+        let rumble = state.read_bool()?;
+        if let Some(motor) = self.rumble.as_mut() {
+            *motor = rumble;
+        }
+        state.read_bytes_into(&mut self.ram, "cartridge RAM")
+    }
+}
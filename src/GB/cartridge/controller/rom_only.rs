@@ -0,0 +1,134 @@
+use crate::GB::bus::BusDevice;
+use crate::GB::cartridge::Cartridge;
+use super::RomController;
+use crate::GB::cartridge::header::RomHeader;
+use crate::GB::types::address::{Address, AddressRangeInclusive};
+use crate::GB::save_state::{SaveState, SaveStateError, StateReader, StateWriter};
+use crate::GB::types::Byte;
+
+/// Cartridge without a memory bank controller: 32KB of ROM directly mapped and up to 8KB of optional RAM.
+/// Writes on ROM area are ignored as there isn't any register to drive.
+#[derive(Clone, Debug)]
+pub struct RomOnly {
+    header: RomHeader,
+    rom: Vec<Byte>,
+    ram: Vec<Byte>,
+    battery: bool,
+}
+
+impl RomOnly {
+    pub const ROM_ONLY_ROM_START: Address = Address(0x0);
+    pub const ROM_ONLY_ROM_END: Address = Address(0x7FFF);
+    pub const ROM_ONLY_ROM_RANGE: AddressRangeInclusive = Self::ROM_ONLY_ROM_START..=Self::ROM_ONLY_ROM_END;
+    pub const ROM_ONLY_RAM_START: Address = Address(0xA000);
+    pub const ROM_ONLY_RAM_END: Address = Address(0xBFFF);
+    pub const ROM_ONLY_RAM_RANGE: AddressRangeInclusive = Self::ROM_ONLY_RAM_START..=Self::ROM_ONLY_RAM_END;
+}
+
+impl RomOnly {
+    pub fn new(rom: Vec<Byte>, battery: bool) -> Result<Self, std::io::Error> {
+        let header = RomHeader::from_rom(&rom)?;
+
+        // RAM can't be larger than the only bank mapped
+        let ram_size = (Cartridge::RAM_BANK_SIZE * header.ram_banks()).min(Cartridge::RAM_BANK_SIZE);
+        Ok(Self {
+            rom,
+            ram: vec![0; ram_size],
+            battery,
+            header,
+        })
+    }
+}
+
+impl BusDevice for RomOnly {
+    fn read(&self, address: Address) -> Byte {
+        match address {
+            address if Self::ROM_ONLY_ROM_RANGE.contains(&address) => {
+                // Smaller dumps read as open bus outside their size
+                *self.rom.get(address.as_usize()).unwrap_or(&0xFF)
+            }
+            address if Self::ROM_ONLY_RAM_RANGE.contains(&address) => {
+                let ram_idx = address.as_usize() - Self::ROM_ONLY_RAM_START.as_usize();
+                *self.ram.get(ram_idx).unwrap_or(&0xFF)
+            }
+            _ => unreachable!()
+        }
+    }
+
+    fn write(&mut self, address: Address, data: Byte) {
+        match address {
+            address if Self::ROM_ONLY_ROM_RANGE.contains(&address) => {}
+            address if Self::ROM_ONLY_RAM_RANGE.contains(&address) => {
+                let ram_idx = address.as_usize() - Self::ROM_ONLY_RAM_START.as_usize();
+                if let Some(byte) = self.ram.get_mut(ram_idx) {
+                    *byte = data;
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl RomController for RomOnly {
+    fn load(&mut self, rom_path: &str) -> Result<(), std::io::Error> {
+        std::fs::File::open(rom_path).map(|_| ())
+    }
+
+    fn header(&self) -> &RomHeader {
+        &self.header
+    }
+
+    #[inline]
+    fn low_rom_bank_addressed(&self) -> u16 {
+        0
+    }
+
+    #[inline]
+    fn high_rom_bank_addressed(&self) -> u16 {
+        1
+    }
+
+    #[inline]
+    fn ram_bank_addressed(&self) -> u16 {
+        0
+    }
+
+    #[inline]
+    fn rom_bank_register(&self) -> u16 {
+        0
+    }
+
+    #[inline]
+    fn ram_bank_register(&self) -> u16 {
+        0
+    }
+
+    #[inline]
+    fn has_battery(&self) -> bool {
+        self.battery
+    }
+
+    fn stores_ram_write(&self, address: Address) -> bool {
+        Self::ROM_ONLY_RAM_RANGE.contains(&address)
+            && address.as_usize() - Self::ROM_ONLY_RAM_START.as_usize() < self.ram.len()
+    }
+
+    fn export_battery_ram(&self) -> Vec<Byte> {
+        self.ram.clone()
+    }
+
+    fn import_battery_ram(&mut self, data: &[Byte]) {
+        let len = data.len().min(self.ram.len());
+        self.ram[..len].copy_from_slice(&data[..len]);
+    }
+}
+
+impl SaveState for RomOnly {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_bytes(&self.ram);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        state.read_bytes_into(&mut self.ram, "cartridge RAM")
+    }
+}
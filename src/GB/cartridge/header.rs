@@ -17,6 +17,43 @@ const HEADER_START_ADDRESS: Address = Address(0x0100);
 const HEADER_END_ADDRESS: Address = Address(0x014F);
 const HEADER_SIZE: usize = HEADER_END_ADDRESS.as_usize() - HEADER_START_ADDRESS.as_usize() + 1;
 
+/// Errors on ROM header parsing
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum HeaderError {
+    /// ROM dump is too small to contain the header (size in bytes)
+    RomTooSmall(usize),
+    /// Unknown ROM size byte at $0148
+    InvalidRomSize(Byte),
+    /// Unknown RAM size byte at $0149
+    InvalidRamSize(Byte),
+}
+
+impl std::fmt::Display for HeaderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HeaderError::RomTooSmall(size) => write!(f, "ROM is too small to contain a header ({} bytes)", size),
+            HeaderError::InvalidRomSize(byte) => write!(f, "Invalid ROM size in header (${:02X})", byte),
+            HeaderError::InvalidRamSize(byte) => write!(f, "Invalid RAM size in header (${:02X})", byte),
+        }
+    }
+}
+
+impl std::error::Error for HeaderError {}
+
+impl From<HeaderError> for std::io::Error {
+    fn from(err: HeaderError) -> Self {
+        std::io::Error::new(std::io::ErrorKind::InvalidData, err)
+    }
+}
+
+/// Color support declared by CGB flag ($0143)
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CgbSupport {
+    None,
+    Enhanced, // $80 - Works on DMG too
+    Only,     // $C0
+}
+
 #[derive(Clone, Debug)]
 pub struct RomHeader {
     raw_header: [u8; HEADER_SIZE],
@@ -27,8 +64,13 @@ pub struct RomHeader {
     new_license_code: NewLicenseCode,
     rom_version: Byte,
     title: String,
+    cgb_flag: Byte,
+    sgb_flag: Byte,
+    valid_logo: bool,
     rom_checksum: u8,
+    computed_rom_checksum: u8,
     global_checksum: u16,
+    computed_global_checksum: Option<u16>, // Available only if the header is parsed from the whole ROM
 }
 
 impl RomHeader {
@@ -37,10 +79,14 @@ impl RomHeader {
     pub const HEADER_END_ADDRESS: Address = HEADER_END_ADDRESS;
     pub const HEADER_SIZE: usize = HEADER_SIZE;
     pub const HEADER_ENTRY_POINT: Address = Address(0x0100);
+    pub const HEADER_LOGO_START_ADDRESS: Address = Address(0x0104);
+    pub const HEADER_LOGO_END_ADDRESS: Address = Address(0x0133);
     pub const HEADER_TITLE_START_ADDRESS: Address = Address(0x0134);
     pub const HEADER_TITLE_END_ADDRESS: Address = Address(0x0143);
+    pub const HEADER_CGB_FLAG_ADDRESS: Address = Address(0x0143);
     pub const HEADER_NEW_LICENSE_HIGH_BYTE_ADDRESS: Address = Address(0x0144);
     pub const HEADER_NEW_LICENSE_LOW_BYTE_ADDRESS: Address = Address(0x0145);
+    pub const HEADER_SGB_FLAG_ADDRESS: Address = Address(0x0146);
     pub const HEADER_CONTROLLER_TYPE_ADDRESS: Address = Address(0x0147);
     pub const HEADER_ROM_SIZE_ADDRESS: Address = Address(0x0148);
     pub const HEADER_RAM_SIZE_ADDRESS: Address = Address(0x0149);
@@ -49,61 +95,91 @@ impl RomHeader {
     pub const HEADER_ROM_CHECKSUM_ADDRESS: Address = Address(0x014D);
     pub const HEADER_GLOBAL_CHECKSUM_HIGH_BYTE_ADDRESS: Address = Address(0x014E);
     pub const HEADER_GLOBAL_CHECKSUM_LOW_BYTE_ADDRESS: Address = Address(0x014F);
+    pub const CGB_FLAG_ENHANCED: Byte = 0x80;
+    pub const CGB_FLAG_ONLY: Byte = 0xC0;
+    pub const SGB_FLAG_SUPPORTED: Byte = 0x03;
 }
 
 impl RomHeader {
-    pub fn new(header_slice: &[Byte; Self::HEADER_SIZE]) -> Self {
-        let title_result = String::from_utf8(
-            Vec::from(&header_slice[
-                (Self::HEADER_TITLE_START_ADDRESS.as_usize() - Self::HEADER_START_ADDRESS.as_usize())..=(Self::HEADER_TITLE_END_ADDRESS.as_usize() - Self::HEADER_START_ADDRESS.as_usize())
-                ])
-        ).unwrap();
-
-        let old_license_code = header_slice[Self::HEADER_OLD_LICENSE_ADDRESS.as_usize() - Self::HEADER_START_ADDRESS.as_usize()];
-        let new_license_high_byte = header_slice[Self::HEADER_NEW_LICENSE_HIGH_BYTE_ADDRESS.as_usize() - Self::HEADER_START_ADDRESS.as_usize()];
-        let new_license_low_byte = header_slice[Self::HEADER_NEW_LICENSE_LOW_BYTE_ADDRESS.as_usize() - Self::HEADER_START_ADDRESS.as_usize()];
-        let new_license_code = ((new_license_high_byte as u16) << 8) | new_license_low_byte as u16;
-
-        let rom_version = header_slice[Self::HEADER_ROM_VERSION_ADDRESS.as_usize() - Self::HEADER_START_ADDRESS.as_usize()];
-        let rom_checksum = header_slice[Self::HEADER_ROM_CHECKSUM_ADDRESS.as_usize() - Self::HEADER_START_ADDRESS.as_usize()];
-        let global_checksum_high = header_slice[Self::HEADER_GLOBAL_CHECKSUM_HIGH_BYTE_ADDRESS.as_usize() - Self::HEADER_START_ADDRESS.as_usize()];
-        let global_checksum_low = header_slice[Self::HEADER_GLOBAL_CHECKSUM_LOW_BYTE_ADDRESS.as_usize() - Self::HEADER_START_ADDRESS.as_usize()];
-        let global_checksum = ((global_checksum_high as u16) << 8) | global_checksum_low as u16;
-        let rom_controller_type = header_slice[Self::HEADER_CONTROLLER_TYPE_ADDRESS.as_usize() - Self::HEADER_START_ADDRESS.as_usize()];
-
-        Self {
-            raw_header: header_slice.clone(),
-            rom_controller_type: rom_controller_type.into(),
-            rom_banks: Self::rom_banks_from_byte(header_slice[Self::HEADER_ROM_SIZE_ADDRESS.as_usize() - Self::HEADER_START_ADDRESS.as_usize()]),
-            ram_banks: Self::ram_banks_from_byte(header_slice[Self::HEADER_RAM_SIZE_ADDRESS.as_usize() - Self::HEADER_START_ADDRESS.as_usize()]),
-            old_license_code: old_license_code.into(),
+    pub fn new(header_slice: &[Byte; Self::HEADER_SIZE]) -> Result<Self, HeaderError> {
+        let byte = |address: Address| header_slice[address.as_usize() - Self::HEADER_START_ADDRESS.as_usize()];
+        let slice = |start: Address, end: Address| {
+            &header_slice[(start.as_usize() - Self::HEADER_START_ADDRESS.as_usize())..=(end.as_usize() - Self::HEADER_START_ADDRESS.as_usize())]
+        };
+
+        // On CGB cartridges last title byte is the CGB flag. Title is padded with $00
+        let cgb_flag = byte(Self::HEADER_CGB_FLAG_ADDRESS);
+        let title_end = if cgb_flag & Self::CGB_FLAG_ENHANCED != 0 {
+            Address(Self::HEADER_CGB_FLAG_ADDRESS.0 - 1)
+        } else {
+            Self::HEADER_TITLE_END_ADDRESS
+        };
+        let title_bytes = slice(Self::HEADER_TITLE_START_ADDRESS, title_end);
+        let title_len = title_bytes.iter().position(|b| *b == 0).unwrap_or(title_bytes.len());
+        let title = String::from_utf8_lossy(&title_bytes[..title_len]).into_owned();
+
+        let new_license_code = ((byte(Self::HEADER_NEW_LICENSE_HIGH_BYTE_ADDRESS) as u16) << 8) | byte(Self::HEADER_NEW_LICENSE_LOW_BYTE_ADDRESS) as u16;
+        let global_checksum = ((byte(Self::HEADER_GLOBAL_CHECKSUM_HIGH_BYTE_ADDRESS) as u16) << 8) | byte(Self::HEADER_GLOBAL_CHECKSUM_LOW_BYTE_ADDRESS) as u16;
+        let computed_rom_checksum = slice(Self::HEADER_TITLE_START_ADDRESS, Self::HEADER_ROM_VERSION_ADDRESS)
+            .iter()
+            .fold(0u8, |checksum, b| checksum.wrapping_sub(*b).wrapping_sub(1));
+
+        Ok(Self {
+            raw_header: *header_slice,
+            rom_controller_type: byte(Self::HEADER_CONTROLLER_TYPE_ADDRESS).into(),
+            rom_banks: Self::rom_banks_from_byte(byte(Self::HEADER_ROM_SIZE_ADDRESS))?,
+            ram_banks: Self::ram_banks_from_byte(byte(Self::HEADER_RAM_SIZE_ADDRESS))?,
+            old_license_code: byte(Self::HEADER_OLD_LICENSE_ADDRESS).into(),
             new_license_code: new_license_code.into(),
-            rom_version,
-            title: title_result,
-            rom_checksum,
+            rom_version: byte(Self::HEADER_ROM_VERSION_ADDRESS),
+            title,
+            cgb_flag,
+            sgb_flag: byte(Self::HEADER_SGB_FLAG_ADDRESS),
+            valid_logo: slice(Self::HEADER_LOGO_START_ADDRESS, Self::HEADER_LOGO_END_ADDRESS) == Self::HEADER_NINTENDO_LOGO,
+            rom_checksum: byte(Self::HEADER_ROM_CHECKSUM_ADDRESS),
+            computed_rom_checksum,
             global_checksum,
-        }
+            computed_global_checksum: None,
+        })
+    }
+
+    /// Parse the header from the whole ROM dump, verifying the global checksum too
+    pub fn from_rom(rom: &[Byte]) -> Result<Self, HeaderError> {
+        let header_slice = rom
+            .get(Self::HEADER_START_ADDRESS.as_usize()..=Self::HEADER_END_ADDRESS.as_usize())
+            .ok_or(HeaderError::RomTooSmall(rom.len()))?;
+        let mut header = Self::new(header_slice.try_into().unwrap())?;
+
+        // Sum of every ROM byte, except the global checksum ones
+        let checksum_bytes = Self::HEADER_GLOBAL_CHECKSUM_HIGH_BYTE_ADDRESS.as_usize()..=Self::HEADER_GLOBAL_CHECKSUM_LOW_BYTE_ADDRESS.as_usize();
+        let computed_global_checksum = rom
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !checksum_bytes.contains(i))
+            .fold(0u16, |checksum, (_, b)| checksum.wrapping_add(*b as u16));
+        header.computed_global_checksum = Some(computed_global_checksum);
+        Ok(header)
     }
 
     #[inline]
-    pub fn rom_banks_from_byte(byte: Byte) -> usize {
+    pub fn rom_banks_from_byte(byte: Byte) -> Result<usize, HeaderError> {
         match byte {
-            0..=8 => 2 << byte,
-            0x52..=0x54 => 64 + (2 << (byte & 0x0F)),
-            _ => unimplemented!(),
+            0..=8 => Ok(2 << byte),
+            0x52..=0x54 => Ok(64 + (2 << (byte & 0x0F))),
+            _ => Err(HeaderError::InvalidRomSize(byte)),
         }
     }
 
     #[inline]
-    pub fn ram_banks_from_byte(byte: Byte) -> usize {
+    pub fn ram_banks_from_byte(byte: Byte) -> Result<usize, HeaderError> {
         match byte {
-            0 => 0,
-            1 => unimplemented!(),
-            2 => 1,
-            3 => 4,
-            4 => 16,
-            5 => 8,
-            _ => unimplemented!(),
+            0 => Ok(0),
+            1 => Ok(1), // Unofficial 2KB, fits in a single bank
+            2 => Ok(1),
+            3 => Ok(4),
+            4 => Ok(16),
+            5 => Ok(8),
+            _ => Err(HeaderError::InvalidRamSize(byte)),
         }
     }
 
@@ -156,4 +232,56 @@ impl RomHeader {
     pub fn title(&self) -> &String {
         &self.title
     }
+
+    #[inline]
+    pub fn cgb_flag(&self) -> Byte {
+        self.cgb_flag
+    }
+
+    #[inline]
+    pub fn cgb_support(&self) -> CgbSupport {
+        match self.cgb_flag {
+            Self::CGB_FLAG_ONLY => CgbSupport::Only,
+            Self::CGB_FLAG_ENHANCED => CgbSupport::Enhanced,
+            _ => CgbSupport::None,
+        }
+    }
+
+    #[inline]
+    pub fn sgb_flag(&self) -> Byte {
+        self.sgb_flag
+    }
+
+    #[inline]
+    pub fn sgb_support(&self) -> bool {
+        self.sgb_flag == Self::SGB_FLAG_SUPPORTED
+    }
+
+    /// True if the logo matches the one checked by the boot ROM
+    #[inline]
+    pub fn valid_logo(&self) -> bool {
+        self.valid_logo
+    }
+
+    #[inline]
+    pub fn computed_rom_checksum(&self) -> u8 {
+        self.computed_rom_checksum
+    }
+
+    /// True if header checksum matches the one computed by the boot ROM
+    #[inline]
+    pub fn valid_rom_checksum(&self) -> bool {
+        self.rom_checksum == self.computed_rom_checksum
+    }
+
+    #[inline]
+    pub fn computed_global_checksum(&self) -> Option<u16> {
+        self.computed_global_checksum
+    }
+
+    /// None if the header isn't parsed from the whole ROM. The global checksum is not verified by real hardware.
+    #[inline]
+    pub fn valid_global_checksum(&self) -> Option<bool> {
+        self.computed_global_checksum.map(|checksum| checksum == self.global_checksum)
+    }
 }
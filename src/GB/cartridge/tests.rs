@@ -1,20 +1,553 @@
 use crate::GB::cartridge::{Cartridge, CartridgeControllerType};
+use crate::GB::bus::BusDevice;
+use crate::GB::cartridge::header::{CgbSupport, HeaderError, RomHeader};
+use crate::GB::cartridge::controller::RomController;
+use crate::GB::cartridge::controller::mbc3::{Mbc3, RtcClock};
+use crate::GB::GB;
+use crate::GB::types::address::Address;
+use std::cell::Cell;
+use std::rc::Rc;
+
+/// Manually advanced clock for deterministic RTC tests
+struct TestClock(Rc<Cell<u64>>);
+
+impl RtcClock for TestClock {
+    fn now(&self) -> u64 {
+        self.0.get()
+    }
+}
+
+fn mbc3_with_test_clock(rom: Vec<u8>) -> (Mbc3, Rc<Cell<u64>>) {
+    let time = Rc::new(Cell::new(1_000));
+    let mbc3 = Mbc3::new_with_clock(rom, true, true, Box::new(TestClock(time.clone()))).unwrap();
+    (mbc3, time)
+}
+
+fn latch_rtc(mbc3: &mut Mbc3) {
+    mbc3.write(Address(0x6000), 0x00);
+    mbc3.write(Address(0x6000), 0x01);
+}
+
+fn read_rtc(mbc3: &mut Mbc3, register: u8) -> u8 {
+    mbc3.write(Address(0x4000), register);
+    mbc3.read(Address(0xA000))
+}
+
+/// Build a 32KB ROM dump with the given controller type and ROM/RAM size header bytes
+fn rom_with_header(controller_type: u8, rom_size: u8, ram_size: u8) -> Vec<u8> {
+    let mut rom = vec![0u8; 2 * Cartridge::ROM_BANK_SIZE << rom_size];
+    rom[RomHeader::HEADER_CONTROLLER_TYPE_ADDRESS.as_usize()] = controller_type;
+    rom[RomHeader::HEADER_ROM_SIZE_ADDRESS.as_usize()] = rom_size;
+    rom[RomHeader::HEADER_RAM_SIZE_ADDRESS.as_usize()] = ram_size;
+    rom
+}
 
 #[test]
 fn test_new() {
     let cart = Cartridge::new("resources/test/mbc1_rom_banks.gb".to_string()).unwrap();
     println!("{}", cart);
-    assert_eq!(cart.get_rom_size(), 2 * 1024 * 1024);
-    assert_eq!(cart.get_ram_size(), 8 * 1024);
+    // Sizes declared in the header: the ROM image itself is padded to 2MB
+    assert_eq!(cart.get_rom_size(), 32 * 1024);
+    assert_eq!(cart.get_ram_size(), 128 * 1024);
     assert_eq!(cart.get_cart_type(), CartridgeControllerType::Mbc1RamBattery);
+    assert_eq!(cart.cart_type(), cart.get_cart_type());
+}
+
+#[test]
+fn test_controller_from_header() {
+    let cart = Cartridge::new_from_vec(rom_with_header(0x01, 0, 0), String::new()).unwrap();
+    assert_eq!(cart.cart_type(), CartridgeControllerType::Mbc1);
+}
+
+#[test]
+fn test_rom_only_ignores_bank_switch() {
+    let mut rom = rom_with_header(0x00, 0, 0);
+    rom[0x0000] = 0x12;
+    rom[0x4000] = 0x34;
+    let mut cart = Cartridge::new_from_vec(rom, String::new()).unwrap();
+    assert_eq!(cart.cart_type(), CartridgeControllerType::RomOnly);
+    cart.write(Address(0x2000), 0x02);
+    cart.write(Address(0x0000), 0x56);
+    assert_eq!(cart.read(Address(0x0000)), 0x12);
+    assert_eq!(cart.read(Address(0x4000)), 0x34);
+    assert_eq!(cart.rom_bank(), 1);
+    // No RAM on cartridge
+    cart.write(Address(0xA000), 0x56);
+    assert_eq!(cart.read(Address(0xA000)), 0xFF);
+}
+
+#[test]
+fn test_rom_ram_is_always_enabled() {
+    let mut cart = Cartridge::new_from_vec(rom_with_header(0x09, 0, 2), String::new()).unwrap();
+    assert_eq!(cart.cart_type(), CartridgeControllerType::RomRamBattery);
+    cart.write(Address(0xA000), 0x56);
+    cart.write(Address(0xBFFF), 0x78);
+    assert_eq!(cart.read(Address(0xA000)), 0x56);
+    assert_eq!(cart.read(Address(0xBFFF)), 0x78);
+}
+
+#[test]
+fn test_mbc2_register_select() {
+    // 256KB ROM, 16 banks
+    let mut rom = rom_with_header(0x05, 3, 0);
+    for bank in 0..16 {
+        rom[bank * Cartridge::ROM_BANK_SIZE + 1] = bank as u8;
+    }
+    let mut cart = Cartridge::new_from_vec(rom, String::new()).unwrap();
+    assert_eq!(cart.cart_type(), CartridgeControllerType::Mbc2);
+    // Address bit 8 set: ROM bank register
+    cart.write(Address(0x2100), 0x05);
+    assert_eq!(cart.read(Address(0x4001)), 5);
+    cart.write(Address(0x0100), 0x1F);
+    assert_eq!(cart.read(Address(0x4001)), 0x0F);
+    cart.write(Address(0x3FFF), 0x00);
+    assert_eq!(cart.read(Address(0x4001)), 1);
+    // Address bit 8 clear: RAM enable register, ROM bank unchanged
+    cart.write(Address(0x3EFF), 0x0A);
+    assert_eq!(cart.read(Address(0x4001)), 1);
+    cart.write(Address(0xA000), 0x05);
+    assert_eq!(cart.read(Address(0xA000)), 0xF5);
+    cart.write(Address(0x0000), 0x00);
+    assert_eq!(cart.read(Address(0xA000)), 0xFF);
+}
+
+#[test]
+fn test_mbc2_nibble_ram_mirroring() {
+    let mut cart = Cartridge::new_from_vec(rom_with_header(0x06, 0, 0), String::new()).unwrap();
+    assert_eq!(cart.cart_type(), CartridgeControllerType::Mbc2Battery);
+    cart.write(Address(0x0000), 0x0A);
+    cart.write(Address(0xA000), 0xAB);
+    cart.write(Address(0xA1FF), 0x3C);
+    // Only lower nibble is stored, upper nibble reads as 1
+    assert_eq!(cart.read(Address(0xA000)), 0xFB);
+    assert_eq!(cart.read(Address(0xA1FF)), 0xFC);
+    // 512 bytes are mirrored over $A000-$BFFF
+    for mirror in (0xA000..=0xBFFF).step_by(0x200) {
+        assert_eq!(cart.read(Address(mirror)), 0xFB);
+        assert_eq!(cart.read(Address(mirror + 0x1FF)), 0xFC);
+    }
+    cart.write(Address(0xBE00), 0x01);
+    assert_eq!(cart.read(Address(0xA000)), 0xF1);
+}
+
+#[test]
+fn test_mbc3_rom_banking() {
+    // 2MB ROM, 128 banks
+    let mut rom = rom_with_header(0x13, 6, 3);
+    for bank in 0..128 {
+        rom[bank * Cartridge::ROM_BANK_SIZE + 1] = bank as u8;
+    }
+    let mut cart = Cartridge::new_from_vec(rom, String::new()).unwrap();
+    assert_eq!(cart.cart_type(), CartridgeControllerType::Mbc3RamBattery);
+    assert_eq!(cart.read(Address(0x4001)), 1);
+    cart.write(Address(0x2000), 0x00);
+    assert_eq!(cart.read(Address(0x4001)), 1);
+    cart.write(Address(0x2000), 0x7F);
+    assert_eq!(cart.read(Address(0x4001)), 0x7F);
+    // Bit 7 is ignored
+    cart.write(Address(0x3FFF), 0x85);
+    assert_eq!(cart.read(Address(0x4001)), 0x05);
+    assert_eq!(cart.read(Address(0x0001)), 0);
+}
+
+#[test]
+fn test_mbc3_ram_banking() {
+    let mut cart = Cartridge::new_from_vec(rom_with_header(0x12, 0, 3), String::new()).unwrap();
+    assert_eq!(cart.read(Address(0xA000)), 0xFF);
+    cart.write(Address(0x0000), 0x0A);
+    for bank in 0..4 {
+        cart.write(Address(0x4000), bank);
+        cart.write(Address(0xA000), 0x10 + bank);
+    }
+    for bank in 0..4 {
+        cart.write(Address(0x4000), bank);
+        assert_eq!(cart.read(Address(0xA000)), 0x10 + bank);
+        assert_eq!(cart.ram_bank(), bank as u16);
+    }
+    cart.write(Address(0x0000), 0x00);
+    assert_eq!(cart.read(Address(0xA000)), 0xFF);
+}
+
+#[test]
+fn test_mbc3_rtc_latch() {
+    let (mut mbc3, time) = mbc3_with_test_clock(rom_with_header(0x10, 0, 2));
+    mbc3.write(Address(0x0000), 0x0A);
+    time.set(time.get() + 3_661);
+    // Registers keep the latched value until a new $00 -> $01 sequence
+    assert_eq!(read_rtc(&mut mbc3, 0x08), 0);
+    mbc3.write(Address(0x6000), 0x01);
+    assert_eq!(read_rtc(&mut mbc3, 0x08), 0);
+    latch_rtc(&mut mbc3);
+    assert_eq!(read_rtc(&mut mbc3, 0x08), 1);
+    assert_eq!(read_rtc(&mut mbc3, 0x09), 1);
+    assert_eq!(read_rtc(&mut mbc3, 0x0A), 1);
+    time.set(time.get() + 10);
+    assert_eq!(read_rtc(&mut mbc3, 0x08), 1);
+    latch_rtc(&mut mbc3);
+    assert_eq!(read_rtc(&mut mbc3, 0x08), 11);
+}
+
+#[test]
+fn test_mbc3_rtc_halt() {
+    let (mut mbc3, time) = mbc3_with_test_clock(rom_with_header(0x10, 0, 2));
+    mbc3.write(Address(0x0000), 0x0A);
+    mbc3.write(Address(0x4000), 0x0C);
+    mbc3.write(Address(0xA000), 0x40);
+    time.set(time.get() + 100);
+    latch_rtc(&mut mbc3);
+    assert_eq!(read_rtc(&mut mbc3, 0x08), 0);
+    assert_eq!(read_rtc(&mut mbc3, 0x0C), 0x40);
+    // Seconds written while halted, then clock resumes
+    mbc3.write(Address(0x4000), 0x08);
+    mbc3.write(Address(0xA000), 30);
+    mbc3.write(Address(0x4000), 0x0C);
+    mbc3.write(Address(0xA000), 0x00);
+    time.set(time.get() + 5);
+    latch_rtc(&mut mbc3);
+    assert_eq!(read_rtc(&mut mbc3, 0x08), 35);
+}
+
+#[test]
+fn test_mbc3_rtc_day_carry() {
+    let (mut mbc3, time) = mbc3_with_test_clock(rom_with_header(0x10, 0, 2));
+    mbc3.write(Address(0x0000), 0x0A);
+    time.set(time.get() + 255 * 86_400);
+    latch_rtc(&mut mbc3);
+    assert_eq!(read_rtc(&mut mbc3, 0x0B), 0xFF);
+    assert_eq!(read_rtc(&mut mbc3, 0x0C), 0x00);
+    time.set(time.get() + 86_400);
+    latch_rtc(&mut mbc3);
+    assert_eq!(read_rtc(&mut mbc3, 0x0B), 0x00);
+    assert_eq!(read_rtc(&mut mbc3, 0x0C), 0x01);
+    // Day counter overflows past 511
+    time.set(time.get() + 256 * 86_400);
+    latch_rtc(&mut mbc3);
+    assert_eq!(read_rtc(&mut mbc3, 0x0B), 0x00);
+    assert_eq!(read_rtc(&mut mbc3, 0x0C), 0x80);
+    // Carry is sticky 'till cleared by software
+    time.set(time.get() + 86_400);
+    latch_rtc(&mut mbc3);
+    assert_eq!(read_rtc(&mut mbc3, 0x0C), 0x80);
+    mbc3.write(Address(0xA000), 0x00);
+    latch_rtc(&mut mbc3);
+    assert_eq!(read_rtc(&mut mbc3, 0x0B), 0x01);
+    assert_eq!(read_rtc(&mut mbc3, 0x0C), 0x00);
+}
+
+#[test]
+fn test_mbc3_without_timer() {
+    let mut cart = Cartridge::new_from_vec(rom_with_header(0x11, 0, 0), String::new()).unwrap();
+    assert_eq!(cart.cart_type(), CartridgeControllerType::Mbc3);
+    cart.write(Address(0x0000), 0x0A);
+    cart.write(Address(0x4000), 0x08);
+    assert_eq!(cart.read(Address(0xA000)), 0xFF);
+}
+
+#[test]
+fn test_mbc5_rom_banking() {
+    // 8MB ROM, 512 banks
+    let mut rom = rom_with_header(0x19, 8, 0);
+    for bank in 0..512 {
+        rom[bank * Cartridge::ROM_BANK_SIZE] = bank as u8;
+        rom[bank * Cartridge::ROM_BANK_SIZE + 1] = (bank >> 8) as u8;
+    }
+    let mut cart = Cartridge::new_from_vec(rom, String::new()).unwrap();
+    assert_eq!(cart.cart_type(), CartridgeControllerType::Mbc5);
+    assert_eq!(cart.rom_bank(), 1);
+    // Bank 0 can be mapped on $4000-$7FFF
+    cart.write(Address(0x2000), 0x00);
+    assert_eq!(cart.rom_bank(), 0);
+    assert_eq!(cart.read(Address(0x4000)), 0x00);
+    cart.write(Address(0x2FFF), 0x34);
+    cart.write(Address(0x3000), 0x01);
+    assert_eq!(cart.rom_bank(), 0x134);
+    assert_eq!(cart.read(Address(0x4000)), 0x34);
+    assert_eq!(cart.read(Address(0x4001)), 0x01);
+    cart.write(Address(0x3FFF), 0xFE);
+    assert_eq!(cart.rom_bank(), 0x034);
+}
+
+#[test]
+fn test_mbc5_ram_banking() {
+    let mut cart = Cartridge::new_from_vec(rom_with_header(0x1B, 0, 4), String::new()).unwrap();
+    cart.write(Address(0x0000), 0x0A);
+    for bank in 0..16 {
+        cart.write(Address(0x4000), bank);
+        cart.write(Address(0xBFFF), 0x20 + bank);
+    }
+    for bank in 0..16 {
+        cart.write(Address(0x4000), bank);
+        assert_eq!(cart.read(Address(0xBFFF)), 0x20 + bank);
+    }
+    // Only $0A enables RAM
+    cart.write(Address(0x0000), 0x1A);
+    assert_eq!(cart.read(Address(0xBFFF)), 0xFF);
+    assert!(!cart.rumble());
+}
+
+#[test]
+fn test_mbc5_rumble() {
+    let mut cart = Cartridge::new_from_vec(rom_with_header(0x1D, 0, 3), String::new()).unwrap();
+    cart.write(Address(0x0000), 0x0A);
+    cart.write(Address(0x4000), 0x0B);
+    assert!(cart.rumble());
+    assert_eq!(cart.ram_bank(), 0x03);
+    cart.write(Address(0xA000), 0x56);
+    cart.write(Address(0x4000), 0x03);
+    assert!(!cart.rumble());
+    assert_eq!(cart.read(Address(0xA000)), 0x56);
+}
+
+#[test]
+fn test_mbc5_rumble_callback() {
+    let mut gb = GB::new(None).unwrap();
+    gb.cartridge = Some(Cartridge::new_from_vec(rom_with_header(0x1C, 0, 0), String::new()).unwrap());
+    let events = Rc::new(Cell::new(0));
+    let events_callback = events.clone();
+    gb.set_rumble_callback(Box::new(move |rumble| {
+        assert_eq!(rumble, events_callback.get() % 2 == 0);
+        events_callback.set(events_callback.get() + 1);
+    }));
+    gb.write(Address(0x4000), 0x08);
+    gb.tick();
+    assert!(gb.is_rumbling());
+    gb.write(Address(0x4000), 0x08);
+    gb.tick();
+    gb.write(Address(0x4000), 0x00);
+    gb.tick();
+    assert!(!gb.is_rumbling());
+    assert_eq!(events.get(), 2);
+}
+
+#[test]
+fn test_battery_ram_dirty_tracking() {
+    let mut cart = Cartridge::new_from_vec(rom_with_header(0x03, 0, 2), String::new()).unwrap();
+    assert!(cart.has_battery());
+    assert!(!cart.is_dirty());
+    // Dropped while RAM is disabled
+    cart.write(Address(0xA000), 0x42);
+    assert!(!cart.is_dirty());
+    cart.write(Address(0x0000), 0x0A);
+    assert!(!cart.is_dirty());
+    cart.write(Address(0xA000), 0x42);
+    assert!(cart.is_dirty());
+    // Not backed by a file: nothing to save
+    assert_eq!(cart.save_path(), None);
+    let data = cart.export_battery_ram();
+    assert_eq!(data[0], 0x42);
+
+    let mut no_battery = Cartridge::new_from_vec(rom_with_header(0x02, 0, 2), "game.gb".to_string()).unwrap();
+    no_battery.write(Address(0xA000), 0x42);
+    assert!(!no_battery.has_battery());
+    assert!(!no_battery.is_dirty());
+    assert_eq!(no_battery.save_path(), None);
+}
+
+#[test]
+fn test_battery_ram_save_on_eject() {
+    let dir = std::env::temp_dir().join(format!("yaemulator_sav_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let rom_path = dir.join("game.gb");
+    std::fs::write(&rom_path, rom_with_header(0x1B, 0, 3)).unwrap();
+    let rom_path = rom_path.to_str().unwrap().to_string();
+
+    let mut gb = GB::new(None).unwrap();
+    gb.insert_cartridge(&rom_path).unwrap();
+    assert_eq!(gb.cartridge().unwrap().save_path(), Some(dir.join("game.sav")));
+    gb.write(Address(0x0000), 0x0A);
+    gb.write(Address(0x4000), 0x02);
+    gb.write(Address(0xA123), 0x99);
+    gb.eject_cartridge().unwrap();
+    assert!(gb.cartridge().is_none());
+    let save = std::fs::read(dir.join("game.sav")).unwrap();
+    assert_eq!(save.len(), 4 * Cartridge::RAM_BANK_SIZE);
+    assert_eq!(save[2 * Cartridge::RAM_BANK_SIZE + 0x123], 0x99);
+
+    gb.insert_cartridge(&rom_path).unwrap();
+    assert!(!gb.cartridge().unwrap().is_dirty());
+    gb.write(Address(0x0000), 0x0A);
+    gb.write(Address(0x4000), 0x02);
+    assert_eq!(gb.read(Address(0xA123)), 0x99);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_mbc3_rtc_battery_ram() {
+    let (mut mbc3, time) = mbc3_with_test_clock(rom_with_header(0x10, 0, 2));
+    mbc3.write(Address(0x0000), 0x0A);
+    mbc3.write(Address(0x4000), 0x00);
+    mbc3.write(Address(0xA000), 0x42);
+    time.set(time.get() + 90);
+    latch_rtc(&mut mbc3);
+    time.set(time.get() + 30);
+    let data = mbc3.export_battery_ram();
+    assert_eq!(data.len(), Cartridge::RAM_BANK_SIZE + Mbc3::RTC_SAVE_SIZE);
+
+    // One hour elapsed while the save was on disk
+    let (mut restored, restored_time) = mbc3_with_test_clock(rom_with_header(0x10, 0, 2));
+    restored_time.set(time.get() + 3_600);
+    restored.import_battery_ram(&data);
+    restored.write(Address(0x0000), 0x0A);
+    assert_eq!(read_rtc(&mut restored, 0x08), 30);
+    assert_eq!(read_rtc(&mut restored, 0x09), 1);
+    latch_rtc(&mut restored);
+    assert_eq!(read_rtc(&mut restored, 0x08), 0);
+    assert_eq!(read_rtc(&mut restored, 0x09), 2);
+    assert_eq!(read_rtc(&mut restored, 0x0A), 1);
+    restored.write(Address(0x4000), 0x00);
+    assert_eq!(restored.read(Address(0xA000)), 0x42);
+}
+
+#[test]
+fn test_unsupported_controller() {
+    let result = Cartridge::new_from_vec(rom_with_header(0x22, 0, 0), String::new());
+    let err = result.err().unwrap();
+    assert_eq!(err.kind(), std::io::ErrorKind::Unsupported);
+    assert!(err.to_string().contains("MBC7"));
+}
+
+#[test]
+fn test_header_title_and_flags() {
+    let mut rom = rom_with_header(0x00, 0, 0);
+    rom[0x0134..0x0139].copy_from_slice(b"TETRI");
+    rom[0x0146] = 0x03;
+    let header = RomHeader::from_rom(&rom).unwrap();
+    assert_eq!(header.title(), "TETRI");
+    assert_eq!(header.cgb_support(), CgbSupport::None);
+    assert!(header.sgb_support());
+
+    // On CGB cartridges $0143 is the CGB flag, not part of the title
+    rom[0x0134..0x0143].copy_from_slice(b"POKEMON_YELLOWA");
+    rom[0x0143] = 0x80;
+    rom[0x0146] = 0x00;
+    let header = RomHeader::from_rom(&rom).unwrap();
+    assert_eq!(header.title(), "POKEMON_YELLOWA");
+    assert_eq!(header.cgb_flag(), 0x80);
+    assert_eq!(header.cgb_support(), CgbSupport::Enhanced);
+    assert!(!header.sgb_support());
+    rom[0x0143] = 0xC0;
+    assert_eq!(RomHeader::from_rom(&rom).unwrap().cgb_support(), CgbSupport::Only);
+
+    // Invalid UTF-8 doesn't panic
+    rom[0x0134] = 0xFF;
+    rom[0x0143] = 0x00;
+    assert!(RomHeader::from_rom(&rom).unwrap().title().ends_with("OKEMON_YELLOWA"));
+}
+
+#[test]
+fn test_header_logo_and_checksums() {
+    let mut rom = rom_with_header(0x00, 0, 0);
+    let header = RomHeader::from_rom(&rom).unwrap();
+    assert!(!header.valid_logo());
+    assert!(!header.valid_rom_checksum());
+    assert_eq!(header.valid_global_checksum(), Some(true));
+
+    rom[0x0104..=0x0133].copy_from_slice(&RomHeader::HEADER_NINTENDO_LOGO);
+    rom[0x0134..0x0138].copy_from_slice(b"TEST");
+    let header_checksum = rom[0x0134..=0x014C].iter().fold(0u8, |c, b| c.wrapping_sub(*b).wrapping_sub(1));
+    rom[0x014D] = header_checksum;
+    let global_checksum = rom.iter().fold(0u16, |c, b| c.wrapping_add(*b as u16));
+    rom[0x014E..=0x014F].copy_from_slice(&global_checksum.to_be_bytes());
+    let header = RomHeader::from_rom(&rom).unwrap();
+    assert!(header.valid_logo());
+    assert!(header.valid_rom_checksum());
+    assert_eq!(header.computed_rom_checksum(), header_checksum);
+    assert_eq!(header.valid_global_checksum(), Some(true));
+    assert_eq!(header.computed_global_checksum(), Some(global_checksum));
+
+    rom[0x7FFF] = 0x01;
+    assert_eq!(RomHeader::from_rom(&rom).unwrap().valid_global_checksum(), Some(false));
+    // Global checksum can't be verified from the header alone
+    let header = RomHeader::new(rom[0x0100..=0x014F].try_into().unwrap()).unwrap();
+    assert_eq!(header.valid_global_checksum(), None);
+}
+
+#[test]
+fn test_header_invalid_sizes() {
+    let mut rom = rom_with_header(0x00, 0, 0);
+    rom[RomHeader::HEADER_ROM_SIZE_ADDRESS.as_usize()] = 0x09;
+    assert_eq!(RomHeader::from_rom(&rom).err(), Some(HeaderError::InvalidRomSize(0x09)));
+    rom[RomHeader::HEADER_ROM_SIZE_ADDRESS.as_usize()] = 0x00;
+    rom[RomHeader::HEADER_RAM_SIZE_ADDRESS.as_usize()] = 0x06;
+    assert_eq!(RomHeader::from_rom(&rom).err(), Some(HeaderError::InvalidRamSize(0x06)));
+    assert_eq!(RomHeader::from_rom(&rom[..0x100]).err(), Some(HeaderError::RomTooSmall(0x100)));
+    // Corrupted header is reported instead of panicking
+    let err = Cartridge::new_from_vec(rom, String::new()).err().unwrap();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    assert!(err.to_string().contains("RAM size"));
+}
+
+#[test]
+fn test_rom_too_small() {
+    let result = Cartridge::new_from_vec(vec![0; 0x100], String::new());
+    assert_eq!(result.err().unwrap().kind(), std::io::ErrorKind::InvalidData);
 }
 
 #[test]
 fn test_rom_bank() {
-    todo!("Implement")
+    // 512KB MBC1 ROM, 32 banks
+    let mut rom = rom_with_header(0x01, 4, 0);
+    for bank in 0..32 {
+        rom[bank * Cartridge::ROM_BANK_SIZE + 1] = bank as u8;
+    }
+    let mut cart = Cartridge::new_from_vec(rom, String::new()).unwrap();
+    assert_eq!(cart.read(Address(0x4001)), 1);
+    // Bank 0 can't be mapped in the switchable area
+    cart.write(Address(0x2000), 0x00);
+    assert_eq!(cart.read(Address(0x4001)), 1);
+    for bank in 1..32 {
+        cart.write(Address(0x2000), bank);
+        assert_eq!(cart.read(Address(0x4001)), bank);
+    }
+    // Only 5 bits are used
+    cart.write(Address(0x3FFF), 0xE3);
+    assert_eq!(cart.read(Address(0x4001)), 0x03);
+    assert_eq!(cart.read(Address(0x0001)), 0);
 }
 
 #[test]
 fn test_ram_bank() {
-    todo!("Implement")
+    let mut cart = Cartridge::new_from_vec(rom_with_header(0x03, 2, 3), String::new()).unwrap();
+    assert_eq!(cart.read(Address(0xA000)), 0xFF);
+    cart.write(Address(0x0000), 0x0A);
+    // RAM banking needs the advanced banking mode
+    cart.write(Address(0x6000), 0x01);
+    for bank in 0..4 {
+        cart.write(Address(0x4000), bank);
+        cart.write(Address(0xA000), 0x10 + bank);
+    }
+    for bank in 0..4 {
+        cart.write(Address(0x4000), bank);
+        assert_eq!(cart.read(Address(0xA000)), 0x10 + bank);
+    }
+    // Simple banking mode always maps RAM bank 0
+    cart.write(Address(0x6000), 0x00);
+    assert_eq!(cart.read(Address(0xA000)), 0x10);
+    cart.write(Address(0x0000), 0x00);
+    assert_eq!(cart.read(Address(0xA000)), 0xFF);
+}
+
+#[test]
+fn test_battery_ram_autosave_error() {
+    let dir = std::env::temp_dir().join(format!("yaemulator_autosave_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let rom_path = dir.join("game.gb");
+    std::fs::write(&rom_path, rom_with_header(0x03, 0, 2)).unwrap();
+
+    let mut gb = GB::new(None).unwrap();
+    gb.insert_cartridge(&rom_path.to_str().unwrap().to_string()).unwrap();
+    gb.write(Address(0x0000), 0x0A);
+    gb.write(Address(0xA000), 0x42);
+    // Save file can't be written anymore
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    gb.cycles = GB::AUTOSAVE_PERIOD - 2;
+    let summary = gb.run_cycles(1);
+    assert_eq!(summary.autosave_error, None);
+    let summary = gb.run_cycles(1);
+    assert_eq!(summary.autosave_error, Some(std::io::ErrorKind::NotFound));
+    assert!(gb.cartridge().unwrap().is_dirty());
+    let summary = gb.run_cycles(1);
+    assert_eq!(summary.autosave_error, None);
 }
@@ -1,76 +1,73 @@
 pub mod instructions;
 pub mod registers;
 pub mod cpu_mmio;
+pub mod bus_trace;
 
-use crate::GB::bus::{Bus, MmioContextWrite, BusDevice};
+use crate::GB::bus::{MemoryBus, BusDevice};
 use crate::GB::types::{address::Address, Byte};
 use crate::GB::cpu::instructions::microcode::{CheckCondition, IduOp, MicroFlow, SetFlag, SetFlagZ};
-use crate::GB::cpu::instructions::{Instruction, InstructionMicroOpIndex};
+use crate::GB::cpu::instructions::Instruction;
 use crate::GB::cpu::registers::core_registers::Flags;
-use crate::GB::{bus, GB};
+use crate::GB::bus;
 use instructions::microcode::{AluOp, MCycleOp, MicroOp};
 use registers::{core_registers::Registers, interrupt_registers::InterruptRegisters};
 use crate::GB::cpu::cpu_mmio::CpuMmio;
 use crate::GB::traits::Tick;
+use crate::GB::save_state::{SaveState, SaveStateError, StateReader, StateWriter};
+use bus_trace::{BusCycle, BusOperation, BusTrace};
+use std::fmt;
 pub use instructions::InterruptType;
-use crate::GB::cpu::registers::interrupt_registers::InterruptFlagsMask;
-
-pub const DIVIDER_FREQUENCY: u64 = 16384; // Divider Update Frequency in Hz
-pub const CPU_INTERRUPT_CYCLES: u64 = 5; // Number of cycle to manage a requested Interrupt
 
 #[cfg(test)]
 mod test {
-    use crate::GB::cpu::CPU;
-    use crate::GB::memory::wram::WRAM;
+    use crate::GB::cpu::{CpuLockup, InterruptType, CPU};
+    use crate::GB::cpu::bus_trace::{BusCycle, BusOperation};
+    use crate::GB::cpu::instructions::microcode::{MCycleOp, MicroOp};
+    use crate::GB::GB;
+    use crate::GB::ppu::ppu_mmio::PpuMmio;
+    use crate::GB::types::address::Address;
+    use crate::GB::cpu::registers::interrupt_registers::{InterruptFlagsMask, InterruptRegisters, INTERRUPT_TIMER_ADDR, INTERRUPT_VBLANK_ADDR};
+    use crate::GB::joypad::{Joypad, JoypadButtonsBits};
 
     #[test]
     fn cpu_new_8bit_registers() {
+        // DMG values, as left by the boot ROM (flags excluded: they depend on the header checksum)
         let cpu = CPU::new();
-        assert_eq!(cpu.registers.get_a(), 0);
+        assert_eq!(cpu.registers.get_a(), 0x01);
         assert_eq!(cpu.registers.get_f(), 0);
-        assert_eq!(cpu.registers.get_b(), 0);
-        assert_eq!(cpu.registers.get_c(), 0);
-        assert_eq!(cpu.registers.get_d(), 0);
-        assert_eq!(cpu.registers.get_e(), 0);
-        assert_eq!(cpu.registers.get_h(), 0);
-        assert_eq!(cpu.registers.get_l(), 0);
+        assert_eq!(cpu.registers.get_b(), 0x00);
+        assert_eq!(cpu.registers.get_c(), 0x13);
+        assert_eq!(cpu.registers.get_d(), 0x00);
+        assert_eq!(cpu.registers.get_e(), 0xD8);
+        assert_eq!(cpu.registers.get_h(), 0x01);
+        assert_eq!(cpu.registers.get_l(), 0x4D);
     }
 
     #[test]
     fn cpu_new_16bit_registers() {
         let cpu = CPU::new();
-        assert_eq!(cpu.registers.get_af(), 0);
-        assert_eq!(cpu.registers.get_bc(), 0);
-        assert_eq!(cpu.registers.get_de(), 0);
-        assert_eq!(cpu.registers.get_hl(), 0);
-        assert_eq!(
-            cpu.registers.get_sp(),
-            WRAM::WRAM_END_ADDRESS.as_u16()
-        );
-        assert_eq!(cpu.registers.get_pc(), 0);
+        assert_eq!(cpu.registers.get_af(), 0x0100);
+        assert_eq!(cpu.registers.get_bc(), 0x0013);
+        assert_eq!(cpu.registers.get_de(), 0x00D8);
+        assert_eq!(cpu.registers.get_hl(), 0x014D);
+        assert_eq!(cpu.registers.get_sp(), 0xFFFE);
+        assert_eq!(cpu.registers.get_pc(), 0x0100);
     }
 
     #[test]
     fn cpu_new_16_8bit_registers() {
-        // 16 Bit register should be 0 as the compound of low register is 0 (and should not be altered by access of 8bit register)
-        let cpu = CPU::new();
-        assert_eq!(cpu.registers.get_a(), 0);
-        assert_eq!(cpu.registers.get_f(), 0);
-        assert_eq!(cpu.registers.get_b(), 0);
-        assert_eq!(cpu.registers.get_c(), 0);
-        assert_eq!(cpu.registers.get_d(), 0);
-        assert_eq!(cpu.registers.get_e(), 0);
-        assert_eq!(cpu.registers.get_h(), 0);
-        assert_eq!(cpu.registers.get_l(), 0);
-        assert_eq!(cpu.registers.get_af(), 0);
-        assert_eq!(cpu.registers.get_bc(), 0);
-        assert_eq!(cpu.registers.get_de(), 0);
-        assert_eq!(cpu.registers.get_hl(), 0);
-        assert_eq!(
-            cpu.registers.get_sp(),
-            WRAM::WRAM_END_ADDRESS.as_u16()
-        );
-        assert_eq!(cpu.registers.get_pc(), 0);
+        // 16 Bit registers are the compound of the 8 bit ones (and should not be altered by access of 8bit register)
+        let mut cpu = CPU::new();
+        cpu.registers.set_b(0x12);
+        cpu.registers.set_c(0x34);
+        assert_eq!(cpu.registers.get_bc(), 0x1234);
+        cpu.registers.set_hl(0xABCD);
+        assert_eq!(cpu.registers.get_h(), 0xAB);
+        assert_eq!(cpu.registers.get_l(), 0xCD);
+        assert_eq!(cpu.registers.get_de(), 0x00D8);
+        cpu.registers.set_sp(0xDFF0);
+        assert_eq!(cpu.registers.get_sp(), 0xDFF0);
+        assert_eq!(cpu.registers.get_pc(), 0x0100);
     }
 
     #[test]
@@ -86,13 +83,489 @@ mod test {
         // assert_eq!(cpu.registers.get_sp(), start_sp);
         // assert_eq!(popped_val, test_value);
     }
+
+    const PROGRAM_ADDRESS: u16 = 0xC000;
+
+    /// Build a GB running the given program from WRAM, with PPU turned off to not alter IF register
+    fn gb_with_program(program: &[u8]) -> GB {
+        gb_with_program_at(PROGRAM_ADDRESS, program)
+    }
+
+    fn gb_with_program_at(address: u16, program: &[u8]) -> GB {
+        let mut gb = GB::new(None).unwrap();
+        gb.write(PpuMmio::LCDC_ADDRESS, 0x00);
+        for (i, byte) in program.iter().enumerate() {
+            gb.write(Address(address + i as u16), *byte);
+        }
+        gb.cpu_ctx.cpu.registers.set_pc(address);
+        gb.cpu_ctx.cpu.registers.set_a(0);
+        gb
+    }
+
+    fn tick_m_cycles(gb: &mut GB, m_cycles: usize) {
+        for _ in 0..(m_cycles * 4) {
+            gb.tick();
+        }
+    }
+
+    #[test]
+    fn halt_waits_for_interrupt() {
+        // HALT, INC A
+        let mut gb = gb_with_program(&[0x76, 0x3C]);
+        gb.write(InterruptRegisters::IE_ADDRESS, InterruptFlagsMask::Timer as u8);
+        gb.write(InterruptRegisters::IF_ADDRESS, 0);
+        tick_m_cycles(&mut gb, 10);
+        assert!(gb.cpu().is_halted());
+        assert_eq!(gb.cpu().registers.get_pc(), PROGRAM_ADDRESS + 1);
+        assert_eq!(gb.cpu().registers.get_a(), 0);
+
+        // Requested but not enabled interrupts don't wake up the CPU
+        gb.write(InterruptRegisters::IF_ADDRESS, InterruptFlagsMask::VBlank as u8);
+        tick_m_cycles(&mut gb, 4);
+        assert!(gb.cpu().is_halted());
+    }
+
+    #[test]
+    fn halt_wakeup_without_ime() {
+        // HALT, INC A, NOP
+        let mut gb = gb_with_program(&[0x76, 0x3C, 0x00]);
+        gb.write(InterruptRegisters::IE_ADDRESS, InterruptFlagsMask::Timer as u8);
+        gb.write(InterruptRegisters::IF_ADDRESS, 0);
+        tick_m_cycles(&mut gb, 10);
+        assert!(gb.cpu().is_halted());
+
+        gb.write(InterruptRegisters::IF_ADDRESS, InterruptFlagsMask::Timer as u8);
+        tick_m_cycles(&mut gb, 2);
+        assert!(!gb.cpu().is_halted());
+        assert_eq!(gb.cpu().registers.get_a(), 1);
+        assert_eq!(gb.cpu().registers.get_pc(), PROGRAM_ADDRESS + 3);
+        // Interrupt is not serviced with IME off
+        assert!(gb.cpu().maneging_interrupt().is_none());
+        assert_eq!(gb.read(InterruptRegisters::IF_ADDRESS), InterruptFlagsMask::Timer as u8);
+    }
+
+    #[test]
+    fn halt_wakeup_with_ime_dispatch_interrupt() {
+        // HALT, INC A
+        let mut gb = gb_with_program(&[0x76, 0x3C]);
+        gb.cpu_ctx.cpu.ime = true;
+        gb.cpu_ctx.cpu.registers.set_sp(0xDFF0);
+        gb.write(InterruptRegisters::IE_ADDRESS, InterruptFlagsMask::Timer as u8);
+        gb.write(InterruptRegisters::IF_ADDRESS, 0);
+        tick_m_cycles(&mut gb, 10);
+        assert!(gb.cpu().is_halted());
+
+        gb.write(InterruptRegisters::IF_ADDRESS, InterruptFlagsMask::Timer as u8);
+        tick_m_cycles(&mut gb, 6);
+        assert!(!gb.cpu().is_halted());
+        assert!(!gb.cpu().ime());
+        assert_eq!(gb.read(InterruptRegisters::IF_ADDRESS), 0);
+        assert_eq!(gb.cpu().registers.get_a(), 0);
+        // Return address is the instruction after HALT
+        assert_eq!(gb.cpu().registers.get_sp(), 0xDFEE);
+        assert_eq!(gb.read(Address(0xDFEE)), 0x01);
+        assert_eq!(gb.read(Address(0xDFEF)), 0xC0);
+        assert_eq!(gb.cpu().registers.get_pc(), INTERRUPT_TIMER_ADDR + 1);
+    }
+
+    #[test]
+    fn halt_bug_reads_next_byte_twice() {
+        // HALT, INC A, NOP
+        let mut gb = gb_with_program(&[0x76, 0x3C, 0x00]);
+        gb.write(InterruptRegisters::IE_ADDRESS, InterruptFlagsMask::Timer as u8);
+        gb.write(InterruptRegisters::IF_ADDRESS, InterruptFlagsMask::Timer as u8);
+        tick_m_cycles(&mut gb, 4);
+        assert!(!gb.cpu().is_halted());
+        assert_eq!(gb.cpu().registers.get_a(), 2);
+        assert_eq!(gb.cpu().registers.get_pc(), PROGRAM_ADDRESS + 3);
+    }
+
+    #[test]
+    fn ei_enables_ime_after_next_instruction() {
+        // EI, NOP, INC A
+        let mut gb = gb_with_program(&[0xFB, 0x00, 0x3C]);
+        gb.cpu_ctx.cpu.registers.set_sp(0xDFF0);
+        gb.write(InterruptRegisters::IE_ADDRESS, InterruptFlagsMask::Timer as u8);
+        gb.write(InterruptRegisters::IF_ADDRESS, InterruptFlagsMask::Timer as u8);
+        tick_m_cycles(&mut gb, 2);
+        assert!(!gb.cpu().ime());
+        assert_eq!(gb.cpu().registers.get_pc(), PROGRAM_ADDRESS + 2);
+
+        // NOP is executed, then interrupt is serviced before INC A
+        tick_m_cycles(&mut gb, 1);
+        assert!(gb.cpu().maneging_interrupt().is_some());
+        tick_m_cycles(&mut gb, 5);
+        assert!(!gb.cpu().ime());
+        assert_eq!(gb.cpu().registers.get_a(), 0);
+        assert_eq!(gb.read(Address(0xDFEE)), 0x02);
+        assert_eq!(gb.read(Address(0xDFEF)), 0xC0);
+        assert_eq!(gb.cpu().registers.get_pc(), INTERRUPT_TIMER_ADDR + 1);
+    }
+
+    #[test]
+    fn ei_followed_by_di_never_services_interrupts() {
+        // EI, DI, INC A
+        let mut gb = gb_with_program(&[0xFB, 0xF3, 0x3C]);
+        gb.write(InterruptRegisters::IE_ADDRESS, InterruptFlagsMask::Timer as u8);
+        gb.write(InterruptRegisters::IF_ADDRESS, InterruptFlagsMask::Timer as u8);
+        tick_m_cycles(&mut gb, 4);
+        assert!(!gb.cpu().ime());
+        assert_eq!(gb.cpu().registers.get_a(), 1);
+        assert_eq!(gb.read(InterruptRegisters::IF_ADDRESS), InterruptFlagsMask::Timer as u8);
+    }
+
+    #[test]
+    fn ei_before_halt_returns_to_halt() {
+        // EI, HALT
+        let mut gb = gb_with_program(&[0xFB, 0x76]);
+        gb.cpu_ctx.cpu.registers.set_sp(0xDFF0);
+        gb.write(InterruptRegisters::IE_ADDRESS, InterruptFlagsMask::Timer as u8);
+        gb.write(InterruptRegisters::IF_ADDRESS, InterruptFlagsMask::Timer as u8);
+        tick_m_cycles(&mut gb, 8);
+        // Pushed return address is HALT itself
+        assert_eq!(gb.read(Address(0xDFEE)), 0x01);
+        assert_eq!(gb.read(Address(0xDFEF)), 0xC0);
+        assert_eq!(gb.cpu().registers.get_pc(), INTERRUPT_TIMER_ADDR + 1);
+    }
+
+    #[test]
+    fn interrupt_dispatch_cancelled_by_ie_push() {
+        // NOP - PC msb ($C0) pushed on IE disables all interrupts
+        let mut gb = gb_with_program(&[0x00]);
+        gb.cpu_ctx.cpu.ime = true;
+        gb.cpu_ctx.cpu.registers.set_sp(0x0000);
+        gb.write(InterruptRegisters::IE_ADDRESS, InterruptFlagsMask::Timer as u8);
+        gb.write(InterruptRegisters::IF_ADDRESS, InterruptFlagsMask::Timer as u8);
+        tick_m_cycles(&mut gb, 6);
+        assert_eq!(gb.read(InterruptRegisters::IE_ADDRESS), 0x00);
+        assert_eq!(gb.read(InterruptRegisters::IF_ADDRESS), InterruptFlagsMask::Timer as u8);
+        assert_eq!(gb.cpu().registers.get_sp(), 0xFFFE);
+        assert_eq!(gb.cpu().registers.get_pc(), 0x0001);
+    }
+
+    #[test]
+    fn interrupt_dispatch_redirected_by_ie_push() {
+        // NOP - PC msb ($C1) pushed on IE enables VBlank only
+        let mut gb = gb_with_program_at(0xC100, &[0x00]);
+        gb.cpu_ctx.cpu.ime = true;
+        gb.cpu_ctx.cpu.registers.set_sp(0x0000);
+        gb.write(InterruptRegisters::IE_ADDRESS, InterruptFlagsMask::Timer as u8);
+        gb.write(
+            InterruptRegisters::IF_ADDRESS,
+            InterruptFlagsMask::Timer as u8 | InterruptFlagsMask::VBlank as u8,
+        );
+        tick_m_cycles(&mut gb, 6);
+        assert_eq!(gb.read(InterruptRegisters::IE_ADDRESS), InterruptFlagsMask::VBlank as u8);
+        assert_eq!(gb.read(InterruptRegisters::IF_ADDRESS), InterruptFlagsMask::Timer as u8);
+        assert_eq!(gb.cpu().registers.get_pc(), INTERRUPT_VBLANK_ADDR + 1);
+    }
+
+    #[test]
+    fn stop_stops_system_until_joypad_input() {
+        // STOP, $00, INC A
+        let mut gb = gb_with_program(&[0x10, 0x00, 0x3C]);
+        gb.write(PpuMmio::LCDC_ADDRESS, 0x91);
+        gb.write(Joypad::JOYPAD_REGISTER_ADDRESS, 0x10);
+        gb.write(InterruptRegisters::IF_ADDRESS, 0);
+        tick_m_cycles(&mut gb, 2);
+        assert!(gb.cpu().is_stopped());
+        assert_eq!(gb.cpu().registers.get_pc(), PROGRAM_ADDRESS + 2);
+
+        // PPU is stopped as well
+        let ly = gb.read(PpuMmio::LY_ADDRESS);
+        tick_m_cycles(&mut gb, 1000);
+        assert!(gb.cpu().is_stopped());
+        assert_eq!(gb.read(PpuMmio::LY_ADDRESS), ly);
+        assert_eq!(gb.cpu().registers.get_a(), 0);
+
+        gb.press_button(JoypadButtonsBits::A, true);
+        tick_m_cycles(&mut gb, 2);
+        assert!(!gb.cpu().is_stopped());
+        assert_eq!(gb.cpu().registers.get_a(), 1);
+    }
+
+    #[test]
+    fn stop_with_pending_interrupt_is_one_byte() {
+        // STOP, INC A
+        let mut gb = gb_with_program(&[0x10, 0x3C]);
+        gb.write(InterruptRegisters::IE_ADDRESS, InterruptFlagsMask::Timer as u8);
+        gb.write(InterruptRegisters::IF_ADDRESS, InterruptFlagsMask::Timer as u8);
+        tick_m_cycles(&mut gb, 2);
+        assert!(gb.cpu().is_stopped());
+        assert_eq!(gb.cpu().registers.get_pc(), PROGRAM_ADDRESS + 1);
+    }
+
+    #[test]
+    fn stop_with_button_held_enters_halt() {
+        // STOP, $00, INC A
+        let mut gb = gb_with_program(&[0x10, 0x00, 0x3C]);
+        gb.write(Joypad::JOYPAD_REGISTER_ADDRESS, 0x10);
+        gb.write(InterruptRegisters::IF_ADDRESS, 0);
+        gb.press_button(JoypadButtonsBits::Start, true);
+        tick_m_cycles(&mut gb, 2);
+        assert!(!gb.cpu().is_stopped());
+        assert!(gb.cpu().is_halted());
+        assert_eq!(gb.cpu().registers.get_pc(), PROGRAM_ADDRESS + 2);
+    }
+
+    #[test]
+    fn step_instruction_retires_one_instruction() {
+        // LD A, $41, INC A, HALT
+        let mut gb = gb_with_program(&[0x3E, 0x41, 0x3C, 0x76]);
+        gb.write(InterruptRegisters::IE_ADDRESS, 0);
+        gb.step_instruction();
+        assert!(gb.cpu().is_instruction_start());
+        assert_eq!(gb.cpu().opcode(), 0x3E);
+
+        let summary = gb.step_instruction();
+        let retired = summary.last_instruction.unwrap();
+        assert_eq!((retired.pc, retired.opcode, retired.prefixed), (PROGRAM_ADDRESS, 0x3E, false));
+        assert_eq!(summary.cycles, 8);
+        assert_eq!(summary.instructions, 1);
+        assert_eq!(gb.cpu().registers.get_a(), 0x41);
+
+        let summary = gb.step_instruction();
+        assert_eq!(summary.last_instruction.unwrap().pc, PROGRAM_ADDRESS + 2);
+        assert_eq!(summary.cycles, 4);
+        assert_eq!(gb.cpu().opcode(), 0x76);
+        assert_eq!(gb.cpu().registers.get_a(), 0x42);
+        gb.step_instruction();
+        assert!(gb.cpu().is_halted());
+
+        // Halted CPU is stepped by M-Cycles
+        let pc = gb.cpu().registers.get_pc();
+        let summary = gb.step_instruction();
+        assert!(gb.cpu().is_halted());
+        assert_eq!(gb.cpu().registers.get_pc(), pc);
+        assert_eq!(summary.cycles, 4);
+        assert_eq!(summary.instructions, 0);
+    }
+
+    #[test]
+    fn step_instruction_reports_prefixed_opcodes() {
+        // SWAP A, NOP
+        let mut gb = gb_with_program(&[0xCB, 0x37, 0x00]);
+        gb.step_instruction();
+        let summary = gb.step_instruction();
+        let retired = summary.last_instruction.unwrap();
+        assert_eq!((retired.pc, retired.opcode, retired.prefixed), (PROGRAM_ADDRESS, 0x37, true));
+        assert_eq!(retired.name, CPU::decode(0x37, true).unwrap().name);
+        assert_eq!(summary.cycles, 8);
+    }
+
+    #[test]
+    fn bus_trace_records_m_cycles() {
+        // PUSH BC, NOP
+        let mut gb = gb_with_program(&[0xC5, 0x00]);
+        gb.cpu_ctx.cpu.registers.set_sp(0xDFF0);
+        gb.cpu_ctx.cpu.registers.set_bc(0x1234);
+        gb.step_instruction();
+        gb.enable_bus_trace(8);
+        gb.step_instruction();
+
+        let trace: Vec<BusCycle> = gb.bus_trace().unwrap().iter().copied().collect();
+        let activity: Vec<(BusOperation, u16, Option<u8>)> = trace.iter()
+            .map(|cycle| (cycle.operation, cycle.address, cycle.data))
+            .collect();
+        assert_eq!(activity, vec![
+            (BusOperation::Idle, PROGRAM_ADDRESS, None), // SP decrement: latch still holds the PUSH fetch address
+            (BusOperation::Write, 0xDFEF, Some(0x12)),
+            (BusOperation::Write, 0xDFEE, Some(0x34)),
+            (BusOperation::Fetch, PROGRAM_ADDRESS + 1, Some(0x00)),
+        ]);
+        assert!(matches!(trace[0].micro_op, MCycleOp::Main(MicroOp::Dec16(_))));
+        assert!(matches!(trace[3].micro_op, MCycleOp::End(MicroOp::Idle)));
+        assert_eq!(gb.cpu().address_latch(), PROGRAM_ADDRESS + 1);
+
+        // Ring buffer keeps only the latest M-Cycles
+        gb.enable_bus_trace(2);
+        gb.step_instruction();
+        gb.step_instruction();
+        let trace = gb.bus_trace().unwrap();
+        assert_eq!(trace.len(), 2);
+        assert_eq!(trace.last().unwrap().address, PROGRAM_ADDRESS + 3);
+
+        gb.disable_bus_trace();
+        assert!(gb.bus_trace().is_none());
+    }
+
+    #[test]
+    fn bus_trace_has_one_entry_per_m_cycle() {
+        // LD [HL+], A - CALL $C100 - RLC [HL]
+        for program in [&[0x22, 0x00][..], &[0xCD, 0x00, 0xC1], &[0xCB, 0x06, 0x00]] {
+            let mut gb = gb_with_program(program);
+            gb.cpu_ctx.cpu.registers.set_sp(0xDFF0);
+            gb.cpu_ctx.cpu.registers.set_hl(0xD000);
+            gb.step_instruction();
+            gb.enable_bus_trace(16);
+            let summary = gb.step_instruction();
+            assert_eq!(gb.bus_trace().unwrap().len() as u64, summary.cycles / 4, "{:02X?}", program);
+        }
+
+        let mut gb = gb_with_program(&[0xCD, 0x00, 0xC1]);
+        gb.cpu_ctx.cpu.registers.set_sp(0xDFF0);
+        gb.step_instruction();
+        gb.enable_bus_trace(16);
+        gb.step_instruction();
+        let activity: Vec<(BusOperation, u16)> = gb.bus_trace().unwrap().iter()
+            .map(|cycle| (cycle.operation, cycle.address))
+            .collect();
+        assert_eq!(activity, vec![
+            (BusOperation::Read, PROGRAM_ADDRESS + 1),
+            (BusOperation::Read, PROGRAM_ADDRESS + 2),
+            (BusOperation::Idle, PROGRAM_ADDRESS + 2),
+            (BusOperation::Write, 0xDFEF),
+            (BusOperation::Write, 0xDFEE),
+            (BusOperation::Fetch, 0xC100),
+        ]);
+    }
+
+    #[test]
+    fn run_cycles_reports_serviced_interrupts() {
+        // EI, NOP, NOP
+        let mut gb = gb_with_program(&[0xFB, 0x00, 0x00]);
+        gb.write(InterruptRegisters::IE_ADDRESS, InterruptFlagsMask::VBlank as u8);
+        gb.write(InterruptRegisters::IF_ADDRESS, InterruptFlagsMask::VBlank as u8);
+        let summary = gb.run_cycles(64);
+        assert_eq!(summary.cycles, 64);
+        assert!(matches!(summary.interrupts.as_slice(), [InterruptType::VBlank]));
+
+        // Dispatch is retired once the handler first instruction starts
+        let mut gb = gb_with_program(&[0xFB, 0x00, 0x00]);
+        gb.write(InterruptRegisters::IE_ADDRESS, InterruptFlagsMask::VBlank as u8);
+        gb.write(InterruptRegisters::IF_ADDRESS, InterruptFlagsMask::VBlank as u8);
+        while gb.step_instruction().interrupts.is_empty() {}
+        let return_address = gb.cpu().instruction_pc();
+        let summary = gb.step_instruction();
+        let retired = summary.last_instruction.unwrap();
+        assert!(matches!(retired.interrupt, Some(InterruptType::VBlank)));
+        assert_eq!(retired.pc, return_address);
+        assert_eq!(summary.instructions, 0);
+        assert_eq!(summary.cycles, 20);
+        assert_eq!(gb.cpu().instruction_pc(), INTERRUPT_VBLANK_ADDR);
+    }
+
+    #[test]
+    fn run_frame_stops_on_vblank() {
+        let mut gb = gb_with_program(&[0x18, 0xFE]); // JR -2
+        let summary = gb.run_frame();
+        assert!(!summary.frame_completed);
+        assert_eq!(summary.cycles, GB::FRAME_T_CYCLES);
+
+        gb.write(PpuMmio::LCDC_ADDRESS, 0x91);
+        gb.run_frame();
+        let summary = gb.run_frame();
+        assert!(summary.frame_completed);
+        assert!(gb.is_vblank_edge());
+        assert!(summary.instructions > 0);
+    }
+
+    #[test]
+    fn illegal_opcode_locks_cpu() {
+        // NOP, illegal $D3
+        let mut gb = gb_with_program(&[0x00, 0xD3]);
+        gb.cpu_ctx.cpu.ime = true;
+        gb.step_instruction();
+        let summary = gb.step_instruction();
+        let lockup = CpuLockup { pc: PROGRAM_ADDRESS + 1, opcode: 0xD3 };
+        assert!(gb.cpu().is_locked());
+        assert_eq!(gb.cpu().lockup(), Some(lockup));
+        assert_eq!(summary.lockup, Some(lockup));
+        assert_eq!(lockup.to_string(), "CPU hung at PC $C001, opcode $D3");
+
+        // Not even interrupts wake up a locked CPU
+        gb.write(InterruptRegisters::IE_ADDRESS, InterruptFlagsMask::Timer as u8);
+        gb.write(InterruptRegisters::IF_ADDRESS, InterruptFlagsMask::Timer as u8);
+        let summary = gb.step_instruction();
+        assert_eq!(summary.cycles, 4);
+        let summary = gb.run_cycles(1000);
+        assert_eq!(summary.instructions, 0);
+        assert!(summary.interrupts.is_empty());
+        assert_eq!(summary.lockup, Some(lockup));
+
+        let state = gb.save_state();
+        let mut restored = GB::new(None).unwrap();
+        restored.load_state(&state).unwrap();
+        assert_eq!(restored.cpu().lockup(), Some(lockup));
+    }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
 enum CpuStatus {
     Execute,
     Ready,
-    Halt
+    Halt,
+    Stop,
+    Locked, // Illegal opcode fetched: CPU hangs 'till reset
+}
+
+/// Opcode table the running instruction comes from - instruction itself can be recovered from it and the opcode
+#[derive(Debug, Copy, Clone)]
+enum InstructionTable {
+    Opcodes,
+    OpcodesCb,
+    Interrupt(InterruptType),
+}
+
+/// Instruction (or interrupt dispatch) run by the CPU, with the address it has been fetched from
+#[derive(Debug, Copy, Clone)]
+pub struct RetiredInstruction {
+    pub pc: u16, // For interrupt dispatch this is the return address
+    pub opcode: u8,
+    pub prefixed: bool, // Opcode comes from CB table
+    pub interrupt: Option<InterruptType>,
+    pub name: &'static str, // As in opcode tables, e.g. "LD A, [HL+]"
+}
+
+/// CPU registers as seen by the running program
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CpuRegisters {
+    pub a: u8,
+    pub f: u8,
+    pub b: u8,
+    pub c: u8,
+    pub d: u8,
+    pub e: u8,
+    pub h: u8,
+    pub l: u8,
+    pub sp: u16,
+    pub pc: u16, // Address of the running instruction: PC register is already past the prefetched opcode
+    pub ime: bool,
+}
+
+impl CpuRegisters {
+    #[inline]
+    pub fn af(&self) -> u16 {
+        u16::from_be_bytes([self.a, self.f])
+    }
+
+    #[inline]
+    pub fn bc(&self) -> u16 {
+        u16::from_be_bytes([self.b, self.c])
+    }
+
+    #[inline]
+    pub fn de(&self) -> u16 {
+        u16::from_be_bytes([self.d, self.e])
+    }
+
+    #[inline]
+    pub fn hl(&self) -> u16 {
+        u16::from_be_bytes([self.h, self.l])
+    }
+}
+
+/// CPU hung after fetching an illegal opcode, as it stays 'till reset
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CpuLockup {
+    pub pc: u16, // Address of the illegal opcode
+    pub opcode: u8,
+}
+
+impl fmt::Display for CpuLockup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CPU hung at PC ${:04X}, opcode ${:02X}", self.pc, self.opcode)
+    }
 }
 
 pub struct CPU {
@@ -101,16 +574,21 @@ pub struct CPU {
     interrupt: Option<InterruptType>,
     pub opcode: u8, // Running Instruction Opcode - Known as IR (Instruction Register),
     pub instruction: Option<&'static Instruction>, // Instruction microcode to execute
+    instruction_table: InstructionTable,
+    instruction_pc: u16, // Address of running instruction (or PC pushed by interrupt dispatch)
     pub micro_code: MCycleOp, // Instruction microcode to execute
     pub micro_code_index: usize, // Index of Instruction's MicroOp
     pub micro_code_t_cycle: u8, // T-Cycles counting of a M-Cycle during instruction execution
     pub micro_code_m_cycle: u8, // T-Cycles counting of a M-Cycle during instruction execution
     status: CpuStatus,
+    halt_bug: bool, // Next opcode fetch doesn't increment PC (HALT executed with IME off and a pending interrupt)
+    ime_delay: u8, // Instruction boundaries left before EI takes effect on IME
+    address_latch: u16, // Last address driven on the bus, kept by the latch in M-Cycles without access
+    bus_cycle: Option<BusCycle>, // Bus access latched in the running M-Cycle, recorded in the trace when it completes
+    bus_trace: Option<BusTrace>,
 }
 
 impl CPU {
-    pub const CPU_FREQUENCY_CLOCK: u32 = GB::SYSTEM_FREQUENCY_CLOCK / 4;
-
     pub fn new() -> Self {
         Self {
             registers: Registers::new(),
@@ -118,40 +596,70 @@ impl CPU {
             interrupt: None,
             opcode: 0,
             instruction: None,
+            instruction_table: InstructionTable::Opcodes,
+            instruction_pc: 0,
             micro_code: MCycleOp::None,
             micro_code_index: 0,
             micro_code_t_cycle: 0,
             micro_code_m_cycle: 0,
             status: CpuStatus::Execute,
+            halt_bug: false,
+            ime_delay: 0,
+            address_latch: 0,
+            bus_cycle: None,
+            bus_trace: None,
         }
     }
 
     #[inline]
+    #[allow(dead_code)]
     pub fn registers(&self) -> &Registers {
         &self.registers
     }
 
     #[inline]
+    #[cfg(test)]
     pub fn ime(&self) -> bool {
         self.ime
     }
 
     #[inline]
+    #[cfg(test)]
     pub fn opcode(&self) -> u8 {
         self.opcode
     }
 
     #[inline]
+    #[allow(dead_code)]
     pub fn instruction(&self) -> &Option<&'static Instruction> {
         &self.instruction
     }
 
     #[inline]
+    #[cfg(test)]
+    pub fn instruction_pc(&self) -> u16 {
+        self.instruction_pc
+    }
+
+    /// Running instruction, that is the one retired as soon as the next one starts
+    pub fn running_instruction(&self) -> Option<RetiredInstruction> {
+        self.instruction.map(|instruction| RetiredInstruction {
+            pc: self.instruction_pc,
+            opcode: self.opcode,
+            prefixed: matches!(self.instruction_table, InstructionTable::OpcodesCb),
+            interrupt: self.interrupt,
+            name: instruction.name,
+        })
+    }
+
+    #[inline]
+    #[allow(dead_code)]
     pub fn micro_code(&self) -> &MCycleOp {
         &self.micro_code
     }
 
     #[inline]
+    #[allow(dead_code)]
     pub fn micro_code_index(&self) -> usize {
         self.micro_code_index
     }
@@ -162,13 +670,116 @@ impl CPU {
     }
 
     #[inline]
+    #[allow(dead_code)]
     pub fn instruction_m_cycle(&self) -> u8 {
         self.micro_code_m_cycle
     }
 
-    pub fn fetch_next(&mut self, bus: &bus::Bus, ctx: &mut bus::MmioContextWrite) -> Byte {
-        let addr = self.registers.get_and_inc_pc();
-        bus.read(&ctx.as_read(), Address(addr))
+    /// True in the T-Cycle a new instruction (or interrupt dispatch) has been fetched and its execution begins
+    #[inline]
+    pub fn is_instruction_start(&self) -> bool {
+        self.status == CpuStatus::Execute && self.micro_code_m_cycle == 0 && self.micro_code_t_cycle == 0
+    }
+
+    /// True if CPU is in low-power mode waiting for an interrupt (HALT instruction)
+    #[inline]
+    pub fn is_halted(&self) -> bool {
+        self.status == CpuStatus::Halt
+    }
+
+    /// True if CPU is in STOP mode waiting for a joypad input (the whole system is stopped too)
+    #[inline]
+    pub fn is_stopped(&self) -> bool {
+        self.status == CpuStatus::Stop
+    }
+
+    /// True if CPU is hung on an illegal opcode (see `lockup`)
+    #[inline]
+    pub fn is_locked(&self) -> bool {
+        self.status == CpuStatus::Locked
+    }
+
+    /// Illegal opcode, and its address, the CPU is hung on
+    pub fn lockup(&self) -> Option<CpuLockup> {
+        self.is_locked().then_some(CpuLockup {
+            pc: self.instruction_pc,
+            opcode: self.opcode,
+        })
+    }
+
+    pub fn registers_view(&self) -> CpuRegisters {
+        let registers = &self.registers;
+        CpuRegisters {
+            a: registers.get_a(),
+            f: registers.get_f(),
+            b: registers.get_b(),
+            c: registers.get_c(),
+            d: registers.get_d(),
+            e: registers.get_e(),
+            h: registers.get_h(),
+            l: registers.get_l(),
+            sp: registers.get_sp(),
+            pc: self.instruction_pc,
+            ime: self.ime,
+        }
+    }
+
+    /// Address held by the address latch, that is the last one driven on the bus
+    #[inline]
+    #[cfg(test)]
+    pub fn address_latch(&self) -> u16 {
+        self.address_latch
+    }
+
+    /// Start recording the bus activity of the last `capacity` M-Cycles (a running trace is restarted)
+    pub fn enable_bus_trace(&mut self, capacity: usize) {
+        self.bus_trace = Some(BusTrace::new(capacity));
+    }
+
+    pub fn disable_bus_trace(&mut self) {
+        self.bus_trace = None;
+    }
+
+    pub fn bus_trace(&self) -> Option<&BusTrace> {
+        self.bus_trace.as_ref()
+    }
+
+    fn bus_read<B: MemoryBus>(&mut self, bus: &B, ctx: &mut bus::MmioContextWrite, address: Address, operation: BusOperation) -> Byte {
+        let data = bus.read(&ctx.as_read(), address);
+        self.latch_bus_access(address, operation, data);
+        data
+    }
+
+    fn bus_write<B: MemoryBus>(&mut self, bus: &mut B, ctx: &mut bus::MmioContextWrite, address: Address, data: Byte) {
+        bus.write(ctx, address, data);
+        self.latch_bus_access(address, BusOperation::Write, data);
+    }
+
+    #[inline]
+    fn latch_bus_access(&mut self, address: Address, operation: BusOperation, data: Byte) {
+        self.address_latch = address.as_u16();
+        self.bus_cycle = Some(BusCycle {
+            address: self.address_latch,
+            operation,
+            data: Some(data),
+            micro_op: self.micro_code,
+        });
+    }
+
+    pub fn fetch_next<B: MemoryBus>(&mut self, bus: &B, ctx: &mut bus::MmioContextWrite) -> Byte {
+        self.fetch(bus, ctx, BusOperation::Read)
+    }
+
+    fn fetch<B: MemoryBus>(&mut self, bus: &B, ctx: &mut bus::MmioContextWrite, operation: BusOperation) -> Byte {
+        let addr;
+        if self.halt_bug {
+            // HALT bug: PC fails to increment, so the same byte will be read twice
+            addr = self.registers.get_pc();
+            self.halt_bug = false;
+        } else {
+            addr = self.registers.get_and_inc_pc();
+        }
+        self.bus_read(bus, ctx, Address(addr), operation)
     }
 
     pub fn decode(opcode: u8, cb_optable: bool) -> Option<&'static Instruction> {
@@ -179,11 +790,12 @@ impl CPU {
         instructions::OPCODES[opcode_usize]
     }
 
-    pub fn fetch_and_decode(&mut self, bus: &bus::Bus, ctx: &mut bus::MmioContextWrite, cb_optable: bool) -> (Option<&'static Instruction>, Byte) {
-        let opcode = self.fetch_next(bus, ctx);
+    pub fn fetch_and_decode<B: MemoryBus>(&mut self, bus: &B, ctx: &mut bus::MmioContextWrite, cb_optable: bool) -> (Option<&'static Instruction>, Byte) {
+        let opcode = self.fetch(bus, ctx, BusOperation::Fetch);
         (Self::decode(opcode, cb_optable), opcode)
     }
 
+    #[allow(dead_code)]
     pub fn load(&mut self, data: &Vec<u8>) {
         // let mut addr: u16 = 0;
         // for byte in data {
@@ -224,51 +836,13 @@ impl CPU {
         interrupt_type
     }
 
-    /// Check and jump to requested interrupt address after take a snapshot of status on stack.
-    ///
-    /// Interrupt bit has priority from lower bit to higher (bit 0 has the higher priority).
-    pub fn interrupt_instruction(&self, interrupt_registers: &InterruptRegisters) -> Option<&'static Instruction> {
-        let interrupt;
-        if self.ime {
-            match self.interrupt_request(interrupt_registers) {
-                None => {
-                    interrupt = None;
-                }
-                Some(interrupt_type) => {
-                    interrupt = Some(interrupt_type.instruction());
-                }
-            }
-        } else {
-            interrupt = None;
-        }
-        interrupt
-    }
-
     pub fn maneging_interrupt(&self) -> Option<InterruptType> {
         self.interrupt
     }
 
-    /**
-       CPU Push 1-byte using SP register (to not confuse with instruction PUSH r16, that PUSH in a 2-bytes value from a double-register)
-    */
-    #[inline]
-    pub fn push(&mut self, bus: &mut Bus, ctx: &mut MmioContextWrite, byte: u8) {
-        bus.write(ctx, self.registers.get_sp_as_address(), byte);
-        self.registers.set_sp(self.registers.get_sp() - 1);
-    }
-
-    /**
-       CPU Pop 1-byte using SP register (to not confuse with instruction POP r16, that pop out a 2-bytes value to put in a double-register)
-    */
-    #[inline]
-    pub fn pop(&mut self, bus: &mut Bus, ctx: &mut MmioContextWrite) -> Byte {
-        self.registers.set_sp(self.registers.get_sp() + 1);
-        bus.read(&ctx.as_read(), self.registers.get_sp_as_address())
-    }
-
-    fn m_cycle_tick(
+    fn m_cycle_tick<B: MemoryBus>(
         &mut self,
-        bus: &mut bus::Bus,
+        bus: &mut B,
         ctx: &mut bus::MmioContextWrite,
         m_cycle_op: MCycleOp,
     ) -> CpuStatus {
@@ -331,6 +905,9 @@ impl CPU {
             MCycleOp::Halt => {
                 flow = MicroFlow::Halt;
             }
+            MCycleOp::Stop => {
+                flow = MicroFlow::Stop;
+            }
             MCycleOp::None => {
                 flow = MicroFlow::End;
             }
@@ -354,11 +931,22 @@ impl CPU {
                 // CB Prefix Reset the microcode flow and fetch/decode next byte with CB optable
                 self.micro_code_index = 0;
                 (self.instruction, self.opcode) = self.fetch_and_decode(bus, ctx, true);
+                self.instruction_table = InstructionTable::OpcodesCb;
                 self.micro_code = self.instruction.unwrap().micro_ops[self.micro_code_index];
                 cpu_status = CpuStatus::Execute;
             }
             MicroFlow::Halt => {
-                cpu_status = CpuStatus::Halt;
+                // HALT ends immediately if an interrupt is already pending (IE & IF != 0).
+                // With IME off the interrupt is not serviced and the CPU runs into the HALT bug
+                if ctx.cpu_mmio.interrupt_registers().some_irq() {
+                    self.halt_bug = !self.ime;
+                    cpu_status = CpuStatus::Ready;
+                } else {
+                    cpu_status = CpuStatus::Halt;
+                }
+            }
+            MicroFlow::Stop => {
+                cpu_status = self.stop(ctx);
             }
             MicroFlow::End => {
                 cpu_status = CpuStatus::Ready;
@@ -367,9 +955,37 @@ impl CPU {
         cpu_status
     }
 
-    fn micro_tick(
+    /// Evaluate the outcome of a STOP instruction following DMG behavior, based on joypad lines and pending interrupts:
+    /// - Button held & pending interrupt: STOP is a 1-byte opcode and stop mode is not entered
+    /// - Button held & no interrupt: STOP is a 2-byte opcode and HALT mode is entered instead (DIV is not reset)
+    /// - No button held & pending interrupt: STOP is a 1-byte opcode, stop mode is entered and DIV is reset
+    /// - No button held & no interrupt: STOP is a 2-byte opcode, stop mode is entered and DIV is reset
+    ///
+    /// Only DMG behavior is emulated: there is no KEY1 register, so STOP never triggers a CGB speed switch.
+    fn stop(&mut self, ctx: &mut bus::MmioContextWrite) -> CpuStatus {
+        let button_held = ctx.joypad.selected_line_low();
+        let pending_irq = ctx.cpu_mmio.interrupt_registers().some_irq();
+        match (button_held, pending_irq) {
+            (true, true) => CpuStatus::Ready,
+            (true, false) => {
+                self.registers.inc_pc();
+                CpuStatus::Halt
+            }
+            (false, true) => {
+                ctx.timer_mmio.reset_div();
+                CpuStatus::Stop
+            }
+            (false, false) => {
+                self.registers.inc_pc();
+                ctx.timer_mmio.reset_div();
+                CpuStatus::Stop
+            }
+        }
+    }
+
+    fn micro_tick<B: MemoryBus>(
         &mut self,
-        bus: &mut bus::Bus,
+        bus: &mut B,
         ctx: &mut bus::MmioContextWrite,
         micro_op: MicroOp,
     ) -> MicroFlow {
@@ -389,107 +1005,125 @@ impl CPU {
             }
             MicroOp::Read8H(lhs, rhs) => {
                 let addr = Address(0xFF00 | self.registers.get_byte(rhs) as u16);
-                let value = bus.read(&ctx.as_read(), addr);
+                let value = self.bus_read(bus, ctx, addr, BusOperation::Read);
                 self.registers.set_byte(lhs, value);
             }
             MicroOp::Write8H(lhs, rhs) => {
                 let addr = Address(0xFF00 | self.registers.get_byte(lhs) as u16);
                 let value =  self.registers.get_byte(rhs);
-                bus.write(ctx, addr, value);
+                self.bus_write(bus, ctx, addr, value);
             }
             MicroOp::Read8(lhs, rhs) => {
                 let addr = Address(self.registers.get_word(rhs));
-                let value = bus.read(&ctx.as_read(), addr);
+                let value = self.bus_read(bus, ctx, addr, BusOperation::Read);
                 self.registers.set_byte(lhs, value);
             }
             MicroOp::Read8Inc(lhs, rhs) => {
                 let addr = Address(self.registers.get_word(rhs));
-                let value = bus.read(&ctx.as_read(), addr);
+                let value = self.bus_read(bus, ctx, addr, BusOperation::Read);
                 self.registers.set_byte(lhs, value);
                 self.registers.set_word(rhs, addr.as_u16().wrapping_add(1));
             }
             MicroOp::Read8Dec(lhs, rhs) => {
                 let addr = Address(self.registers.get_word(rhs));
-                let value = bus.read(&ctx.as_read(), addr);
+                let value = self.bus_read(bus, ctx, addr, BusOperation::Read);
                 self.registers.set_byte(lhs, value);
                 self.registers.set_word(rhs, addr.as_u16().wrapping_sub(1));
             }
             MicroOp::Read16msbInc(lhs, rhs) => {
                 let addr = Address(self.registers.get_word(rhs));
-                let value = bus.read(&ctx.as_read(), addr);
+                let value = self.bus_read(bus, ctx, addr, BusOperation::Read);
                 self.registers.set_word_msb(lhs, value);
                 self.registers.set_word(rhs, addr.as_u16().wrapping_add(1));
             }
             MicroOp::Read16msbDec(lhs, rhs) => {
                 let addr = Address(self.registers.get_word(rhs));
-                let value = bus.read(&ctx.as_read(), addr);
+                let value = self.bus_read(bus, ctx, addr, BusOperation::Read);
                 self.registers.set_word_msb(lhs, value);
                 self.registers.set_word(rhs, addr.as_u16().wrapping_sub(1));
             }
             MicroOp::Read16lsbInc(lhs, rhs) => {
                 let addr = Address(self.registers.get_word(rhs));
-                let value = bus.read(&ctx.as_read(), addr);
+                let value = self.bus_read(bus, ctx, addr, BusOperation::Read);
                 self.registers.set_word_lsb(lhs, value);
                 self.registers.set_word(rhs, addr.as_u16().wrapping_add(1));
             }
             MicroOp::Read16lsbDec(lhs, rhs) => {
                 let addr = Address(self.registers.get_word(rhs));
-                let value = bus.read(&ctx.as_read(), addr);
+                let value = self.bus_read(bus, ctx, addr, BusOperation::Read);
                 self.registers.set_word_lsb(lhs, value);
                 self.registers.set_word(rhs, addr.as_u16().wrapping_sub(1));
             }
             MicroOp::Write8(lhs, rhs) => {
                 let addr = Address(self.registers.get_word(lhs));
                 let value = self.registers.get_byte(rhs);
-                bus.write(ctx, addr, value);
+                self.bus_write(bus, ctx, addr, value);
             }
             MicroOp::Write8Inc(lhs, rhs) => {
                 let addr = Address(self.registers.get_word(lhs));
                 let value = self.registers.get_byte(rhs);
-                bus.write(ctx, addr, value);
+                self.bus_write(bus, ctx, addr, value);
                 self.registers.set_word(lhs, addr.as_u16().wrapping_add(1));
             }
             MicroOp::Write8Dec(lhs, rhs) => {
                 let addr = Address(self.registers.get_word(lhs));
                 let value = self.registers.get_byte(rhs);
-                bus.write(ctx, addr, value);
+                self.bus_write(bus, ctx, addr, value);
                 self.registers.set_word(lhs, addr.as_u16().wrapping_sub(1));
             }
             MicroOp::Write16msb(lhs, rhs) => {
                 let addr = Address(self.registers.get_word(lhs));
                 let msb = self.registers.get_word_msb(rhs);
-                bus.write(ctx, addr, msb);
+                self.bus_write(bus, ctx, addr, msb);
             }
             MicroOp::Write16lsb(lhs, rhs) => {
                 let addr = Address(self.registers.get_word(lhs));
                 let lsb = self.registers.get_word_lsb(rhs);
-                bus.write(ctx, addr, lsb);
+                self.bus_write(bus, ctx, addr, lsb);
+            }
+            MicroOp::Write16lsbIntAck(lhs, rhs) => {
+                // Interrupt is chosen only now: the msb push could have written IE (SP = $0000) and
+                // redirected the dispatch to another vector, or cancelled it jumping to $0000
+                let interrupt = self.interrupt_request(ctx.cpu_mmio.interrupt_registers());
+                match interrupt {
+                    Some(interrupt_type) => {
+                        ctx.cpu_mmio.interrupt_registers_mut().reset_if_bit(interrupt_type.flag_mask());
+                        self.registers.set_wz(interrupt_type.vector() as u16);
+                    }
+                    None => {
+                        self.registers.set_wz(0x0000);
+                    }
+                }
+                self.interrupt = interrupt;
+                let addr = Address(self.registers.get_word(lhs));
+                let lsb = self.registers.get_word_lsb(rhs);
+                self.bus_write(bus, ctx, addr, lsb);
             }
             MicroOp::Write16msbInc(lhs, rhs) => {
                 let addr = Address(self.registers.get_word(lhs));
                 let msb = self.registers.get_word_msb(rhs);
-                bus.write(ctx, addr, msb);
+                self.bus_write(bus, ctx, addr, msb);
                 let word = self.registers.get_word(lhs);
                 self.registers.set_word(lhs, word.wrapping_add(1));
             }
             MicroOp::Write16msbDec(lhs, rhs) => {
                 let addr = Address(self.registers.get_word(lhs));
                 let msb = self.registers.get_word_msb(rhs);
-                bus.write(ctx, addr, msb);
+                self.bus_write(bus, ctx, addr, msb);
                 let word = self.registers.get_word(lhs);
                 self.registers.set_word(lhs, word.wrapping_sub(1));
             }
             MicroOp::Write16lsbInc(lhs, rhs) => {
                 let addr = Address(self.registers.get_word(lhs));
                 let lsb = self.registers.get_word_lsb(rhs);
-                bus.write(ctx, addr, lsb);
+                self.bus_write(bus, ctx, addr, lsb);
                 let word = self.registers.get_word(lhs);
                 self.registers.set_word(lhs, word.wrapping_add(1));
             }
             MicroOp::Write16lsbDec(lhs, rhs) => {
                 let addr = Address(self.registers.get_word(lhs));
                 let lsb = self.registers.get_word_lsb(rhs);
-                bus.write(ctx, addr, lsb);
+                self.bus_write(bus, ctx, addr, lsb);
                 let word = self.registers.get_word(lhs);
                 self.registers.set_word(lhs, word.wrapping_sub(1));
             }
@@ -542,10 +1176,15 @@ impl CPU {
                 self.alu_operation(alu_op);
                 let addr = Address(self.registers.get_word(lhs));
                 let value = self.registers.get_byte(rhs);
-                bus.write(ctx, addr, value);
+                self.bus_write(bus, ctx, addr, value);
             }
             MicroOp::ImeEnabled(enabled) => {
                 self.ime = enabled;
+                self.ime_delay = 0;
+            }
+            MicroOp::ImeEnabledDelayed => {
+                // Boundary after EI itself + boundary after the next instruction
+                self.ime_delay = 2;
             }
             MicroOp::PrefixCB => {
                 micro_flow = MicroFlow::PrefixCB;
@@ -633,8 +1272,8 @@ impl CPU {
                 self.registers.set_flags(Flags::new(
                     new_lhs == 0,
                     true,
-                    Flags::add_half_carry(old_lhs, rhs, false),
-                    Flags::add_carry(old_lhs, rhs, false),
+                    Flags::sub_half_carry(old_lhs, rhs, false),
+                    Flags::sub_carry(old_lhs, rhs, false),
                 ));
             }
             AluOp::Sbc(lhs, rhs) => {
@@ -646,8 +1285,8 @@ impl CPU {
                 self.registers.set_flags(Flags::new(
                     new_lhs == 0,
                     true,
-                    Flags::add_half_carry(old_lhs, rhs, carry),
-                    Flags::add_carry(old_lhs, rhs, carry),
+                    Flags::sub_half_carry(old_lhs, rhs, carry),
+                    Flags::sub_carry(old_lhs, rhs, carry),
                 ));
             }
             AluOp::Cp(lhs, rhs) => {
@@ -885,6 +1524,15 @@ impl CPU {
 
 impl Tick for CPU {
     fn tick(&mut self, bus: &mut bus::Bus, ctx: &mut bus::MmioContextWrite) {
+        self.tick_on(bus, ctx);
+    }
+}
+
+impl CPU {
+    /// T-Cycle ticking with memory accesses driven on any bus, e.g. a flat memory test double
+    pub fn tick_on<B: MemoryBus>(&mut self, bus: &mut B, ctx: &mut bus::MmioContextWrite) {
+        let micro_op = self.micro_code;
+        let running = self.status != CpuStatus::Ready;
         match self.status {
             CpuStatus::Execute => {
                 self.micro_code_t_cycle = (self.micro_code_t_cycle + 1) & 0b0000_0011; // Just a Bit version of (value = value % 4)
@@ -894,60 +1542,161 @@ impl Tick for CPU {
                 } else {
                     self.status = CpuStatus::Execute;
                 }},
-            _ => {},
+            CpuStatus::Halt => {
+                // Low-power mode: keep M-Cycle timing and wake up on any IE & IF request, whatever IME value is
+                self.micro_code_t_cycle = (self.micro_code_t_cycle + 1) & 0b0000_0011;
+                if self.micro_code_t_cycle == 0 && ctx.cpu_mmio.interrupt_registers().some_irq() {
+                    self.status = CpuStatus::Ready;
+                }
+            }
+            CpuStatus::Stop => {
+                // Stop mode: only a selected joypad line going low can wake up the CPU
+                self.micro_code_t_cycle = (self.micro_code_t_cycle + 1) & 0b0000_0011;
+                if self.micro_code_t_cycle == 0 && ctx.joypad.selected_line_low() {
+                    self.status = CpuStatus::Ready;
+                }
+            }
+            CpuStatus::Locked => {
+                // Neither interrupts nor joypad can wake up a locked CPU: just keep M-Cycle timing
+                self.micro_code_t_cycle = (self.micro_code_t_cycle + 1) & 0b0000_0011;
+            }
+            CpuStatus::Ready => {},
         }
 
         match self.status {
             CpuStatus::Execute => { /* Wait 'till execution complete */}
             CpuStatus::Ready => {
+                if self.ime_delay > 0 {
+                    self.ime_delay -= 1;
+                    if self.ime_delay == 0 {
+                        self.ime = true;
+                    }
+                }
                 let interrupt = self.interrupt_request(ctx.cpu_mmio.interrupt_registers());
                 if !self.ime || interrupt.is_none() {
+                    self.instruction_pc = self.registers.get_pc();
                     let (instr, opcode) = self.fetch_and_decode(bus, ctx, false);
                     self.interrupt = None;
                     self.instruction = instr;
+                    self.instruction_table = InstructionTable::Opcodes;
                     self.opcode = opcode;
                 } else {
                     let instruction_type = interrupt.unwrap();
                     self.interrupt = interrupt;
                     self.instruction = Some(instruction_type.instruction());
+                    self.instruction_table = InstructionTable::Interrupt(instruction_type);
                     self.ime = false;
-                    match instruction_type {
-                        InterruptType::VBlank => {
-                            ctx.cpu_mmio.interrupt_registers_mut().reset_if_bit(InterruptFlagsMask::VBlank);
-                        }
-                        InterruptType::LCD => {
-                            ctx.cpu_mmio.interrupt_registers_mut().reset_if_bit(InterruptFlagsMask::LCD);
-                        }
-                        InterruptType::Timer => {
-                            ctx.cpu_mmio.interrupt_registers_mut().reset_if_bit(InterruptFlagsMask::Timer);
-                        }
-                        InterruptType::Serial => {
-                            ctx.cpu_mmio.interrupt_registers_mut().reset_if_bit(InterruptFlagsMask::Serial);
-                        }
-                        InterruptType::Joypad => {
-                            ctx.cpu_mmio.interrupt_registers_mut().reset_if_bit(InterruptFlagsMask::JoyPad);
-                        }
+                    if self.halt_bug {
+                        // HALT right after EI: the not incremented PC is pushed, so handler returns to HALT
+                        self.registers.set_pc(self.registers.get_pc().wrapping_sub(1));
+                        self.halt_bug = false;
                     }
+                    self.instruction_pc = self.registers.get_pc();
+                    // IF bit is acknowledged later, when the vector is resolved (after the PC msb push)
                 }
                 self.micro_code_m_cycle = 0;
                 self.micro_code_index = 0;
-                self.micro_code = self.instruction.unwrap().micro_ops[0];
-                self.status = CpuStatus::Execute;
-            }
-            CpuStatus::Halt => {
-                let interrupt_registers = ctx.cpu_mmio.interrupt_registers();
-                if (interrupt_registers.get_ie() & interrupt_registers.get_if()) != 0 {
-                    self.status = CpuStatus::Ready;
-
-                    if !self.ime {
-                        todo!("Implement HALT bug");
+                match self.instruction {
+                    Some(instruction) => {
+                        self.micro_code = instruction.micro_ops[0];
+                        self.status = CpuStatus::Execute;
+                    }
+                    None => {
+                        // Illegal opcode ($D3, $DB, $DD, $E3, $E4, $EB-$ED, $F4, $FC-$FD)
+                        self.micro_code = MCycleOp::None;
+                        self.status = CpuStatus::Locked;
                     }
                 }
             }
+            CpuStatus::Halt => { /* Wait 'till an interrupt is requested */ }
+            CpuStatus::Stop => { /* Wait 'till a joypad input */ }
+            CpuStatus::Locked => { /* Wait 'till reset */ }
+        }
+
+        // A single entry per M-Cycle: the latched access, or Idle if the bus has not been accessed
+        if self.micro_code_t_cycle == 0 && (running || self.bus_cycle.is_some()) {
+            let cycle = self.bus_cycle.take().unwrap_or(BusCycle {
+                address: self.address_latch,
+                operation: BusOperation::Idle,
+                data: None,
+                micro_op,
+            });
+            if let Some(trace) = self.bus_trace.as_mut() {
+                trace.record(cycle);
+            }
         }
     }
 }
 
+impl SaveState for CPU {
+    fn save_state(&self, state: &mut StateWriter) {
+        self.registers.save_state(state);
+        state.write_bool(self.ime);
+        state.write_option_u8(self.interrupt.map(|interrupt| interrupt as u8));
+        state.write_u8(self.opcode);
+        state.write_u16(self.instruction_pc);
+        // Instruction is a static reference: it's stored as its table and opcode
+        state.write_bool(self.instruction.is_some());
+        match self.instruction_table {
+            InstructionTable::Opcodes => state.write_u8(0),
+            InstructionTable::OpcodesCb => state.write_u8(1),
+            InstructionTable::Interrupt(interrupt) => state.write_u8(2 + interrupt as u8),
+        }
+        state.write_u64(self.micro_code_index as u64);
+        state.write_u8(self.micro_code_t_cycle);
+        state.write_u8(self.micro_code_m_cycle);
+        state.write_u8(match self.status {
+            CpuStatus::Execute => 0,
+            CpuStatus::Ready => 1,
+            CpuStatus::Halt => 2,
+            CpuStatus::Stop => 3,
+            CpuStatus::Locked => 4,
+        });
+        state.write_bool(self.halt_bug);
+        state.write_u8(self.ime_delay);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        let interrupt_from_index = |index: u8| InterruptType::ALL.get(index as usize).copied().ok_or(SaveStateError::InvalidValue("CPU interrupt"));
+
+        self.registers.load_state(state)?;
+        self.ime = state.read_bool()?;
+        self.interrupt = state.read_option_u8()?.map(interrupt_from_index).transpose()?;
+        self.opcode = state.read_u8()?;
+        self.instruction_pc = state.read_u16()?;
+        let has_instruction = state.read_bool()?;
+        self.instruction_table = match state.read_u8()? {
+            0 => InstructionTable::Opcodes,
+            1 => InstructionTable::OpcodesCb,
+            index => InstructionTable::Interrupt(interrupt_from_index(index - 2)?),
+        };
+        self.instruction = match (has_instruction, self.instruction_table) {
+            (false, _) => None,
+            (true, InstructionTable::Opcodes) => Some(Self::decode(self.opcode, false).ok_or(SaveStateError::InvalidValue("CPU opcode"))?),
+            (true, InstructionTable::OpcodesCb) => Some(Self::decode(self.opcode, true).ok_or(SaveStateError::InvalidValue("CPU opcode"))?),
+            (true, InstructionTable::Interrupt(interrupt)) => Some(interrupt.instruction()),
+        };
+        self.micro_code_index = state.read_u64()? as usize;
+        self.micro_code = match self.instruction {
+            Some(instruction) => *instruction.micro_ops.get(self.micro_code_index).ok_or(SaveStateError::InvalidValue("CPU micro-code index"))?,
+            None => MCycleOp::None,
+        };
+        self.micro_code_t_cycle = state.read_u8()?;
+        self.micro_code_m_cycle = state.read_u8()?;
+        self.status = match state.read_u8()? {
+            0 => CpuStatus::Execute,
+            1 => CpuStatus::Ready,
+            2 => CpuStatus::Halt,
+            3 => CpuStatus::Stop,
+            4 => CpuStatus::Locked,
+            _ => return Err(SaveStateError::InvalidValue("CPU status")),
+        };
+        self.halt_bug = state.read_bool()?;
+        self.ime_delay = state.read_u8()?;
+        Ok(())
+    }
+}
+
 pub struct CpuCtx {
     pub cpu: CPU,
     pub mmio: CpuMmio
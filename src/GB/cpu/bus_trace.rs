@@ -0,0 +1,77 @@
+use std::collections::VecDeque;
+use crate::GB::cpu::instructions::microcode::MCycleOp;
+use crate::GB::types::Byte;
+
+/// Transaction done by the CPU on the bus during a M-Cycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BusOperation {
+    Fetch, // Opcode fetch, also of the opcode following a CB prefix
+    Read,
+    Write,
+    Idle, // No access: the address latch keeps driving its last address
+}
+
+/// Bus activity of a M-Cycle
+#[derive(Debug, Clone, Copy)]
+pub struct BusCycle {
+    pub address: u16, // Address driven on the bus (the latched one in Idle M-Cycles)
+    pub operation: BusOperation,
+    pub data: Option<Byte>, // Data read or written, None in Idle M-Cycles
+    pub micro_op: MCycleOp, // Micro-op running in the M-Cycle. Opcode fetches overlap the last one of the previous instruction
+}
+
+/// Ring buffer of the last `capacity` M-Cycles bus activity, one entry per M-Cycle
+pub struct BusTrace {
+    cycles: VecDeque<BusCycle>,
+    capacity: usize,
+}
+
+impl BusTrace {
+    pub const DEFAULT_CAPACITY: usize = 1024;
+
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            cycles: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.cycles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cycles.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.cycles.clear();
+    }
+
+    /// Recorded M-Cycles, from the oldest to the latest
+    pub fn iter(&self) -> impl Iterator<Item = &BusCycle> {
+        self.cycles.iter()
+    }
+
+    pub fn last(&self) -> Option<&BusCycle> {
+        self.cycles.back()
+    }
+
+    pub(crate) fn record(&mut self, cycle: BusCycle) {
+        if self.cycles.len() == self.capacity {
+            self.cycles.pop_front();
+        }
+        self.cycles.push_back(cycle);
+    }
+}
+
+impl Default for BusTrace {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_CAPACITY)
+    }
+}
@@ -3,6 +3,7 @@ use crate::GB::memory::hram::HRAM;
 use super::registers::interrupt_registers::InterruptRegisters;
 use crate::GB::types::address::Address;
 use crate::GB::types::Byte;
+use crate::GB::save_state::{SaveState, SaveStateError, StateReader, StateWriter};
 
 pub struct CpuMmio {
     interrupt_registers: InterruptRegisters,
@@ -24,6 +25,7 @@ impl CpuMmio {
         &mut self.interrupt_registers
     }
 
+    #[allow(dead_code)]
     pub fn hram(&self) -> &HRAM {
         &self.hram
     }
@@ -52,3 +54,15 @@ impl Default for CpuMmio {
         Self::new()
     }
 }
+
+impl SaveState for CpuMmio {
+    fn save_state(&self, state: &mut StateWriter) {
+        self.interrupt_registers.save_state(state);
+        self.hram.save_state(state);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        self.interrupt_registers.load_state(state)?;
+        self.hram.load_state(state)
+    }
+}
@@ -7,8 +7,6 @@ pub use interrupt::InterruptType;
 use super::registers::core_registers::{FlagBits};
 use microcode::{*};
 
-pub type InstructionMicroOpIndex = usize;
-
 #[derive(Debug, Clone)]
 pub struct Instruction {
     pub opcode: u8,
@@ -25,6 +23,7 @@ impl Instruction {
     }
 }
 
+#[allow(dead_code)]
 const fn daa(mut a: u8, mut flags: u8) -> (u8, u8) {
     // Code recovered inspired by other gits, but not sure if precise as expected from GB Docs
     let mut adjust = 0;
@@ -87,8 +86,8 @@ pub const INTERRUPT_VBLANK: Instruction = Instruction {
         MCycleOp::Main(MicroOp::Idle),
         MCycleOp::Main(MicroOp::Dec16(Rhs16Bit::SP)),
         MCycleOp::Main(MicroOp::Write16msbDec(Rhs16Bit::SP, Rhs16Bit::PC)),
-        MCycleOp::Main(MicroOp::Write16lsb(Rhs16Bit::SP, Rhs16Bit::PC)),
-        MCycleOp::End(MicroOp::JumpVector(VectorAddress::VBlank)),
+        MCycleOp::Main(MicroOp::Write16lsbIntAck(Rhs16Bit::SP, Rhs16Bit::PC)),
+        MCycleOp::End(MicroOp::Ld16(Rhs16Bit::PC, Rhs16Bit::WZ)),
     ],
 };
 
@@ -102,8 +101,8 @@ pub const INTERRUPT_LCD: Instruction = Instruction {
         MCycleOp::Main(MicroOp::Idle),
         MCycleOp::Main(MicroOp::Dec16(Rhs16Bit::SP)),
         MCycleOp::Main(MicroOp::Write16msbDec(Rhs16Bit::SP, Rhs16Bit::PC)),
-        MCycleOp::Main(MicroOp::Write16lsb(Rhs16Bit::SP, Rhs16Bit::PC)),
-        MCycleOp::End(MicroOp::JumpVector(VectorAddress::STAT)),
+        MCycleOp::Main(MicroOp::Write16lsbIntAck(Rhs16Bit::SP, Rhs16Bit::PC)),
+        MCycleOp::End(MicroOp::Ld16(Rhs16Bit::PC, Rhs16Bit::WZ)),
     ],
 };
 
@@ -117,8 +116,8 @@ pub const INTERRUPT_TIMER: Instruction = Instruction {
         MCycleOp::Main(MicroOp::Idle),
         MCycleOp::Main(MicroOp::Dec16(Rhs16Bit::SP)),
         MCycleOp::Main(MicroOp::Write16msbDec(Rhs16Bit::SP, Rhs16Bit::PC)),
-        MCycleOp::Main(MicroOp::Write16lsb(Rhs16Bit::SP, Rhs16Bit::PC)),
-        MCycleOp::End(MicroOp::JumpVector(VectorAddress::Timer)),
+        MCycleOp::Main(MicroOp::Write16lsbIntAck(Rhs16Bit::SP, Rhs16Bit::PC)),
+        MCycleOp::End(MicroOp::Ld16(Rhs16Bit::PC, Rhs16Bit::WZ)),
     ],
 };
 
@@ -132,8 +131,8 @@ pub const INTERRUPT_SERIAL: Instruction = Instruction {
         MCycleOp::Main(MicroOp::Idle),
         MCycleOp::Main(MicroOp::Dec16(Rhs16Bit::SP)),
         MCycleOp::Main(MicroOp::Write16msbDec(Rhs16Bit::SP, Rhs16Bit::PC)),
-        MCycleOp::Main(MicroOp::Write16lsb(Rhs16Bit::SP, Rhs16Bit::PC)),
-        MCycleOp::End(MicroOp::JumpVector(VectorAddress::Serial)),
+        MCycleOp::Main(MicroOp::Write16lsbIntAck(Rhs16Bit::SP, Rhs16Bit::PC)),
+        MCycleOp::End(MicroOp::Ld16(Rhs16Bit::PC, Rhs16Bit::WZ)),
     ],
 };
 
@@ -147,8 +146,8 @@ pub const INTERRUPT_JOYPAD: Instruction = Instruction {
         MCycleOp::Main(MicroOp::Idle),
         MCycleOp::Main(MicroOp::Dec16(Rhs16Bit::SP)),
         MCycleOp::Main(MicroOp::Write16msbDec(Rhs16Bit::SP, Rhs16Bit::PC)),
-        MCycleOp::Main(MicroOp::Write16lsb(Rhs16Bit::SP, Rhs16Bit::PC)),
-        MCycleOp::End(MicroOp::JumpVector(VectorAddress::Joypad)),
+        MCycleOp::Main(MicroOp::Write16lsbIntAck(Rhs16Bit::SP, Rhs16Bit::PC)),
+        MCycleOp::End(MicroOp::Ld16(Rhs16Bit::PC, Rhs16Bit::WZ)),
     ],
 };
 
@@ -334,8 +333,8 @@ const fn create_opcodes() -> [Option<&'static Instruction>; 256] {
         size: 2,
         flags: &[],
         micro_ops: &[
-            // TODO: implement STOP
-            MCycleOp::End(MicroOp::Alu(AluOp::Rrca())),
+            // Stop is not a simple instruction, it alters CPU ticking flux (and the system one)
+            MCycleOp::Stop,
         ],
     });
     opcodes[0x11] = Some(&Instruction {
@@ -613,7 +612,7 @@ const fn create_opcodes() -> [Option<&'static Instruction>; 256] {
         ],
     });
     opcodes[0x2A] = Some(&Instruction {
-        opcode: 0x3A,
+        opcode: 0x2A,
         name: "LD A, [HL+]",
         cycles: 2,
         size: 1,
@@ -651,7 +650,7 @@ const fn create_opcodes() -> [Option<&'static Instruction>; 256] {
         size: 1,
         flags: &[FlagBits::Z, FlagBits::N, FlagBits::H],
         micro_ops: &[
-            MCycleOp::End(MicroOp::Alu(AluOp::Dec(Rhs8Bit::E)))
+            MCycleOp::End(MicroOp::Alu(AluOp::Dec(Rhs8Bit::L)))
         ],
     });
     opcodes[0x2E] = Some(&Instruction {
@@ -676,7 +675,7 @@ const fn create_opcodes() -> [Option<&'static Instruction>; 256] {
         ],
     });
     opcodes[0x30] = Some(&Instruction {
-        opcode: 0x20,
+        opcode: 0x30,
         name: "JR NC, e8",
         cycles: 3, // 2 Cycles if condition doesn't match
         size: 2,
@@ -810,7 +809,7 @@ const fn create_opcodes() -> [Option<&'static Instruction>; 256] {
         size: 1,
         flags: &[],
         micro_ops: &[
-            MCycleOp::Main(MicroOp::Dec16(AddressRegister::HL)),
+            MCycleOp::Main(MicroOp::Dec16(AddressRegister::SP)),
             MCycleOp::End(MicroOp::Idle), // Simulating M-Cycle to stabilize bit signals after Inc/Dec in the 16-bit register
         ],
     });
@@ -852,7 +851,7 @@ const fn create_opcodes() -> [Option<&'static Instruction>; 256] {
         size: 1,
         flags: &[FlagBits::N, FlagBits::H, FlagBits::C],
         micro_ops: &[
-            MCycleOp::End(MicroOp::Alu(AluOp::SetFlags(SetFlagZ::Same, SetFlagN::Off, SetFlagH::Off, SetFlagC::On))),
+            MCycleOp::End(MicroOp::Alu(AluOp::SetFlags(SetFlagZ::Same, SetFlagN::Off, SetFlagH::Off, SetFlagC::Cpl))),
         ],
     });
     opcodes[0x40] = Some(&Instruction {
@@ -1602,7 +1601,7 @@ const fn create_opcodes() -> [Option<&'static Instruction>; 256] {
         ],
     });
     opcodes[0x89] = Some(&Instruction {
-        opcode: 0x88,
+        opcode: 0x89,
         name: "ADC A, C",
         cycles: 1,
         size: 1,
@@ -2063,7 +2062,7 @@ const fn create_opcodes() -> [Option<&'static Instruction>; 256] {
         size: 1,
         flags: &[FlagBits::Z, FlagBits::N, FlagBits::H, FlagBits::C],
         micro_ops: &[
-            MCycleOp::End(MicroOp::Read8(Rhs8Bit::Z, AddressRegister::HL)),
+            MCycleOp::Main(MicroOp::Read8(Rhs8Bit::Z, AddressRegister::HL)),
             MCycleOp::End(MicroOp::Alu(AluOp::Or(Rhs8Bit::A, Rhs8Bit::Z))),
         ],
     });
@@ -2805,7 +2804,7 @@ const fn create_opcodes() -> [Option<&'static Instruction>; 256] {
         cycles: 1,
         size: 1,
         micro_ops: &[
-            MCycleOp::End(MicroOp::ImeEnabled(true)),
+            MCycleOp::End(MicroOp::ImeEnabledDelayed),
         ],
         flags: &[],
     });
@@ -2859,8 +2858,8 @@ const fn create_cb_opcodes() -> [Option<&'static Instruction>; 256] {
                 flags: &[FlagBits::Z, FlagBits::N, FlagBits::H, FlagBits::C],
                 micro_ops: &[
                     MCycleOp::Main(MicroOp::Read8($rhs8bit, $address_register)),
-                    MCycleOp::Main(MicroOp::Alu(AluOp::Rlc($rhs8bit))),
-                    MCycleOp::End(MicroOp::Write8($address_register, $rhs8bit)), // TODO refactor: not 100% M-Cycle accurate. This is done in the previous and this just reset PC as buffer address
+                    MCycleOp::Main(MicroOp::AluAndWrite8(AluOp::Rlc($rhs8bit), $address_register, $rhs8bit)),
+                    MCycleOp::End(MicroOp::Idle), // Simulating M-Cycle to restore Buffer Address to PC
                 ],
             })
         };
@@ -2888,8 +2887,8 @@ const fn create_cb_opcodes() -> [Option<&'static Instruction>; 256] {
                 flags: &[FlagBits::Z, FlagBits::N, FlagBits::H, FlagBits::C],
                 micro_ops: &[
                     MCycleOp::Main(MicroOp::Read8($rhs8bit, $address_register)),
-                    MCycleOp::Main(MicroOp::Alu(AluOp::Rlc($rhs8bit))),
-                    MCycleOp::End(MicroOp::Write8($address_register, $rhs8bit)), // TODO refactor: not 100% M-Cycle accurate. This is done in the previous and this just reset PC as buffer address
+                    MCycleOp::Main(MicroOp::AluAndWrite8(AluOp::Rrc($rhs8bit), $address_register, $rhs8bit)),
+                    MCycleOp::End(MicroOp::Idle), // Simulating M-Cycle to restore Buffer Address to PC
                 ],
             })
         };
@@ -2917,8 +2916,8 @@ const fn create_cb_opcodes() -> [Option<&'static Instruction>; 256] {
                 flags: &[FlagBits::Z, FlagBits::N, FlagBits::H, FlagBits::C],
                 micro_ops: &[
                     MCycleOp::Main(MicroOp::Read8($rhs8bit, $address_register)),
-                    MCycleOp::Main(MicroOp::Alu(AluOp::Rl($rhs8bit))),
-                    MCycleOp::End(MicroOp::Write8($address_register, $rhs8bit)), // TODO refactor: not 100% M-Cycle accurate. This is done in the previous and this just reset PC as buffer address
+                    MCycleOp::Main(MicroOp::AluAndWrite8(AluOp::Rl($rhs8bit), $address_register, $rhs8bit)),
+                    MCycleOp::End(MicroOp::Idle), // Simulating M-Cycle to restore Buffer Address to PC
                 ],
             })
         };
@@ -2945,8 +2944,8 @@ const fn create_cb_opcodes() -> [Option<&'static Instruction>; 256] {
                 flags: &[FlagBits::Z, FlagBits::N, FlagBits::H, FlagBits::C],
                 micro_ops: &[
                     MCycleOp::Main(MicroOp::Read8($rhs8bit, $address_register)),
-                    MCycleOp::Main(MicroOp::Alu(AluOp::Sla($rhs8bit))),
-                    MCycleOp::End(MicroOp::Write8($address_register, $rhs8bit)), // TODO refactor: not 100% M-Cycle accurate. This is done in the previous and this just reset PC as buffer address
+                    MCycleOp::Main(MicroOp::AluAndWrite8(AluOp::Sla($rhs8bit), $address_register, $rhs8bit)),
+                    MCycleOp::End(MicroOp::Idle), // Simulating M-Cycle to restore Buffer Address to PC
                 ],
             })
         };
@@ -2974,8 +2973,8 @@ const fn create_cb_opcodes() -> [Option<&'static Instruction>; 256] {
                 flags: &[FlagBits::Z, FlagBits::N, FlagBits::H, FlagBits::C],
                 micro_ops: &[
                     MCycleOp::Main(MicroOp::Read8($rhs8bit, $address_register)),
-                    MCycleOp::Main(MicroOp::Alu(AluOp::Rr($rhs8bit))),
-                    MCycleOp::End(MicroOp::Write8($address_register, $rhs8bit)), // TODO refactor: not 100% M-Cycle accurate. This is done in the previous and this just reset PC as buffer address
+                    MCycleOp::Main(MicroOp::AluAndWrite8(AluOp::Rr($rhs8bit), $address_register, $rhs8bit)),
+                    MCycleOp::End(MicroOp::Idle), // Simulating M-Cycle to restore Buffer Address to PC
                 ],
             })
         };
@@ -3002,8 +3001,8 @@ const fn create_cb_opcodes() -> [Option<&'static Instruction>; 256] {
                 flags: &[FlagBits::Z, FlagBits::N, FlagBits::H, FlagBits::C],
                 micro_ops: &[
                     MCycleOp::Main(MicroOp::Read8($rhs8bit, $address_register)),
-                    MCycleOp::Main(MicroOp::Alu(AluOp::Sra($rhs8bit))),
-                    MCycleOp::End(MicroOp::Write8($address_register, $rhs8bit)), // TODO refactor: not 100% M-Cycle accurate. This is done in the previous and this just reset PC as buffer address
+                    MCycleOp::Main(MicroOp::AluAndWrite8(AluOp::Sra($rhs8bit), $address_register, $rhs8bit)),
+                    MCycleOp::End(MicroOp::Idle), // Simulating M-Cycle to restore Buffer Address to PC
                 ],
             })
         };
@@ -3031,8 +3030,8 @@ const fn create_cb_opcodes() -> [Option<&'static Instruction>; 256] {
                 flags: &[FlagBits::Z, FlagBits::N, FlagBits::H, FlagBits::C],
                 micro_ops: &[
                     MCycleOp::Main(MicroOp::Read8($rhs8bit, $address_register)),
-                    MCycleOp::Main(MicroOp::Alu(AluOp::Srl($rhs8bit))),
-                    MCycleOp::End(MicroOp::Write8($address_register, $rhs8bit)), // TODO refactor: not 100% M-Cycle accurate. This is done in the previous and this just reset PC as buffer address
+                    MCycleOp::Main(MicroOp::AluAndWrite8(AluOp::Srl($rhs8bit), $address_register, $rhs8bit)),
+                    MCycleOp::End(MicroOp::Idle), // Simulating M-Cycle to restore Buffer Address to PC
                 ],
             })
         };
@@ -3060,8 +3059,8 @@ const fn create_cb_opcodes() -> [Option<&'static Instruction>; 256] {
                 flags: &[FlagBits::Z, FlagBits::N, FlagBits::H, FlagBits::C],
                 micro_ops: &[
                     MCycleOp::Main(MicroOp::Read8($rhs8bit, $address_register)),
-                    MCycleOp::Main(MicroOp::Alu(AluOp::Swap($rhs8bit))),
-                    MCycleOp::End(MicroOp::Write8($address_register, $rhs8bit)), // TODO refactor: not 100% M-Cycle accurate. This is done in the previous and this just reset PC as buffer address
+                    MCycleOp::Main(MicroOp::AluAndWrite8(AluOp::Swap($rhs8bit), $address_register, $rhs8bit)),
+                    MCycleOp::End(MicroOp::Idle), // Simulating M-Cycle to restore Buffer Address to PC
                 ],
             })
         };
@@ -3090,7 +3089,7 @@ const fn create_cb_opcodes() -> [Option<&'static Instruction>; 256] {
                 micro_ops: &[
                     MCycleOp::Main(MicroOp::Read8($rhs8bit, $address_register)),
                     MCycleOp::Main(MicroOp::Alu(AluOp::Bit($bit, $rhs8bit))),
-                    MCycleOp::End(MicroOp::Write8($address_register, $rhs8bit)), // TODO refactor: not 100% M-Cycle accurate. This is done in the previous and this just reset PC as buffer address
+                    MCycleOp::End(MicroOp::Idle), // Simulating M-Cycle to restore Buffer Address to PC
                 ],
             })
         };
@@ -3118,8 +3117,8 @@ const fn create_cb_opcodes() -> [Option<&'static Instruction>; 256] {
                 flags: &[],
                 micro_ops: &[
                     MCycleOp::Main(MicroOp::Read8($rhs8bit, $address_register)),
-                    MCycleOp::Main(MicroOp::Alu(AluOp::Res($bit, $rhs8bit))),
-                    MCycleOp::End(MicroOp::Write8($address_register, $rhs8bit)), // TODO refactor: not 100% M-Cycle accurate. This is done in the previous and this just reset PC as buffer address
+                    MCycleOp::Main(MicroOp::AluAndWrite8(AluOp::Res($bit, $rhs8bit), $address_register, $rhs8bit)),
+                    MCycleOp::End(MicroOp::Idle), // Simulating M-Cycle to restore Buffer Address to PC
                 ],
             })
         };
@@ -3147,8 +3146,8 @@ const fn create_cb_opcodes() -> [Option<&'static Instruction>; 256] {
                 flags: &[],
                 micro_ops: &[
                     MCycleOp::Main(MicroOp::Read8($rhs8bit, $address_register)),
-                    MCycleOp::Main(MicroOp::Alu(AluOp::Set($bit, $rhs8bit))),
-                    MCycleOp::End(MicroOp::Write8($address_register, $rhs8bit)), // TODO refactor: not 100% M-Cycle accurate. This is done in the previous and this just reset PC as buffer address
+                    MCycleOp::Main(MicroOp::AluAndWrite8(AluOp::Set($bit, $rhs8bit), $address_register, $rhs8bit)),
+                    MCycleOp::End(MicroOp::Idle), // Simulating M-Cycle to restore Buffer Address to PC
                 ],
             })
         };
@@ -3234,7 +3233,7 @@ const fn create_cb_opcodes() -> [Option<&'static Instruction>; 256] {
     opcodes[0x44] = bit!(0x44, "BIT 0, H", ByteBit::Zero, Rhs8Bit::H);
     opcodes[0x45] = bit!(0x45, "BIT 0, L", ByteBit::Zero, Rhs8Bit::L);
     opcodes[0x46] = bit!(0x46, "BIT 0, [HL]", ByteBit::Zero, AddressRegister::HL, Rhs8Bit::Z);
-    opcodes[0x47] = bit!(0x47, "BIT 0, A", ByteBit::Zero, Rhs8Bit::C);
+    opcodes[0x47] = bit!(0x47, "BIT 0, A", ByteBit::Zero, Rhs8Bit::A);
 
     opcodes[0x48] = bit!(0x48, "BIT 1, B", ByteBit::One, Rhs8Bit::B);
     opcodes[0x49] = bit!(0x49, "BIT 1, C", ByteBit::One, Rhs8Bit::C);
@@ -3243,7 +3242,7 @@ const fn create_cb_opcodes() -> [Option<&'static Instruction>; 256] {
     opcodes[0x4C] = bit!(0x4c, "BIT 1, H", ByteBit::One, Rhs8Bit::H);
     opcodes[0x4D] = bit!(0x4d, "BIT 1, L", ByteBit::One, Rhs8Bit::L);
     opcodes[0x4E] = bit!(0x4e, "BIT 1, [HL]", ByteBit::One, AddressRegister::HL, Rhs8Bit::Z);
-    opcodes[0x4F] = bit!(0x4f, "BIT 1, A", ByteBit::One, Rhs8Bit::C);
+    opcodes[0x4F] = bit!(0x4f, "BIT 1, A", ByteBit::One, Rhs8Bit::A);
 
     opcodes[0x50] = bit!(0x50, "BIT 2, B", ByteBit::Two, Rhs8Bit::B);
     opcodes[0x51] = bit!(0x51, "BIT 2, C", ByteBit::Two, Rhs8Bit::C);
@@ -3252,7 +3251,7 @@ const fn create_cb_opcodes() -> [Option<&'static Instruction>; 256] {
     opcodes[0x54] = bit!(0x54, "BIT 2, H", ByteBit::Two, Rhs8Bit::H);
     opcodes[0x55] = bit!(0x55, "BIT 2, L", ByteBit::Two, Rhs8Bit::L);
     opcodes[0x56] = bit!(0x56, "BIT 2, [HL]", ByteBit::Two, AddressRegister::HL, Rhs8Bit::Z);
-    opcodes[0x57] = bit!(0x57, "BIT 2, A", ByteBit::Two, Rhs8Bit::C);
+    opcodes[0x57] = bit!(0x57, "BIT 2, A", ByteBit::Two, Rhs8Bit::A);
 
     opcodes[0x58] = bit!(0x58, "BIT 3, B", ByteBit::Three, Rhs8Bit::B);
     opcodes[0x59] = bit!(0x59, "BIT 3, C", ByteBit::Three, Rhs8Bit::C);
@@ -3261,7 +3260,7 @@ const fn create_cb_opcodes() -> [Option<&'static Instruction>; 256] {
     opcodes[0x5C] = bit!(0x5c, "BIT 3, H", ByteBit::Three, Rhs8Bit::H);
     opcodes[0x5D] = bit!(0x5d, "BIT 3, L", ByteBit::Three, Rhs8Bit::L);
     opcodes[0x5E] = bit!(0x5e, "BIT 3, [HL]", ByteBit::Three, AddressRegister::HL, Rhs8Bit::Z);
-    opcodes[0x5F] = bit!(0x5f, "BIT 3, A", ByteBit::Three, Rhs8Bit::C);
+    opcodes[0x5F] = bit!(0x5f, "BIT 3, A", ByteBit::Three, Rhs8Bit::A);
 
     opcodes[0x60] = bit!(0x60, "BIT 4, B", ByteBit::Four, Rhs8Bit::B);
     opcodes[0x61] = bit!(0x61, "BIT 4, C", ByteBit::Four, Rhs8Bit::C);
@@ -3270,7 +3269,7 @@ const fn create_cb_opcodes() -> [Option<&'static Instruction>; 256] {
     opcodes[0x64] = bit!(0x64, "BIT 4, H", ByteBit::Four, Rhs8Bit::H);
     opcodes[0x65] = bit!(0x65, "BIT 4, L", ByteBit::Four, Rhs8Bit::L);
     opcodes[0x66] = bit!(0x66, "BIT 4, [HL]", ByteBit::Four, AddressRegister::HL, Rhs8Bit::Z);
-    opcodes[0x67] = bit!(0x67, "BIT 4, A", ByteBit::Four, Rhs8Bit::C);
+    opcodes[0x67] = bit!(0x67, "BIT 4, A", ByteBit::Four, Rhs8Bit::A);
 
     opcodes[0x68] = bit!(0x68, "BIT 5, B", ByteBit::Five, Rhs8Bit::B);
     opcodes[0x69] = bit!(0x69, "BIT 5, C", ByteBit::Five, Rhs8Bit::C);
@@ -3279,7 +3278,7 @@ const fn create_cb_opcodes() -> [Option<&'static Instruction>; 256] {
     opcodes[0x6C] = bit!(0x6c, "BIT 5, H", ByteBit::Five, Rhs8Bit::H);
     opcodes[0x6D] = bit!(0x6d, "BIT 5, L", ByteBit::Five, Rhs8Bit::L);
     opcodes[0x6E] = bit!(0x6e, "BIT 5, [HL]", ByteBit::Five, AddressRegister::HL, Rhs8Bit::Z);
-    opcodes[0x6F] = bit!(0x6f, "BIT 5, A", ByteBit::Five, Rhs8Bit::C);
+    opcodes[0x6F] = bit!(0x6f, "BIT 5, A", ByteBit::Five, Rhs8Bit::A);
 
     opcodes[0x70] = bit!(0x70, "BIT 6, B", ByteBit::Six, Rhs8Bit::B);
     opcodes[0x71] = bit!(0x71, "BIT 6, C", ByteBit::Six, Rhs8Bit::C);
@@ -3288,7 +3287,7 @@ const fn create_cb_opcodes() -> [Option<&'static Instruction>; 256] {
     opcodes[0x74] = bit!(0x74, "BIT 6, H", ByteBit::Six, Rhs8Bit::H);
     opcodes[0x75] = bit!(0x75, "BIT 6, L", ByteBit::Six, Rhs8Bit::L);
     opcodes[0x76] = bit!(0x76, "BIT 6, [HL]", ByteBit::Six, AddressRegister::HL, Rhs8Bit::Z);
-    opcodes[0x77] = bit!(0x77, "BIT 6, A", ByteBit::Six, Rhs8Bit::C);
+    opcodes[0x77] = bit!(0x77, "BIT 6, A", ByteBit::Six, Rhs8Bit::A);
 
     opcodes[0x78] = bit!(0x78, "BIT 7, B", ByteBit::Seven, Rhs8Bit::B);
     opcodes[0x79] = bit!(0x79, "BIT 7, C", ByteBit::Seven, Rhs8Bit::C);
@@ -3297,7 +3296,7 @@ const fn create_cb_opcodes() -> [Option<&'static Instruction>; 256] {
     opcodes[0x7C] = bit!(0x7c, "BIT 7, H", ByteBit::Seven, Rhs8Bit::H);
     opcodes[0x7D] = bit!(0x7d, "BIT 7, L", ByteBit::Seven, Rhs8Bit::L);
     opcodes[0x7E] = bit!(0x7e, "BIT 7, [HL]", ByteBit::Seven, AddressRegister::HL, Rhs8Bit::Z);
-    opcodes[0x7F] = bit!(0x7f, "BIT 7, A", ByteBit::Seven, Rhs8Bit::C);
+    opcodes[0x7F] = bit!(0x7f, "BIT 7, A", ByteBit::Seven, Rhs8Bit::A);
 
     opcodes[0x80] = res!(0x80, "RES 0, B", ByteBit::Zero, Rhs8Bit::B);
     opcodes[0x81] = res!(0x81, "RES 0, C", ByteBit::Zero, Rhs8Bit::C);
@@ -3306,7 +3305,7 @@ const fn create_cb_opcodes() -> [Option<&'static Instruction>; 256] {
     opcodes[0x84] = res!(0x84, "RES 0, H", ByteBit::Zero, Rhs8Bit::H);
     opcodes[0x85] = res!(0x85, "RES 0, L", ByteBit::Zero, Rhs8Bit::L);
     opcodes[0x86] = res!(0x86, "RES 0, [HL]", ByteBit::Zero, AddressRegister::HL, Rhs8Bit::Z);
-    opcodes[0x87] = res!(0x87, "RES 0, A", ByteBit::Zero, Rhs8Bit::C);
+    opcodes[0x87] = res!(0x87, "RES 0, A", ByteBit::Zero, Rhs8Bit::A);
 
     opcodes[0x88] = res!(0x88, "RES 1, B", ByteBit::One, Rhs8Bit::B);
     opcodes[0x89] = res!(0x89, "RES 1, C", ByteBit::One, Rhs8Bit::C);
@@ -3315,7 +3314,7 @@ const fn create_cb_opcodes() -> [Option<&'static Instruction>; 256] {
     opcodes[0x8C] = res!(0x8c, "RES 1, H", ByteBit::One, Rhs8Bit::H);
     opcodes[0x8D] = res!(0x8d, "RES 1, L", ByteBit::One, Rhs8Bit::L);
     opcodes[0x8E] = res!(0x8e, "RES 1, [HL]", ByteBit::One, AddressRegister::HL, Rhs8Bit::Z);
-    opcodes[0x8F] = res!(0x8f, "RES 1, A", ByteBit::One, Rhs8Bit::C);
+    opcodes[0x8F] = res!(0x8f, "RES 1, A", ByteBit::One, Rhs8Bit::A);
 
     opcodes[0x90] = res!(0x90, "RES 2, B", ByteBit::Two, Rhs8Bit::B);
     opcodes[0x91] = res!(0x91, "RES 2, C", ByteBit::Two, Rhs8Bit::C);
@@ -3324,7 +3323,7 @@ const fn create_cb_opcodes() -> [Option<&'static Instruction>; 256] {
     opcodes[0x94] = res!(0x94, "RES 2, H", ByteBit::Two, Rhs8Bit::H);
     opcodes[0x95] = res!(0x95, "RES 2, L", ByteBit::Two, Rhs8Bit::L);
     opcodes[0x96] = res!(0x96, "RES 2, [HL]", ByteBit::Two, AddressRegister::HL, Rhs8Bit::Z);
-    opcodes[0x97] = res!(0x97, "RES 2, A", ByteBit::Two, Rhs8Bit::C);
+    opcodes[0x97] = res!(0x97, "RES 2, A", ByteBit::Two, Rhs8Bit::A);
 
     opcodes[0x98] = res!(0x98, "RES 3, B", ByteBit::Three, Rhs8Bit::B);
     opcodes[0x99] = res!(0x99, "RES 3, C", ByteBit::Three, Rhs8Bit::C);
@@ -3333,7 +3332,7 @@ const fn create_cb_opcodes() -> [Option<&'static Instruction>; 256] {
     opcodes[0x9C] = res!(0x9c, "RES 3, H", ByteBit::Three, Rhs8Bit::H);
     opcodes[0x9D] = res!(0x9d, "RES 3, L", ByteBit::Three, Rhs8Bit::L);
     opcodes[0x9E] = res!(0x9e, "RES 3, [HL]", ByteBit::Three, AddressRegister::HL, Rhs8Bit::Z);
-    opcodes[0x9F] = res!(0x9f, "RES 3, A", ByteBit::Three, Rhs8Bit::C);
+    opcodes[0x9F] = res!(0x9f, "RES 3, A", ByteBit::Three, Rhs8Bit::A);
 
     opcodes[0xA0] = res!(0xA0, "RES 4, B", ByteBit::Four, Rhs8Bit::B);
     opcodes[0xA1] = res!(0xA1, "RES 4, C", ByteBit::Four, Rhs8Bit::C);
@@ -3342,7 +3341,7 @@ const fn create_cb_opcodes() -> [Option<&'static Instruction>; 256] {
     opcodes[0xA4] = res!(0xA4, "RES 4, H", ByteBit::Four, Rhs8Bit::H);
     opcodes[0xA5] = res!(0xA5, "RES 4, L", ByteBit::Four, Rhs8Bit::L);
     opcodes[0xA6] = res!(0xA6, "RES 4, [HL]", ByteBit::Four, AddressRegister::HL, Rhs8Bit::Z);
-    opcodes[0xA7] = res!(0xA7, "RES 4, A", ByteBit::Four, Rhs8Bit::C);
+    opcodes[0xA7] = res!(0xA7, "RES 4, A", ByteBit::Four, Rhs8Bit::A);
 
     opcodes[0xA8] = res!(0xA8, "RES 5, B", ByteBit::Five, Rhs8Bit::B);
     opcodes[0xA9] = res!(0xA9, "RES 5, C", ByteBit::Five, Rhs8Bit::C);
@@ -3351,7 +3350,7 @@ const fn create_cb_opcodes() -> [Option<&'static Instruction>; 256] {
     opcodes[0xAC] = res!(0xAc, "RES 5, H", ByteBit::Five, Rhs8Bit::H);
     opcodes[0xAD] = res!(0xAd, "RES 5, L", ByteBit::Five, Rhs8Bit::L);
     opcodes[0xAE] = res!(0xAe, "RES 5, [HL]", ByteBit::Five, AddressRegister::HL, Rhs8Bit::Z);
-    opcodes[0xAF] = res!(0xAf, "RES 5, A", ByteBit::Five, Rhs8Bit::C);
+    opcodes[0xAF] = res!(0xAf, "RES 5, A", ByteBit::Five, Rhs8Bit::A);
 
     opcodes[0xB0] = res!(0xB0, "RES 6, B", ByteBit::Six, Rhs8Bit::B);
     opcodes[0xB1] = res!(0xB1, "RES 6, C", ByteBit::Six, Rhs8Bit::C);
@@ -3360,7 +3359,7 @@ const fn create_cb_opcodes() -> [Option<&'static Instruction>; 256] {
     opcodes[0xB4] = res!(0xB4, "RES 6, H", ByteBit::Six, Rhs8Bit::H);
     opcodes[0xB5] = res!(0xB5, "RES 6, L", ByteBit::Six, Rhs8Bit::L);
     opcodes[0xB6] = res!(0xB6, "RES 6, [HL]", ByteBit::Six, AddressRegister::HL, Rhs8Bit::Z);
-    opcodes[0xB7] = res!(0xB7, "RES 6, A", ByteBit::Six, Rhs8Bit::C);
+    opcodes[0xB7] = res!(0xB7, "RES 6, A", ByteBit::Six, Rhs8Bit::A);
 
     opcodes[0xB8] = res!(0xB8, "RES 7, B", ByteBit::Seven, Rhs8Bit::B);
     opcodes[0xB9] = res!(0xB9, "RES 7, C", ByteBit::Seven, Rhs8Bit::C);
@@ -3369,7 +3368,7 @@ const fn create_cb_opcodes() -> [Option<&'static Instruction>; 256] {
     opcodes[0xBC] = res!(0xBc, "RES 7, H", ByteBit::Seven, Rhs8Bit::H);
     opcodes[0xBD] = res!(0xBd, "RES 7, L", ByteBit::Seven, Rhs8Bit::L);
     opcodes[0xBE] = res!(0xBe, "RES 7, [HL]", ByteBit::Seven, AddressRegister::HL, Rhs8Bit::Z);
-    opcodes[0xBF] = res!(0xBf, "RES 7, A", ByteBit::Seven, Rhs8Bit::C);
+    opcodes[0xBF] = res!(0xBf, "RES 7, A", ByteBit::Seven, Rhs8Bit::A);
 
     opcodes[0xC0] = set!(0xC0, "SET 0, B", ByteBit::Zero, Rhs8Bit::B);
     opcodes[0xC1] = set!(0xC1, "SET 0, C", ByteBit::Zero, Rhs8Bit::C);
@@ -3378,7 +3377,7 @@ const fn create_cb_opcodes() -> [Option<&'static Instruction>; 256] {
     opcodes[0xC4] = set!(0xC4, "SET 0, H", ByteBit::Zero, Rhs8Bit::H);
     opcodes[0xC5] = set!(0xC5, "SET 0, L", ByteBit::Zero, Rhs8Bit::L);
     opcodes[0xC6] = set!(0xC6, "SET 0, [HL]", ByteBit::Zero, AddressRegister::HL, Rhs8Bit::Z);
-    opcodes[0xC7] = set!(0xC7, "SET 0, A", ByteBit::Zero, Rhs8Bit::C);
+    opcodes[0xC7] = set!(0xC7, "SET 0, A", ByteBit::Zero, Rhs8Bit::A);
 
     opcodes[0xC8] = set!(0xc8, "SET 1, B", ByteBit::One, Rhs8Bit::B);
     opcodes[0xC9] = set!(0xc9, "SET 1, C", ByteBit::One, Rhs8Bit::C);
@@ -3386,8 +3385,8 @@ const fn create_cb_opcodes() -> [Option<&'static Instruction>; 256] {
     opcodes[0xCB] = set!(0xcb, "SET 1, E", ByteBit::One, Rhs8Bit::E);
     opcodes[0xCC] = set!(0xcc, "SET 1, H", ByteBit::One, Rhs8Bit::H);
     opcodes[0xCD] = set!(0xcd, "SET 1, L", ByteBit::One, Rhs8Bit::L);
-    opcodes[0xCE] = set!(0xce, "SET 1, [HL ", ByteBit::One, AddressRegister::HL, Rhs8Bit::Z);
-    opcodes[0xCF] = set!(0xcf, "SET 1, A", ByteBit::One, Rhs8Bit::C);
+    opcodes[0xCE] = set!(0xCE, "SET 1, [HL]", ByteBit::One, AddressRegister::HL, Rhs8Bit::Z);
+    opcodes[0xCF] = set!(0xcf, "SET 1, A", ByteBit::One, Rhs8Bit::A);
 
     opcodes[0xD0] = set!(0xd0, "SET 2, B", ByteBit::Two, Rhs8Bit::B);
     opcodes[0xD1] = set!(0xd1, "SET 2, C", ByteBit::Two, Rhs8Bit::C);
@@ -3396,7 +3395,7 @@ const fn create_cb_opcodes() -> [Option<&'static Instruction>; 256] {
     opcodes[0xD4] = set!(0xd4, "SET 2, H", ByteBit::Two, Rhs8Bit::H);
     opcodes[0xD5] = set!(0xd5, "SET 2, L", ByteBit::Two, Rhs8Bit::L);
     opcodes[0xD6] = set!(0xd6, "SET 2, [HL]", ByteBit::Two, AddressRegister::HL, Rhs8Bit::Z);
-    opcodes[0xD7] = set!(0xd7, "SET 2, A", ByteBit::Two, Rhs8Bit::C);
+    opcodes[0xD7] = set!(0xd7, "SET 2, A", ByteBit::Two, Rhs8Bit::A);
 
     opcodes[0xD8] = set!(0xd8, "SET 3, B", ByteBit::Three, Rhs8Bit::B);
     opcodes[0xD9] = set!(0xd9, "SET 3, C", ByteBit::Three, Rhs8Bit::C);
@@ -3405,7 +3404,7 @@ const fn create_cb_opcodes() -> [Option<&'static Instruction>; 256] {
     opcodes[0xDC] = set!(0xdc, "SET 3, H", ByteBit::Three, Rhs8Bit::H);
     opcodes[0xDD] = set!(0xdd, "SET 3, L", ByteBit::Three, Rhs8Bit::L);
     opcodes[0xDE] = set!(0xde, "SET 3, [HL]", ByteBit::Three, AddressRegister::HL, Rhs8Bit::Z);
-    opcodes[0xDF] = set!(0xdf, "SET 3, A", ByteBit::Three, Rhs8Bit::C);
+    opcodes[0xDF] = set!(0xdf, "SET 3, A", ByteBit::Three, Rhs8Bit::A);
 
     opcodes[0xE0] = set!(0xe0, "SET 4, B", ByteBit::Four, Rhs8Bit::B);
     opcodes[0xE1] = set!(0xe1, "SET 4, C", ByteBit::Four, Rhs8Bit::C);
@@ -3414,7 +3413,7 @@ const fn create_cb_opcodes() -> [Option<&'static Instruction>; 256] {
     opcodes[0xE4] = set!(0xe4, "SET 4, H", ByteBit::Four, Rhs8Bit::H);
     opcodes[0xE5] = set!(0xe5, "SET 4, L", ByteBit::Four, Rhs8Bit::L);
     opcodes[0xE6] = set!(0xe6, "SET 4, [HL]", ByteBit::Four, AddressRegister::HL, Rhs8Bit::Z);
-    opcodes[0xE7] = set!(0xe7, "SET 4, A", ByteBit::Four, Rhs8Bit::C);
+    opcodes[0xE7] = set!(0xe7, "SET 4, A", ByteBit::Four, Rhs8Bit::A);
 
     opcodes[0xE8] = set!(0xe8, "SET 5, B", ByteBit::Five, Rhs8Bit::B);
     opcodes[0xE9] = set!(0xe9, "SET 5, C", ByteBit::Five, Rhs8Bit::C);
@@ -3423,7 +3422,7 @@ const fn create_cb_opcodes() -> [Option<&'static Instruction>; 256] {
     opcodes[0xEC] = set!(0xec, "SET 5, H", ByteBit::Five, Rhs8Bit::H);
     opcodes[0xED] = set!(0xed, "SET 5, L", ByteBit::Five, Rhs8Bit::L);
     opcodes[0xEE] = set!(0xee, "SET 5, [HL]", ByteBit::Five, AddressRegister::HL, Rhs8Bit::Z);
-    opcodes[0xEF] = set!(0xef, "SET 5, A", ByteBit::Five, Rhs8Bit::C);
+    opcodes[0xEF] = set!(0xef, "SET 5, A", ByteBit::Five, Rhs8Bit::A);
 
     opcodes[0xF0] = set!(0xF0, "SET 6, B", ByteBit::Six, Rhs8Bit::B);
     opcodes[0xF1] = set!(0xF1, "SET 6, C", ByteBit::Six, Rhs8Bit::C);
@@ -3432,7 +3431,7 @@ const fn create_cb_opcodes() -> [Option<&'static Instruction>; 256] {
     opcodes[0xF4] = set!(0xF4, "SET 6, H", ByteBit::Six, Rhs8Bit::H);
     opcodes[0xF5] = set!(0xF5, "SET 6, L", ByteBit::Six, Rhs8Bit::L);
     opcodes[0xF6] = set!(0xF6, "SET 6, [HL]", ByteBit::Six, AddressRegister::HL, Rhs8Bit::Z);
-    opcodes[0xF7] = set!(0xF7, "SET 6, A", ByteBit::Six, Rhs8Bit::C);
+    opcodes[0xF7] = set!(0xF7, "SET 6, A", ByteBit::Six, Rhs8Bit::A);
 
     opcodes[0xF8] = set!(0xF8, "SET 7, B", ByteBit::Seven, Rhs8Bit::B);
     opcodes[0xF9] = set!(0xF9, "SET 7, C", ByteBit::Seven, Rhs8Bit::C);
@@ -3441,7 +3440,7 @@ const fn create_cb_opcodes() -> [Option<&'static Instruction>; 256] {
     opcodes[0xFC] = set!(0xFc, "SET 7, H", ByteBit::Seven, Rhs8Bit::H);
     opcodes[0xFD] = set!(0xFd, "SET 7, L", ByteBit::Seven, Rhs8Bit::L);
     opcodes[0xFE] = set!(0xFe, "SET 7, [HL]", ByteBit::Seven, AddressRegister::HL, Rhs8Bit::Z);
-    opcodes[0xFF] = set!(0xFf, "SET 7, A", ByteBit::Seven, Rhs8Bit::C);
+    opcodes[0xFF] = set!(0xFf, "SET 7, A", ByteBit::Seven, Rhs8Bit::A);
     opcodes
 }
 
@@ -1,4 +1,6 @@
 use crate::GB::cpu::instructions::{self, Instruction};
+use crate::GB::cpu::instructions::microcode::VectorAddress;
+use crate::GB::cpu::registers::interrupt_registers::InterruptFlagsMask;
 
 #[derive(Copy, Clone, Debug)]
 pub enum InterruptType {
@@ -10,6 +12,9 @@ pub enum InterruptType {
 }
 
 impl InterruptType {
+    /// Every interrupt, ordered by priority
+    pub const ALL: [InterruptType; 5] = [InterruptType::VBlank, InterruptType::LCD, InterruptType::Timer, InterruptType::Serial, InterruptType::Joypad];
+
     pub fn interrupt_instruction(interrupt_type: InterruptType) -> &'static Instruction {
         match interrupt_type {
             InterruptType::VBlank => &instructions::INTERRUPT_VBLANK,
@@ -23,4 +28,24 @@ impl InterruptType {
     pub fn instruction(&self) -> &'static Instruction {
         Self::interrupt_instruction(*self)
     }
+
+    pub fn vector(&self) -> VectorAddress {
+        match self {
+            InterruptType::VBlank => VectorAddress::VBlank,
+            InterruptType::LCD => VectorAddress::STAT,
+            InterruptType::Timer => VectorAddress::Timer,
+            InterruptType::Serial => VectorAddress::Serial,
+            InterruptType::Joypad => VectorAddress::Joypad,
+        }
+    }
+
+    pub fn flag_mask(&self) -> InterruptFlagsMask {
+        match self {
+            InterruptType::VBlank => InterruptFlagsMask::VBlank,
+            InterruptType::LCD => InterruptFlagsMask::LCD,
+            InterruptType::Timer => InterruptFlagsMask::Timer,
+            InterruptType::Serial => InterruptFlagsMask::Serial,
+            InterruptType::Joypad => InterruptFlagsMask::JoyPad,
+        }
+    }
 }
@@ -132,6 +132,7 @@ pub enum MicroOp {
     Write16msbDec(AddressRegister, Rhs16Bit),
     Write16lsbInc(AddressRegister, Rhs16Bit),
     Write16lsbDec(AddressRegister, Rhs16Bit),
+    Write16lsbIntAck(AddressRegister, Rhs16Bit), // Interrupt dispatch: ack the pending interrupt (vector on WZ) and push lsb
     Inc16(Rhs16Bit),
     Dec16(Rhs16Bit),
     JumpVector(VectorAddress), // To immediate set PC during interrupts and RST
@@ -140,6 +141,7 @@ pub enum MicroOp {
     Idu(IduOp),
     AluAndWrite8(AluOp, AddressRegister, Rhs8Bit),
     ImeEnabled(bool),
+    ImeEnabledDelayed, // EI - IME is set only after the next instruction
     PrefixCB,
     Idle,
 }
@@ -150,6 +152,7 @@ pub enum MCycleOp {
     Cc(MicroOp, CheckCondition, usize), // Check Condition - If true micro-code index to execute must be changed with the given one
     End(MicroOp),
     Halt,
+    Stop,
     None,
 }
 
@@ -159,5 +162,6 @@ pub enum MicroFlow {
     Jump(usize),
     End,
     Halt,
+    Stop,
     PrefixCB,
 }
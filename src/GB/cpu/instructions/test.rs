@@ -2,3 +2,181 @@
 mod test_default;
 #[cfg(test)]
 mod test_subset;
+#[cfg(test)]
+mod single_step;
+
+use std::ops::{Deref, DerefMut};
+use crate::GB::apu::apu_mmio::ApuMmio;
+use crate::GB::bus::{Bus, MemoryBus, MmioContextRead, MmioContextWrite};
+use crate::GB::cartridge::Cartridge;
+use crate::GB::cpu::cpu_mmio::CpuMmio;
+use crate::GB::cpu::{CpuStatus, CPU};
+use crate::GB::dma::dma_mmio::DmaMmio;
+use crate::GB::joypad::Joypad;
+use crate::GB::memory::oam_memory::OamMemory;
+use crate::GB::memory::wram::WRAM;
+use crate::GB::ppu::ppu_mmio::PpuMmio;
+use crate::GB::serial::serial_mmio::SerialMmio;
+use crate::GB::timer::TimerRegisters;
+use crate::GB::traits::Tick;
+use crate::GB::types::address::Address;
+use crate::GB::types::Byte;
+use crate::GB::BIOS::BIOS;
+
+pub const WRAM_ADDRESS: usize = WRAM::WRAM_START_ADDRESS.as_usize();
+pub const HRAM_ADDRESS: usize = 0xFF80;
+
+macro_rules! test_cpu_ctx_mut {
+    ($test_cpu:ident) => {
+        MmioContextWrite {
+            cpu_mmio: &mut $test_cpu.cpu_mmio,
+            bios_mmio: &mut $test_cpu.bios,
+            rom_mmio: &mut $test_cpu.cartridge,
+            ppu_mmio: &mut $test_cpu.ppu_mmio,
+            apu_mmio: &mut $test_cpu.apu_mmio,
+            dma_mmio: &mut $test_cpu.dma_mmio,
+            oam_mmio: &mut $test_cpu.oam_memory,
+            wram_mmio: &mut $test_cpu.wram,
+            timer_mmio: &mut $test_cpu.timer_mmio,
+            serial_mmio: &mut $test_cpu.serial_mmio,
+            joypad: &mut $test_cpu.joypad,
+        }
+    };
+}
+
+macro_rules! test_cpu_ctx {
+    ($test_cpu:ident) => {
+        MmioContextRead {
+            cpu_mmio: &$test_cpu.cpu_mmio,
+            bios_mmio: &$test_cpu.bios,
+            rom_mmio: &$test_cpu.cartridge,
+            ppu_mmio: &$test_cpu.ppu_mmio,
+            apu_mmio: &$test_cpu.apu_mmio,
+            dma_mmio: &$test_cpu.dma_mmio,
+            oam_mmio: &$test_cpu.oam_memory,
+            wram_mmio: &$test_cpu.wram,
+            timer_mmio: &$test_cpu.timer_mmio,
+            serial_mmio: &$test_cpu.serial_mmio,
+            joypad: &$test_cpu.joypad,
+        }
+    };
+}
+
+/// CPU wired to a Bus and the MMIO devices of a system without cartridge, running instructions through the
+/// micro-op pipeline. Only the CPU is ticked, so no other device alters memory or interrupt flags:
+/// WRAM and HRAM act as flat RAM for programs, data and stack.
+///
+/// As on hardware, the opcode of the next instruction is fetched during the last M-Cycle of the running one:
+/// after `execute_next` PC already points past that opcode, while `instruction_pc()` is the next instruction address.
+pub struct TestCpu<B: MemoryBus = Bus> {
+    cpu: CPU,
+    bus: B,
+    cpu_mmio: CpuMmio,
+    bios: BIOS,
+    cartridge: Option<Cartridge>,
+    ppu_mmio: PpuMmio,
+    apu_mmio: ApuMmio,
+    dma_mmio: DmaMmio,
+    oam_memory: OamMemory,
+    wram: WRAM,
+    timer_mmio: TimerRegisters,
+    serial_mmio: SerialMmio,
+    joypad: Joypad,
+}
+
+impl TestCpu {
+    /// Address where `load` places programs
+    pub const PROGRAM_ADDRESS: u16 = WRAM_ADDRESS as u16;
+
+    pub fn new() -> Self {
+        Self::with_bus(Bus::new())
+    }
+
+    pub fn load(&mut self, program: &[Byte]) {
+        self.load_at(Self::PROGRAM_ADDRESS, program);
+    }
+}
+
+impl<B: MemoryBus> TestCpu<B> {
+    /// Test CPU driving its memory accesses on the given bus, e.g. a flat-memory test double in place of the memory map
+    pub fn with_bus(bus: B) -> Self {
+        Self {
+            cpu: CPU::new(),
+            bus,
+            cpu_mmio: CpuMmio::new(),
+            bios: BIOS::new(),
+            cartridge: None,
+            ppu_mmio: PpuMmio::new(),
+            apu_mmio: ApuMmio::new(),
+            dma_mmio: DmaMmio::new(),
+            oam_memory: OamMemory::new(),
+            wram: WRAM::new(),
+            timer_mmio: TimerRegisters::new(),
+            serial_mmio: SerialMmio::new(),
+            joypad: Joypad::new(),
+        }
+    }
+
+    /// Write the program at the given address and fetch its first opcode
+    pub fn load_at(&mut self, address: u16, program: &[Byte]) {
+        for (i, byte) in program.iter().enumerate() {
+            self.write_memory(address.wrapping_add(i as u16), *byte);
+        }
+        self.cpu.registers.set_pc(address);
+        self.cpu.status = CpuStatus::Ready;
+        self.tick();
+    }
+
+    /// Run the fetched instruction 'till the next one is fetched, returning its M-Cycles.
+    /// While CPU is halted, stopped or locked a single M-Cycle is run.
+    pub fn execute_next(&mut self) -> u8 {
+        let mut t_cycles: usize = 0;
+        loop {
+            self.tick();
+            t_cycles += 1;
+            if self.cpu.is_instruction_start() {
+                break;
+            }
+            if (self.cpu.is_halted() || self.cpu.is_stopped() || self.cpu.is_locked()) && self.cpu.instruction_t_cycle() == 0 {
+                break;
+            }
+        }
+        (t_cycles / 4) as u8
+    }
+
+    fn tick(&mut self) {
+        let mut ctx = test_cpu_ctx_mut!(self);
+        self.cpu.tick_on(&mut self.bus, &mut ctx);
+    }
+
+    pub fn read_memory(&self, address: u16) -> Byte {
+        let ctx = test_cpu_ctx!(self);
+        self.bus.read(&ctx, Address(address))
+    }
+
+    pub fn write_memory(&mut self, address: u16, data: Byte) {
+        let mut ctx = test_cpu_ctx_mut!(self);
+        self.bus.write(&mut ctx, Address(address), data);
+    }
+
+    /// Push a byte on the stack as PUSH and CALL do: SP is decremented, then the byte is written
+    pub fn push(&mut self, data: Byte) {
+        let sp = self.cpu.registers.get_sp().wrapping_sub(1);
+        self.cpu.registers.set_sp(sp);
+        self.write_memory(sp, data);
+    }
+}
+
+impl<B: MemoryBus> Deref for TestCpu<B> {
+    type Target = CPU;
+
+    fn deref(&self) -> &Self::Target {
+        &self.cpu
+    }
+}
+
+impl<B: MemoryBus> DerefMut for TestCpu<B> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.cpu
+    }
+}
@@ -0,0 +1,267 @@
+use std::cell::RefCell;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::{env, fs};
+use serde::Deserialize;
+use crate::GB::bus::{MemoryBus, MmioContextRead, MmioContextWrite};
+use crate::GB::cpu::instructions::test::TestCpu;
+use crate::GB::cpu::CpuStatus;
+use crate::GB::types::address::Address;
+use crate::GB::types::Byte;
+
+// Runner for the SM83 "SingleStepTests" JSON format (https://github.com/SingleStepTests/sm83): each test sets up
+// registers and RAM, executes one instruction and checks the final state plus the bus activity of every M-Cycle.
+// Test files (one per opcode, like `00.json` or `cb 00.json`) are not part of the repository: they are loaded from
+// `TESTS_DIR`, or from the directory in `SM83_TESTS_DIR` env var. The suite is ignored by default, run it with:
+//     SM83_TESTS_DIR=path/to/sm83/v1 cargo test single_step_tests -- --ignored
+const TESTS_DIR: &str = "resources/test/sm83";
+const TESTS_DIR_ENV: &str = "SM83_TESTS_DIR";
+const MAX_M_CYCLES: usize = 8; // Longest instruction is 6 M-Cycles: anything past this is a runaway execution
+const MAX_REPORTED_FAILURES: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BusAccess {
+    Read(u16, Byte),
+    Write(u16, Byte),
+}
+
+/// 64KB of plain RAM without any memory map, logging every access done on it
+struct FlatMemory {
+    memory: Vec<Byte>,
+    accesses: Rc<RefCell<Vec<BusAccess>>>,
+}
+
+impl FlatMemory {
+    fn new(accesses: Rc<RefCell<Vec<BusAccess>>>) -> Self {
+        Self {
+            memory: vec![0; 0x10000],
+            accesses,
+        }
+    }
+}
+
+impl MemoryBus for FlatMemory {
+    fn read(&self, _ctx: &MmioContextRead, address: Address) -> Byte {
+        let data = self.memory[address.as_usize()];
+        self.accesses.borrow_mut().push(BusAccess::Read(address.as_u16(), data));
+        data
+    }
+
+    fn write(&mut self, _ctx: &mut MmioContextWrite, address: Address, data: Byte) {
+        self.memory[address.as_usize()] = data;
+        self.accesses.borrow_mut().push(BusAccess::Write(address.as_u16(), data));
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct CpuState {
+    pc: u16,
+    sp: u16,
+    a: Byte,
+    b: Byte,
+    c: Byte,
+    d: Byte,
+    e: Byte,
+    f: Byte,
+    h: Byte,
+    l: Byte,
+    #[serde(default)]
+    ime: u8,
+    #[serde(default)]
+    ie: Option<Byte>,
+    ram: Vec<(u16, Byte)>,
+}
+
+/// Bus activity of a M-Cycle: address, data and pins (`r-m` read, `-wm` write, `---` no access).
+/// Cycles without any access can also be `null`.
+type Cycle = Option<(Option<u16>, Option<Byte>, String)>;
+
+#[derive(Debug, Deserialize)]
+struct SingleStepTest {
+    name: String,
+    initial: CpuState,
+    #[serde(rename = "final")]
+    expected: CpuState,
+    cycles: Vec<Cycle>,
+}
+
+impl SingleStepTest {
+    /// Run the test on a CPU wired to a flat memory, returning found mismatches (empty if test passes)
+    fn run(&self) -> Vec<String> {
+        let accesses = Rc::new(RefCell::new(Vec::new()));
+        let mut cpu = TestCpu::with_bus(FlatMemory::new(Rc::clone(&accesses)));
+
+        let initial = &self.initial;
+        for &(address, data) in &initial.ram {
+            cpu.write_memory(address, data);
+        }
+        if let Some(ie) = initial.ie {
+            cpu.write_memory(0xFFFF, ie);
+        }
+        cpu.registers.set_a(initial.a);
+        cpu.registers.set_f(initial.f);
+        cpu.registers.set_b(initial.b);
+        cpu.registers.set_c(initial.c);
+        cpu.registers.set_d(initial.d);
+        cpu.registers.set_e(initial.e);
+        cpu.registers.set_h(initial.h);
+        cpu.registers.set_l(initial.l);
+        cpu.registers.set_sp(initial.sp);
+        cpu.registers.set_pc(initial.pc);
+        cpu.ime = initial.ime != 0;
+        cpu.status = CpuStatus::Ready;
+        accesses.borrow_mut().clear();
+
+        // Tests count the opcode fetch as first M-Cycle, that here is the last one of the previous instruction
+        cpu.tick();
+        let mut m_cycles = vec![accesses.take()];
+        loop {
+            for _ in 0..4 {
+                cpu.tick();
+            }
+            m_cycles.push(accesses.take());
+            if cpu.is_instruction_start() || cpu.is_halted() || cpu.is_stopped() || m_cycles.len() > MAX_M_CYCLES {
+                break;
+            }
+        }
+
+        let suspended = cpu.is_halted() || cpu.is_stopped();
+        let pc = if suspended { cpu.registers.get_pc() } else { cpu.instruction_pc() };
+        // Last M-Cycle overlaps with the next opcode fetch, which is the first M-Cycle of the next instruction
+        let last_m_cycle = m_cycles.last_mut().unwrap();
+        if !suspended && matches!(last_m_cycle.last(), Some(BusAccess::Read(address, _)) if *address == pc) {
+            last_m_cycle.pop();
+        }
+        if last_m_cycle.is_empty() {
+            m_cycles.pop();
+        }
+
+        let mut errors = Vec::new();
+        let mut check = |name: &str, actual: u16, expected: u16| {
+            if actual != expected {
+                errors.push(format!("{name}: ${actual:02X} (expected ${expected:02X})"));
+            }
+        };
+        let expected = &self.expected;
+        check("PC", pc, expected.pc);
+        check("SP", cpu.registers.get_sp(), expected.sp);
+        check("A", cpu.registers.get_a() as u16, expected.a as u16);
+        check("F", cpu.registers.get_f() as u16, expected.f as u16);
+        check("B", cpu.registers.get_b() as u16, expected.b as u16);
+        check("C", cpu.registers.get_c() as u16, expected.c as u16);
+        check("D", cpu.registers.get_d() as u16, expected.d as u16);
+        check("E", cpu.registers.get_e() as u16, expected.e as u16);
+        check("H", cpu.registers.get_h() as u16, expected.h as u16);
+        check("L", cpu.registers.get_l() as u16, expected.l as u16);
+        check("IME", cpu.ime as u16, expected.ime as u16);
+        if let Some(ie) = expected.ie {
+            check("IE", cpu.read_memory(0xFFFF) as u16, ie as u16);
+        }
+        for &(address, data) in &expected.ram {
+            check(&format!("[${address:04X}]"), cpu.read_memory(address) as u16, data as u16);
+        }
+
+        if m_cycles.len() != self.cycles.len() {
+            errors.push(format!("M-Cycles: {} (expected {})", m_cycles.len(), self.cycles.len()));
+        }
+        for (i, (actual, expected)) in m_cycles.iter().zip(&self.cycles).enumerate() {
+            let expected = match expected {
+                Some((Some(address), Some(data), pins)) if pins.contains('r') => Some(BusAccess::Read(*address, *data)),
+                Some((Some(address), Some(data), pins)) if pins.contains('w') => Some(BusAccess::Write(*address, *data)),
+                _ => None,
+            };
+            if actual.len() > 1 || actual.first().copied() != expected {
+                errors.push(format!("M-Cycle {}: {:?} (expected {:?})", i + 1, actual, expected));
+            }
+        }
+        errors
+    }
+}
+
+fn tests_dir() -> PathBuf {
+    env::var_os(TESTS_DIR_ENV)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| Path::new(env!("CARGO_MANIFEST_DIR")).join(TESTS_DIR))
+}
+
+fn load_tests(path: &Path) -> Vec<SingleStepTest> {
+    let json = fs::read_to_string(path).unwrap_or_else(|e| panic!("Can't read {}: {e}", path.display()));
+    serde_json::from_str(&json).unwrap_or_else(|e| panic!("Invalid test file {}: {e}", path.display()))
+}
+
+#[test]
+#[ignore = "needs SingleStepTests JSON files, see SM83_TESTS_DIR"]
+fn single_step_tests() {
+    let dir = tests_dir();
+    let entries = fs::read_dir(&dir)
+        .unwrap_or_else(|e| panic!("SingleStepTests not found in {} (set {TESTS_DIR_ENV}): {e}", dir.display()));
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|extension| extension == "json"))
+        .collect();
+    files.sort();
+    assert!(!files.is_empty(), "No SingleStepTests JSON file in {}", dir.display());
+
+    let mut executed = 0;
+    let mut failures = Vec::new();
+    for file in &files {
+        for test in load_tests(file) {
+            executed += 1;
+            match panic::catch_unwind(AssertUnwindSafe(|| test.run())) {
+                Ok(errors) if errors.is_empty() => {}
+                Ok(errors) => failures.push(format!("{}: {}", test.name, errors.join(", "))),
+                Err(_) => {
+                    // A panicking instruction would panic on every test of its file
+                    failures.push(format!("{}: panicked, rest of {} skipped", test.name, file.display()));
+                    break;
+                }
+            }
+        }
+    }
+
+    let report: Vec<&String> = failures.iter().take(MAX_REPORTED_FAILURES).collect();
+    assert!(failures.is_empty(), "{} of {} SingleStepTests failed:\n{:#?}", failures.len(), executed, report);
+}
+
+#[test]
+fn single_step_test_load_imm8() {
+    let tests: Vec<SingleStepTest> = serde_json::from_str(r#"[{
+        "name": "06 0000",
+        "initial": {"pc": 49152, "sp": 57342, "a": 1, "b": 0, "c": 2, "d": 3, "e": 4, "f": 176, "h": 5, "l": 6, "ime": 0,
+                    "ram": [[49152, 6], [49153, 90]]},
+        "final": {"pc": 49154, "sp": 57342, "a": 1, "b": 90, "c": 2, "d": 3, "e": 4, "f": 176, "h": 5, "l": 6, "ime": 0,
+                  "ram": [[49152, 6], [49153, 90]]},
+        "cycles": [[49152, 6, "r-m"], [49153, 90, "r-m"]]
+    }]"#).unwrap();
+    assert_eq!(tests[0].run(), Vec::<String>::new());
+}
+
+#[test]
+fn single_step_test_push_internal_cycle() {
+    let tests: Vec<SingleStepTest> = serde_json::from_str(r#"[{
+        "name": "c5 0000",
+        "initial": {"pc": 49152, "sp": 57342, "a": 1, "b": 18, "c": 52, "d": 3, "e": 4, "f": 0, "h": 5, "l": 6, "ime": 0,
+                    "ram": [[49152, 197]]},
+        "final": {"pc": 49153, "sp": 57340, "a": 1, "b": 18, "c": 52, "d": 3, "e": 4, "f": 0, "h": 5, "l": 6, "ime": 0,
+                  "ram": [[49152, 197], [57340, 52], [57341, 18]]},
+        "cycles": [[49152, 197, "r-m"], null, [57341, 18, "-wm"], [57340, 52, "-wm"]]
+    }]"#).unwrap();
+    assert_eq!(tests[0].run(), Vec::<String>::new());
+}
+
+#[test]
+fn single_step_test_reports_mismatches() {
+    let tests: Vec<SingleStepTest> = serde_json::from_str(r#"[{
+        "name": "06 0001",
+        "initial": {"pc": 49152, "sp": 57342, "a": 1, "b": 0, "c": 2, "d": 3, "e": 4, "f": 176, "h": 5, "l": 6, "ime": 0,
+                    "ram": [[49152, 6], [49153, 90]]},
+        "final": {"pc": 49154, "sp": 57342, "a": 1, "b": 91, "c": 2, "d": 3, "e": 4, "f": 176, "h": 5, "l": 6, "ime": 0,
+                  "ram": [[49152, 6], [49153, 90]]},
+        "cycles": [[49152, 6, "r-m"], [49153, 90, "r-m"], [49154, 0, "---"]]
+    }]"#).unwrap();
+    let errors = tests[0].run();
+    assert_eq!(errors.len(), 2);
+    assert!(errors[0].starts_with("B: $5A"));
+    assert!(errors[1].starts_with("M-Cycles: 2"));
+}
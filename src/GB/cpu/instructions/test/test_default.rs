@@ -1,14 +1,5 @@
-use std::cell::RefCell;
-use std::rc::Rc;
-use crate::GB::cpu::{CPU};
-use crate::GB::memory::{self, RAM, USER_PROGRAM_ADDRESS, WRAM_ADDRESS};
-use crate::GB::joypad::Joypad as GBInput;
-
-macro_rules! create_memory {
-    () => {
-        Rc::new(RefCell::new(RAM::new(Rc::new(RefCell::new(Joypad::default())))))
-    };
-}
+use crate::GB::cpu::instructions::test::{TestCpu, HRAM_ADDRESS, WRAM_ADDRESS};
+use crate::GB::memory::USER_PROGRAM_ADDRESS;
 
 macro_rules! test_flags {
         ($cpu:ident, $zero:expr, $negative:expr, $half:expr, $carry:expr) => {
@@ -24,8 +15,7 @@ macro_rules! test_ld_r8 {
             #[test]
             fn $func() {
                 let test_value: u8 = 0xC4;
-                let mut memory_ref = create_memory!();
-                let mut cpu = CPU::new(Rc::clone(&memory_ref));
+                let mut cpu = TestCpu::new();
                 let program: Vec<u8> = vec![$opcode];
                 cpu.load(&program);
                 let register_copy = cpu.registers;
@@ -50,8 +40,7 @@ macro_rules! test_ld_r8 {
             #[test]
             fn $func() {
                 let test_value: u8 = 0xC4;
-                let mut memory_ref = create_memory!();
-                let mut cpu = CPU::new(Rc::clone(&memory_ref));
+                let mut cpu = TestCpu::new();
                 let program: Vec<u8> = vec![$opcode];
                 cpu.load(&program);
                 let register_copy = cpu.registers;
@@ -76,8 +65,7 @@ macro_rules! test_ld_r16 {
             #[test]
             fn $func() {
                 let test_value: u16 = 0xE5C4;
-                let mut memory_ref = create_memory!();
-                let mut cpu = CPU::new(Rc::clone(&memory_ref));
+                let mut cpu = TestCpu::new();
                 let program: Vec<u8> = vec![$opcode];
                 cpu.load(&program);
                 let register_copy = cpu.registers;
@@ -102,8 +90,7 @@ macro_rules! test_ld_r16 {
             #[test]
             fn $func() {
                 let test_value: u16 = 0xD5C4;
-                let mut memory_ref = create_memory!();
-                let mut cpu = CPU::new(Rc::clone(&memory_ref));
+                let mut cpu = TestCpu::new();
                 let program: Vec<u8> = vec![$opcode];
                 cpu.load(&program);
                 let register_copy = cpu.registers;
@@ -128,8 +115,7 @@ macro_rules! test_ld_r16_imm16 {
             #[test]
             fn $func() {
                 let test_value: u16 = 0xC05A;
-                let mut memory_ref = create_memory!();
-                let mut cpu = CPU::new(Rc::clone(&memory_ref));
+                let mut cpu = TestCpu::new();
                 let program: Vec<u8> = vec![$opcode, 0x5A, 0xC0];
                 cpu.load(&program);
                 let registers_copy = cpu.registers;
@@ -153,8 +139,7 @@ macro_rules! test_ld_r16_imm16 {
             #[test]
             fn $func() {
                 let test_value: u16 = 0xC05A;
-                let mut memory_ref = create_memory!();
-                let mut cpu = CPU::new(Rc::clone(&memory_ref));
+                let mut cpu = TestCpu::new();
                 let program: Vec<u8> = vec![$opcode, 0x5A, 0xC0];
                 cpu.load(&program);
                 let registers_copy = cpu.registers;
@@ -179,9 +164,8 @@ macro_rules! test_ld_r8_imm8 {
             #[test]
             fn $func() {
                 let test_value: u8 = 0x5A;
-                let mut memory_ref = create_memory!();
-                let mut cpu = CPU::new(Rc::clone(&memory_ref));
-                let program: Vec<u8> = vec![$opcode, 0x5A];
+                let mut cpu = TestCpu::new();
+                let program: Vec<u8> = vec![$opcode, test_value];
                 cpu.load(&program);
                 let registers_copy = cpu.registers;
                 cpu.registers.$set_reg(0);
@@ -206,8 +190,7 @@ macro_rules! test_ld_ar16_r8 {
             fn $func() {
                 let test_value_1: u8 = 0xC4;
                 let test_address_1: u16 = WRAM_ADDRESS as u16 + 0x99;
-                let mut memory_ref = create_memory!();
-                let mut cpu = CPU::new(Rc::clone(&memory_ref));
+                let mut cpu = TestCpu::new();
                 let program_1: Vec<u8> = vec![$opcode];
                 cpu.load(&program_1);
                 let register_copy = cpu.registers;
@@ -239,8 +222,7 @@ macro_rules! test_ld_imm16_r8 {
                 let test_address_1: u16 = WRAM_ADDRESS as u16 + 0x99;
                 let test_address_1_low: u8 = (test_address_1 & 0xFF) as u8;
                 let test_address_1_high: u8 = (test_address_1 >> 8) as u8;
-                let mut memory_ref = create_memory!();
-                let mut cpu = CPU::new(Rc::clone(&memory_ref));
+                let mut cpu = TestCpu::new();
                 let program_1: Vec<u8> = vec![$opcode, test_address_1_low, test_address_1_high];
                 cpu.load(&program_1);
                 let register_copy = cpu.registers;
@@ -270,8 +252,7 @@ macro_rules! test_ld_r8_imm16 {
                 let test_address_1: u16 = WRAM_ADDRESS as u16 + 0x99;
                 let test_address_1_low: u8 = (test_address_1 & 0xFF) as u8;
                 let test_address_1_high: u8 = (test_address_1 >> 8) as u8;
-                let mut memory_ref = create_memory!();
-                let mut cpu = CPU::new(Rc::clone(&memory_ref));
+                let mut cpu = TestCpu::new();
                 let program_1: Vec<u8> = vec![$opcode, test_address_1_low, test_address_1_high];
                 cpu.load(&program_1);
                 let register_copy = cpu.registers;
@@ -299,8 +280,7 @@ macro_rules! test_ld_ar16 {
             fn $func() {
                 let test_value: u8 = 0xC4;
                 let test_address: u16 = WRAM_ADDRESS as u16 + 0x15B;
-                let mut memory_ref = create_memory!();
-                let mut cpu = CPU::new(Rc::clone(&memory_ref));
+                let mut cpu = TestCpu::new();
                 let program: Vec<u8> = vec![$opcode];
                 cpu.load(&program);
                 let register_copy = cpu.registers;
@@ -330,11 +310,10 @@ macro_rules! test_ldh_r8_imm8 {
             #[test]
             fn $func() {
                 let test_value: u8 = 0xD8;
-                let test_addr_low: u8 = 0x5A;
-                let test_addr: u16 = 0xFF00 | (test_addr_low as u16 & 0xFF);
-                let mut memory_ref = create_memory!();
-                let mut cpu = CPU::new(Rc::clone(&memory_ref));
-                let program: Vec<u8> = vec![$opcode, 0x5A];
+                let test_addr_low: u8 = (HRAM_ADDRESS as u16 + 0x5A) as u8;
+                let test_addr: u16 = 0xFF00 | (test_addr_low as u16 & 0xFF); // HRAM
+                let mut cpu = TestCpu::new();
+                let program: Vec<u8> = vec![$opcode, test_addr_low];
                 cpu.load(&program);
                 let registers_copy = cpu.registers;
                 cpu.write_memory(test_addr, if $byte_is_src {test_value} else {0});
@@ -361,10 +340,9 @@ macro_rules! test_ldh_r8_r8 {
             #[test]
             fn $func() {
                 let test_value: u8 = 0xD8;
-                let test_addr_low: u8 = 0x5A;
-                let test_addr: u16 = 0xFF00 | (test_addr_low as u16 & 0xFF);
-                let mut memory_ref = create_memory!();
-                let mut cpu = CPU::new(Rc::clone(&memory_ref));
+                let test_addr_low: u8 = (HRAM_ADDRESS as u16 + 0x5A) as u8;
+                let test_addr: u16 = 0xFF00 | (test_addr_low as u16 & 0xFF); // HRAM
+                let mut cpu = TestCpu::new();
                 let program: Vec<u8> = vec![$opcode];
                 cpu.load(&program);
                 let registers_copy = cpu.registers;
@@ -393,8 +371,7 @@ macro_rules! test_inc_r16 {
             #[test]
             fn $func() {
                 let test_value: u16 = 0xC0F4;
-                let mut memory_ref = create_memory!();
-                let mut cpu = CPU::new(Rc::clone(&memory_ref));
+                let mut cpu = TestCpu::new();
                 let program: Vec<u8> = vec![$opcode];
                 cpu.load(&program);
                 let register_copy = cpu.registers;
@@ -417,8 +394,7 @@ macro_rules! test_inc_r16 {
             #[test]
             fn $func() {
                 let test_value: u16 = 0xC0F4;
-                let mut memory_ref = create_memory!();
-                let mut cpu = CPU::new(Rc::clone(&memory_ref));
+                let mut cpu = TestCpu::new();
                 let program: Vec<u8> = vec![$opcode];
                 cpu.load(&program);
                 let register_copy = cpu.registers;
@@ -442,8 +418,7 @@ macro_rules! test_dec_r16 {
             #[test]
             fn $func() {
                 let test_value: u16 = 0xC0F4;
-                let mut memory_ref = create_memory!();
-                let mut cpu = CPU::new(Rc::clone(&memory_ref));
+                let mut cpu = TestCpu::new();
                 let program: Vec<u8> = vec![$opcode];
                 cpu.load(&program);
                 let register_copy = cpu.registers;
@@ -467,8 +442,7 @@ macro_rules! test_dec_r16 {
             #[test]
             fn $func() {
                 let test_value: u16 = 0xC0F4;
-                let mut memory_ref = create_memory!();
-                let mut cpu = CPU::new(Rc::clone(&memory_ref));
+                let mut cpu = TestCpu::new();
                 let program: Vec<u8> = vec![$opcode];
                 cpu.load(&program);
                 let register_copy = cpu.registers;
@@ -493,8 +467,7 @@ macro_rules! test_inc_r8 {
             fn $func() {
                 //No Flags
                 let mut test_value_1: u8 = 0b1111_0100;
-                let mut memory_ref = create_memory!();
-                let mut cpu = CPU::new(Rc::clone(&memory_ref));
+                let mut cpu = TestCpu::new();
                 let program_1: Vec<u8> = vec![$opcode];
                 cpu.load(&program_1);
                 let registers_copy = cpu.registers;
@@ -505,7 +478,7 @@ macro_rules! test_inc_r8 {
 
                 // Flags Z/H
                 test_value_1 = 0xFF;
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 let registers_copy = cpu.registers;
                 cpu.registers.$set_reg(test_value_1);
@@ -515,7 +488,7 @@ macro_rules! test_inc_r8 {
 
                 // Flags H
                 test_value_1 = 0x0F;
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 let registers_copy = cpu.registers;
                 cpu.registers.$set_reg(test_value_1);
@@ -532,8 +505,7 @@ macro_rules! test_dec_r8 {
             fn $func() {
                 //No Flags
                 let mut test_value_1: u8 = 0xF4;
-                let mut memory_ref = create_memory!();
-                let mut cpu = CPU::new(Rc::clone(&memory_ref));
+                let mut cpu = TestCpu::new();
                 let program_1: Vec<u8> = vec![$opcode];
                 cpu.load(&program_1);
                 let registers_copy = cpu.registers;
@@ -545,7 +517,7 @@ macro_rules! test_dec_r8 {
 
                 // Flags H
                 test_value_1 = 0x00;
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 let registers_copy = cpu.registers;
                 cpu.registers.$set_reg(test_value_1);
@@ -556,8 +528,7 @@ macro_rules! test_dec_r8 {
 
                 // Flags Z
                 test_value_1 = 0x00;
-                let mut memory_ref = create_memory!();
-                let mut cpu = CPU::new(Rc::clone(&memory_ref));
+                let mut cpu = TestCpu::new();
                 cpu.load(&program_1);
                 let registers_copy = cpu.registers;
                 cpu.registers.$set_reg(test_value_1 + 1);
@@ -568,7 +539,7 @@ macro_rules! test_dec_r8 {
 
                 // Flags H
                 test_value_1 = 0xF0;
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 let registers_copy = cpu.registers;
                 cpu.registers.$set_reg(test_value_1);
@@ -587,8 +558,7 @@ macro_rules! test_add_r8_r8 {
                 let mut test_value_1: u8 = 0xC4;
                 let mut test_value_2: u8 = 0x16;
                 let mut expected_value: u8 = test_value_1.wrapping_add(test_value_2);
-                let mut memory_ref = create_memory!();
-                let mut cpu = CPU::new(Rc::clone(&memory_ref));
+                let mut cpu = TestCpu::new();
                 let program_1: Vec<u8> = vec![$opcode];
                 cpu.load(&program_1);
                 cpu.registers.$set_reg_to(test_value_1);
@@ -603,7 +573,7 @@ macro_rules! test_add_r8_r8 {
                 test_value_1 = 0xF0;
                 test_value_2 = 0x10;
                 expected_value = 0x00;
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 cpu.registers.$set_reg_to(test_value_1);
                 cpu.registers.$set_reg_from(test_value_2);
@@ -617,7 +587,7 @@ macro_rules! test_add_r8_r8 {
                 test_value_1 = 0x0F;
                 test_value_2 = 0x01;
                 expected_value = 0x10;
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 cpu.registers.$set_reg_to(test_value_1);
                 cpu.registers.$set_reg_from(test_value_2);
@@ -631,7 +601,7 @@ macro_rules! test_add_r8_r8 {
                 test_value_1 = 0xFF;
                 test_value_2 = 0x01;
                 expected_value = 0x00;
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 cpu.registers.$set_reg_to(test_value_1);
                 cpu.registers.$set_reg_from(test_value_2);
@@ -652,8 +622,7 @@ macro_rules! test_sub_r8_r8 {
                 let mut test_value_1: u8 = 0xC4;
                 let mut test_value_2: u8 = 0x11;
                 let mut expected_value: u8 = test_value_1.wrapping_sub(test_value_2);
-                let mut memory_ref = create_memory!();
-                let mut cpu = CPU::new(Rc::clone(&memory_ref));
+                let mut cpu = TestCpu::new();
                 let program_1: Vec<u8> = vec![$opcode];
                 cpu.load(&program_1);
                 cpu.registers.$set_reg_to(test_value_1);
@@ -668,7 +637,7 @@ macro_rules! test_sub_r8_r8 {
                 test_value_1 = 0xF0;
                 test_value_2 = 0xF0;
                 expected_value = 0x00;
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 cpu.registers.$set_reg_to(test_value_1);
                 cpu.registers.$set_reg_from(test_value_2);
@@ -682,7 +651,7 @@ macro_rules! test_sub_r8_r8 {
                 test_value_1 = 0x10;
                 test_value_2 = 0x01;
                 expected_value = 0x0F;
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 cpu.registers.$set_reg_to(test_value_1);
                 cpu.registers.$set_reg_from(test_value_2);
@@ -696,7 +665,7 @@ macro_rules! test_sub_r8_r8 {
                 test_value_1 = 0x10;
                 test_value_2 = 0x20;
                 expected_value = test_value_1.wrapping_sub(test_value_2);
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 cpu.registers.$set_reg_to(test_value_1);
                 cpu.registers.$set_reg_from(test_value_2);
@@ -710,7 +679,7 @@ macro_rules! test_sub_r8_r8 {
                 test_value_1 = 0x00;
                 test_value_2 = 0x01;
                 expected_value = 0xFF;
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 cpu.registers.$set_reg_to(test_value_1);
                 cpu.registers.$set_reg_from(test_value_2);
@@ -731,8 +700,7 @@ macro_rules! test_add_r16_r16 {
                 //No Flags
                 let mut test_value_1: u16 = 0xBD89;
                 let mut test_value_2: u16 = 0x1029;
-                let mut memory_ref = create_memory!();
-                let mut cpu = CPU::new(Rc::clone(&memory_ref));
+                let mut cpu = TestCpu::new();
                 let program: Vec<u8> = vec![$opcode];
                 cpu.load(&program);
                 cpu.registers.$set_reg_to(test_value_1);
@@ -803,8 +771,7 @@ macro_rules! test_adc_r8_r8 {
                 let mut test_value_1: u8 = 0xC4;
                 let mut test_value_2: u8 = 0x16;
                 let mut expected_value: u8 = test_value_1.wrapping_add(test_value_2).wrapping_add($carry as u8);
-                let mut memory_ref = create_memory!();
-                let mut cpu = CPU::new(Rc::clone(&memory_ref));
+                let mut cpu = TestCpu::new();
                 let program_1: Vec<u8> = vec![$opcode];
                 cpu.load(&program_1);
                 cpu.registers.$set_reg_to(test_value_1);
@@ -820,7 +787,7 @@ macro_rules! test_adc_r8_r8 {
                 test_value_1 = 0xF0;
                 test_value_2 = 0x10 - $carry as u8;
                 expected_value = 0x00;
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 cpu.registers.$set_reg_to(test_value_1);
                 cpu.registers.$set_reg_from(test_value_2);
@@ -835,7 +802,7 @@ macro_rules! test_adc_r8_r8 {
                 test_value_1 = 0x0F;
                 test_value_2 = 0x01;
                 expected_value = 0x10 + $carry as u8;
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 cpu.registers.$set_reg_to(test_value_1);
                 cpu.registers.$set_reg_from(test_value_2);
@@ -850,7 +817,7 @@ macro_rules! test_adc_r8_r8 {
                 test_value_1 = 0xFF - $carry as u8;
                 test_value_2 = 0x01;
                 expected_value = 0x00;
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 cpu.registers.$set_reg_to(test_value_1);
                 cpu.registers.$set_reg_from(test_value_2);
@@ -872,8 +839,7 @@ macro_rules! test_sbc_r8_r8 {
                 let mut test_value_1: u8 = 0xC4;
                 let mut test_value_2: u8 = 0x12 - $carry as u8;
                 let mut expected_value: u8 = test_value_1.wrapping_sub(test_value_2 + $carry as u8);
-                let mut memory_ref = create_memory!();
-                let mut cpu = CPU::new(Rc::clone(&memory_ref));
+                let mut cpu = TestCpu::new();
                 let program_1: Vec<u8> = vec![$opcode];
                 cpu.load(&program_1);
                 cpu.registers.$set_reg_to(test_value_1);
@@ -889,7 +855,7 @@ macro_rules! test_sbc_r8_r8 {
                 test_value_1 = 0x0F;
                 test_value_2 = 0x0F - $carry as u8;
                 expected_value = 0x00;
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 cpu.registers.$set_reg_to(test_value_1);
                 cpu.registers.$set_reg_from(test_value_2);
@@ -904,7 +870,7 @@ macro_rules! test_sbc_r8_r8 {
                 test_value_1 = 0xF0;
                 test_value_2 = 0x01 - $carry as u8;
                 expected_value = 0xEF;
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 cpu.registers.$set_reg_to(test_value_1);
                 cpu.registers.$set_reg_from(test_value_2);
@@ -919,7 +885,7 @@ macro_rules! test_sbc_r8_r8 {
                 test_value_1 = 0x00;
                 test_value_2 = 0x01 - $carry as u8;
                 expected_value = 0xFF;
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 cpu.registers.$set_reg_to(test_value_1);
                 cpu.registers.$set_reg_from(test_value_2);
@@ -941,8 +907,7 @@ macro_rules! test_add_r8_imm8 {
                 let mut test_value_1: u8 = 0xC4;
                 let mut test_value_2: u8 = 0x16;
                 let mut expected_value: u8 = test_value_1.wrapping_add(test_value_2);
-                let mut memory_ref = create_memory!();
-                let mut cpu = CPU::new(Rc::clone(&memory_ref));
+                let mut cpu = TestCpu::new();
                 let mut program_1: Vec<u8> = vec![$opcode, test_value_2];
                 cpu.load(&program_1);
                 cpu.registers.$set_reg_to(test_value_1);
@@ -956,7 +921,7 @@ macro_rules! test_add_r8_imm8 {
                 test_value_2 = 0x10;
                 expected_value = 0x00;
                 program_1[1] = test_value_2;
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 cpu.registers.$set_reg_to(test_value_1);
                 cycles = cpu.execute_next();
@@ -969,7 +934,7 @@ macro_rules! test_add_r8_imm8 {
                 test_value_2 = 0x01;
                 expected_value = 0x10;
                 program_1[1] = test_value_2;
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 cpu.registers.$set_reg_to(test_value_1);
                 cycles = cpu.execute_next();
@@ -982,7 +947,7 @@ macro_rules! test_add_r8_imm8 {
                 test_value_2 = 0x01;
                 expected_value = 0x00;
                 program_1[1] = test_value_2;
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 cpu.registers.$set_reg_to(test_value_1);
                 cycles = cpu.execute_next();
@@ -1001,8 +966,7 @@ macro_rules! test_adc_r8_imm8 {
                 let mut test_value_1: u8 = 0xC4;
                 let mut test_value_2: u8 = 0x16;
                 let mut expected_value: u8 = test_value_1.wrapping_add(test_value_2).wrapping_add($carry as u8);
-                let mut memory_ref = create_memory!();
-                let mut cpu = CPU::new(Rc::clone(&memory_ref));
+                let mut cpu = TestCpu::new();
                 let mut program_1: Vec<u8> = vec![$opcode, test_value_2];
                 cpu.load(&program_1);
                 cpu.registers.$set_reg_to(test_value_1);
@@ -1017,7 +981,7 @@ macro_rules! test_adc_r8_imm8 {
                 test_value_2 = 0x10 - $carry as u8;
                 expected_value = 0x00;
                 program_1[1] = test_value_2;
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 cpu.registers.$set_reg_to(test_value_1);
                 cpu.registers.set_carry_flag($carry);
@@ -1031,7 +995,7 @@ macro_rules! test_adc_r8_imm8 {
                 test_value_2 = 0x01;
                 expected_value = 0x10 + $carry as u8;
                 program_1[1] = test_value_2;
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 cpu.registers.$set_reg_to(test_value_1);
                 cpu.registers.set_carry_flag($carry);
@@ -1045,7 +1009,7 @@ macro_rules! test_adc_r8_imm8 {
                 test_value_2 = 0x01;
                 expected_value = 0x00;
                 program_1[1] = test_value_2;
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 cpu.registers.$set_reg_to(test_value_1);
                 cpu.registers.set_carry_flag($carry);
@@ -1065,8 +1029,7 @@ macro_rules! test_sbc_r8_imm8 {
                 let mut test_value_1: u8 = 0xC4;
                 let mut test_value_2: u8 = 0x13;
                 let mut expected_value: u8 = test_value_1.wrapping_sub(test_value_2 + $carry as u8);
-                let mut memory_ref = create_memory!();
-                let mut cpu = CPU::new(Rc::clone(&memory_ref));
+                let mut cpu = TestCpu::new();
                 let mut program_1: Vec<u8> = vec![$opcode, test_value_2];
                 cpu.load(&program_1);
                 cpu.registers.set_a(test_value_1);
@@ -1081,7 +1044,7 @@ macro_rules! test_sbc_r8_imm8 {
                 test_value_2 = 0x0E;
                 expected_value = test_value_1.wrapping_sub(test_value_2 + $carry as u8);
                 program_1[1] = test_value_2;
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 cpu.registers.set_a(test_value_1);
                 cpu.registers.set_carry_flag($carry);
@@ -1095,7 +1058,7 @@ macro_rules! test_sbc_r8_imm8 {
                 test_value_2 = 0x0F;
                 expected_value = test_value_1.wrapping_sub(test_value_2).wrapping_sub($carry as u8);
                 program_1[1] = test_value_2;
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 cpu.registers.set_a(test_value_1);
                 cpu.registers.set_carry_flag($carry);
@@ -1109,7 +1072,7 @@ macro_rules! test_sbc_r8_imm8 {
                 test_value_2 = 0;
                 expected_value = test_value_1.wrapping_sub($carry as u8);
                 program_1[1] = test_value_2;
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 cpu.registers.set_a(test_value_1);
                 cpu.registers.set_carry_flag($carry);
@@ -1129,8 +1092,7 @@ macro_rules! test_and_a_r8 {
                 let mut test_value_1: u8 = 0b0110_1001;
                 let mut test_value_2: u8 = 0b0100_0111;
                 let mut expected_value: u8 = test_value_1 & test_value_2;
-                let mut memory_ref = create_memory!();
-                let mut cpu = CPU::new(Rc::clone(&memory_ref));
+                let mut cpu = TestCpu::new();
                 let program_1: Vec<u8> = vec![$opcode];
                 cpu.load(&program_1);
                 cpu.registers.set_a(test_value_1);
@@ -1145,7 +1107,7 @@ macro_rules! test_and_a_r8 {
                 test_value_1 = 0b1010_1001;
                 test_value_2 = 0b0100_0110;
                 expected_value = 0x0;
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 cpu.registers.set_a(test_value_1);
                 cpu.registers.$set_reg(test_value_2);
@@ -1164,8 +1126,7 @@ macro_rules! test_and_a_r8 {
                 let mut test_value_2: u8 = 0b0100_0111;
                 let test_address: u16 = WRAM_ADDRESS as u16 + 0x22;
                 let mut expected_value: u8 = test_value_1 & test_value_2;
-                let mut memory_ref = create_memory!();
-                let mut cpu = CPU::new(Rc::clone(&memory_ref));
+                let mut cpu = TestCpu::new();
                 let program_1: Vec<u8> = vec![$opcode];
                 cpu.load(&program_1);
                 cpu.registers.set_a(test_value_1);
@@ -1182,7 +1143,7 @@ macro_rules! test_and_a_r8 {
                 test_value_1 = 0b1010_1001;
                 test_value_2 = 0b0100_0110;
                 expected_value = 0x0;
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 cpu.registers.set_a(test_value_1);
                 cpu.registers.set_hl(test_address);
@@ -1201,8 +1162,7 @@ macro_rules! test_and_a_r8 {
             fn $func() {
                 let mut test_value_1: u8 = 0b0110_1001;
                 let mut expected_value: u8 = test_value_1 & test_value_1;
-                let mut memory_ref = create_memory!();
-                let mut cpu = CPU::new(Rc::clone(&memory_ref));
+                let mut cpu = TestCpu::new();
                 let program_1: Vec<u8> = vec![$opcode];
                 cpu.load(&program_1);
                 cpu.registers.set_a(test_value_1);
@@ -1214,7 +1174,7 @@ macro_rules! test_and_a_r8 {
 
                 test_value_1 = 0b1010_1001;
                 expected_value = test_value_1;
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 cpu.registers.set_a(test_value_1);
                 let mut cycles = cpu.execute_next();
@@ -1233,8 +1193,7 @@ macro_rules! test_and_a_imm8 {
                 let mut test_value_1: u8 = 0b0110_1001;
                 let mut test_value_2: u8 = 0b0100_0111;
                 let mut expected_value: u8 = test_value_1 & test_value_2;
-                let mut memory_ref = create_memory!();
-                let mut cpu = CPU::new(Rc::clone(&memory_ref));
+                let mut cpu = TestCpu::new();
                 let mut program_1: Vec<u8> = vec![$opcode, test_value_2];
                 cpu.load(&program_1);
                 cpu.registers.set_a(test_value_1);
@@ -1248,7 +1207,7 @@ macro_rules! test_and_a_imm8 {
                 test_value_2 = 0b0100_0110;
                 program_1[1] = test_value_2;
                 expected_value = 0x0;
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 cpu.registers.set_a(test_value_1);
                 let mut cycles = cpu.execute_next();
@@ -1267,8 +1226,7 @@ macro_rules! test_xor_a_r8 {
                 let mut test_value_1: u8 = 0b0110_1001;
                 let mut test_value_2: u8 = 0b0100_0111;
                 let mut expected_value: u8 = test_value_1 ^ test_value_2;
-                let mut memory_ref = create_memory!();
-                let mut cpu = CPU::new(Rc::clone(&memory_ref));
+                let mut cpu = TestCpu::new();
                 let program_1: Vec<u8> = vec![$opcode];
                 cpu.load(&program_1);
                 cpu.registers.set_a(test_value_1);
@@ -1283,7 +1241,7 @@ macro_rules! test_xor_a_r8 {
                 test_value_1 = 0b1010_1001;
                 test_value_2 = 0b0101_0110;
                 expected_value = 0xFF;
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 cpu.registers.set_a(test_value_1);
                 cpu.registers.$set_reg(test_value_2);
@@ -1297,7 +1255,7 @@ macro_rules! test_xor_a_r8 {
                 test_value_1 = 0b1010_1001;
                 test_value_2 = 0b1010_1001;
                 expected_value = 0x0;
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 cpu.registers.set_a(test_value_1);
                 cpu.registers.$set_reg(test_value_2);
@@ -1316,8 +1274,7 @@ macro_rules! test_xor_a_r8 {
                 let mut test_value_2: u8 = 0b0100_0111;
                 let test_address: u16 = WRAM_ADDRESS as u16 + 0x22;
                 let mut expected_value: u8 = test_value_1 ^ test_value_2;
-                let mut memory_ref = create_memory!();
-                let mut cpu = CPU::new(Rc::clone(&memory_ref));
+                let mut cpu = TestCpu::new();
                 let program_1: Vec<u8> = vec![$opcode];
                 cpu.load(&program_1);
                 cpu.registers.set_a(test_value_1);
@@ -1334,7 +1291,7 @@ macro_rules! test_xor_a_r8 {
                 test_value_1 = 0b1010_1001;
                 test_value_2 = 0b0101_0110;
                 expected_value = 0xFF;
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 cpu.registers.set_a(test_value_1);
                 cpu.registers.set_hl(test_address);
@@ -1350,7 +1307,7 @@ macro_rules! test_xor_a_r8 {
                 test_value_1 = 0b1010_1001;
                 test_value_2 = 0b1010_1001;
                 expected_value = 0x0;
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 cpu.registers.set_a(test_value_1);
                 cpu.registers.set_hl(test_address);
@@ -1369,8 +1326,7 @@ macro_rules! test_xor_a_r8 {
             fn $func() {
                 let mut test_value_1: u8 = 0b0110_1001;
                 let mut expected_value: u8 = 0x0;
-                let mut memory_ref = create_memory!();
-                let mut cpu = CPU::new(Rc::clone(&memory_ref));
+                let mut cpu = TestCpu::new();
                 let program_1: Vec<u8> = vec![$opcode];
                 cpu.load(&program_1);
                 cpu.registers.set_a(test_value_1);
@@ -1390,8 +1346,7 @@ macro_rules! test_xor_a_imm8 {
                 let mut test_value_1: u8 = 0b0110_1001;
                 let mut test_value_2: u8 = 0b0100_0111;
                 let mut expected_value: u8 = test_value_1 ^ test_value_2;
-                let mut memory_ref = create_memory!();
-                let mut cpu = CPU::new(Rc::clone(&memory_ref));
+                let mut cpu = TestCpu::new();
                 let mut program_1: Vec<u8> = vec![$opcode, test_value_2];
                 cpu.load(&program_1);
                 cpu.registers.set_a(test_value_1);
@@ -1405,7 +1360,7 @@ macro_rules! test_xor_a_imm8 {
                 test_value_2 = 0b0101_0110;
                 program_1[1] = test_value_2;
                 expected_value = 0xFF;
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 cpu.registers.set_a(test_value_1);
                 let mut cycles = cpu.execute_next();
@@ -1418,7 +1373,7 @@ macro_rules! test_xor_a_imm8 {
                 test_value_2 = 0b1010_1001;
                 expected_value = 0x0;
                 program_1[1] = test_value_2;
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 cpu.registers.set_a(test_value_1);
                 let mut cycles = cpu.execute_next();
@@ -1437,8 +1392,7 @@ macro_rules! test_or_a_r8 {
                 let mut test_value_1: u8 = 0b0110_1001;
                 let mut test_value_2: u8 = 0b0100_0111;
                 let mut expected_value: u8 = test_value_1 | test_value_2;
-                let mut memory_ref = create_memory!();
-                let mut cpu = CPU::new(Rc::clone(&memory_ref));
+                let mut cpu = TestCpu::new();
                 let program_1: Vec<u8> = vec![$opcode];
                 cpu.load(&program_1);
                 cpu.registers.set_a(test_value_1);
@@ -1453,7 +1407,7 @@ macro_rules! test_or_a_r8 {
                 test_value_1 = 0b1010_1001;
                 test_value_2 = 0b0101_0110;
                 expected_value = 0xFF;
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 cpu.registers.set_a(test_value_1);
                 cpu.registers.$set_reg(test_value_2);
@@ -1467,7 +1421,7 @@ macro_rules! test_or_a_r8 {
                 test_value_1 = 0b1010_1001;
                 test_value_2 = 0b1010_1001;
                 expected_value = test_value_1;
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 cpu.registers.set_a(test_value_1);
                 cpu.registers.$set_reg(test_value_2);
@@ -1481,7 +1435,7 @@ macro_rules! test_or_a_r8 {
                 test_value_1 = 0b0;
                 test_value_2 = 0b0;
                 expected_value = test_value_1;
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 cpu.registers.set_a(test_value_1);
                 cpu.registers.$set_reg(test_value_2);
@@ -1500,8 +1454,7 @@ macro_rules! test_or_a_r8 {
                 let mut test_value_2: u8 = 0b0100_0111;
                 let test_address: u16 = WRAM_ADDRESS as u16 + 0x22;
                 let mut expected_value: u8 = test_value_1 | test_value_2;
-                let mut memory_ref = create_memory!();
-                let mut cpu = CPU::new(Rc::clone(&memory_ref));
+                let mut cpu = TestCpu::new();
                 let program_1: Vec<u8> = vec![$opcode];
                 cpu.load(&program_1);
                 cpu.registers.set_a(test_value_1);
@@ -1518,7 +1471,7 @@ macro_rules! test_or_a_r8 {
                 test_value_1 = 0b1010_1001;
                 test_value_2 = 0b0101_0110;
                 expected_value = 0xFF;
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 cpu.registers.set_a(test_value_1);
                 cpu.registers.set_hl(test_address);
@@ -1534,7 +1487,7 @@ macro_rules! test_or_a_r8 {
                 test_value_1 = 0b1010_1001;
                 test_value_2 = 0b0;
                 expected_value = test_value_1;
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 cpu.registers.set_a(test_value_1);
                 cpu.registers.set_hl(test_address);
@@ -1550,7 +1503,7 @@ macro_rules! test_or_a_r8 {
                 test_value_1 = 0b0;
                 test_value_2 = 0b0;
                 expected_value = test_value_1;
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 cpu.registers.set_a(test_value_1);
                 cpu.registers.set_hl(test_address);
@@ -1569,8 +1522,7 @@ macro_rules! test_or_a_r8 {
             fn $func() {
                 let mut test_value_1: u8 = 0b0110_1001;
                 let mut expected_value: u8 = test_value_1;
-                let mut memory_ref = create_memory!();
-                let mut cpu = CPU::new(Rc::clone(&memory_ref));
+                let mut cpu = TestCpu::new();
                 let program_1: Vec<u8> = vec![$opcode];
                 cpu.load(&program_1);
                 cpu.registers.set_a(test_value_1);
@@ -1582,7 +1534,7 @@ macro_rules! test_or_a_r8 {
 
                 test_value_1 = 0b0;
                 expected_value = test_value_1;
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 cpu.registers.set_a(test_value_1);
                 let mut cycles = cpu.execute_next();
@@ -1601,8 +1553,7 @@ macro_rules! test_or_a_imm8 {
                 let mut test_value_1: u8 = 0b0110_1001;
                 let mut test_value_2: u8 = 0b0100_0111;
                 let mut expected_value: u8 = test_value_1 | test_value_2;
-                let mut memory_ref = create_memory!();
-                let mut cpu = CPU::new(Rc::clone(&memory_ref));
+                let mut cpu = TestCpu::new();
                 let mut program_1: Vec<u8> = vec![$opcode, test_value_2];
                 cpu.load(&program_1);
                 cpu.registers.set_a(test_value_1);
@@ -1616,7 +1567,7 @@ macro_rules! test_or_a_imm8 {
                 test_value_2 = 0b0101_0110;
                 expected_value = 0xFF;
                 program_1[1] = test_value_2;
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 cpu.registers.set_a(test_value_1);
                 let mut cycles = cpu.execute_next();
@@ -1629,7 +1580,7 @@ macro_rules! test_or_a_imm8 {
                 test_value_2 = 0b1010_1001;
                 expected_value = test_value_1;
                 program_1[1] = test_value_2;
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 cpu.registers.set_a(test_value_1);
                 let mut cycles = cpu.execute_next();
@@ -1642,7 +1593,7 @@ macro_rules! test_or_a_imm8 {
                 test_value_2 = 0b0;
                 expected_value = test_value_1;
                 program_1[1] = test_value_2;
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 cpu.registers.set_a(test_value_1);
                 let mut cycles = cpu.execute_next();
@@ -1661,8 +1612,7 @@ macro_rules! test_cp_a_r8 {
                 let mut test_value_1: u8 = 0xC4;
                 let mut test_value_2: u8 = 0x11;
                 let mut expected_value: u8 = test_value_1.wrapping_sub(test_value_2);
-                let mut memory_ref = create_memory!();
-                let mut cpu = CPU::new(Rc::clone(&memory_ref));
+                let mut cpu = TestCpu::new();
                 let program_1: Vec<u8> = vec![$opcode];
                 cpu.load(&program_1);
                 cpu.registers.set_a(test_value_1);
@@ -1677,7 +1627,7 @@ macro_rules! test_cp_a_r8 {
                 test_value_1 = 0xF0;
                 test_value_2 = 0xF0;
                 expected_value = 0x00;
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 cpu.registers.set_a(test_value_1);
                 cpu.registers.$set_reg(test_value_2);
@@ -1691,7 +1641,7 @@ macro_rules! test_cp_a_r8 {
                 test_value_1 = 0x10;
                 test_value_2 = 0x01;
                 expected_value = 0x0F;
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 cpu.registers.set_a(test_value_1);
                 cpu.registers.$set_reg(test_value_2);
@@ -1705,7 +1655,7 @@ macro_rules! test_cp_a_r8 {
                 test_value_1 = 0x10;
                 test_value_2 = 0x20;
                 expected_value = test_value_1.wrapping_sub(test_value_2);
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 cpu.registers.set_a(test_value_1);
                 cpu.registers.$set_reg(test_value_2);
@@ -1719,7 +1669,7 @@ macro_rules! test_cp_a_r8 {
                 test_value_1 = 0x00;
                 test_value_2 = 0x01;
                 expected_value = 0xFF;
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 cpu.registers.set_a(test_value_1);
                 cpu.registers.$set_reg(test_value_2);
@@ -1738,8 +1688,7 @@ macro_rules! test_cp_a_r8 {
                 let mut test_value_1: u8 = 0xC4;
                 let mut test_value_2: u8 = 0x11;
                 let mut expected_value: u8 = test_value_1.wrapping_sub(test_value_2);
-                let mut memory_ref = create_memory!();
-                let mut cpu = CPU::new(Rc::clone(&memory_ref));
+                let mut cpu = TestCpu::new();
                 let program_1: Vec<u8> = vec![$opcode];
                 cpu.load(&program_1);
                 cpu.registers.set_a(test_value_1);
@@ -1756,7 +1705,7 @@ macro_rules! test_cp_a_r8 {
                 test_value_1 = 0xF0;
                 test_value_2 = 0xF0;
                 expected_value = 0x00;
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 cpu.registers.set_a(test_value_1);
                 cpu.registers.set_hl(test_address);
@@ -1772,7 +1721,7 @@ macro_rules! test_cp_a_r8 {
                 test_value_1 = 0x10;
                 test_value_2 = 0x01;
                 expected_value = 0x0F;
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 cpu.registers.set_a(test_value_1);
                 cpu.registers.set_hl(test_address);
@@ -1788,7 +1737,7 @@ macro_rules! test_cp_a_r8 {
                 test_value_1 = 0x10;
                 test_value_2 = 0x20;
                 expected_value = test_value_1.wrapping_sub(test_value_2);
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 cpu.registers.set_a(test_value_1);
                 cpu.registers.set_hl(test_address);
@@ -1804,7 +1753,7 @@ macro_rules! test_cp_a_r8 {
                 test_value_1 = 0x00;
                 test_value_2 = 0x01;
                 expected_value = 0xFF;
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 cpu.registers.set_a(test_value_1);
                 cpu.registers.set_hl(test_address);
@@ -1822,8 +1771,7 @@ macro_rules! test_cp_a_r8 {
             #[test]
             fn $func() {
                 let mut test_value_1: u8 = 0b0110_1001;
-                let mut memory_ref = create_memory!();
-                let mut cpu = CPU::new(Rc::clone(&memory_ref));
+                let mut cpu = TestCpu::new();
                 let program_1: Vec<u8> = vec![$opcode];
                 cpu.load(&program_1);
                 cpu.registers.set_a(test_value_1);
@@ -1834,7 +1782,7 @@ macro_rules! test_cp_a_r8 {
                 test_flags!(cpu, true, true, false, false);
 
                 test_value_1 = 0b0;
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 cpu.registers.set_a(test_value_1);
                 let mut cycles = cpu.execute_next();
@@ -1853,8 +1801,7 @@ macro_rules! test_cp_a_imm8 {
                 let mut test_value_1: u8 = 0xC4;
                 let mut test_value_2: u8 = 0x11;
                 let mut expected_value: u8 = test_value_1.wrapping_sub(test_value_2);
-                let mut memory_ref = create_memory!();
-                let mut cpu = CPU::new(Rc::clone(&memory_ref));
+                let mut cpu = TestCpu::new();
                 let mut program_1: Vec<u8> = vec![$opcode, test_value_2];
                 cpu.load(&program_1);
                 cpu.registers.set_a(test_value_1);
@@ -1868,7 +1815,7 @@ macro_rules! test_cp_a_imm8 {
                 test_value_2 = 0xF0;
                 expected_value = 0x00;
                 program_1[1] = test_value_2;
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 cpu.registers.set_a(test_value_1);
                 cycles = cpu.execute_next();
@@ -1881,7 +1828,7 @@ macro_rules! test_cp_a_imm8 {
                 test_value_2 = 0x01;
                 expected_value = 0x0F;
                 program_1[1] = test_value_2;
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 cpu.registers.set_a(test_value_1);
                 cycles = cpu.execute_next();
@@ -1894,7 +1841,7 @@ macro_rules! test_cp_a_imm8 {
                 test_value_2 = 0x20;
                 expected_value = test_value_1.wrapping_sub(test_value_2);
                 program_1[1] = test_value_2;
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 cpu.registers.set_a(test_value_1);
                 cycles = cpu.execute_next();
@@ -1907,7 +1854,7 @@ macro_rules! test_cp_a_imm8 {
                 test_value_2 = 0x01;
                 expected_value = 0xFF;
                 program_1[1] = test_value_2;
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 cpu.registers.set_a(test_value_1);
                 cycles = cpu.execute_next();
@@ -1926,20 +1873,19 @@ macro_rules! test_call {
                 let test_call_address: u16 = USER_PROGRAM_ADDRESS as u16 + 0x210;
                 let test_call_low: u8 = (test_call_address & 0xFF) as u8;
                 let test_call_high: u8 = (test_call_address >> 8) as u8;
-                let mut memory_ref = create_memory!();
-                let mut cpu = CPU::new(Rc::clone(&memory_ref));
+                let mut cpu = TestCpu::new();
                 let program_1: Vec<u8> = vec![$opcode, test_call_low, test_call_high];
                 cpu.load(&program_1);
                 let registers_copy = cpu.registers;
-                let return_address = cpu.registers.get_pc() + 3;
+                let return_address = TestCpu::PROGRAM_ADDRESS + 3;
                 let return_address_low = (return_address & 0xFF) as u8;
                 let return_address_high = (return_address >> 8) as u8;
                 let mut cycles = cpu.execute_next();
                 assert_eq!(cycles, 6);
                 assert_eq!(cpu.registers.get_sp(), registers_copy.get_sp() - 2);
-                assert_eq!(cpu.registers.get_pc(), test_call_address);
-                assert_eq!(cpu.read_memory(cpu.registers.get_sp() + 1), return_address_low);
-                assert_eq!(cpu.read_memory(cpu.registers.get_sp() + 2), return_address_high);
+                assert_eq!(cpu.instruction_pc(), test_call_address);
+                assert_eq!(cpu.read_memory(cpu.registers.get_sp()), return_address_low);
+                assert_eq!(cpu.read_memory(cpu.registers.get_sp() + 1), return_address_high);
             }
         };
         ($opcode:expr, $func:ident, $inverse:expr, $set_flag:ident, $get_flag:ident) => {
@@ -1948,8 +1894,7 @@ macro_rules! test_call {
                 let test_call_address: u16 = USER_PROGRAM_ADDRESS as u16 + 0x210;
                 let test_call_low: u8 = (test_call_address & 0xFF) as u8;
                 let test_call_high: u8 = (test_call_address >> 8) as u8;
-                let mut memory_ref = create_memory!();
-                let mut cpu = CPU::new(Rc::clone(&memory_ref));
+                let mut cpu = TestCpu::new();
                 let program_1: Vec<u8> = vec![$opcode, test_call_low, test_call_high];
                 cpu.load(&program_1);
                 let registers_copy = cpu.registers;
@@ -1959,19 +1904,19 @@ macro_rules! test_call {
                 assert_eq!(cpu.registers.get_sp(), registers_copy.get_sp());
                 assert_eq!(cpu.registers.get_pc(), registers_copy.get_pc() + 3);
 
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 let registers_copy = cpu.registers;
-                let return_address = cpu.registers.get_pc() + 3;
+                let return_address = TestCpu::PROGRAM_ADDRESS + 3;
                 let return_address_low = (return_address & 0xFF) as u8;
                 let return_address_high = (return_address >> 8) as u8;
                 if $inverse {cpu.registers.$set_flag(false)} else {cpu.registers.$set_flag(true)};
                 let mut cycles = cpu.execute_next();
                 assert_eq!(cycles, 6);
                 assert_eq!(cpu.registers.get_sp(), registers_copy.get_sp() - 2);
-                assert_eq!(cpu.registers.get_pc(), test_call_address);
-                assert_eq!(cpu.read_memory(cpu.registers.get_sp() + 1), return_address_low);
-                assert_eq!(cpu.read_memory(cpu.registers.get_sp() + 2), return_address_high);
+                assert_eq!(cpu.instruction_pc(), test_call_address);
+                assert_eq!(cpu.read_memory(cpu.registers.get_sp()), return_address_low);
+                assert_eq!(cpu.read_memory(cpu.registers.get_sp() + 1), return_address_high);
             }
         };
     }
@@ -1981,8 +1926,7 @@ macro_rules! test_ret {
             #[test]
             fn $func() {
                 let test_return_address: u16 = USER_PROGRAM_ADDRESS as u16 + 0x210;
-                let mut memory_ref = create_memory!();
-                let mut cpu = CPU::new(Rc::clone(&memory_ref));
+                let mut cpu = TestCpu::new();
                 let program_1: Vec<u8> = vec![$opcode];
                 cpu.load(&program_1);
                 let registers_copy = cpu.registers;
@@ -1991,15 +1935,14 @@ macro_rules! test_ret {
                 let mut cycles = cpu.execute_next();
                 assert_eq!(cycles, 4);
                 assert_eq!(cpu.registers.get_sp(), registers_copy.get_sp());
-                assert_eq!(cpu.registers.get_pc(), test_return_address);
+                assert_eq!(cpu.instruction_pc(), test_return_address);
             }
         };
         ($opcode:expr, $func:ident, $inverse:expr, $set_flag:ident, $get_flag:ident) => {
             #[test]
             fn $func() {
                 let test_return_address: u16 = USER_PROGRAM_ADDRESS as u16 + 0x210;
-                let mut memory_ref = create_memory!();
-                let mut cpu = CPU::new(Rc::clone(&memory_ref));
+                let mut cpu = TestCpu::new();
                 let program_1: Vec<u8> = vec![$opcode];
                 cpu.load(&program_1);
                 let registers_copy = cpu.registers;
@@ -2011,7 +1954,7 @@ macro_rules! test_ret {
                 assert_eq!(cpu.registers.get_sp(), registers_copy.get_sp() - 2);
                 assert_eq!(cpu.registers.get_pc(), registers_copy.get_pc() + 1);
 
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 let registers_copy = cpu.registers;
                 cpu.push((test_return_address >> 8) as u8);
@@ -2020,7 +1963,7 @@ macro_rules! test_ret {
                 let mut cycles = cpu.execute_next();
                 assert_eq!(cycles, 5);
                 assert_eq!(cpu.registers.get_sp(), registers_copy.get_sp());
-                assert_eq!(cpu.registers.get_pc(), test_return_address);
+                assert_eq!(cpu.instruction_pc(), test_return_address);
             }
         };
     }
@@ -2032,20 +1975,19 @@ macro_rules! test_call {
                 let test_call_address: u16 = USER_PROGRAM_ADDRESS as u16 + 0x0C16;
                 let test_call_address_high: u8 = (test_call_address >> 8) as u8;
                 let test_call_address_low: u8 = (test_call_address & 0xFF) as u8;
-                let mut memory_ref = create_memory!();
-                let mut cpu = CPU::new(Rc::clone(&memory_ref));
+                let mut cpu = TestCpu::new();
                 let program_1: Vec<u8> = vec![$opcode, test_call_address_low, test_call_address_high];
                 cpu.load(&program_1);
                 let registers_copy = cpu.registers;
-                let test_return_address = registers_copy.get_pc() + program_1.len() as u16;
+                let test_return_address = TestCpu::PROGRAM_ADDRESS + program_1.len() as u16;
                 let test_return_address_high = (test_return_address >> 8) as u8;
                 let test_return_address_low = (test_return_address & 0xFF) as u8;
                 let mut cycles = cpu.execute_next();
                 assert_eq!(cycles, 6);
                 assert_eq!(cpu.registers.get_sp(), registers_copy.get_sp() - 2);
-                assert_eq!(cpu.registers.get_pc(), test_call_address);
-                assert_eq!(cpu.read_memory(cpu.registers.get_sp() + 1), test_return_address_low);
-                assert_eq!(cpu.read_memory(cpu.registers.get_sp() + 2), test_return_address_high);
+                assert_eq!(cpu.instruction_pc(), test_call_address);
+                assert_eq!(cpu.read_memory(cpu.registers.get_sp()), test_return_address_low);
+                assert_eq!(cpu.read_memory(cpu.registers.get_sp() + 1), test_return_address_high);
             }
         };
         ($opcode:expr, $func:ident, $inverse:expr, $set_flag:ident, $get_flag:ident) => {
@@ -2054,8 +1996,7 @@ macro_rules! test_call {
                 let test_call_address: u16 = USER_PROGRAM_ADDRESS as u16 + 0x0C16;
                 let test_call_address_high: u8 = (test_call_address >> 8) as u8;
                 let test_call_address_low: u8 = (test_call_address & 0xFF) as u8;
-                let mut memory_ref = create_memory!();
-                let mut cpu = CPU::new(Rc::clone(&memory_ref));
+                let mut cpu = TestCpu::new();
                 let program_1: Vec<u8> = vec![$opcode, test_call_address_low, test_call_address_high];
                 cpu.load(&program_1);
                 let registers_copy = cpu.registers;
@@ -2065,19 +2006,19 @@ macro_rules! test_call {
                 assert_eq!(cpu.registers.get_sp(), registers_copy.get_sp());
                 assert_eq!(cpu.registers.get_pc(), registers_copy.get_pc() + program_1.len() as u16);
 
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 let registers_copy = cpu.registers;
                 if $inverse {cpu.registers.$set_flag(false)} else {cpu.registers.$set_flag(true)};
-                let test_return_address = registers_copy.get_pc() + program_1.len() as u16;
+                let test_return_address = TestCpu::PROGRAM_ADDRESS + program_1.len() as u16;
                 let test_return_address_high = (test_return_address >> 8) as u8;
                 let test_return_address_low = (test_return_address & 0xFF) as u8;
                 cycles = cpu.execute_next();
                 assert_eq!(cycles, 6);
                 assert_eq!(cpu.registers.get_sp(), registers_copy.get_sp() - 2);
-                assert_eq!(cpu.registers.get_pc(), test_call_address);
-                assert_eq!(cpu.read_memory(cpu.registers.get_sp() + 1), test_return_address_low);
-                assert_eq!(cpu.read_memory(cpu.registers.get_sp() + 2), test_return_address_high);
+                assert_eq!(cpu.instruction_pc(), test_call_address);
+                assert_eq!(cpu.read_memory(cpu.registers.get_sp()), test_return_address_low);
+                assert_eq!(cpu.read_memory(cpu.registers.get_sp() + 1), test_return_address_high);
             }
         };
     }
@@ -2089,14 +2030,13 @@ macro_rules! test_jump {
                 let test_jump_address: u16 = USER_PROGRAM_ADDRESS as u16 + 0x210;
                 let test_address_high_byte: u8 = (test_jump_address >> 8) as u8;
                 let test_address_low_byte: u8 = (test_jump_address & 0xFF) as u8;
-                let mut memory_ref = create_memory!();
-                let mut cpu = CPU::new(Rc::clone(&memory_ref));
+                let mut cpu = TestCpu::new();
                 let program_1: Vec<u8> = vec![$opcode, test_address_low_byte, test_address_high_byte];
                 cpu.load(&program_1);
                 let registers_copy = cpu.registers;
                 let mut cycles = cpu.execute_next();
                 assert_eq!(cycles, 4);
-                assert_eq!(cpu.registers.get_pc(), test_jump_address);
+                assert_eq!(cpu.instruction_pc(), test_jump_address);
             }
         };
         ($opcode:expr, $func:ident, $inverse:expr, $set_flag:ident, $get_flag:ident) => {
@@ -2105,8 +2045,7 @@ macro_rules! test_jump {
                 let test_jump_address: u16 = USER_PROGRAM_ADDRESS as u16 + 0x210;
                 let test_address_high_byte: u8 = (test_jump_address >> 8) as u8;
                 let test_address_low_byte: u8 = (test_jump_address & 0xFF) as u8;
-                let mut memory_ref = create_memory!();
-                let mut cpu = CPU::new(Rc::clone(&memory_ref));
+                let mut cpu = TestCpu::new();
                 let program_1: Vec<u8> = vec![$opcode, test_address_low_byte, test_address_high_byte];
                 cpu.load(&program_1);
                 let registers_copy = cpu.registers;
@@ -2115,28 +2054,27 @@ macro_rules! test_jump {
                 assert_eq!(cycles, 3);
                 assert_eq!(cpu.registers.get_pc(), registers_copy.get_pc() + 3);
 
-                cpu = CPU::new(Rc::clone(&memory_ref));
+                cpu = TestCpu::new();
                 cpu.load(&program_1);
                 let registers_copy = cpu.registers;
                 if $inverse {cpu.registers.$set_flag(false)} else {cpu.registers.$set_flag(true)};
                 cycles = cpu.execute_next();
                 assert_eq!(cycles, 4);
-                assert_eq!(cpu.registers.get_pc(), test_jump_address);
+                assert_eq!(cpu.instruction_pc(), test_jump_address);
             }
         };
         ($opcode:expr, $func:ident, $set_from_reg:ident, $get_from_reg:ident) => {
             #[test]
             fn $func() {
                 let test_jump_address: u16 = USER_PROGRAM_ADDRESS as u16 + 0x210;
-                let mut memory_ref = create_memory!();
-                let mut cpu = CPU::new(Rc::clone(&memory_ref));
+                let mut cpu = TestCpu::new();
                 let program_1: Vec<u8> = vec![$opcode];
                 cpu.load(&program_1);
                 let registers_copy = cpu.registers;
                 cpu.registers.$set_from_reg(test_jump_address);
                 let mut cycles = cpu.execute_next();
                 assert_eq!(cycles, 1);
-                assert_eq!(cpu.registers.get_pc(), test_jump_address);
+                assert_eq!(cpu.instruction_pc(), test_jump_address);
                 assert_eq!(cpu.registers.$get_from_reg(), test_jump_address);
             }
         };
@@ -2149,8 +2087,7 @@ macro_rules! test_pop {
                 let test_value: u16 = 0x521B;
                 let test_high_byte: u8 = (test_value >> 8) as u8;
                 let test_low_byte: u8 = (test_value & 0xFF) as u8;
-                let mut memory_ref = create_memory!();
-                let mut cpu = CPU::new(Rc::clone(&memory_ref));
+                let mut cpu = TestCpu::new();
                 let program_1: Vec<u8> = vec![$opcode];
                 cpu.load(&program_1);
                 cpu.registers.$set_reg(0);
@@ -2176,21 +2113,20 @@ macro_rules! test_push {
                 let test_value: u16 = 0x521B;
                 let test_high_byte: u8 = (test_value >> 8) as u8;
                 let test_low_byte: u8 = (test_value & 0xFF) as u8;
-                let mut memory_ref = create_memory!();
-                let mut cpu = CPU::new(Rc::clone(&memory_ref));
+                let mut cpu = TestCpu::new();
                 let program_1: Vec<u8> = vec![$opcode];
                 cpu.load(&program_1);
                 cpu.registers.$set_reg(test_value);
                 let registers_copy = cpu.registers;
                 let mut cycles = cpu.execute_next();
-                assert_eq!(cycles, 3);
+                assert_eq!(cycles, 4);
                 assert_eq!(cpu.registers.$get_reg(), if stringify!($get_reg) == "get_af" {test_value & 0xFFF0} else {test_value});
                 assert_eq!(cpu.registers.get_sp(), registers_copy.get_sp() - 2);
-                assert_eq!(cpu.read_memory(cpu.registers.get_sp() + 2), test_high_byte);
+                assert_eq!(cpu.read_memory(cpu.registers.get_sp() + 1), test_high_byte);
                 if (stringify!($get_reg) == "get_af") {
-                    assert_eq!(cpu.read_memory(cpu.registers.get_sp() + 1), test_low_byte & 0xF0);
+                    assert_eq!(cpu.read_memory(cpu.registers.get_sp()), test_low_byte & 0xF0);
                 } else {
-                    assert_eq!(cpu.read_memory(cpu.registers.get_sp() + 1), test_low_byte);
+                    assert_eq!(cpu.read_memory(cpu.registers.get_sp()), test_low_byte);
                 }
             }
         };
@@ -2201,18 +2137,17 @@ macro_rules! test_rst {
             #[test]
             fn $func() {
                 let int_addr: u16 = (($opcode & 0b00_111_000) >> 3) as u16 * 8;
-                let mut memory_ref = create_memory!();
-                let mut cpu = CPU::new(Rc::clone(&memory_ref));
+                let mut cpu = TestCpu::new();
                 let mut program_1: Vec<u8> = vec![$opcode];
                 cpu.load(&program_1);
                 let registers_copy = cpu.registers;
-                let expected_return_addr = cpu.registers.get_pc() + program_1.len() as u16;
+                let expected_return_addr = TestCpu::PROGRAM_ADDRESS + program_1.len() as u16;
                 let mut cycles = cpu.execute_next();
                 assert_eq!(cycles, 4);
-                assert_eq!(cpu.registers.get_pc(), int_addr);
+                assert_eq!(cpu.instruction_pc(), int_addr);
                 assert_eq!(cpu.registers.get_sp(), registers_copy.get_sp() - 2);
-                assert_eq!(cpu.read_memory(cpu.registers.get_sp() + 1), (expected_return_addr & 0xFF) as u8);
-                assert_eq!(cpu.read_memory(cpu.registers.get_sp() + 2), (expected_return_addr >> 8) as u8);
+                assert_eq!(cpu.read_memory(cpu.registers.get_sp()), (expected_return_addr & 0xFF) as u8);
+                assert_eq!(cpu.read_memory(cpu.registers.get_sp() + 1), (expected_return_addr >> 8) as u8);
                 // Flags untouched
                 test_flags!(
                     cpu,
@@ -2228,8 +2163,7 @@ macro_rules! test_rst {
 
 #[test]
 fn test_0x00_nop() {
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
     let registers_copy = cpu.registers;
     let program: Vec<u8> = vec![0x00, 0x00];
     cpu.load(&program);
@@ -2257,8 +2191,7 @@ test_ld_r8_imm8!(0x06, test_0x06_ld_b_imm8, set_b, get_b);
 fn test_0x07_rlca() {
     //No Flags
     let test_value_1: u8 = 0b1000_1000;
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
     let program_1: Vec<u8> = vec![0x07, 0x07];
     cpu.load(&program_1);
     cpu.registers.set_a(test_value_1);
@@ -2282,8 +2215,7 @@ fn test_0x08_ld__a16__sp() {
     //No Flags
     let test_value_1: u16 = 0xBD89;
     let test_address_1: u16 = WRAM_ADDRESS as u16 + 0x89;
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
     let program_1: Vec<u8> = vec![0x08, 0x89, (test_address_1 >> 8) as u8];
     cpu.load(&program_1);
     cpu.registers.set_sp(test_value_1);
@@ -2301,8 +2233,7 @@ test_add_r16_r16!(0x09, test_0x09_add_hl_bc, set_hl, get_hl, set_bc, get_bc);
 fn test_0x0a_ld_a__bc_() {
     let mut test_value_1: u8 = 0xBD;
     let mut test_address_1: u16 = WRAM_ADDRESS as u16 + 0x0128;
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
     let program: Vec<u8> = vec![0x0A];
     cpu.load(&program);
     cpu.registers.set_bc(test_address_1);
@@ -2324,8 +2255,7 @@ test_ld_r8_imm8!(0x0E, test_0x0e_ld_c_imm8, set_c, get_c);
 fn test_0x0f_rrca() {
     //No Flags
     let test_value_1: u8 = 0b0001_0001;
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
     let program_1: Vec<u8> = vec![0x0F, 0x0F];
     cpu.load(&program_1);
     cpu.registers.set_a(test_value_1);
@@ -2360,8 +2290,7 @@ test_ld_r8_imm8!(0x16, test_0x16_ld_d_imm8, set_d, get_d);
 fn test_0x17_rla() {
     //No Flags
     let test_value_1: u8 = 0b1000_1000;
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
     let program_1: Vec<u8> = vec![0x17, 0x17];
     cpu.load(&program_1);
     cpu.registers.set_a(test_value_1);
@@ -2384,30 +2313,22 @@ fn test_0x17_rla() {
 #[test]
 fn test_0x18_jr_e8() {
     let mut test_value: i8 = -50;
-    let mut start_address: i16 = 0x0350;
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut start_address: u16 = WRAM_ADDRESS as u16 + 0x0350;
+    let mut cpu = TestCpu::new();
     let mut program: Vec<u8> = vec![0x18, test_value as u8];
-    cpu.load(&program);
-    cpu.write_memory(0x0350, program[0]);
-    cpu.write_memory(0x0351, program[1]);
-    cpu.registers.set_pc(0x0350);
+    cpu.load_at(start_address, &program);
     let mut cycles = cpu.execute_next();
     assert_eq!(cycles, 3);
-    assert_eq!(cpu.registers.get_pc(), ((start_address + test_value as i16 + program.len() as i16)) as u16);
+    assert_eq!(cpu.instruction_pc(), start_address.wrapping_add_signed(test_value as i16 + program.len() as i16));
 
     let mut test_value: i8 = 11;
-    let mut start_address: i16 = 0x0350;
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut start_address: u16 = WRAM_ADDRESS as u16 + 0x0350;
+    let mut cpu = TestCpu::new();
     let mut program: Vec<u8> = vec![0x18, test_value as u8];
-    cpu.load(&program);
-    cpu.write_memory(0x0350, program[0]);
-    cpu.write_memory(0x0351, program[1]);
-    cpu.registers.set_pc(0x0350);
+    cpu.load_at(start_address, &program);
     let mut cycles = cpu.execute_next();
     assert_eq!(cycles, 3);
-    assert_eq!(cpu.registers.get_pc(), ((start_address + test_value as i16 + program.len() as i16)) as u16);
+    assert_eq!(cpu.instruction_pc(), start_address.wrapping_add_signed(test_value as i16 + program.len() as i16));
 }
 
 test_add_r16_r16!(0x19, test_0x19_add_hl_de, set_hl, get_hl, set_de, get_de);
@@ -2416,8 +2337,7 @@ test_add_r16_r16!(0x19, test_0x19_add_hl_de, set_hl, get_hl, set_de, get_de);
 fn test_0x1a_ld_a__de_() {
     let mut test_value_1: u8 = 0xBD;
     let mut test_address_1: u16 = WRAM_ADDRESS as u16 + 0x0128;
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
     let program: Vec<u8> = vec![0x1A];
     cpu.load(&program);
     cpu.registers.set_de(test_address_1);
@@ -2439,8 +2359,7 @@ test_ld_r8_imm8!(0x1E, test_0x1e_ld_e_imm8, set_e, get_e);
 fn test_0x1f_rra() {
     //No Flags
     let test_value_1: u8 = 0b0001_0001;
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
     let program_1: Vec<u8> = vec![0x1F, 0x1F];
     cpu.load(&program_1);
     cpu.registers.set_a(test_value_1);
@@ -2463,51 +2382,37 @@ fn test_0x1f_rra() {
 #[test]
 fn test_0x20_jr_nz_e8() {
     let mut test_value: i8 = -50;
-    let mut start_address: i16 = 0x0350;
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut start_address: u16 = WRAM_ADDRESS as u16 + 0x0350;
+    let mut cpu = TestCpu::new();
     let mut program: Vec<u8> = vec![0x20, test_value as u8];
-    cpu.load(&program);
-    cpu.write_memory(0x0350, program[0]);
-    cpu.write_memory(0x0351, program[1]);
-    cpu.registers.set_pc(0x0350);
+    cpu.load_at(start_address, &program);
     cpu.registers.set_zero_flag(false);
     let mut cycles = cpu.execute_next();
     assert_eq!(cycles, 3);
-    assert_eq!(cpu.registers.get_pc(), ((start_address + test_value as i16 + program.len() as i16)) as u16);
+    assert_eq!(cpu.instruction_pc(), start_address.wrapping_add_signed(test_value as i16 + program.len() as i16));
 
-    cpu = CPU::new(Rc::clone(&memory_ref));
+    cpu = TestCpu::new();
     assert_eq!(cycles, 3);
-    cpu.load(&program);
-    cpu.write_memory(0x0350, program[0]);
-    cpu.write_memory(0x0351, program[1]);
-    cpu.registers.set_pc(0x0350);
+    cpu.load_at(start_address, &program);
 
     // Positive Value
     let mut test_value: i8 = 11;
-    let mut start_address: i16 = 0x0350;
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut start_address: u16 = WRAM_ADDRESS as u16 + 0x0350;
+    let mut cpu = TestCpu::new();
     let mut program: Vec<u8> = vec![0x20, test_value as u8];
-    cpu.load(&program);
-    cpu.write_memory(0x0350, program[0]);
-    cpu.write_memory(0x0351, program[1]);
-    cpu.registers.set_pc(0x0350);
+    cpu.load_at(start_address, &program);
     cpu.registers.set_zero_flag(false);
     let mut cycles = cpu.execute_next();
     assert_eq!(cycles, 3);
-    assert_eq!(cpu.registers.get_pc(), ((start_address + test_value as i16 + program.len() as i16)) as u16);
+    assert_eq!(cpu.instruction_pc(), start_address.wrapping_add_signed(test_value as i16 + program.len() as i16));
 
-    cpu = CPU::new(Rc::clone(&memory_ref));
+    cpu = TestCpu::new();
     assert_eq!(cycles, 3);
-    cpu.load(&program);
-    cpu.write_memory(0x0350, program[0]);
-    cpu.write_memory(0x0351, program[1]);
-    cpu.registers.set_pc(0x0350);
+    cpu.load_at(start_address, &program);
     cpu.registers.set_zero_flag(true);
     cycles = cpu.execute_next();
     assert_eq!(cycles, 2);
-    assert_eq!(cpu.registers.get_pc(), 0x352);
+    assert_eq!(cpu.instruction_pc(), start_address + program.len() as u16);
 }
 
 test_ld_r16_imm16!(0x21, test_0x21_ld_hl_imm16, set_hl, get_hl, get_h, get_l);
@@ -2515,9 +2420,8 @@ test_ld_r16_imm16!(0x21, test_0x21_ld_hl_imm16, set_hl, get_hl, get_h, get_l);
 #[test]
 fn test_0x22_ld__hli__a() {
     let test_value: u8 = 0xF4;
-    let test_address: u16 = memory::WRAM_ADDRESS as u16 + 0x0500;
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let test_address: u16 = WRAM_ADDRESS as u16 + 0x0500;
+    let mut cpu = TestCpu::new();
     let program: Vec<u8> = vec![0x22];
     cpu.load(&program);
     cpu.registers.set_a(test_value);
@@ -2543,50 +2447,38 @@ fn test_0x27_daa() {
 #[test]
 fn test_0x28_jr_z_e8() {
     let mut test_value: i8 = -50;
-    let mut start_address: i16 = 0x0350;
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut start_address: u16 = WRAM_ADDRESS as u16 + 0x0350;
+    let mut cpu = TestCpu::new();
     let mut program: Vec<u8> = vec![0x28, test_value as u8];
-    cpu.load(&program);
-    cpu.write_memory(0x0350, program[0]);
-    cpu.write_memory(0x0351, program[1]);
-    cpu.registers.set_pc(0x0350);
+    cpu.load_at(start_address, &program);
     cpu.registers.set_zero_flag(true);
     let mut cycles = cpu.execute_next();
     assert_eq!(cycles, 3);
-    assert_eq!(cpu.registers.get_pc(), ((start_address + test_value as i16 + program.len() as i16)) as u16);
+    assert_eq!(cpu.instruction_pc(), start_address.wrapping_add_signed(test_value as i16 + program.len() as i16));
 
     let mut test_value: i8 = 11;
-    let mut start_address: i16 = 0x0350;
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut start_address: u16 = WRAM_ADDRESS as u16 + 0x0350;
+    let mut cpu = TestCpu::new();
     let mut program: Vec<u8> = vec![0x28, test_value as u8];
-    cpu.load(&program);
-    cpu.write_memory(0x0350, program[0]);
-    cpu.write_memory(0x0351, program[1]);
-    cpu.registers.set_pc(0x0350);
+    cpu.load_at(start_address, &program);
     cpu.registers.set_zero_flag(true);
     let mut cycles = cpu.execute_next();
     assert_eq!(cycles, 3);
-    assert_eq!(cpu.registers.get_pc(), ((start_address + test_value as i16 + program.len() as i16)) as u16);
+    assert_eq!(cpu.instruction_pc(), start_address.wrapping_add_signed(test_value as i16 + program.len() as i16));
 
-    cpu = CPU::new(Rc::clone(&memory_ref));
+    cpu = TestCpu::new();
     assert_eq!(cycles, 3);
-    cpu.load(&program);
-    cpu.write_memory(0x0350, program[0]);
-    cpu.write_memory(0x0351, program[1]);
-    cpu.registers.set_pc(0x0350);
+    cpu.load_at(start_address, &program);
     cpu.registers.set_zero_flag(false);
     cycles = cpu.execute_next();
     assert_eq!(cycles, 2);
-    assert_eq!(cpu.registers.get_pc(), 0x352);
+    assert_eq!(cpu.instruction_pc(), start_address + program.len() as u16);
 }
 
 #[test]
 fn test_0x29_add_hl_hl() {
     let mut test_value: u16 = 0x1029;
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
     let program: Vec<u8> = vec![0x29];
     cpu.load(&program);
     cpu.registers.set_hl(test_value);
@@ -2646,8 +2538,7 @@ fn test_0x29_add_hl_hl() {
 fn test_0x2a_ld_a__hli_() {
     let mut test_value_1: u8 = 0xBD;
     let mut test_address_1: u16 = WRAM_ADDRESS as u16 + 0x0128;
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
     let program: Vec<u8> = vec![0x2A];
     cpu.load(&program);
     cpu.registers.set_hl(test_address_1);
@@ -2669,8 +2560,7 @@ test_ld_r8_imm8!(0x2E, test_0x2e_ld_l_imm8, set_l, get_l);
 fn test_0x2f_cpl() {
     //No Flags
     let test_value_1: u8 = 0xD4;
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
     let program_1: Vec<u8> = vec![0x2F];
     cpu.load(&program_1);
     let register_copy = cpu.registers;
@@ -2688,43 +2578,32 @@ fn test_0x2f_cpl() {
 #[test]
 fn test_0x30_jr_nc_e8() {
     let mut test_value: i8 = -50;
-    let mut start_address: i16 = 0x0350;
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut start_address: u16 = WRAM_ADDRESS as u16 + 0x0350;
+    let mut cpu = TestCpu::new();
     let mut program: Vec<u8> = vec![0x30, test_value as u8];
-    cpu.load(&program);
-    cpu.write_memory(0x0350, program[0]);
-    cpu.write_memory(0x0351, program[1]);
-    cpu.registers.set_pc(0x0350);
+    cpu.load_at(start_address, &program);
     cpu.registers.set_carry_flag(false);
     let mut cycles = cpu.execute_next();
     assert_eq!(cycles, 3);
-    assert_eq!(cpu.registers.get_pc(), ((start_address + test_value as i16 + program.len() as i16)) as u16);
+    assert_eq!(cpu.instruction_pc(), start_address.wrapping_add_signed(test_value as i16 + program.len() as i16));
 
     let mut test_value: i8 = 11;
-    let mut start_address: i16 = 0x0350;
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut start_address: u16 = WRAM_ADDRESS as u16 + 0x0350;
+    let mut cpu = TestCpu::new();
     let mut program: Vec<u8> = vec![0x30, test_value as u8];
-    cpu.load(&program);
-    cpu.write_memory(0x0350, program[0]);
-    cpu.write_memory(0x0351, program[1]);
-    cpu.registers.set_pc(0x0350);
+    cpu.load_at(start_address, &program);
     cpu.registers.set_carry_flag(false);
     let mut cycles = cpu.execute_next();
     assert_eq!(cycles, 3);
-    assert_eq!(cpu.registers.get_pc(), ((start_address + test_value as i16 + program.len() as i16)) as u16);
+    assert_eq!(cpu.instruction_pc(), start_address.wrapping_add_signed(test_value as i16 + program.len() as i16));
 
-    cpu = CPU::new(Rc::clone(&memory_ref));
+    cpu = TestCpu::new();
     assert_eq!(cycles, 3);
-    cpu.load(&program);
-    cpu.write_memory(0x0350, program[0]);
-    cpu.write_memory(0x0351, program[1]);
-    cpu.registers.set_pc(0x0350);
+    cpu.load_at(start_address, &program);
     cpu.registers.set_carry_flag(true);
     cycles = cpu.execute_next();
     assert_eq!(cycles, 2);
-    assert_eq!(cpu.registers.get_pc(), 0x352);
+    assert_eq!(cpu.instruction_pc(), start_address + program.len() as u16);
 }
 
 test_ld_r16_imm16!(0x31, test_0x31_ld_sp_imm16, set_sp, get_sp);
@@ -2732,9 +2611,8 @@ test_ld_r16_imm16!(0x31, test_0x31_ld_sp_imm16, set_sp, get_sp);
 #[test]
 fn test_0x32_ld__hld__a() {
     let test_value: u8 = 0xF4;
-    let test_address: u16 = memory::WRAM_ADDRESS as u16 + 0x0500;
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let test_address: u16 = WRAM_ADDRESS as u16 + 0x0500;
+    let mut cpu = TestCpu::new();
     let program: Vec<u8> = vec![0x32];
     cpu.load(&program);
     cpu.registers.set_a(test_value);
@@ -2754,8 +2632,7 @@ fn test_0x34_inc__hl_() {
     // No Flags
     let test_value_1: u8 = 0b1111_0100;
     let test_address = WRAM_ADDRESS as u16 + 0x50;
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
     let program_1: Vec<u8> = vec![0x34];
     cpu.load(&program_1);
     cpu.registers.set_hl(test_address);
@@ -2769,7 +2646,7 @@ fn test_0x34_inc__hl_() {
 
     // Flags Z/H
     let test_value_2: u8 = 0xFF;
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
     cpu.load(&program_1);
     cpu.registers.set_hl(test_address);
     cpu.write_memory(test_address, test_value_2);
@@ -2781,7 +2658,7 @@ fn test_0x34_inc__hl_() {
 
     // Flags H
     let test_value_3: u8 = 0x0F;
-    cpu = CPU::new(Rc::clone(&memory_ref));
+    cpu = TestCpu::new();
     cpu.load(&program_1);
     cpu.registers.set_hl(test_address);
     cpu.write_memory(test_address, test_value_3);
@@ -2797,8 +2674,7 @@ fn test_0x35_dec__hl_() {
     // No Flags
     let test_value_1: u8 = 0b1111_0100;
     let test_address = WRAM_ADDRESS as u16 + 0x50;
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
     let program_1: Vec<u8> = vec![0x35];
     cpu.load(&program_1);
     cpu.registers.set_hl(test_address);
@@ -2812,7 +2688,7 @@ fn test_0x35_dec__hl_() {
 
     // Flags Z
     let test_value_2: u8 = 0x01;
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
     cpu.load(&program_1);
     cpu.registers.set_hl(test_address);
     cpu.write_memory(test_address, test_value_2);
@@ -2824,7 +2700,7 @@ fn test_0x35_dec__hl_() {
 
     // Flags H
     let test_value_3: u8 = 0xF0;
-    cpu = CPU::new(Rc::clone(&memory_ref));
+    cpu = TestCpu::new();
     cpu.load(&program_1);
     cpu.registers.set_hl(test_address);
     cpu.write_memory(test_address, test_value_3);
@@ -2836,7 +2712,7 @@ fn test_0x35_dec__hl_() {
 
     // Test Underflow
     let test_value_4: u8 = 0x00;
-    cpu = CPU::new(Rc::clone(&memory_ref));
+    cpu = TestCpu::new();
     cpu.load(&program_1);
     cpu.registers.set_hl(test_address);
     cpu.write_memory(test_address, test_value_4);
@@ -2852,8 +2728,7 @@ fn test_0x36_ld__hl__imm8() {
     //No Flags
     let test_value_1: u8 = 0xCD;
     let test_address: u16 = WRAM_ADDRESS as u16 + 0x88;
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
     let program_1: Vec<u8> = vec![0x36, test_value_1];
     cpu.load(&program_1);
     let register_copy = cpu.registers;
@@ -2874,8 +2749,7 @@ fn test_0x36_ld__hl__imm8() {
 // SCF = Set Carry Flag
 fn test_0x37_scf() {
     //No Flags
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
     let program_1: Vec<u8> = vec![0x37];
     cpu.load(&program_1);
     cpu.registers.set_carry_flag(false);
@@ -2890,43 +2764,32 @@ fn test_0x37_scf() {
 #[test]
 fn test_0x38_jr_c_e8() {
     let mut test_value: i8 = -50;
-    let mut start_address: i16 = 0x0350;
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut start_address: u16 = WRAM_ADDRESS as u16 + 0x0350;
+    let mut cpu = TestCpu::new();
     let mut program: Vec<u8> = vec![0x38, test_value as u8];
-    cpu.load(&program);
-    cpu.write_memory(0x0350, program[0]);
-    cpu.write_memory(0x0351, program[1]);
-    cpu.registers.set_pc(0x0350);
+    cpu.load_at(start_address, &program);
     cpu.registers.set_carry_flag(true);
     let mut cycles = cpu.execute_next();
     assert_eq!(cycles, 3);
-    assert_eq!(cpu.registers.get_pc(), ((start_address + test_value as i16 + program.len() as i16)) as u16);
+    assert_eq!(cpu.instruction_pc(), start_address.wrapping_add_signed(test_value as i16 + program.len() as i16));
 
     let mut test_value: i8 = 11;
-    let mut start_address: i16 = 0x0350;
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut start_address: u16 = WRAM_ADDRESS as u16 + 0x0350;
+    let mut cpu = TestCpu::new();
     let mut program: Vec<u8> = vec![0x38, test_value as u8];
-    cpu.load(&program);
-    cpu.write_memory(0x0350, program[0]);
-    cpu.write_memory(0x0351, program[1]);
-    cpu.registers.set_pc(0x0350);
+    cpu.load_at(start_address, &program);
     cpu.registers.set_carry_flag(true);
     let mut cycles = cpu.execute_next();
     assert_eq!(cycles, 3);
-    assert_eq!(cpu.registers.get_pc(), ((start_address + test_value as i16 + program.len() as i16)) as u16);
+    assert_eq!(cpu.instruction_pc(), start_address.wrapping_add_signed(test_value as i16 + program.len() as i16));
 
-    cpu = CPU::new(Rc::clone(&memory_ref));
+    cpu = TestCpu::new();
     assert_eq!(cycles, 3);
-    cpu.load(&program);
-    cpu.write_memory(0x0350, program[0]);
-    cpu.write_memory(0x0351, program[1]);
-    cpu.registers.set_pc(0x0350);
+    cpu.load_at(start_address, &program);
     cpu.registers.set_carry_flag(false);
     cycles = cpu.execute_next();
     assert_eq!(cycles, 2);
-    assert_eq!(cpu.registers.get_pc(), 0x352);
+    assert_eq!(cpu.instruction_pc(), start_address + program.len() as u16);
 }
 
 test_add_r16_r16!(0x39, test_0x39_add_hl_sp, set_hl, get_hl, set_sp, get_sp);
@@ -2935,8 +2798,7 @@ test_add_r16_r16!(0x39, test_0x39_add_hl_sp, set_hl, get_hl, set_sp, get_sp);
 fn test_0x3a_ld_a__hld_() {
     let mut test_value_1: u8 = 0xBD;
     let mut test_address_1: u16 = WRAM_ADDRESS as u16 + 0x0128;
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
     let program: Vec<u8> = vec![0x3A];
     cpu.load(&program);
     cpu.registers.set_hl(test_address_1);
@@ -2957,8 +2819,7 @@ test_ld_r8_imm8!(0x3E, test_0x3e_ld_a_imm8, set_a, get_a);
 #[test]
 fn test_0x3f_ccf() {
     // CCF = Complement Carry Flag
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
     let program_1: Vec<u8> = vec![0x3F, 0x3F];
     cpu.load(&program_1);
     let register_copy = cpu.registers;
@@ -3031,8 +2892,7 @@ test_ld_r8!(0x65, test_0x65_ld_h_l, set_h, get_h, set_l, get_l);
 fn test_0x66_ld_h__hl_() {
     let test_value_1: u8 = 0xFF;
     let test_address_1: u16 = WRAM_ADDRESS as u16 + 0x99;
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
     let program_1: Vec<u8> = vec![0x66];
     cpu.load(&program_1);
     let register_copy = cpu.registers;
@@ -3061,8 +2921,7 @@ test_ld_r8!(0x6D, test_0x6d_ld_l_l, set_l, get_l);
 fn test_0x6e_ld_l__hl_() {
     let test_value_1: u8 = 0xFF;
     let test_address_1: u16 = WRAM_ADDRESS as u16 + 0x99;
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
     let program_1: Vec<u8> = vec![0x6E];
     cpu.load(&program_1);
     let register_copy = cpu.registers;
@@ -3090,8 +2949,7 @@ test_ld_ar16_r8!(0x73, test_0x73_ld__hl__e, set_hl, get_hl, set_e, get_e);
 fn test_0x74_ld__hl__h() {
     let test_address_1: u16 = WRAM_ADDRESS as u16 + 0x99;
     let expected_value = test_address_1.wrapping_shr(8) as u8;
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
     let program_1: Vec<u8> = vec![0x74];
     cpu.load(&program_1);
     let register_copy = cpu.registers;
@@ -3116,8 +2974,7 @@ fn test_0x74_ld__hl__h() {
 fn test_0x75_ld__hl__l() {
     let test_address_1: u16 = WRAM_ADDRESS as u16 + 0x99;
     let expected_value = (test_address_1 & 0xFF) as u8;
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
     let program_1: Vec<u8> = vec![0x75];
     cpu.load(&program_1);
     let register_copy = cpu.registers;
@@ -3165,8 +3022,7 @@ fn test_0x86_add_a__hl_() {
     let mut test_value_2: u8 = 0x16;
     let test_address: u16 = WRAM_ADDRESS as u16 + 0xDD;
     let mut expected_value: u8 = test_value_1.wrapping_add(test_value_2);
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
     let program_1: Vec<u8> = vec![0x86];
     cpu.load(&program_1);
     cpu.registers.set_a(test_value_1);
@@ -3185,7 +3041,7 @@ fn test_0x86_add_a__hl_() {
     test_value_1 = 0xF0;
     test_value_2 = 0x10;
     expected_value = 0x00;
-    cpu = CPU::new(Rc::clone(&memory_ref));
+    cpu = TestCpu::new();
     cpu.load(&program_1);
     cpu.registers.set_a(test_value_1);
     cpu.registers.set_hl(test_address);
@@ -3204,7 +3060,7 @@ fn test_0x86_add_a__hl_() {
     test_value_1 = 0x0F;
     test_value_2 = 0x01;
     expected_value = 0x10;
-    cpu = CPU::new(Rc::clone(&memory_ref));
+    cpu = TestCpu::new();
     cpu.load(&program_1);
     cpu.registers.set_a(test_value_1);
     cpu.registers.set_hl(test_address);
@@ -3223,7 +3079,7 @@ fn test_0x86_add_a__hl_() {
     test_value_1 = 0xFF;
     test_value_2 = 0x01;
     expected_value = 0x00;
-    cpu = CPU::new(Rc::clone(&memory_ref));
+    cpu = TestCpu::new();
     cpu.load(&program_1);
     cpu.registers.set_a(test_value_1);
     cpu.registers.set_hl(test_address);
@@ -3243,8 +3099,7 @@ fn test_0x86_add_a__hl_() {
 fn test_0x87_add_a_a() {
     let mut test_value_1: u8 = 0x24;
     let mut expected_value: u8 = test_value_1.wrapping_add(test_value_1);
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
     let program_1: Vec<u8> = vec![0x87];
     cpu.load(&program_1);
     cpu.registers.set_a(test_value_1);
@@ -3259,7 +3114,7 @@ fn test_0x87_add_a_a() {
 
     test_value_1 = 0x80;
     expected_value = 0x00;
-    cpu = CPU::new(Rc::clone(&memory_ref));
+    cpu = TestCpu::new();
     cpu.load(&program_1);
     cpu.registers.set_a(test_value_1);
     cycles = cpu.execute_next();
@@ -3273,7 +3128,7 @@ fn test_0x87_add_a_a() {
 
     test_value_1 = 0x08;
     expected_value = 0x10;
-    cpu = CPU::new(Rc::clone(&memory_ref));
+    cpu = TestCpu::new();
     cpu.load(&program_1);
     cpu.registers.set_a(test_value_1);
     cycles = cpu.execute_next();
@@ -3287,7 +3142,7 @@ fn test_0x87_add_a_a() {
 
     test_value_1 = 0xFF;
     expected_value = 0xFE;
-    cpu = CPU::new(Rc::clone(&memory_ref));
+    cpu = TestCpu::new();
     cpu.load(&program_1);
     cpu.registers.set_a(test_value_1);
     cycles = cpu.execute_next();
@@ -3320,8 +3175,7 @@ fn test_0x8e_adc_a__hl___c_off() {
     let mut test_value_2: u8 = 0x16;
     let test_address: u16 = WRAM_ADDRESS as u16 + 0xAA;
     let mut expected_value: u8 = test_value_1.wrapping_add(test_value_2);
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
     let program_1: Vec<u8> = vec![0x8E];
     cpu.load(&program_1);
     cpu.registers.set_a(test_value_1);
@@ -3341,7 +3195,7 @@ fn test_0x8e_adc_a__hl___c_off() {
     test_value_1 = 0xF0;
     test_value_2 = 0x10;
     expected_value = 0x00;
-    cpu = CPU::new(Rc::clone(&memory_ref));
+    cpu = TestCpu::new();
     cpu.load(&program_1);
     cpu.registers.set_a(test_value_1);
     cpu.registers.set_hl(test_address);
@@ -3360,7 +3214,7 @@ fn test_0x8e_adc_a__hl___c_off() {
     test_value_1 = 0x0F;
     test_value_2 = 0x01;
     expected_value = 0x10;
-    cpu = CPU::new(Rc::clone(&memory_ref));
+    cpu = TestCpu::new();
     cpu.load(&program_1);
     cpu.registers.set_a(test_value_1);
     cpu.registers.set_hl(test_address);
@@ -3379,7 +3233,7 @@ fn test_0x8e_adc_a__hl___c_off() {
     test_value_1 = 0xFF;
     test_value_2 = 0x01;
     expected_value = 0x00;
-    cpu = CPU::new(Rc::clone(&memory_ref));
+    cpu = TestCpu::new();
     cpu.load(&program_1);
     cpu.registers.set_a(test_value_1);
     cpu.registers.set_hl(test_address);
@@ -3402,8 +3256,7 @@ fn test_0x8e_adc_a__hl___c_on() {
     let mut test_value_2: u8 = 0x16;
     let test_address: u16 = WRAM_ADDRESS as u16 + 0xAA;
     let mut expected_value: u8 = test_value_1.wrapping_add(test_value_2 + 1);
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
     let program_1: Vec<u8> = vec![0x8E];
     cpu.load(&program_1);
     cpu.registers.set_a(test_value_1);
@@ -3423,7 +3276,7 @@ fn test_0x8e_adc_a__hl___c_on() {
     test_value_1 = 0xF0;
     test_value_2 = 0x0F;
     expected_value = 0x00;
-    cpu = CPU::new(Rc::clone(&memory_ref));
+    cpu = TestCpu::new();
     cpu.load(&program_1);
     cpu.registers.set_a(test_value_1);
     cpu.registers.set_hl(test_address);
@@ -3442,7 +3295,7 @@ fn test_0x8e_adc_a__hl___c_on() {
     test_value_1 = 0x0D;
     test_value_2 = 0x02;
     expected_value = 0x10;
-    cpu = CPU::new(Rc::clone(&memory_ref));
+    cpu = TestCpu::new();
     cpu.load(&program_1);
     cpu.registers.set_a(test_value_1);
     cpu.registers.set_hl(test_address);
@@ -3461,7 +3314,7 @@ fn test_0x8e_adc_a__hl___c_on() {
     test_value_1 = 0xFE;
     test_value_2 = 0x01;
     expected_value = 0x00;
-    cpu = CPU::new(Rc::clone(&memory_ref));
+    cpu = TestCpu::new();
     cpu.load(&program_1);
     cpu.registers.set_a(test_value_1);
     cpu.registers.set_hl(test_address);
@@ -3482,8 +3335,7 @@ fn test_0x8e_adc_a__hl___c_on() {
 fn test_0x8f_adc_a_a__c_off() {
     let mut test_value_1: u8 = 0x16;
     let mut expected_value: u8 = test_value_1.wrapping_add(test_value_1);
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
     let program_1: Vec<u8> = vec![0x8F];
     cpu.load(&program_1);
     cpu.registers.set_a(test_value_1);
@@ -3499,7 +3351,7 @@ fn test_0x8f_adc_a_a__c_off() {
 
     test_value_1 = 0x80;
     expected_value = 0x00;
-    cpu = CPU::new(Rc::clone(&memory_ref));
+    cpu = TestCpu::new();
     cpu.load(&program_1);
     cpu.registers.set_a(test_value_1);
     cpu.registers.set_carry_flag(false);
@@ -3514,7 +3366,7 @@ fn test_0x8f_adc_a_a__c_off() {
 
     test_value_1 = 0x08;
     expected_value = 0x10;
-    cpu = CPU::new(Rc::clone(&memory_ref));
+    cpu = TestCpu::new();
     cpu.load(&program_1);
     cpu.registers.set_a(test_value_1);
     cpu.registers.set_carry_flag(false);
@@ -3529,7 +3381,7 @@ fn test_0x8f_adc_a_a__c_off() {
 
     test_value_1 = 0x88;
     expected_value = 0x10;
-    cpu = CPU::new(Rc::clone(&memory_ref));
+    cpu = TestCpu::new();
     cpu.load(&program_1);
     cpu.registers.set_a(test_value_1);
     cpu.registers.set_carry_flag(false);
@@ -3547,8 +3399,7 @@ fn test_0x8f_adc_a_a__c_off() {
 fn test_0x8f_adc_a_a__c_on() {
     let mut test_value_1: u8 = 0x16;
     let mut expected_value: u8 = test_value_1.wrapping_add(test_value_1 + 1);
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
     let program_1: Vec<u8> = vec![0x8F];
     cpu.load(&program_1);
     cpu.registers.set_a(test_value_1);
@@ -3564,7 +3415,7 @@ fn test_0x8f_adc_a_a__c_on() {
 
     test_value_1 = 0x80;
     expected_value = 0x01;
-    cpu = CPU::new(Rc::clone(&memory_ref));
+    cpu = TestCpu::new();
     cpu.load(&program_1);
     cpu.registers.set_a(test_value_1);
     cpu.registers.set_carry_flag(true);
@@ -3579,7 +3430,7 @@ fn test_0x8f_adc_a_a__c_on() {
 
     test_value_1 = 0x08;
     expected_value = 0x11;
-    cpu = CPU::new(Rc::clone(&memory_ref));
+    cpu = TestCpu::new();
     cpu.load(&program_1);
     cpu.registers.set_a(test_value_1);
     cpu.registers.set_carry_flag(true);
@@ -3594,7 +3445,7 @@ fn test_0x8f_adc_a_a__c_on() {
 
     test_value_1 = 0x88;
     expected_value = 0x11;
-    cpu = CPU::new(Rc::clone(&memory_ref));
+    cpu = TestCpu::new();
     cpu.load(&program_1);
     cpu.registers.set_a(test_value_1);
     cpu.registers.set_carry_flag(true);
@@ -3621,8 +3472,7 @@ fn test_0x96_sub_a__hl_() {
     let mut test_value_2: u8 = 0x11;
     let test_address: u16 = WRAM_ADDRESS as u16 + 0x55;
     let mut expected_value: u8 = test_value_1.wrapping_sub(test_value_2);
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
     let program_1: Vec<u8> = vec![0x96];
     cpu.load(&program_1);
     cpu.registers.set_a(test_value_1);
@@ -3642,7 +3492,7 @@ fn test_0x96_sub_a__hl_() {
     test_value_1 = 0xF0;
     test_value_2 = 0xF0;
     expected_value = 0x00;
-    cpu = CPU::new(Rc::clone(&memory_ref));
+    cpu = TestCpu::new();
     cpu.load(&program_1);
     cpu.registers.set_a(test_value_1);
     cpu.registers.set_hl(test_address);
@@ -3661,7 +3511,7 @@ fn test_0x96_sub_a__hl_() {
     test_value_1 = 0x10;
     test_value_2 = 0x01;
     expected_value = 0x0F;
-    cpu = CPU::new(Rc::clone(&memory_ref));
+    cpu = TestCpu::new();
     cpu.load(&program_1);
     cpu.registers.set_a(test_value_1);
     cpu.registers.set_l(test_value_2);
@@ -3681,7 +3531,7 @@ fn test_0x96_sub_a__hl_() {
     test_value_1 = 0x10;
     test_value_2 = 0x20;
     expected_value = test_value_1.wrapping_sub(test_value_2);
-    cpu = CPU::new(Rc::clone(&memory_ref));
+    cpu = TestCpu::new();
     cpu.load(&program_1);
     cpu.registers.set_a(test_value_1);
     cpu.registers.set_hl(test_address);
@@ -3700,7 +3550,7 @@ fn test_0x96_sub_a__hl_() {
     test_value_1 = 0x00;
     test_value_2 = 0x01;
     expected_value = 0xFF;
-    cpu = CPU::new(Rc::clone(&memory_ref));
+    cpu = TestCpu::new();
     cpu.load(&program_1);
     cpu.registers.set_a(test_value_1);
     cpu.registers.set_hl(test_address);
@@ -3721,8 +3571,7 @@ fn test_0x96_sub_a__hl_() {
 fn test_0x97_sub_a_a() {
     let mut test_value_1: u8 = 0xC4;
     let mut expected_value: u8 = 0;
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
     let program_1: Vec<u8> = vec![0x97];
     cpu.load(&program_1);
     cpu.registers.set_a(test_value_1);
@@ -3737,7 +3586,7 @@ fn test_0x97_sub_a_a() {
 
     test_value_1 = 0xF0;
     expected_value = 0x00;
-    cpu = CPU::new(Rc::clone(&memory_ref));
+    cpu = TestCpu::new();
     cpu.load(&program_1);
     cpu.registers.set_a(test_value_1);
     cycles = cpu.execute_next();
@@ -3769,8 +3618,7 @@ fn test_0x9e_sbc_a__hl___c_off() {
     let mut test_value_2: u8 = 0x12;
     let test_address: u16 = WRAM_ADDRESS as u16 + 0x11;
     let mut expected_value: u8 = test_value_1.wrapping_sub(test_value_2);
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
     let program_1: Vec<u8> = vec![0x9E];
     cpu.load(&program_1);
     cpu.registers.set_a(test_value_1);
@@ -3790,7 +3638,7 @@ fn test_0x9e_sbc_a__hl___c_off() {
     test_value_1 = 0x0F;
     test_value_2 = 0x0F;
     expected_value = 0x00;
-    cpu = CPU::new(Rc::clone(&memory_ref));
+    cpu = TestCpu::new();
     cpu.load(&program_1);
     cpu.registers.set_a(test_value_1);
     cpu.registers.set_hl(test_address);
@@ -3809,7 +3657,7 @@ fn test_0x9e_sbc_a__hl___c_off() {
     test_value_1 = 0xF0;
     test_value_2 = 0x01;
     expected_value = 0xEF;
-    cpu = CPU::new(Rc::clone(&memory_ref));
+    cpu = TestCpu::new();
     cpu.load(&program_1);
     cpu.registers.set_a(test_value_1);
     cpu.registers.set_hl(test_address);
@@ -3828,7 +3676,7 @@ fn test_0x9e_sbc_a__hl___c_off() {
     test_value_1 = 0x00;
     test_value_2 = 0x01;
     expected_value = 0xFF;
-    cpu = CPU::new(Rc::clone(&memory_ref));
+    cpu = TestCpu::new();
     cpu.load(&program_1);
     cpu.registers.set_a(test_value_1);
     cpu.registers.set_hl(test_address);
@@ -3851,8 +3699,7 @@ fn test_0x9e_sbc_a__hl___c_on() {
     let mut test_value_2: u8 = 0x13;
     let test_address: u16 = WRAM_ADDRESS as u16 + 0x11;
     let mut expected_value: u8 = test_value_1.wrapping_sub(test_value_2 + 1);
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
     let program_1: Vec<u8> = vec![0x9E];
     cpu.load(&program_1);
     cpu.registers.set_a(test_value_1);
@@ -3872,7 +3719,7 @@ fn test_0x9e_sbc_a__hl___c_on() {
     test_value_1 = 0x10;
     test_value_2 = 0x0E;
     expected_value = test_value_1.wrapping_sub(test_value_2 + 1);
-    cpu = CPU::new(Rc::clone(&memory_ref));
+    cpu = TestCpu::new();
     cpu.load(&program_1);
     cpu.registers.set_a(test_value_1);
     cpu.registers.set_hl(test_address);
@@ -3891,7 +3738,7 @@ fn test_0x9e_sbc_a__hl___c_on() {
     test_value_1 = 0x10;
     test_value_2 = 0x0F;
     expected_value = 0x00;
-    cpu = CPU::new(Rc::clone(&memory_ref));
+    cpu = TestCpu::new();
     cpu.load(&program_1);
     cpu.registers.set_a(test_value_1);
     cpu.registers.set_hl(test_address);
@@ -3910,7 +3757,7 @@ fn test_0x9e_sbc_a__hl___c_on() {
     test_value_1 = 0x00;
     test_value_2 = 0x00;
     expected_value = 0xFF;
-    cpu = CPU::new(Rc::clone(&memory_ref));
+    cpu = TestCpu::new();
     cpu.load(&program_1);
     cpu.registers.set_a(test_value_1);
     cpu.registers.set_hl(test_address);
@@ -3931,8 +3778,7 @@ fn test_0x9e_sbc_a__hl___c_on() {
 fn test_0x9f_sbc_a_a__c_off() {
     let mut test_value_1: u8 = 0xC4;
     let mut expected_value: u8 = 0;
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
     let program_1: Vec<u8> = vec![0x9F];
     cpu.load(&program_1);
     cpu.registers.set_a(test_value_1);
@@ -3948,7 +3794,7 @@ fn test_0x9f_sbc_a_a__c_off() {
 
     test_value_1 = 0xF0;
     expected_value = 0x00;
-    cpu = CPU::new(Rc::clone(&memory_ref));
+    cpu = TestCpu::new();
     cpu.load(&program_1);
     cpu.registers.set_a(test_value_1);
     cpu.registers.set_carry_flag(false);
@@ -3966,8 +3812,7 @@ fn test_0x9f_sbc_a_a__c_off() {
 fn test_0x9f_sbc_a_a__c_on() {
     let mut test_value_1: u8 = 0xC4;
     let mut expected_value: u8 = 0xFF;
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
     let program_1: Vec<u8> = vec![0x9F];
     cpu.load(&program_1);
     cpu.registers.set_a(test_value_1);
@@ -3983,7 +3828,7 @@ fn test_0x9f_sbc_a_a__c_on() {
 
     test_value_1 = 0xF0;
     expected_value = 0xFF;
-    cpu = CPU::new(Rc::clone(&memory_ref));
+    cpu = TestCpu::new();
     cpu.load(&program_1);
     cpu.registers.set_a(test_value_1);
     cpu.registers.set_carry_flag(true);
@@ -4068,8 +3913,7 @@ fn test_0xd6_sub_a_imm8() {
     let mut test_value_1: u8 = 0xC4;
     let mut test_value_2: u8 = 0x11;
     let mut expected_value: u8 = test_value_1.wrapping_sub(test_value_2);
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
     let mut program_1: Vec<u8> = vec![0xD6, test_value_2];
     cpu.load(&program_1);
     cpu.registers.set_a(test_value_1);
@@ -4084,7 +3928,7 @@ fn test_0xd6_sub_a_imm8() {
     test_value_2 = 0xF0;
     expected_value = 0x00;
     program_1[1] = test_value_2;
-    cpu = CPU::new(Rc::clone(&memory_ref));
+    cpu = TestCpu::new();
     cpu.load(&program_1);
     cpu.registers.set_a(test_value_1);
     cpu.registers.set_b(test_value_2);
@@ -4098,7 +3942,7 @@ fn test_0xd6_sub_a_imm8() {
     test_value_2 = 0x01;
     expected_value = 0x0F;
     program_1[1] = test_value_2;
-    cpu = CPU::new(Rc::clone(&memory_ref));
+    cpu = TestCpu::new();
     cpu.load(&program_1);
     cpu.registers.set_a(test_value_1);
     cycles = cpu.execute_next();
@@ -4111,7 +3955,7 @@ fn test_0xd6_sub_a_imm8() {
     test_value_2 = 0x20;
     expected_value = test_value_1.wrapping_sub(test_value_2);
     program_1[1] = test_value_2;
-    cpu = CPU::new(Rc::clone(&memory_ref));
+    cpu = TestCpu::new();
     cpu.load(&program_1);
     cpu.registers.set_a(test_value_1);
     cycles = cpu.execute_next();
@@ -4124,7 +3968,7 @@ fn test_0xd6_sub_a_imm8() {
     test_value_2 = 0x01;
     expected_value = 0xFF;
     program_1[1] = test_value_2;
-    cpu = CPU::new(Rc::clone(&memory_ref));
+    cpu = TestCpu::new();
     cpu.load(&program_1);
     cpu.registers.set_a(test_value_1);
     cycles = cpu.execute_next();
@@ -4155,8 +3999,7 @@ fn test_0xe8_add_sp_e8() {
     let mut test_value_2: i8 = 0x12;
     let mut test_value_2_abs: u16 = 0x12;
     let mut expected_value: u16 = test_value_1.wrapping_add(test_value_2_abs);
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
     let mut program_1: Vec<u8> = vec![0xE8, test_value_2 as u8];
     cpu.load(&program_1);
     cpu.registers.set_sp(test_value_1);
@@ -4169,12 +4012,11 @@ fn test_0xe8_add_sp_e8() {
     assert_eq!(cpu.registers.get_half_carry_flag(), false);
     assert_eq!(cpu.registers.get_carry_flag(), false);
 
-    test_value_1 = 0x0FFF;
+    test_value_1 = 0x0F0F;
     test_value_2 = 0x01;
     test_value_2_abs = 0x01;
     expected_value = test_value_1.wrapping_add(test_value_2_abs);
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
     program_1[1] = test_value_2 as u8;
     cpu.load(&program_1);
     cpu.registers.set_sp(test_value_1);
@@ -4191,8 +4033,7 @@ fn test_0xe8_add_sp_e8() {
     test_value_2 = 0x01;
     test_value_2_abs = 0x01;
     expected_value = 0x0;
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
     program_1[1] = test_value_2 as u8;
     cpu.load(&program_1);
     cpu.registers.set_sp(test_value_1);
@@ -4205,12 +4046,11 @@ fn test_0xe8_add_sp_e8() {
     assert_eq!(cpu.registers.get_half_carry_flag(), true);
     assert_eq!(cpu.registers.get_carry_flag(), true);
 
-    test_value_1 = 0xDDFF;
+    test_value_1 = 0xDD7F;
     test_value_2 = -128;
     test_value_2_abs = 128;
     expected_value = test_value_1.wrapping_sub(test_value_2_abs);
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
     program_1[1] = test_value_2 as u8;
     cpu.load(&program_1);
     cpu.registers.set_sp(test_value_1);
@@ -4223,12 +4063,11 @@ fn test_0xe8_add_sp_e8() {
     assert_eq!(cpu.registers.get_half_carry_flag(), false);
     assert_eq!(cpu.registers.get_carry_flag(), false);
 
-    test_value_1 = 0xD000;
-    test_value_2 = -1;
-    test_value_2_abs = 1;
+    test_value_1 = 0xD00F;
+    test_value_2 = -127;
+    test_value_2_abs = 127;
     expected_value = test_value_1.wrapping_sub(test_value_2_abs);
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
     program_1[1] = test_value_2 as u8;
     cpu.load(&program_1);
     cpu.registers.set_sp(test_value_1);
@@ -4241,19 +4080,18 @@ fn test_0xe8_add_sp_e8() {
     assert_eq!(cpu.registers.get_half_carry_flag(), true);
     assert_eq!(cpu.registers.get_carry_flag(), false);
 
-    test_value_1 = 0x0000;
+    test_value_1 = 0x0001;
     test_value_2 = -1;
     test_value_2_abs = 1;
     expected_value = test_value_1.wrapping_sub(test_value_2_abs);
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
     program_1[1] = test_value_2 as u8;
     cpu.load(&program_1);
     cpu.registers.set_sp(test_value_1);
     let mut cycles = cpu.execute_next();
     assert_eq!(cycles, 4);
     assert_eq!(cpu.registers.get_sp(), expected_value);
-    // H Flags
+    // H/C Flags
     assert_eq!(cpu.registers.get_zero_flag(), false);
     assert_eq!(cpu.registers.get_negative_flag(), false);
     assert_eq!(cpu.registers.get_half_carry_flag(), true);
@@ -4270,8 +4108,7 @@ test_pop!(0xF1, test_0xf1_pop_af, set_af, get_af);
 test_ldh_r8_r8!(0xF2, test_0xf2_ldh_a__c_, set_a, get_a, set_c, get_c, true);
 #[test]
 fn test_0xf3_di() {
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
     let mut program_1: Vec<u8> = vec![0xF3];
     cpu.load(&program_1);
     cpu.ime = true;
@@ -4288,8 +4125,7 @@ fn test_0xf8_ld_hl_sp_e8() {
     let mut test_value_2: i8 = 0x12;
     let mut test_value_2_abs: u16 = 0x12;
     let mut expected_value: u16 = test_value_1.wrapping_add(test_value_2_abs);
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
     let mut program_1: Vec<u8> = vec![0xF8, test_value_2 as u8];
     cpu.load(&program_1);
     cpu.registers.set_sp(test_value_1);
@@ -4302,12 +4138,11 @@ fn test_0xf8_ld_hl_sp_e8() {
     assert_eq!(cpu.registers.get_half_carry_flag(), false);
     assert_eq!(cpu.registers.get_carry_flag(), false);
 
-    test_value_1 = 0x0FFF;
+    test_value_1 = 0x0F0F;
     test_value_2 = 0x01;
     test_value_2_abs = 0x01;
     expected_value = test_value_1.wrapping_add(test_value_2_abs);
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
     program_1[1] = test_value_2 as u8;
     cpu.load(&program_1);
     cpu.registers.set_sp(test_value_1);
@@ -4324,8 +4159,7 @@ fn test_0xf8_ld_hl_sp_e8() {
     test_value_2 = 0x01;
     test_value_2_abs = 0x01;
     expected_value = 0x0;
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
     program_1[1] = test_value_2 as u8;
     cpu.load(&program_1);
     cpu.registers.set_sp(test_value_1);
@@ -4338,12 +4172,11 @@ fn test_0xf8_ld_hl_sp_e8() {
     assert_eq!(cpu.registers.get_half_carry_flag(), true);
     assert_eq!(cpu.registers.get_carry_flag(), true);
 
-    test_value_1 = 0xDDFF;
+    test_value_1 = 0xDD7F;
     test_value_2 = -128;
     test_value_2_abs = 128;
     expected_value = test_value_1.wrapping_sub(test_value_2_abs);
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
     program_1[1] = test_value_2 as u8;
     cpu.load(&program_1);
     cpu.registers.set_sp(test_value_1);
@@ -4356,12 +4189,11 @@ fn test_0xf8_ld_hl_sp_e8() {
     assert_eq!(cpu.registers.get_half_carry_flag(), false);
     assert_eq!(cpu.registers.get_carry_flag(), false);
 
-    test_value_1 = 0xD000;
-    test_value_2 = -1;
-    test_value_2_abs = 1;
+    test_value_1 = 0xD00F;
+    test_value_2 = -127;
+    test_value_2_abs = 127;
     expected_value = test_value_1.wrapping_sub(test_value_2_abs);
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
     program_1[1] = test_value_2 as u8;
     cpu.load(&program_1);
     cpu.registers.set_sp(test_value_1);
@@ -4374,19 +4206,18 @@ fn test_0xf8_ld_hl_sp_e8() {
     assert_eq!(cpu.registers.get_half_carry_flag(), true);
     assert_eq!(cpu.registers.get_carry_flag(), false);
 
-    test_value_1 = 0x0000;
+    test_value_1 = 0x0001;
     test_value_2 = -1;
     test_value_2_abs = 1;
     expected_value = test_value_1.wrapping_sub(test_value_2_abs);
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
     program_1[1] = test_value_2 as u8;
     cpu.load(&program_1);
     cpu.registers.set_sp(test_value_1);
     let mut cycles = cpu.execute_next();
     assert_eq!(cycles, 3);
     assert_eq!(cpu.registers.get_hl(), expected_value);
-    // H Flags
+    // H/C Flags
     assert_eq!(cpu.registers.get_zero_flag(), false);
     assert_eq!(cpu.registers.get_negative_flag(), false);
     assert_eq!(cpu.registers.get_half_carry_flag(), true);
@@ -4396,12 +4227,15 @@ test_ld_r16!(0xF9, test_0xf9_ld_sp_hl, set_sp, get_sp, set_hl, get_hl);
 test_ld_r8_imm16!(0xFA, test_0xfa_ld__imm16__a, set_a, get_a);
 #[test]
 fn test_0xfb_ei() {
-    let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
-    let mut program_1: Vec<u8> = vec![0xFB];
+    let mut cpu = TestCpu::new();
+    let mut program_1: Vec<u8> = vec![0xFB, 0x00];
     cpu.load(&program_1);
     let mut cycles = cpu.execute_next();
     assert_eq!(cycles, 1);
+    // IME is set only after the instruction following EI
+    assert_eq!(cpu.ime, false);
+    cycles = cpu.execute_next();
+    assert_eq!(cycles, 1);
     assert_eq!(cpu.ime, true);
 }
 test_cp_a_imm8!(0xFE, test_0xfe_cp_a_imm8);
@@ -1,15 +1,5 @@
-use std::rc::Rc;
-use std::cell::RefCell;
-use crate::GB::cpu::CPU;
-use crate::GB::memory;
-use crate::GB::memory::{RAM, USER_PROGRAM_ADDRESS, WRAM_ADDRESS};
-use crate::GB::joypad::Joypad as GBInput;
-
-macro_rules! create_memory {
-    () => {
-        Rc::new(RefCell::new(RAM::new(Rc::new(RefCell::new(Joypad::default())))))
-    };
-}
+use crate::GB::cpu::instructions::test::{TestCpu, HRAM_ADDRESS, WRAM_ADDRESS};
+use crate::GB::memory::USER_PROGRAM_ADDRESS;
 
 macro_rules! test_flags {
     ($cpu:ident, $zero:expr, $negative:expr, $half:expr, $carry:expr) => {
@@ -26,8 +16,7 @@ macro_rules! test_rlc {
         fn $func() {
             let test_value_1: u8 = 0b1000_1000;
             let test_addr: u16 = WRAM_ADDRESS as u16 + 0xC6;
-            let mut memory_ref = create_memory!();
-            let mut cpu = CPU::new(Rc::clone(&memory_ref));
+            let mut cpu = TestCpu::new();
             let program_1: Vec<u8> = vec![0xCB, $opcode, 0xCB, $opcode];
             cpu.load(&program_1);
             cpu.registers.$set_reg_src(test_value_1);
@@ -46,8 +35,7 @@ macro_rules! test_rlc {
         fn $func() {
             let test_value_1: u8 = 0b1000_1000;
             let test_addr: u16 = WRAM_ADDRESS as u16 + 0xC6;
-            let mut memory_ref = create_memory!();
-            let mut cpu = CPU::new(Rc::clone(&memory_ref));
+            let mut cpu = TestCpu::new();
             let program_1: Vec<u8> = vec![0xCB, $opcode, 0xCB, $opcode];
             cpu.load(&program_1);
             cpu.write_memory(test_addr, test_value_1);
@@ -69,8 +57,7 @@ macro_rules! test_rl {
         fn $func() {
             let test_value_1: u8 = 0b1000_1000;
             let test_addr: u16 = WRAM_ADDRESS as u16 + 0xC6;
-            let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
             let program_1: Vec<u8> = vec![0xCB, $opcode, 0xCB, $opcode];
             cpu.load(&program_1);
             cpu.registers.$set_reg_src(test_value_1);
@@ -90,8 +77,7 @@ macro_rules! test_rl {
         fn $func() {
             let test_value_1: u8 = 0b1000_1000;
             let test_addr: u16 = WRAM_ADDRESS as u16 + 0xC6;
-            let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
             let program_1: Vec<u8> = vec![0xCB, $opcode, 0xCB, $opcode];
             cpu.load(&program_1);
             cpu.write_memory(test_addr, test_value_1);
@@ -114,8 +100,7 @@ macro_rules! test_sla {
         fn $func() {
             let test_value_1: u8 = 0b1000_1000;
             let test_addr: u16 = WRAM_ADDRESS as u16 + 0xC6;
-            let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
             let program_1: Vec<u8> = vec![0xCB, $opcode, 0xCB, $opcode];
             cpu.load(&program_1);
             cpu.registers.$set_reg_src(test_value_1);
@@ -135,8 +120,7 @@ macro_rules! test_sla {
         fn $func() {
             let test_value_1: u8 = 0b1000_1000;
             let test_addr: u16 = WRAM_ADDRESS as u16 + 0xC6;
-            let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
             let program_1: Vec<u8> = vec![0xCB, $opcode, 0xCB, $opcode];
             cpu.load(&program_1);
             cpu.write_memory(test_addr, test_value_1);
@@ -160,8 +144,7 @@ macro_rules! test_rrc {
         fn $func() {
             let test_value_1: u8 = 0b0001_0001;
             let test_addr: u16 = WRAM_ADDRESS as u16 + 0xC6;
-            let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
             let program_1: Vec<u8> = vec![0xCB, $opcode, 0xCB, $opcode];
             cpu.load(&program_1);
             cpu.registers.$set_reg_src(test_value_1);
@@ -180,8 +163,7 @@ macro_rules! test_rrc {
         fn $func() {
             let test_value_1: u8 = 0b0001_0001;
             let test_addr: u16 = WRAM_ADDRESS as u16 + 0xC6;
-            let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
             let program_1: Vec<u8> = vec![0xCB, $opcode, 0xCB, $opcode];
             cpu.load(&program_1);
             cpu.write_memory(test_addr, test_value_1);
@@ -203,8 +185,7 @@ macro_rules! test_rr {
         fn $func() {
             let test_value_1: u8 = 0b0001_0001;
             let test_addr: u16 = WRAM_ADDRESS as u16 + 0xC6;
-            let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
             let program_1: Vec<u8> = vec![0xCB, $opcode, 0xCB, $opcode];
             cpu.load(&program_1);
             cpu.registers.$set_reg_src(test_value_1);
@@ -224,8 +205,7 @@ macro_rules! test_rr {
         fn $func() {
             let test_value_1: u8 = 0b0001_0001;
             let test_addr: u16 = WRAM_ADDRESS as u16 + 0xC6;
-            let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
             let program_1: Vec<u8> = vec![0xCB, $opcode, 0xCB, $opcode];
             cpu.load(&program_1);
             cpu.write_memory(test_addr, test_value_1);
@@ -248,8 +228,7 @@ macro_rules! test_sra {
         fn $func() {
             let test_value_1: u8 = 0b1001_0001;
             let test_addr: u16 = WRAM_ADDRESS as u16 + 0xC6;
-            let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
             let program_1: Vec<u8> = vec![0xCB, $opcode, 0xCB, $opcode];
             cpu.load(&program_1);
             cpu.registers.$set_reg_src(test_value_1);
@@ -269,8 +248,7 @@ macro_rules! test_sra {
         fn $func() {
             let test_value_1: u8 = 0b1001_0001;
             let test_addr: u16 = WRAM_ADDRESS as u16 + 0xC6;
-            let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
             let program_1: Vec<u8> = vec![0xCB, $opcode, 0xCB, $opcode];
             cpu.load(&program_1);
             cpu.write_memory(test_addr, test_value_1);
@@ -293,8 +271,7 @@ macro_rules! test_srl {
         fn $func() {
             let test_value_1: u8 = 0b0001_0001;
             let test_addr: u16 = WRAM_ADDRESS as u16 + 0xC6;
-            let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
             let program_1: Vec<u8> = vec![0xCB, $opcode, 0xCB, $opcode];
             cpu.load(&program_1);
             cpu.registers.$set_reg_src(test_value_1);
@@ -314,8 +291,7 @@ macro_rules! test_srl {
         fn $func() {
             let test_value_1: u8 = 0b0001_0001;
             let test_addr: u16 = WRAM_ADDRESS as u16 + 0xC6;
-            let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
             let program_1: Vec<u8> = vec![0xCB, $opcode, 0xCB, $opcode];
             cpu.load(&program_1);
             cpu.write_memory(test_addr, test_value_1);
@@ -339,8 +315,7 @@ macro_rules! test_swap {
         fn $func() {
             let test_value_1: u8 = 0b0001_1000;
             let test_addr: u16 = WRAM_ADDRESS as u16 + 0xC6;
-            let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
             let program_1: Vec<u8> = vec![0xCB, $opcode, 0xCB, $opcode];
             cpu.load(&program_1);
             cpu.registers.$set_reg_src(test_value_1);
@@ -360,8 +335,7 @@ macro_rules! test_swap {
         fn $func() {
             let test_value_1: u8 = 0b0001_1000;
             let test_addr: u16 = WRAM_ADDRESS as u16 + 0xC6;
-            let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
             let program_1: Vec<u8> = vec![0xCB, $opcode, 0xCB, $opcode];
             cpu.load(&program_1);
             cpu.write_memory(test_addr, test_value_1);
@@ -384,8 +358,7 @@ macro_rules! test_bit {
         #[test]
         fn $func() {
             let test_value_1: u8 = !(1 << $bit);
-            let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
             let program_1: Vec<u8> = vec![0xCB, $opcode, 0xCB, $opcode];
             cpu.load(&program_1);
             cpu.registers.$set_reg_src(test_value_1);
@@ -410,8 +383,7 @@ macro_rules! test_bit {
         fn $func() {
             let test_value_1: u8 = !(1 << $bit);
             let test_addr: u16 = WRAM_ADDRESS as u16 + 0xC6;
-            let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
             let program_1: Vec<u8> = vec![0xCB, $opcode, 0xCB, $opcode];
             cpu.load(&program_1);
             cpu.write_memory(test_addr, test_value_1);
@@ -442,8 +414,7 @@ macro_rules! test_res {
         fn $func() {
             let test_value_1: u8 = 0xFF;
             let test_mask: u8 = !(1 << $bit);
-            let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
             let program_1: Vec<u8> = vec![0xCB, $opcode];
             cpu.load(&program_1);
             cpu.registers.$set_reg_src(test_value_1);
@@ -469,8 +440,7 @@ macro_rules! test_res {
             let test_value_1: u8 = 0xFF;
             let test_mask: u8 = !(1 << $bit);
             let test_addr: u16 = WRAM_ADDRESS as u16 + 0xC6;
-            let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
             let program_1: Vec<u8> = vec![0xCB, $opcode];
             cpu.load(&program_1);
             cpu.write_memory(test_addr, test_value_1);
@@ -500,8 +470,7 @@ macro_rules! test_set {
         fn $func() {
             let test_value_1: u8 = 0x0;
             let test_mask: u8 = (1 << $bit);
-            let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
             let program_1: Vec<u8> = vec![0xCB, $opcode];
             cpu.load(&program_1);
             cpu.registers.$set_reg_src(test_value_1);
@@ -527,8 +496,7 @@ macro_rules! test_set {
             let test_value_1: u8 = 0x0;
             let test_mask: u8 = (1 << $bit);
             let test_addr: u16 = WRAM_ADDRESS as u16 + 0xC6;
-            let mut memory_ref = create_memory!();
-    let mut cpu = CPU::new(Rc::clone(&memory_ref));
+    let mut cpu = TestCpu::new();
             let program_1: Vec<u8> = vec![0xCB, $opcode];
             cpu.load(&program_1);
             cpu.write_memory(test_addr, test_value_1);
@@ -3,6 +3,7 @@ use std::fmt;
 use crate::{mask_flag_enum_default_impl, default_enum_u8_bit_ops};
 use super::macro_registers;
 use crate::GB::debug_print;
+use crate::GB::save_state::{SaveState, SaveStateError, StateReader, StateWriter};
 
 #[derive(Debug, Clone, Copy)]
 #[repr(u8)]
@@ -171,11 +172,13 @@ impl Registers {
     }
 
     #[inline]
+    #[allow(dead_code)]
     pub fn get_sp_as_address(&self) -> Address {
         Address(self.sp)
     }
 
     #[inline]
+    #[allow(dead_code)]
     pub fn get_pc_as_address(&self) -> Address {
         Address(self.pc)
     }
@@ -213,6 +216,7 @@ impl Registers {
     }
 
     #[inline]
+    #[allow(dead_code)]
     pub fn get_af_as_address(&self) -> Address {
         Address(self.get_af())
     }
@@ -357,6 +361,26 @@ impl Registers {
     }
 }
 
+impl SaveState for Registers {
+    fn save_state(&self, state: &mut StateWriter) {
+        for register in [self.a, self.b, self.c, self.d, self.e, self.f, self.h, self.l, self.w, self.z] {
+            state.write_u8(register);
+        }
+        state.write_u16(self.sp);
+        state.write_u16(self.pc);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        for register in [&mut self.a, &mut self.b, &mut self.c, &mut self.d, &mut self.e, &mut self.f, &mut self.h, &mut self.l, &mut self.w, &mut self.z] {
+            *register = state.read_u8()?;
+        }
+        self.f &= 0xF0;
+        self.sp = state.read_u16()?;
+        self.pc = state.read_u16()?;
+        Ok(())
+    }
+}
+
 impl fmt::Display for Registers {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
@@ -1,15 +1,21 @@
 use crate::GB::bus::BusDevice;
 use crate::GB::types::address::Address;
 use crate::GB::types::Byte;
+use crate::GB::save_state::{SaveState, SaveStateError, StateReader, StateWriter};
 use super::macro_registers;
 
 use crate::{mask_flag_enum_default_impl, default_enum_u8_bit_ops};
 
 
+#[allow(dead_code)]
 pub const INTERRUPT_VBLANK_ADDR: u16 = 0x40;
+#[allow(dead_code)]
 pub const INTERRUPT_STAT_ADDR: u16 = 0x48;
+#[allow(dead_code)]
 pub const INTERRUPT_TIMER_ADDR: u16 = 0x50;
+#[allow(dead_code)]
 pub const INTERRUPT_SERIAL_ADDR: u16 = 0x58;
+#[allow(dead_code)]
 pub const INTERRUPT_JOYPAD_ADDR: u16 = 0x60;
 
 #[derive(Debug, Copy, Clone)]
@@ -38,6 +44,7 @@ mask_flag_enum_default_impl!(InterruptEnableMask);
 mask_flag_enum_default_impl!(InterruptFlagsMask);
 
 /// Structure for a quick view of Interrupt Flags status in IF and IE registers
+#[allow(dead_code)]
 pub struct InterruptFlags {
     pub joy_pad: bool,
     pub serial: bool,
@@ -47,6 +54,7 @@ pub struct InterruptFlags {
 }
 
 impl InterruptFlags {
+    #[allow(dead_code)]
     pub fn new(byte: Byte) -> Self {
         Self {
             joy_pad: (byte & InterruptFlagsMask::JoyPad) != 0,
@@ -68,10 +76,15 @@ impl InterruptRegisters {
     pub const IE_ADDRESS: Address = Address(0xFFFF);
     pub const IF_ADDRESS: Address = Address(0xFF0F);
     pub const AVAILABLE_BITS_MASK: u8 = 0b0001_1111;
+    #[allow(dead_code)]
     pub const BIT_VBLANK_MASK: u8 = InterruptFlagsMask::VBlank as u8;
+    #[allow(dead_code)]
     pub const BIT_LCD_MASK: u8 = InterruptFlagsMask::LCD as u8;
+    #[allow(dead_code)]
     pub const BIT_TIMER_MASK: u8 = InterruptFlagsMask::Timer as u8;
+    #[allow(dead_code)]
     pub const BIT_SERIAL_MASK: u8 = InterruptFlagsMask::Serial as u8;
+    #[allow(dead_code)]
     pub const BIT_JOYPAD_MASK: u8 = InterruptFlagsMask::JoyPad as u8;
 
     #[inline]
@@ -136,11 +149,13 @@ impl InterruptRegisters {
     }
 
     #[inline]
+    #[allow(dead_code)]
     pub fn set_ie_bit(&mut self, bit: InterruptEnableMask) {
         self.ie |= bit as Byte;
     }
 
     #[inline]
+    #[allow(dead_code)]
     pub fn reset_ie_bit(&mut self, bit: InterruptEnableMask) {
         self.ie &= !(bit as Byte);
     }
@@ -189,3 +204,16 @@ impl Default for InterruptRegisters {
         Self::new()
     }
 }
+
+impl SaveState for InterruptRegisters {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_u8(self.ie);
+        state.write_u8(self.iflag);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        self.ie = state.read_u8()?;
+        self.iflag = state.read_u8()?;
+        Ok(())
+    }
+}
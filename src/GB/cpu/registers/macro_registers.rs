@@ -6,6 +6,7 @@ macro_rules! get_set {
         }
 
         #[inline]
+        #[allow(dead_code)]
         pub fn $set_name(&mut self, val: $size) {
             self.$reg = val;
         }
@@ -21,6 +22,7 @@ macro_rules! get_set_dual {
         }
 
         #[inline]
+        #[allow(dead_code)]
         pub fn $get_name_as_address(&self) -> Address {
             Address(self.$get_name())
         }
@@ -0,0 +1,287 @@
+pub mod rom;
+
+use crate::GB::cpu::instructions::{Instruction, OPCODES, OPCODES_CB};
+use crate::GB::types::Byte;
+
+/// Instruction operand, decoded from opcode tables names and immediate bytes
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Operand {
+    Register(&'static str), // 8/16-bit register (A, HL, SP, AF, ...)
+    Condition(&'static str), // Jump condition (Z, NZ, C, NC)
+    Indirect(&'static str), // Memory pointed by a register ([HL], [HL+], [HL-], [BC], [DE], [C])
+    Imm8(u8),
+    Imm16(u16),
+    Address(u16), // Memory pointed by an immediate address ([imm16])
+    HighAddress(u8), // Memory pointed by $FF00 + immediate ([imm8] of LDH)
+    Offset(i8), // Signed immediate of ADD SP, e8
+    SpOffset(i8), // SP + e8 of LD HL, SP + e8
+    Target(u16), // Jump/call destination (relative jumps are resolved)
+    Bit(u8),
+    Vector(u8), // RST vector
+}
+
+/// How an instruction affects the program flow
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Flow {
+    Next,
+    Jump,
+    ConditionalJump,
+    Call,
+    ConditionalCall,
+    Return,
+    ConditionalReturn,
+    Stop, // Execution can't go on (illegal opcode)
+}
+
+/// Decoded SM83 instruction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DisassembledInstruction {
+    pub address: u16,
+    pub bytes: Vec<Byte>,
+    pub opcode: u8, // For CB prefixed instructions this is the opcode following the prefix
+    pub prefixed: bool,
+    pub mnemonic: &'static str, // Upper case as in opcode tables, "DB" for illegal opcodes
+    pub operands: Vec<Operand>,
+    pub branch_target: Option<u16>, // Known destination of jumps, calls and RSTs
+    pub flow: Flow,
+}
+
+impl DisassembledInstruction {
+    pub const ILLEGAL_MNEMONIC: &'static str = "DB";
+    /// Longest instruction size, that is the bytes needed to decode any instruction
+    pub const MAX_SIZE: usize = 3;
+
+    #[inline]
+    pub fn size(&self) -> u8 {
+        self.bytes.len() as u8
+    }
+
+    #[inline]
+    pub fn is_illegal(&self) -> bool {
+        self.mnemonic == Self::ILLEGAL_MNEMONIC
+    }
+
+    /// Address of the instruction that follows
+    #[inline]
+    pub fn next_address(&self) -> u16 {
+        self.address.wrapping_add(self.size() as u16)
+    }
+
+    /// Format as RGBDS source, replacing branch targets and addresses with labels where `label` returns one
+    pub fn to_rgbds(&self, label: &dyn Fn(u16) -> Option<String>) -> String {
+        if self.is_illegal() {
+            return format!("db ${:02X}", self.opcode);
+        }
+        if self.mnemonic == "STOP" && self.bytes.get(1).is_some_and(|byte| *byte != 0) {
+            // RGBDS only emits STOP followed by $00
+            return format!("db ${:02X}, ${:02X}", self.bytes[0], self.bytes[1]);
+        }
+        let mnemonic = self.mnemonic.to_lowercase();
+        let operands: Vec<String> = self.operands.iter()
+            .filter(|operand| !(self.mnemonic == "STOP" && matches!(operand, Operand::Imm8(_))))
+            .map(|operand| Self::operand_to_rgbds(operand, label))
+            .collect();
+        if operands.is_empty() {
+            mnemonic
+        } else {
+            format!("{} {}", mnemonic, operands.join(", "))
+        }
+    }
+
+    fn operand_to_rgbds(operand: &Operand, label: &dyn Fn(u16) -> Option<String>) -> String {
+        let address = |address: u16| label(address).unwrap_or_else(|| format!("${:04X}", address));
+        match *operand {
+            Operand::Register(register) | Operand::Condition(register) => register.to_lowercase(),
+            Operand::Indirect(register) => register.to_lowercase(),
+            Operand::Imm8(value) => format!("${:02X}", value),
+            Operand::Imm16(value) => format!("${:04X}", value),
+            Operand::Address(value) => format!("[{}]", address(value)),
+            Operand::HighAddress(value) => format!("[{}]", address(0xFF00 | value as u16)),
+            Operand::Offset(value) => format!("{}", value),
+            Operand::SpOffset(value) if value < 0 => format!("sp - {}", (value as i16).unsigned_abs()),
+            Operand::SpOffset(value) => format!("sp + {}", value),
+            Operand::Target(value) => address(value),
+            Operand::Bit(bit) => format!("{}", bit),
+            Operand::Vector(vector) => format!("${:02X}", vector),
+        }
+    }
+}
+
+impl std::fmt::Display for DisassembledInstruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_rgbds(&|_| None))
+    }
+}
+
+/// Decode the instruction at the start of `bytes`, placed at the given address.
+/// None if bytes end before the instruction does.
+pub fn decode(bytes: &[Byte], address: u16) -> Option<DisassembledInstruction> {
+    let opcode = *bytes.first()?;
+    let (instruction, prefixed) = match OPCODES[opcode as usize] {
+        Some(_) if opcode == 0xCB => (OPCODES_CB[*bytes.get(1)? as usize]?, true),
+        Some(instruction) => (instruction, false),
+        None => return Some(illegal(opcode, address)),
+    };
+    let size = instruction.size as usize;
+    let bytes = bytes.get(..size)?;
+    Some(decode_instruction(instruction, prefixed, bytes, address))
+}
+
+/// Decode the instruction at the given address, reading memory through `read`
+pub fn decode_at(read: impl Fn(u16) -> Byte, address: u16) -> DisassembledInstruction {
+    let bytes: Vec<Byte> = (0..DisassembledInstruction::MAX_SIZE as u16)
+        .map(|offset| read(address.wrapping_add(offset)))
+        .collect();
+    decode(&bytes, address).expect("Instruction can't be longer than MAX_SIZE")
+}
+
+fn illegal(opcode: u8, address: u16) -> DisassembledInstruction {
+    DisassembledInstruction {
+        address,
+        bytes: vec![opcode],
+        opcode,
+        prefixed: false,
+        mnemonic: DisassembledInstruction::ILLEGAL_MNEMONIC,
+        operands: vec![Operand::Imm8(opcode)],
+        branch_target: None,
+        flow: Flow::Stop,
+    }
+}
+
+fn decode_instruction(instruction: &'static Instruction, prefixed: bool, bytes: &[Byte], address: u16) -> DisassembledInstruction {
+    let (mnemonic, operands_name) = instruction.name.split_once(' ').unwrap_or((instruction.name, ""));
+    let next_address = address.wrapping_add(bytes.len() as u16);
+    let imm8 = bytes.get(1).copied().unwrap_or(0);
+    let imm16 = u16::from_le_bytes([imm8, bytes.get(2).copied().unwrap_or(0)]);
+    let branch = matches!(mnemonic, "JP" | "JR" | "CALL" | "RET");
+
+    let operands: Vec<Operand> = operands_name
+        .split(", ")
+        .filter(|name| !name.is_empty())
+        .map(|name| match name {
+            "Z" | "NZ" | "C" | "NC" if branch => Operand::Condition(name),
+            "imm16" if branch => Operand::Target(imm16),
+            "imm16" => Operand::Imm16(imm16),
+            "[imm16]" | "[a16]" => Operand::Address(imm16),
+            "[imm8]" => Operand::HighAddress(imm8),
+            "imm8" => Operand::Imm8(imm8),
+            "e8" if branch => Operand::Target(next_address.wrapping_add(imm8 as i8 as u16)),
+            "e8" => Operand::Offset(imm8 as i8),
+            "SP + e8" => Operand::SpOffset(imm8 as i8),
+            name if name.starts_with('[') => Operand::Indirect(name),
+            name if name.starts_with('$') => Operand::Vector(u8::from_str_radix(&name[1..], 16).unwrap_or(0)),
+            name if name.len() == 1 && name.as_bytes()[0].is_ascii_digit() && mnemonic != "RST" => Operand::Bit(name.as_bytes()[0] - b'0'),
+            name => Operand::Register(name),
+        })
+        .collect();
+
+    let conditional = operands.iter().any(|operand| matches!(operand, Operand::Condition(_)));
+    let flow = match (mnemonic, conditional) {
+        ("JP" | "JR", false) => Flow::Jump,
+        ("JP" | "JR", true) => Flow::ConditionalJump,
+        ("CALL", false) | ("RST", _) => Flow::Call,
+        ("CALL", true) => Flow::ConditionalCall,
+        ("RET" | "RETI", false) => Flow::Return,
+        ("RET", true) => Flow::ConditionalReturn,
+        _ => Flow::Next,
+    };
+    let branch_target = operands.iter().find_map(|operand| match operand {
+        Operand::Target(target) => Some(*target),
+        Operand::Vector(vector) => Some(*vector as u16),
+        _ => None,
+    });
+
+    DisassembledInstruction {
+        address,
+        bytes: bytes.to_vec(),
+        opcode: instruction.opcode,
+        prefixed,
+        mnemonic,
+        operands,
+        branch_target,
+        flow,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::GB::disasm::{decode, decode_at, Flow, Operand};
+
+    fn rgbds(bytes: &[u8], address: u16) -> String {
+        decode(bytes, address).unwrap().to_string()
+    }
+
+    #[test]
+    fn decode_operands() {
+        let ld = decode(&[0x3E, 0x42], 0x150).unwrap();
+        assert_eq!(ld.mnemonic, "LD");
+        assert_eq!(ld.operands, vec![Operand::Register("A"), Operand::Imm8(0x42)]);
+        assert_eq!(ld.size(), 2);
+        assert_eq!(ld.flow, Flow::Next);
+        assert_eq!(ld.branch_target, None);
+
+        let bit = decode(&[0xCB, 0x7E], 0x150).unwrap();
+        assert!(bit.prefixed);
+        assert_eq!(bit.opcode, 0x7E);
+        assert_eq!(bit.operands, vec![Operand::Bit(7), Operand::Indirect("[HL]")]);
+        assert_eq!(bit.size(), 2);
+
+        let jr = decode(&[0x20, 0xFE], 0x150).unwrap();
+        assert_eq!(jr.operands, vec![Operand::Condition("NZ"), Operand::Target(0x150)]);
+        assert_eq!(jr.branch_target, Some(0x150));
+        assert_eq!(jr.flow, Flow::ConditionalJump);
+
+        let rst = decode(&[0xFF], 0x150).unwrap();
+        assert_eq!(rst.branch_target, Some(0x38));
+        assert_eq!(rst.flow, Flow::Call);
+        assert_eq!(decode(&[0xD8], 0).unwrap().flow, Flow::ConditionalReturn);
+        assert_eq!(decode(&[0xE9], 0).unwrap().flow, Flow::Jump);
+        assert_eq!(decode(&[0xE9], 0).unwrap().branch_target, None);
+    }
+
+    #[test]
+    fn illegal_and_truncated() {
+        let illegal = decode(&[0xD3, 0x00], 0x200).unwrap();
+        assert!(illegal.is_illegal());
+        assert_eq!(illegal.size(), 1);
+        assert_eq!(illegal.flow, Flow::Stop);
+        assert_eq!(illegal.to_string(), "db $D3");
+
+        assert_eq!(decode(&[], 0), None);
+        assert_eq!(decode(&[0xC3, 0x50], 0), None);
+        assert_eq!(decode(&[0xCB], 0), None);
+    }
+
+    #[test]
+    fn format_rgbds() {
+        assert_eq!(rgbds(&[0x00], 0), "nop");
+        assert_eq!(rgbds(&[0xC3, 0x50, 0x01], 0), "jp $0150");
+        assert_eq!(rgbds(&[0x18, 0x10], 0x100), "jr $0112");
+        assert_eq!(rgbds(&[0xCD, 0x00, 0x40], 0), "call $4000");
+        assert_eq!(rgbds(&[0xC4, 0x00, 0x40], 0), "call nz, $4000");
+        assert_eq!(rgbds(&[0xEA, 0x00, 0xC0], 0), "ld [$C000], a");
+        assert_eq!(rgbds(&[0x08, 0x00, 0xC0], 0), "ld [$C000], sp");
+        assert_eq!(rgbds(&[0xE0, 0x40], 0), "ldh [$FF40], a");
+        assert_eq!(rgbds(&[0xF2], 0), "ldh a, [c]");
+        assert_eq!(rgbds(&[0x2A], 0), "ld a, [hl+]");
+        assert_eq!(rgbds(&[0xF8, 0xFE], 0), "ld hl, sp - 2");
+        assert_eq!(rgbds(&[0xE8, 0x05], 0), "add sp, 5");
+        assert_eq!(rgbds(&[0x01, 0x34, 0x12], 0), "ld bc, $1234");
+        assert_eq!(rgbds(&[0xCB, 0x37], 0), "swap a");
+        assert_eq!(rgbds(&[0xCB, 0xC6], 0), "set 0, [hl]");
+        assert_eq!(rgbds(&[0xEF], 0), "rst $28");
+        assert_eq!(rgbds(&[0x10, 0x00], 0), "stop");
+        assert_eq!(rgbds(&[0x10, 0x01], 0), "db $10, $01");
+
+        let call = decode(&[0xCD, 0x00, 0x40], 0).unwrap();
+        assert_eq!(call.to_rgbds(&|address| (address == 0x4000).then(|| "Func".to_string())), "call Func");
+    }
+
+    #[test]
+    fn decode_from_memory() {
+        let memory = [0x00, 0xFA, 0x34, 0x12];
+        let instruction = decode_at(|address| memory.get(address as usize).copied().unwrap_or(0xFF), 1);
+        assert_eq!(instruction.to_string(), "ld a, [$1234]");
+        assert_eq!(instruction.next_address(), 4);
+    }
+}
@@ -0,0 +1,378 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use crate::GB::cartridge::addresses as cartridge_addresses;
+use crate::GB::cartridge::header::RomHeader;
+use crate::GB::cartridge::Cartridge;
+use crate::GB::cpu::InterruptType;
+use crate::GB::disasm::{decode, DisassembledInstruction, Flow, Operand};
+use crate::GB::types::Byte;
+
+/// ROM address inside a given bank (bank 0 is mapped at $0000, others at $4000)
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Location {
+    pub bank: usize,
+    pub address: u16,
+}
+
+impl Location {
+    pub fn new(bank: usize, address: u16) -> Self {
+        Self { bank, address }
+    }
+
+    /// Offset of the location in the ROM file
+    pub fn offset(&self) -> usize {
+        self.bank * Cartridge::ROM_BANK_SIZE + (self.address as usize % Cartridge::ROM_BANK_SIZE)
+    }
+}
+
+/// Byte role, as found walking the code
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum ByteKind {
+    Data,
+    Instruction,
+    Operand, // Following bytes of an instruction
+}
+
+/// Code reached walking the ROM, with the location its branch target resolves to
+struct CodeInstruction {
+    instruction: DisassembledInstruction,
+    target: Option<Location>,
+}
+
+/// Code path still to walk: location and ROM bank assumed mapped at $4000-$7FFF
+type CodePath = (Location, Option<usize>);
+
+/// Static recursive-descent disassembler: walks the ROM from the entry point, the RST and the interrupt vectors,
+/// following jumps and calls, and produces per bank RGBDS sources that assemble back to the same bytes.
+/// Switchable bank targets are resolved from the code bank itself, or from the `ld a, n` / `ld [$2000-$3FFF], a`
+/// bank switch preceding them when walking bank 0. Unresolved code stays data.
+pub struct RomDisassembler<'a> {
+    rom: &'a [Byte],
+    kinds: Vec<ByteKind>,
+    code: BTreeMap<Location, CodeInstruction>,
+    labels: BTreeMap<Location, String>,
+}
+
+impl<'a> RomDisassembler<'a> {
+    /// Bytes emitted by each `db` line
+    const DATA_LINE_BYTES: usize = 16;
+    /// Repeated bytes emitted as a single `ds`
+    const FILL_MIN_BYTES: usize = 16;
+
+    pub fn new(rom: &'a [Byte]) -> Self {
+        let mut disassembler = Self {
+            rom,
+            kinds: vec![ByteKind::Data; rom.len()],
+            code: BTreeMap::new(),
+            labels: BTreeMap::new(),
+        };
+        disassembler.walk();
+        disassembler.generate_labels();
+        disassembler
+    }
+
+    pub fn banks(&self) -> usize {
+        self.rom.len().div_ceil(Cartridge::ROM_BANK_SIZE)
+    }
+
+    /// Whether an instruction starts at the given location
+    pub fn is_code(&self, location: Location) -> bool {
+        self.code.contains_key(&location)
+    }
+
+    pub fn instruction(&self, location: Location) -> Option<&DisassembledInstruction> {
+        self.code.get(&location).map(|code| &code.instruction)
+    }
+
+    pub fn label(&self, location: Location) -> Option<&str> {
+        self.labels.get(&location).map(|label| label.as_str())
+    }
+
+    /// Entry points named after their role
+    fn entry_points() -> Vec<(u16, String)> {
+        let mut entry_points = vec![(cartridge_addresses::ENTRY_POINT as u16, "EntryPoint".to_string())];
+        entry_points.extend((0..8).map(|i| (i * 0x08, format!("RST_{:02X}", i * 0x08))));
+        entry_points.extend(InterruptType::ALL.iter().map(|interrupt| {
+            (interrupt.vector() as u16, format!("{:?}Interrupt", interrupt))
+        }));
+        entry_points
+    }
+
+    fn walk(&mut self) {
+        let mut paths: VecDeque<CodePath> = Self::entry_points().into_iter()
+            .map(|(address, _)| (Location::new(0, address), None))
+            .collect();
+        while let Some(path) = paths.pop_front() {
+            self.walk_path(path, &mut paths);
+        }
+    }
+
+    /// Decode linearly from the path location until the flow can't fall through
+    fn walk_path(&mut self, (mut location, mut mapped_bank): CodePath, paths: &mut VecDeque<CodePath>) {
+        let mut a: Option<u8> = None; // Known value of A, to track bank switches
+        loop {
+            if self.code.contains_key(&location) {
+                return;
+            }
+            let Some(instruction) = self.decode(location) else {
+                return;
+            };
+            let offset = location.offset();
+            let size = instruction.size() as usize;
+            if self.kinds[offset..offset + size].iter().any(|kind| *kind != ByteKind::Data) {
+                return; // Overlaps already decoded code
+            }
+
+            match (instruction.prefixed, instruction.opcode, instruction.operands.as_slice()) {
+                (false, 0x3E, [_, Operand::Imm8(value)]) => a = Some(*value),
+                (false, 0xAF, _) => a = Some(0),
+                (false, 0xEA, [Operand::Address(address), _])
+                    if (cartridge_addresses::MBC_ROM_BANK_SELECTION_ADDRESS_START..=cartridge_addresses::MBC_ROM_BANK_SELECTION_ADDRESS_END)
+                        .contains(&(*address as usize)) => {
+                    mapped_bank = a.map(|bank| (bank as usize).max(1)).filter(|bank| *bank < self.banks());
+                }
+                (_, _, [Operand::Register("A"), ..]) | (false, 0xF1, _) => a = None,
+                _ => {}
+            }
+            if matches!(instruction.flow, Flow::Call | Flow::ConditionalCall) {
+                a = None;
+            }
+
+            let target = instruction.branch_target.and_then(|target| self.resolve(location.bank, mapped_bank, target));
+            if let Some(target) = target {
+                paths.push_back((target, if target.bank == 0 { mapped_bank } else { Some(target.bank) }));
+            }
+            let flow = instruction.flow;
+            let next_address = instruction.next_address();
+            self.kinds[offset] = ByteKind::Instruction;
+            self.kinds[offset + 1..offset + size].fill(ByteKind::Operand);
+            self.code.insert(location, CodeInstruction { instruction, target });
+
+            match flow {
+                Flow::Jump | Flow::Return | Flow::Stop => return,
+                _ => {}
+            }
+            match self.resolve(location.bank, mapped_bank, next_address) {
+                Some(next) if next.bank == location.bank => location = next,
+                _ => return, // Code can't fall through a bank boundary
+            }
+        }
+    }
+
+    /// Decode the instruction at the location, if it's legal and doesn't cross the bank or the header
+    fn decode(&self, location: Location) -> Option<DisassembledInstruction> {
+        let offset = location.offset();
+        let bank_end = ((location.bank + 1) * Cartridge::ROM_BANK_SIZE).min(self.rom.len());
+        let header_start = RomHeader::HEADER_LOGO_START_ADDRESS.as_usize();
+        let header_end = RomHeader::HEADER_END_ADDRESS.as_usize() + 1;
+        let end = if location.bank == 0 && offset < header_start { header_start } else { bank_end };
+        if location.bank == 0 && (header_start..header_end).contains(&offset) {
+            return None;
+        }
+        let instruction = decode(self.rom.get(offset..end)?, location.address)?;
+        if instruction.is_illegal() {
+            return None;
+        }
+        Some(instruction)
+    }
+
+    /// Location an address refers to, from code in the given bank with the given bank mapped at $4000
+    fn resolve(&self, bank: usize, mapped_bank: Option<usize>, address: u16) -> Option<Location> {
+        let bank = match address as usize {
+            address if address < Cartridge::ROM_BANK_SIZE => 0,
+            address if address < 2 * Cartridge::ROM_BANK_SIZE => match (bank, mapped_bank) {
+                (bank, _) if bank > 0 => bank,
+                (_, Some(mapped_bank)) => mapped_bank,
+                _ if self.banks() <= 2 => 1, // No controller, bank 1 is always mapped
+                _ => return None,
+            },
+            _ => return None,
+        };
+        let location = Location::new(bank, address);
+        (location.offset() < self.rom.len()).then_some(location)
+    }
+
+    fn generate_labels(&mut self) {
+        for (address, name) in Self::entry_points() {
+            let location = Location::new(0, address);
+            if self.is_code(location) {
+                self.labels.insert(location, name);
+            }
+        }
+        let targets: Vec<(Location, Flow)> = self.code.values()
+            .filter_map(|code| code.target.map(|target| (target, code.instruction.flow)))
+            .collect();
+        for (target, flow) in targets {
+            if !self.is_code(target) {
+                continue;
+            }
+            let prefix = match flow {
+                Flow::Call | Flow::ConditionalCall => "Call",
+                _ => "Jump",
+            };
+            let label = format!("{}_{:03X}_{:04X}", prefix, target.bank, target.address);
+            let entry = self.labels.entry(target).or_insert_with(|| label.clone());
+            if prefix == "Call" && entry.starts_with("Jump") {
+                *entry = label;
+            }
+        }
+    }
+
+    /// Label of an address referenced by code placed in the given location
+    fn reference_label(&self, code: &CodeInstruction, location: Location, address: u16) -> Option<String> {
+        let target = match code.target {
+            Some(target) if target.address == address => target,
+            _ => match address as usize {
+                address if address < Cartridge::ROM_BANK_SIZE => Location::new(0, address as u16),
+                _ if location.bank > 0 && address < 0x8000 => Location::new(location.bank, address),
+                _ => return None,
+            },
+        };
+        self.labels.get(&target).cloned()
+    }
+
+    /// RGBDS source of a whole bank
+    pub fn bank_source(&self, bank: usize) -> String {
+        let mut source = String::new();
+        let _ = writeln!(source, "; Bank ${:03X}, disassembled by YAEmulator", bank);
+        let _ = writeln!(source);
+        if bank == 0 {
+            let _ = writeln!(source, "SECTION \"ROM Bank $000\", ROM0[$0000]");
+        } else {
+            let _ = writeln!(source, "SECTION \"ROM Bank ${:03X}\", ROMX[$4000], BANK[${:X}]", bank, bank);
+        }
+        let base: u16 = if bank == 0 { 0x0000 } else { Cartridge::ROM_BANK_SIZE as u16 };
+        let bank_start = bank * Cartridge::ROM_BANK_SIZE;
+        let bank_end = (bank_start + Cartridge::ROM_BANK_SIZE).min(self.rom.len());
+
+        let mut offset = bank_start;
+        while offset < bank_end {
+            let location = Location::new(bank, base + (offset - bank_start) as u16);
+            if let Some(label) = self.labels.get(&location) {
+                if !source.ends_with("\n\n") {
+                    let _ = writeln!(source);
+                }
+                let _ = writeln!(source, "{}::", label);
+            }
+            if let Some(code) = self.code.get(&location) {
+                let text = code.instruction.to_rgbds(&|address| self.reference_label(code, location, address));
+                let _ = writeln!(source, "    {}", text);
+                if matches!(code.instruction.flow, Flow::Jump | Flow::Return) {
+                    let _ = writeln!(source);
+                }
+                offset += code.instruction.size() as usize;
+                continue;
+            }
+
+            // Data runs until the next instruction or label
+            let data_end = (offset + 1..bank_end)
+                .find(|offset| {
+                    let location = Location::new(bank, base + (offset - bank_start) as u16);
+                    self.kinds[*offset] != ByteKind::Data || self.labels.contains_key(&location)
+                })
+                .unwrap_or(bank_end);
+            self.write_data(&mut source, &self.rom[offset..data_end]);
+            offset = data_end;
+        }
+        source
+    }
+
+    fn write_data(&self, source: &mut String, data: &[Byte]) {
+        let mut i = 0;
+        while i < data.len() {
+            let fill = data[i..].iter().take_while(|byte| **byte == data[i]).count();
+            if fill >= Self::FILL_MIN_BYTES {
+                let _ = writeln!(source, "    ds {}, ${:02X}", fill, data[i]);
+                i += fill;
+                continue;
+            }
+            // Literals stop where a fill starts
+            let mut end = i;
+            while end < data.len() && end - i < Self::DATA_LINE_BYTES {
+                let fill = data[end..].iter().take_while(|byte| **byte == data[end]).count();
+                if fill >= Self::FILL_MIN_BYTES {
+                    break;
+                }
+                end += 1;
+            }
+            let bytes: Vec<String> = data[i..end].iter().map(|byte| format!("${:02X}", byte)).collect();
+            let _ = writeln!(source, "    db {}", bytes.join(", "));
+            i = end;
+        }
+    }
+
+    /// Write `bank_XXX.asm` for every bank in the directory, returning written files.
+    /// Labels are exported, so banks can be assembled separately and linked together.
+    pub fn write_banks(&self, directory: &Path) -> Result<Vec<PathBuf>, std::io::Error> {
+        std::fs::create_dir_all(directory)?;
+        (0..self.banks())
+            .map(|bank| {
+                let path = directory.join(format!("bank_{:03X}.asm", bank));
+                std::fs::write(&path, self.bank_source(bank))?;
+                Ok(path)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::GB::cartridge::Cartridge;
+    use crate::GB::disasm::rom::{Location, RomDisassembler};
+
+    /// MBC1 ROM of 4 banks: entry jumps to main, which switches to bank 2 and calls into it
+    fn rom() -> Vec<u8> {
+        let mut rom = vec![0xFFu8; 4 * Cartridge::ROM_BANK_SIZE];
+        rom[0x100..0x104].copy_from_slice(&[0x00, 0xC3, 0x50, 0x01]); // NOP; JP $0150
+        rom[0x104..0x150].fill(0x00);
+        rom[0x147] = 0x01;
+        let main = [
+            0x3E, 0x02,       // LD A, $02
+            0xEA, 0x00, 0x20, // LD [$2000], A
+            0xCD, 0x00, 0x40, // CALL $4000
+            0x20, 0xF6,       // JR NZ, $0150
+            0xC9,             // RET
+            0x12, 0x34,       // Data
+        ];
+        rom[0x150..0x150 + main.len()].copy_from_slice(&main);
+        let bank_2 = 2 * Cartridge::ROM_BANK_SIZE;
+        rom[bank_2..bank_2 + 3].copy_from_slice(&[0xCB, 0x37, 0xC9]); // SWAP A; RET
+        rom
+    }
+
+    #[test]
+    fn walks_code_across_banks() {
+        let rom = rom();
+        let disassembler = RomDisassembler::new(&rom);
+        assert_eq!(disassembler.banks(), 4);
+        assert!(disassembler.is_code(Location::new(0, 0x100)));
+        assert!(disassembler.is_code(Location::new(0, 0x15A)));
+        assert!(!disassembler.is_code(Location::new(0, 0x15B)));
+        assert!(!disassembler.is_code(Location::new(0, 0x104)));
+        assert!(disassembler.is_code(Location::new(2, 0x4002)));
+        assert!(!disassembler.is_code(Location::new(1, 0x4000)));
+
+        assert_eq!(disassembler.label(Location::new(0, 0x100)), Some("EntryPoint"));
+        assert_eq!(disassembler.label(Location::new(0, 0x150)), Some("Jump_000_0150"));
+        assert_eq!(disassembler.label(Location::new(2, 0x4000)), Some("Call_002_4000"));
+        assert_eq!(disassembler.label(Location::new(0, 0x38)), Some("RST_38"));
+    }
+
+    #[test]
+    fn emits_rgbds_source() {
+        let rom = rom();
+        let disassembler = RomDisassembler::new(&rom);
+        let bank_0 = disassembler.bank_source(0);
+        assert!(bank_0.contains("SECTION \"ROM Bank $000\", ROM0[$0000]"));
+        assert!(bank_0.contains("EntryPoint::\n    nop\n    jp Jump_000_0150\n"));
+        assert!(bank_0.contains("Jump_000_0150::\n    ld a, $02\n    ld [$2000], a\n    call Call_002_4000\n    jr nz, Jump_000_0150\n    ret\n"));
+        assert!(bank_0.contains("    db $12, $34"));
+        assert!(bank_0.contains("    ds 67, $00"));
+
+        let bank_2 = disassembler.bank_source(2);
+        assert!(bank_2.contains("SECTION \"ROM Bank $002\", ROMX[$4000], BANK[$2]"));
+        assert!(bank_2.contains("Call_002_4000::\n    swap a\n    ret\n"));
+        assert!(bank_2.contains(&format!("    ds {}, $FF", Cartridge::ROM_BANK_SIZE - 3)));
+    }
+}
@@ -5,6 +5,7 @@ use crate::GB::dma::dma_mmio::DmaMmio;
 use crate::GB::traits::Tick;
 use crate::GB::types::address::Address;
 use crate::GB::types::Byte;
+use crate::GB::save_state::{SaveState, SaveStateError, StateReader, StateWriter};
 
 pub struct DMA {
     t_cycle: u8,
@@ -54,6 +55,21 @@ impl Default for DMA {
     }
 }
 
+impl SaveState for DMA {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_u8(self.t_cycle);
+        state.write_u8(self.m_cycle);
+        state.write_bool(self.to_disable);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        self.t_cycle = state.read_u8()?;
+        self.m_cycle = state.read_u8()?;
+        self.to_disable = state.read_bool()?;
+        Ok(())
+    }
+}
+
 pub struct DmaCtx {
     pub dma: DMA,
     pub mmio: DmaMmio,
@@ -1,6 +1,7 @@
 use crate::GB::bus::BusDevice;
 use crate::GB::types::address::Address;
 use crate::GB::types::Byte;
+use crate::GB::save_state::{SaveState, SaveStateError, StateReader, StateWriter};
 use super::DMA;
 
 pub struct DmaMmio {
@@ -52,3 +53,16 @@ impl BusDevice for DmaMmio {
         }
     }
 }
+
+impl SaveState for DmaMmio {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_bool(self.enabled);
+        state.write_u8(self.value);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        self.enabled = state.read_bool()?;
+        self.value = state.read_u8()?;
+        Ok(())
+    }
+}
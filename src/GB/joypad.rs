@@ -1,4 +1,3 @@
-use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
 use crate::{mask_flag_enum_default_impl, default_enum_u8_bit_ops};
 use crate::GB::bus::{Bus, BusDevice, MmioContextWrite};
@@ -6,15 +5,7 @@ use crate::GB::cpu::cpu_mmio::CpuMmio;
 use crate::GB::cpu::registers::interrupt_registers::{InterruptFlagsMask, InterruptRegisters};
 use crate::GB::types::address::Address;
 use crate::GB::types::Byte;
-
-pub const GB_A_BUTTON: u32 = 0x00;
-pub const GB_B_BUTTON: u32 = 0x01;
-pub const GB_START_BUTTON: u32 = 0x02;
-pub const GB_SELECT_BUTTON: u32 = 0x03;
-pub const GB_UP_BUTTON: u32 = 0x04;
-pub const GB_DOWN_BUTTON: u32 = 0x05;
-pub const GB_LEFT_BUTTON: u32 = 0x06;
-pub const GB_RIGHT_BUTTON: u32 = 0x07;
+use crate::GB::save_state::{SaveState, SaveStateError, StateReader, StateWriter};
 
 #[derive(Copy, Clone, Debug)]
 #[repr(u8)]
@@ -51,33 +42,6 @@ pub enum JoypadButton {
     DPad(JoypadDPadBits),
 }
 
-#[derive(Copy, Clone, Debug)]
-struct JoypadMapping {
-    pub a: u32,
-    pub b: u32,
-    pub start: u32,
-    pub select: u32,
-    pub up: u32,
-    pub down: u32,
-    pub left: u32,
-    pub right: u32,
-}
-
-impl JoypadMapping {
-    pub fn new() -> Self {
-        Self {
-            a: 0,
-            b: 0,
-            start: 0,
-            select: 0,
-            up: 0,
-            down: 0,
-            left: 0,
-            right: 0,
-        }
-    }
-}
-
 #[derive(Copy, Clone, Debug)]
 pub struct JoypadInputs {
     pub a: bool,
@@ -162,6 +126,12 @@ impl Joypad {
         return_byte
     }
 
+    /// True if at least one of the lines of selected buttons is low (a selected button is pressed)
+    #[inline]
+    pub fn selected_line_low(&self) -> bool {
+        (self.as_byte() & 0b0000_1111) != 0b0000_1111
+    }
+
     pub fn set_button_status(&mut self, interrupt_registers: &mut InterruptRegisters, btn: JoypadButton, pressed: bool) {
         let old_byte = self.as_byte();
         match btn {
@@ -243,11 +213,6 @@ impl Joypad {
             right: self.right,
         }
     }
-
-    #[inline]
-    pub fn symbolic_display(&self) -> String {
-        self.joypad_view().symbolic_display()
-    }
 }
 
 impl Default for Joypad {
@@ -276,31 +241,6 @@ impl BusDevice for Joypad {
     }
 }
 
-struct InputMapping {
-    mapping: HashMap<u32, u32>,
-    gb_mapping: JoypadMapping,
-}
-
-impl InputMapping {
-    pub fn new() -> Self {
-        let mut hashmap: HashMap<u32, u32> = HashMap::new();
-        hashmap.insert(GB_A_BUTTON, 0x5A);
-        let mut gb_map = JoypadMapping::new();
-        Self {
-            mapping: hashmap,
-            gb_mapping: gb_map,
-        }
-    }
-
-    pub fn set_mapping(&mut self, input_code: u32, key_code: u32) {
-        self.mapping.insert(input_code, key_code);
-    }
-
-    pub fn get_key_code(&self, input_code: u32) -> Option<&u32> {
-        self.mapping.get(&input_code)
-    }
-}
-
 impl Display for Joypad {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(f, "Inputs: [UP: {}, DOWN: {}, LEFT: {}, RIGHT: {}] [A: {}, B: {}, SELECT: {}, START: {}]",
@@ -315,3 +255,24 @@ impl Display for Joypad {
         )
     }
 }
+
+impl SaveState for Joypad {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_u8(self.mode_selection);
+        state.write_u8(self.buttons_byte);
+        state.write_u8(self.dpad_byte);
+        for pressed in [self.a, self.b, self.start, self.select, self.up, self.down, self.left, self.right] {
+            state.write_bool(pressed);
+        }
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        self.mode_selection = state.read_u8()?;
+        self.buttons_byte = state.read_u8()?;
+        self.dpad_byte = state.read_u8()?;
+        for pressed in [&mut self.a, &mut self.b, &mut self.start, &mut self.select, &mut self.up, &mut self.down, &mut self.left, &mut self.right] {
+            *pressed = state.read_bool()?;
+        }
+        Ok(())
+    }
+}
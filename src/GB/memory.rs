@@ -4,13 +4,21 @@ pub mod vram;
 pub mod oam_memory;
 
 pub use self::{wram::WRAM, hram::HRAM, vram::VRAM, oam_memory::OamMemory};
+use crate::GB::save_state::{SaveState, SaveStateError, StateReader, StateWriter};
 
+#[allow(dead_code)]
 pub const RST_INSTRUCTIONS: usize = 0x0000; // Location in memory for RST instructions (not used on emulation)
+#[allow(dead_code)]
 pub const CARTRIDGE_HEADER_ADDRESS: usize = 0x0100; // Location for ROM metadata (as name) (not used on emulation)
+#[allow(dead_code)]
 pub const USER_PROGRAM_ADDRESS: usize = 0x0150; // Location User Program (not used on emulation)
+#[allow(dead_code)]
 pub const EXTERNAL_RAM_ADDRESS: usize = 0xA000; // External Extension memory
+#[allow(dead_code)]
 pub const RST_MEM_SIZE: usize = CARTRIDGE_HEADER_ADDRESS - RST_INSTRUCTIONS;
+#[allow(dead_code)]
 pub const CARTRIDGE_HEADER_SIZE: usize = USER_PROGRAM_ADDRESS - CARTRIDGE_HEADER_ADDRESS;
+#[allow(dead_code)]
 pub const USER_PROGRAM_MEM_SIZE: usize = vram::VRAM::VRAM_START_ADDRESS.as_usize() - USER_PROGRAM_ADDRESS;
 
 pub struct Memory<T> where T: Clone {
@@ -62,7 +70,17 @@ impl<T: Clone + std::marker::Copy>  Memory<T> {
     }
 }
 
+impl SaveState for Memory<u8> {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_bytes(&self.memory);
+    }
 
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        state.read_bytes_into(&mut self.memory, "memory")
+    }
+}
+
+#[allow(dead_code)]
 pub trait Length {
     fn len(&self) -> usize;
 }
@@ -73,34 +91,36 @@ mod test {
     use super::wram::WRAM;
     use crate::GB::types::{Byte, address::Address};
 
+    // WRAM storage is indexed from its start address
+    const OFFSET: usize = 0xD0;
+
     #[test]
     fn test_memory_read() {
         let mut ram = WRAM::new();
-        let address = Address(0xC0D0);
+        let address = Address(WRAM::WRAM_START_ADDRESS.as_u16() + OFFSET as u16);
         let data: Byte = 0x44;
-        ram.memory[address.as_index()] = data;
+        ram.memory[OFFSET] = data;
         assert_eq!(ram.read(address), data);
     }
 
     #[test]
     fn test_memory_write() {
         let mut ram = WRAM::new();
-        let address = Address(0xC0D0);
+        let address = Address(WRAM::WRAM_START_ADDRESS.as_u16() + OFFSET as u16);
         let data: Byte = 0x45;
-        ram.memory[address.as_index()] = 0xFF;
+        ram.memory[OFFSET] = 0xFF;
         ram.write(address, data);
-        assert_eq!(ram.memory[address.as_index()], data);
+        assert_eq!(ram.memory[OFFSET], data);
     }
 
 
     #[test]
     fn test_memory_read_vec() {
         let mut ram = WRAM::new();
-        let start_address = Address(0xC0D0);
         let data: Vec<Byte> = vec![0x44, 0x55, 0xF0, 0x0F, 0x75, 0x1A, 0xA1, 0x92];
         for i in 0..data.len() {
-            ram.memory[start_address.as_index() + i] = data[i];
+            ram.memory[OFFSET + i] = data[i];
         }
-        assert_eq!(ram.read_vec(start_address.as_u16(), data.len() as u16), data);
+        assert_eq!(ram.read_vec(OFFSET as u16, data.len() as u16), data);
     }
 }
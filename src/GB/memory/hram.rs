@@ -2,6 +2,7 @@ use crate::GB::bus::BusDevice;
 use super::{Length, Memory};
 use crate::GB::types::address::{Address, AddressRangeInclusive};
 use crate::GB::types::Byte;
+use crate::GB::save_state::{SaveState, SaveStateError, StateReader, StateWriter};
 
 pub struct HRAM {
     #[cfg(test)]
@@ -21,6 +22,7 @@ impl HRAM {
         }
     }
 
+    #[allow(dead_code)]
     pub fn read_vec(&self, start_address: u16, length: u16) -> &[u8] {
         &self.memory[start_address as usize..(start_address + length) as usize]
     }
@@ -55,3 +57,13 @@ impl BusDevice for HRAM {
         }
     }
 }
+
+impl SaveState for HRAM {
+    fn save_state(&self, state: &mut StateWriter) {
+        self.memory.save_state(state);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        self.memory.load_state(state)
+    }
+}
@@ -3,8 +3,11 @@ use crate::GB::ppu::oam::OAM;
 use super::{Length, Memory};
 use crate::GB::types::address::{Address, AddressRangeInclusive};
 use crate::GB::types::Byte;
+use crate::GB::save_state::{SaveState, SaveStateError, StateReader, StateWriter};
 
+#[allow(dead_code)]
 const OAM_ITEMS: u8 = 40;
+#[allow(dead_code)]
 pub type OamTable = [OAM; OAM_ITEMS as usize];
 
 pub struct OamMemory {
@@ -18,6 +21,7 @@ impl OamMemory {
     pub const OAM_START_ADDRESS: Address = Address(0xFE00);
     pub const OAM_END_ADDRESS: Address = Address(0xFE9F);
     pub const OAM_ADDRESS_RANGE: AddressRangeInclusive = Self::OAM_START_ADDRESS..=Self::OAM_END_ADDRESS; // Working memory
+    #[allow(dead_code)]
     pub const OAM_ITEMS: u8 = OAM_ITEMS;
 
     pub fn new() -> Self {
@@ -26,10 +30,12 @@ impl OamMemory {
         }
     }
 
+    #[allow(dead_code)]
     pub fn read_vec(&self, start_address: u16, length: u16) -> &[u8] {
         &self.memory[start_address as usize..(start_address + length) as usize]
     }
 
+    #[allow(dead_code)]
     pub fn oam(&self, id: u8) -> OAM {
         if id >= Self::OAM_ITEMS {
             panic!("OAM ID out of range. It should be between 0 and {}", Self::OAM_ITEMS);
@@ -43,6 +49,7 @@ impl OamMemory {
         )
     }
 
+    #[allow(dead_code)]
     pub fn oam_table(&self) -> OamTable {
         let mut oam_vec = Vec::with_capacity(Self::OAM_ITEMS as usize);
         for i in 0..Self::OAM_ITEMS {
@@ -81,3 +88,13 @@ impl BusDevice for OamMemory {
         }
     }
 }
+
+impl SaveState for OamMemory {
+    fn save_state(&self, state: &mut StateWriter) {
+        self.memory.save_state(state);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        self.memory.load_state(state)
+    }
+}
@@ -4,6 +4,7 @@ use crate::GB::ppu::tile::{GbPaletteId, Tile, TileDataArea, TileMapArea};
 use super::{Length, Memory};
 use crate::GB::types::address::{Address, AddressRangeInclusive};
 use crate::GB::types::Byte;
+use crate::GB::save_state::{SaveState, SaveStateError, StateReader, StateWriter};
 
 pub struct VRAM {
     #[cfg(test)]
@@ -15,22 +16,28 @@ pub struct VRAM {
 impl VRAM {
     pub const VRAM_TILE_BLOCK_0_START: Address = Address(0x8000);
     pub const VRAM_TILE_BLOCK_0_END: Address = Address(0x87FF);
+    #[allow(dead_code)]
     pub const VRAM_TILE_BLOCK_0_RANGE: AddressRangeInclusive = Self::VRAM_TILE_BLOCK_0_START..=Self::VRAM_TILE_BLOCK_0_END;
     pub const VRAM_TILE_BLOCK_1_START: Address = Address(0x8800);
     pub const VRAM_TILE_BLOCK_1_END: Address = Address(0x8FFF);
+    #[allow(dead_code)]
     pub const VRAM_TILE_BLOCK_1_RANGE: AddressRangeInclusive = Self::VRAM_TILE_BLOCK_1_START..=Self::VRAM_TILE_BLOCK_1_END;
     pub const VRAM_TILE_BLOCK_2_START: Address = Address(0x9000);
     pub const VRAM_TILE_BLOCK_2_END: Address = Address(0x97FF);
+    #[allow(dead_code)]
     pub const VRAM_TILE_BLOCK_2_RANGE: AddressRangeInclusive = Self::VRAM_TILE_BLOCK_2_START..=Self::VRAM_TILE_BLOCK_2_END;
     pub const VRAM_TILE_MAP_0_START: Address = Address(0x9800);
     pub const VRAM_TILE_MAP_0_END: Address = Address(0x9BFF);
+    #[allow(dead_code)]
     pub const VRAM_TILE_MAP_0_RANGE: AddressRangeInclusive = Self::VRAM_TILE_MAP_0_START..=Self::VRAM_TILE_MAP_0_END;
     pub const VRAM_TILE_MAP_1_START: Address = Address(0x9C00);
     pub const VRAM_TILE_MAP_1_END: Address = Address(0x9FFF);
+    #[allow(dead_code)]
     pub const VRAM_TILE_MAP_1_RANGE: AddressRangeInclusive = Self::VRAM_TILE_MAP_1_START..=Self::VRAM_TILE_MAP_1_END;
     pub const VRAM_START_ADDRESS: Address = Self::VRAM_TILE_BLOCK_0_START; // Video memory
     pub const VRAM_END_ADDRESS: Address = Self::VRAM_TILE_MAP_1_END; // Video memory
     pub const VRAM_ADDRESS_RANGE: AddressRangeInclusive = Self::VRAM_START_ADDRESS..=Self::VRAM_END_ADDRESS; // Video memory
+    #[allow(dead_code)]
     pub const VRAM_TILES_PER_MAP: u16 = Self::VRAM_TILE_MAP_1_START.as_u16() - Self::VRAM_TILE_MAP_0_START.as_u16();
 
     pub fn new() -> Self {
@@ -39,6 +46,7 @@ impl VRAM {
         }
     }
 
+    #[allow(dead_code)]
     pub fn read_vec(&self, start_address: u16, length: u16) -> &[Byte] {
         &self.memory[start_address as usize..(start_address + length) as usize]
     }
@@ -56,6 +64,7 @@ impl VRAM {
         base_tile_idx + tile_address_summer as usize * Tile::TILE_SIZE as usize
     }
 
+    #[allow(dead_code)]
     pub fn tile(&self, id: u8, tile_block: TileDataArea) -> Tile {
         let memory_idx = Self::tile_memory_index(id, tile_block);
         let slice = &self.memory[memory_idx..(memory_idx + Tile::TILE_SIZE as usize)];
@@ -91,6 +100,7 @@ impl VRAM {
         self.memory[base_index + (id & 0x3FF) as usize]
     }
 
+    #[allow(dead_code)]
     pub fn tile_id_map(&self, map_area: TileMapArea) -> [u8; Self::VRAM_TILES_PER_MAP as usize] {
         let map_range;
         match map_area {
@@ -109,6 +119,7 @@ impl VRAM {
         map_id
     }
 
+    #[allow(dead_code)]
     pub fn tile_map(&self, map_area: TileMapArea, data_area: TileDataArea) -> [Tile; Self::VRAM_TILES_PER_MAP as usize] {
         let map_id = self.tile_id_map(map_area);
         let mut tile_map = [Tile::default(); Self::VRAM_TILES_PER_MAP as usize];
@@ -148,3 +159,13 @@ impl BusDevice for VRAM {
         }
     }
 }
+
+impl SaveState for VRAM {
+    fn save_state(&self, state: &mut StateWriter) {
+        self.memory.save_state(state);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        self.memory.load_state(state)
+    }
+}
@@ -0,0 +1,193 @@
+use crate::GB::save_state::{SaveState, SaveStateError, StateReader, StateWriter};
+use crate::GB::types::Byte;
+
+#[cfg(test)]
+mod test {
+    use crate::GB::apu::apu_mmio::ApuMmio;
+    use crate::GB::model::GbModel;
+    use crate::GB::ppu::ppu_mmio::PpuMmio;
+    use crate::GB::timer::TimerRegisters;
+    use crate::GB::GB;
+
+    #[test]
+    fn dmg_post_boot_state() {
+        let gb = GB::new(None).unwrap();
+        let registers = gb.cpu().registers;
+        assert_eq!(gb.model(), GbModel::DMG);
+        assert_eq!(registers.get_af(), 0x0180);
+        assert_eq!(registers.get_bc(), 0x0013);
+        assert_eq!(registers.get_de(), 0x00D8);
+        assert_eq!(registers.get_hl(), 0x014D);
+        assert_eq!(registers.get_sp(), 0xFFFE);
+        assert_eq!(registers.get_pc(), 0x0100);
+        assert_eq!(gb.read(TimerRegisters::TIMER_DIV_REGISTER_ADDRESS), 0xAB);
+        assert_eq!(gb.read(TimerRegisters::TIMER_TAC_REGISTER_ADDRESS), 0xF8);
+        assert_eq!(gb.read(PpuMmio::LCDC_ADDRESS), 0x91);
+        assert_eq!(gb.read(PpuMmio::STAT_ADDRESS), 0x85);
+        assert_eq!(gb.read(PpuMmio::BGP_ADDRESS), 0xFC);
+        assert_eq!(gb.read(ApuMmio::APU_NR50_MASTER_VOLUME_ADDRESS), 0x77);
+        assert_eq!(gb.read(ApuMmio::APU_NR51_SOUND_PANNING_ADDRESS), 0xF3);
+        assert_eq!(gb.read(ApuMmio::APU_NR52_MASTER_CONTROL_ADDRESS), 0xF1);
+    }
+
+    #[test]
+    fn header_checksum_sets_half_carry_and_carry() {
+        let state = GbModel::DMG.post_boot_state();
+        assert_eq!(state.f(Some(0x00)), 0x80);
+        assert_eq!(state.f(Some(0x3D)), 0xB0);
+        assert_eq!(GbModel::CgbDmgMode.post_boot_state().f(Some(0x3D)), 0x80);
+    }
+
+    #[test]
+    fn a_register_identifies_model() {
+        let mut gb = GB::new(None).unwrap();
+        for (model, a) in [
+            (GbModel::DMG0, 0x01),
+            (GbModel::DMG, 0x01),
+            (GbModel::MGB, 0xFF),
+            (GbModel::SGB, 0x01),
+            (GbModel::CgbDmgMode, 0x11),
+        ] {
+            gb.set_model(model);
+            assert_eq!(gb.cpu().registers.get_a(), a, "{:?}", model);
+        }
+        assert_eq!(gb.cpu().registers.get_hl(), 0x007C);
+        assert_eq!(gb.read(ApuMmio::APU_NR52_MASTER_CONTROL_ADDRESS), 0xF1);
+        gb.set_model(GbModel::SGB);
+        assert_eq!(gb.read(ApuMmio::APU_NR52_MASTER_CONTROL_ADDRESS), 0xF0);
+    }
+}
+
+/// Game Boy hardware revision. Boot ROMs of each model leave a different state behind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GbModel {
+    DMG0,
+    #[default]
+    DMG,
+    MGB,
+    SGB,
+    CgbDmgMode, // CGB running a DMG cartridge
+}
+
+/// Registers and I/O values found by the cartridge at $0100 after the boot ROM
+pub struct PostBootState {
+    pub a: Byte,
+    pub f: Byte,
+    pub header_checksum_flags: bool, // H and C flags are set if header checksum is not $00
+    pub b: Byte,
+    pub c: Byte,
+    pub d: Byte,
+    pub e: Byte,
+    pub h: Byte,
+    pub l: Byte,
+    pub sp: u16,
+    pub div_counter: u16, // Whole system counter, DIV is the upper byte
+    pub lcdc: Byte,
+    pub stat: Byte,
+    pub bgp: Byte,
+    pub obp0: Byte,
+    pub obp1: Byte,
+    pub apu: &'static [Byte; 0x17], // NR10 ($FF10) to NR52 ($FF26)
+}
+
+const DMG_APU_STATE: [Byte; 0x17] = [
+    0x80, 0xBF, 0xF3, 0xFF, 0xBF, // NR10-NR14
+    0xFF, 0x3F, 0x00, 0xFF, 0xBF, // NR20-NR24
+    0x7F, 0xFF, 0x9F, 0xFF, 0xBF, // NR30-NR34
+    0xFF, 0xFF, 0x00, 0x00, 0xBF, // NR40-NR44
+    0x77, 0xF3, 0xF1,             // NR50-NR52
+];
+
+const SGB_APU_STATE: [Byte; 0x17] = [
+    0x80, 0xBF, 0xF3, 0xFF, 0xBF, // NR10-NR14
+    0xFF, 0x3F, 0x00, 0xFF, 0xBF, // NR20-NR24
+    0x7F, 0xFF, 0x9F, 0xFF, 0xBF, // NR30-NR34
+    0xFF, 0xFF, 0x00, 0x00, 0xBF, // NR40-NR44
+    0x77, 0xF3, 0xF0,             // NR50-NR52 - Channel 1 is not left on by SGB boot
+];
+
+// Values not documented for a model (depending on boot timing) are taken from the DMG ones
+const DMG0_POST_BOOT: PostBootState = PostBootState {
+    a: 0x01, f: 0x00, header_checksum_flags: false,
+    b: 0xFF, c: 0x13, d: 0x00, e: 0xC1, h: 0x84, l: 0x03,
+    sp: 0xFFFE,
+    div_counter: 0x1800,
+    lcdc: 0x91, stat: 0x81, bgp: 0xFC, obp0: 0xFF, obp1: 0xFF,
+    apu: &DMG_APU_STATE,
+};
+
+const DMG_POST_BOOT: PostBootState = PostBootState {
+    a: 0x01, f: 0x80, header_checksum_flags: true,
+    b: 0x00, c: 0x13, d: 0x00, e: 0xD8, h: 0x01, l: 0x4D,
+    sp: 0xFFFE,
+    div_counter: 0xABCC,
+    lcdc: 0x91, stat: 0x85, bgp: 0xFC, obp0: 0xFF, obp1: 0xFF,
+    apu: &DMG_APU_STATE,
+};
+
+const MGB_POST_BOOT: PostBootState = PostBootState {
+    a: 0xFF, f: 0x80, header_checksum_flags: true,
+    b: 0x00, c: 0x13, d: 0x00, e: 0xD8, h: 0x01, l: 0x4D,
+    sp: 0xFFFE,
+    div_counter: 0xABCC,
+    lcdc: 0x91, stat: 0x85, bgp: 0xFC, obp0: 0xFF, obp1: 0xFF,
+    apu: &DMG_APU_STATE,
+};
+
+const SGB_POST_BOOT: PostBootState = PostBootState {
+    a: 0x01, f: 0x00, header_checksum_flags: false,
+    b: 0x00, c: 0x14, d: 0x00, e: 0x00, h: 0xC0, l: 0x60,
+    sp: 0xFFFE,
+    div_counter: 0xABCC,
+    lcdc: 0x91, stat: 0x85, bgp: 0xFC, obp0: 0xFF, obp1: 0xFF,
+    apu: &SGB_APU_STATE,
+};
+
+const CGB_DMG_MODE_POST_BOOT: PostBootState = PostBootState {
+    a: 0x11, f: 0x80, header_checksum_flags: false,
+    b: 0x00, c: 0x00, d: 0x00, e: 0x08, h: 0x00, l: 0x7C,
+    sp: 0xFFFE,
+    div_counter: 0xABCC,
+    lcdc: 0x91, stat: 0x85, bgp: 0xFC, obp0: 0xFF, obp1: 0xFF,
+    apu: &DMG_APU_STATE,
+};
+
+impl GbModel {
+    pub(crate) fn post_boot_state(&self) -> &'static PostBootState {
+        match self {
+            GbModel::DMG0 => &DMG0_POST_BOOT,
+            GbModel::DMG => &DMG_POST_BOOT,
+            GbModel::MGB => &MGB_POST_BOOT,
+            GbModel::SGB => &SGB_POST_BOOT,
+            GbModel::CgbDmgMode => &CGB_DMG_MODE_POST_BOOT,
+        }
+    }
+}
+
+impl PostBootState {
+    /// F register value, given the cartridge header checksum (if any cartridge)
+    pub fn f(&self, header_checksum: Option<u8>) -> Byte {
+        match header_checksum {
+            Some(checksum) if self.header_checksum_flags && checksum != 0 => self.f | 0x30,
+            _ => self.f,
+        }
+    }
+}
+
+impl SaveState for GbModel {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_u8(*self as u8);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        *self = match state.read_u8()? {
+            0 => GbModel::DMG0,
+            1 => GbModel::DMG,
+            2 => GbModel::MGB,
+            3 => GbModel::SGB,
+            4 => GbModel::CgbDmgMode,
+            _ => return Err(SaveStateError::InvalidValue("model")),
+        };
+        Ok(())
+    }
+}
@@ -8,6 +8,7 @@ use crate::GB::ppu::ppu_mmio::PpuMmio;
 use crate::GB::ppu::oam::{OAM};
 use crate::GB::traits::Tick;
 use crate::GB::types::Byte;
+use crate::GB::save_state::{SaveState, SaveStateError, StateReader, StateWriter};
 use lcd::LCD;
 use crate::GB::cpu::registers::interrupt_registers::InterruptFlagsMask;
 use crate::GB::ppu::pixel::{PixelFifo, PixelFifoPaletteRegister};
@@ -56,6 +57,7 @@ impl PPU {
     pub const COLUMN_DOTS: u16 = 456;
     pub const SCREEN_COLUMNS: u16 = SCREEN_COLUMNS;
     pub const OAM_SCAN_DOTS: u16 = 80;
+    #[allow(dead_code)]
     pub const DOTS_PER_FRAME: u32 = (Self::SCAN_LINES as u32) * (Self::COLUMN_DOTS as u32);
     pub const SCREEN_PIXELS: u32 = (Self::SCREEN_LINES as u32) * (Self::SCREEN_COLUMNS as u32);
     pub const OAM_BUFFER: u8 = OAM_BUFFER;
@@ -292,6 +294,46 @@ impl fmt::Display for PPU {
     }
 }
 
+impl SaveState for PPU {
+    fn save_state(&self, state: &mut StateWriter) {
+        let frame: Vec<Byte> = self.frame.iter().map(|color_id| *color_id as Byte).collect();
+        state.write_bytes(&frame);
+        state.write_bool(self.fetching_mode == PpuFetchingMode::FetchSprite);
+        self.bg_fetcher.save_state(state);
+        self.sprite_fetcher.save_state(state);
+        state.write_bytes(&self.oam_loading);
+        state.write_u8(self.oam_scans);
+        state.write_u8(self.discarding_pixels);
+        state.write_u16(self.dot);
+        state.write_u8(self.screen_dot);
+        state.write_bool(self.switch_mode);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        let frame = state.read_bytes()?;
+        if frame.len() != self.frame.len() {
+            return Err(SaveStateError::InvalidValue("PPU frame"));
+        }
+        for (color_id, byte) in self.frame.iter_mut().zip(frame) {
+            *color_id = GbPaletteId::half_nibble_to_palette_map(*byte);
+        }
+        self.fetching_mode = if state.read_bool()? { PpuFetchingMode::FetchSprite } else { PpuFetchingMode::FetchBg };
+        self.bg_fetcher.load_state(state)?;
+        self.sprite_fetcher.load_state(state)?;
+        let oam_loading = state.read_bytes()?;
+        if oam_loading.len() > OAM::OAM_BYTES as usize {
+            return Err(SaveStateError::InvalidValue("PPU OAM loading"));
+        }
+        self.oam_loading = oam_loading.to_vec();
+        self.oam_scans = state.read_u8()?;
+        self.discarding_pixels = state.read_u8()?;
+        self.dot = state.read_u16()?;
+        self.screen_dot = state.read_u8()?;
+        self.switch_mode = state.read_bool()?;
+        Ok(())
+    }
+}
+
 pub struct PpuCtx {
     pub ppu: PPU,
     pub lcd: LCD,
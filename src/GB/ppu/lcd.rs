@@ -4,6 +4,7 @@ use crate::GB::ppu::pixel::{PixelFifo, PixelFifoPaletteRegister};
 use crate::GB::ppu::PPU;
 use crate::GB::ppu::tile::{GbColor, GbPaletteId};
 use crate::GB::traits::Tick;
+use crate::GB::save_state::{SaveState, SaveStateError, StateReader, StateWriter};
 
 pub struct LCD {
     screen: Box<[GbColor; PPU::SCREEN_PIXELS as usize]>,
@@ -23,6 +24,7 @@ impl LCD {
 
 
     #[inline]
+    #[allow(dead_code)]
     pub fn drawing_pixel(&self) -> usize {
         self.pixel
     }
@@ -43,4 +45,28 @@ impl Tick for LCD {
             }
         }
     }
-}
\ No newline at end of file
+}
+
+impl SaveState for LCD {
+    fn save_state(&self, state: &mut StateWriter) {
+        let screen: Vec<u8> = self.screen.iter().map(|color| *color as u8).collect();
+        state.write_bytes(&screen);
+        state.write_u64(self.pixel as u64);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        let screen = state.read_bytes()?;
+        if screen.len() != self.screen.len() {
+            return Err(SaveStateError::InvalidValue("LCD screen"));
+        }
+        for (color, byte) in self.screen.iter_mut().zip(screen) {
+            *color = GbColor::from(*byte & 0b11);
+        }
+        let pixel = state.read_u64()? as usize;
+        if pixel >= PPU::SCREEN_PIXELS as usize {
+            return Err(SaveStateError::InvalidValue("LCD pixel"));
+        }
+        self.pixel = pixel;
+        Ok(())
+    }
+}
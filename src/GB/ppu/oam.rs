@@ -4,6 +4,7 @@ use crate::{default_enum_u8, default_enum_u8_bit_ops};
 use crate::GB::memory::vram::VRAM;
 use crate::GB::ppu::palette::GbPalette;
 use crate::GB::types::Byte;
+use crate::GB::save_state::{SaveState, SaveStateError, StateReader, StateWriter};
 
 pub mod test;
 
@@ -19,9 +20,11 @@ pub enum AttributesMasks {
 default_enum_u8!(AttributesMasks {Priority = 128, YFlip = 64, XFlip = 32, Palette = 16});
 
 
+#[allow(dead_code)]
 const OAM_BYTE_SIZE: usize = 4;
 
 /// 4-bytes of a OAM item. Order of byte in memory is the following: y, x, tile_id, attributes
+#[cfg(test)]
 pub struct OamBytes {
     pub y: Byte,
     pub x: Byte,
@@ -65,6 +68,7 @@ impl OAM {
     }
 
     #[inline]
+    #[allow(dead_code)]
     pub fn id(&self) -> Option<u8> {
         self.id
     }
@@ -105,6 +109,7 @@ impl OAM {
     }
 
     #[inline]
+    #[cfg(test)]
     pub fn attributes_byte(&self) -> Byte {
         (self.priority as u8) << (AttributesMasks::Priority as u8).trailing_zeros()
             | (self.y_flip as u8) << (AttributesMasks::YFlip as u8).trailing_zeros()
@@ -115,6 +120,7 @@ impl OAM {
 
     #[inline]
     /// Return a OamBytes structure with u8 representation of OAM data.
+    #[cfg(test)]
     pub fn get_oam_bytes(&self) -> OamBytes {
         let attributes: Byte = self.attributes_byte();
 
@@ -127,25 +133,30 @@ impl OAM {
     }
 
     #[inline]
+    #[allow(dead_code)]
     pub fn get_y_screen(&self) -> isize {
         self.y as isize - TILE_HEIGHT as isize * 2
     }
 
     #[inline]
+    #[allow(dead_code)]
     pub fn get_x_screen(&self) -> isize {
         self.x as isize - TILE_WIDTH as isize
     }
 
     #[inline]
+    #[allow(dead_code)]
     pub fn get_tile_id(&self) -> u8 {
         self.tile_id
     }
 
     #[inline]
+    #[allow(dead_code)]
     pub fn tile(&self, vram: &VRAM) -> Tile {
         vram.tile(self.tile_id, TileDataArea::DataBlock01)
     }
 
+    #[allow(dead_code)]
     pub fn colored_tile(&self, vram: &VRAM, obp0: GbPalette, obp1: GbPalette) -> ColoredTile {
         let tile = self.tile(vram);
         tile.colored_tile(if self.palette { obp1 } else { obp0 })
@@ -170,3 +181,23 @@ impl PartialOrd<Self> for OAM {
         Some(self.cmp(other))
     }
 }
+
+impl SaveState for OAM {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_option_u8(self.id);
+        state.write_u8(self.y);
+        state.write_u8(self.x);
+        state.write_u8(self.tile_id);
+        state.write_u8(self.original_attributes);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        let id = state.read_option_u8()?;
+        let y = state.read_u8()?;
+        let x = state.read_u8()?;
+        let tile_id = state.read_u8()?;
+        // Attribute flags are decoded again from the raw byte
+        *self = OAM::new(y, x, tile_id, state.read_u8()?, id);
+        Ok(())
+    }
+}
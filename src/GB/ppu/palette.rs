@@ -26,6 +26,7 @@ impl GbPalette {
     }
 
     #[inline]
+    #[allow(dead_code)]
     pub fn byte_repr(&self) -> Byte {
         (self.id0 as u8) | (self.id1 as u8) << 2 | (self.id2 as u8) << 4 | (self.id3 as u8) << 6
     }
@@ -51,6 +52,7 @@ impl GbPalette {
     }
 
     #[inline]
+    #[allow(dead_code)]
     pub fn set_color(&mut self, id: GbPaletteId, color: GbColor) {
         match id {
             GbPaletteId::Id0 => self.id0 = color,
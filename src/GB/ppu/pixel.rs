@@ -1,5 +1,6 @@
 use crate::GB::ppu::tile::{GbColor, GbPaletteId};
 use crate::GB::types::Byte;
+use crate::GB::save_state::{SaveState, SaveStateError, StateReader, StateWriter};
 
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum PixelFifoPaletteRegister {
@@ -41,7 +42,32 @@ impl PixelFifo {
 
     #[inline]
     /// I've not found exact representation of bit order
+    #[allow(dead_code)]
     pub fn byte_repr(&self) -> Byte {
         todo!()
     }
 }
+
+impl SaveState for PixelFifo {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_u8(self.color_id as u8);
+        state.write_u8(match self.palette {
+            PixelFifoPaletteRegister::Bgp => 0,
+            PixelFifoPaletteRegister::Obp0 => 1,
+            PixelFifoPaletteRegister::Obp1 => 2,
+        });
+        state.write_bool(self.priority);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        self.color_id = GbPaletteId::half_nibble_to_palette_map(state.read_u8()?);
+        self.palette = match state.read_u8()? {
+            0 => PixelFifoPaletteRegister::Bgp,
+            1 => PixelFifoPaletteRegister::Obp0,
+            2 => PixelFifoPaletteRegister::Obp1,
+            _ => return Err(SaveStateError::InvalidValue("pixel FIFO palette")),
+        };
+        self.priority = state.read_bool()?;
+        Ok(())
+    }
+}
@@ -3,6 +3,7 @@ mod sprite_fetcher;
 
 pub use bg_fetcher::{BackgroundFetcher, BgFetchingMode};
 pub use sprite_fetcher::SpriteFetcher;
+use crate::GB::save_state::{SaveState, SaveStateError, StateReader, StateWriter};
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum PixelFetcherState {
@@ -15,3 +16,33 @@ pub enum PixelFetcherState {
     PushT1,
     PushT2,
 }
+
+impl SaveState for PixelFetcherState {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_u8(match self {
+            PixelFetcherState::FetchTileT1 => 0,
+            PixelFetcherState::FetchTileT2 => 1,
+            PixelFetcherState::FetchTileDataHighT1 => 2,
+            PixelFetcherState::FetchTileDataHighT2 => 3,
+            PixelFetcherState::FetchTileDataLowT1 => 4,
+            PixelFetcherState::FetchTileDataLowT2 => 5,
+            PixelFetcherState::PushT1 => 6,
+            PixelFetcherState::PushT2 => 7,
+        });
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        *self = match state.read_u8()? {
+            0 => PixelFetcherState::FetchTileT1,
+            1 => PixelFetcherState::FetchTileT2,
+            2 => PixelFetcherState::FetchTileDataHighT1,
+            3 => PixelFetcherState::FetchTileDataHighT2,
+            4 => PixelFetcherState::FetchTileDataLowT1,
+            5 => PixelFetcherState::FetchTileDataLowT2,
+            6 => PixelFetcherState::PushT1,
+            7 => PixelFetcherState::PushT2,
+            _ => return Err(SaveStateError::InvalidValue("pixel fetcher state")),
+        };
+        Ok(())
+    }
+}
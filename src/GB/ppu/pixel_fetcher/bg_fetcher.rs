@@ -7,6 +7,7 @@ use super::super::{PPU, oam::OAM, OAM_BUFFER};
 use crate::GB::traits::Tick;
 use crate::GB::types::Byte;
 use super::PixelFetcherState;
+use crate::GB::save_state::{SaveState, SaveStateError, StateReader, StateWriter};
 
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum BgFetchingMode {
@@ -31,6 +32,7 @@ pub struct BackgroundFetcher {
 }
 
 impl BackgroundFetcher {
+    #[allow(dead_code)]
     pub const OAM_BUFFER: u8 = PPU::OAM_BUFFER;
 
     pub fn new() -> Self {
@@ -57,6 +59,7 @@ impl BackgroundFetcher {
     }
 
     #[inline]
+    #[allow(dead_code)]
     pub fn window_drawn(&self) -> bool {
         self.window_drawn
     }
@@ -93,6 +96,7 @@ impl BackgroundFetcher {
     }
 
     #[inline]
+    #[allow(dead_code)]
     pub fn set_pixel_shift(&mut self, pixel_shift: u8) {
         self.pixel_shift = pixel_shift;
     }
@@ -217,3 +221,38 @@ impl Tick for BackgroundFetcher {
         }
     }
 }
+
+impl SaveState for BackgroundFetcher {
+    fn save_state(&self, state: &mut StateWriter) {
+        self.state.save_state(state);
+        state.write_bool(self.fetching_mode == BgFetchingMode::Window);
+        state.write_bool(self.first_cycle);
+        state.write_u8(self.bg_tile_x);
+        state.write_u8(self.window_line);
+        state.write_u8(self.pixel_shift);
+        state.write_u16(self.tile_map_id);
+        state.write_u8(self.tile_id);
+        state.write_u8(self.line_high_byte);
+        state.write_u8(self.line_low_byte);
+        self.tile_line.save_state(state);
+        state.write_bool(self.window_drawn);
+        state.write_u8(self.discarding_pixels);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        self.state.load_state(state)?;
+        self.fetching_mode = if state.read_bool()? { BgFetchingMode::Window } else { BgFetchingMode::Bg };
+        self.first_cycle = state.read_bool()?;
+        self.bg_tile_x = state.read_u8()?;
+        self.window_line = state.read_u8()?;
+        self.pixel_shift = state.read_u8()?;
+        self.tile_map_id = state.read_u16()?;
+        self.tile_id = state.read_u8()?;
+        self.line_high_byte = state.read_u8()?;
+        self.line_low_byte = state.read_u8()?;
+        self.tile_line.load_state(state)?;
+        self.window_drawn = state.read_bool()?;
+        self.discarding_pixels = state.read_u8()?;
+        Ok(())
+    }
+}
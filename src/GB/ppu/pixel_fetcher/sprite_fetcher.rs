@@ -8,6 +8,7 @@ use super::super::{PPU, oam::OAM, OAM_BUFFER};
 use crate::GB::traits::Tick;
 use crate::GB::types::Byte;
 use super::PixelFetcherState;
+use crate::GB::save_state::{SaveState, SaveStateError, StateReader, StateWriter};
 
 pub struct SpriteFetcher {
     state: PixelFetcherState,
@@ -19,6 +20,7 @@ pub struct SpriteFetcher {
 }
 
 impl SpriteFetcher {
+    #[allow(dead_code)]
     pub const OAM_BUFFER: u8 = PPU::OAM_BUFFER;
 
     pub fn new() -> Self {
@@ -33,6 +35,7 @@ impl SpriteFetcher {
     }
 
     #[inline]
+    #[allow(dead_code)]
     pub fn reset(&mut self) {
         self.state = PixelFetcherState::FetchTileT1;
     }
@@ -130,3 +133,23 @@ impl Tick for SpriteFetcher {
         }
     }
 }
+
+impl SaveState for SpriteFetcher {
+    fn save_state(&self, state: &mut StateWriter) {
+        self.state.save_state(state);
+        self.oam.save_state(state);
+        state.write_u8(self.line_y);
+        state.write_u8(self.line_high_byte);
+        state.write_u8(self.line_low_byte);
+        self.tile_line.save_state(state);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        self.state.load_state(state)?;
+        self.oam.load_state(state)?;
+        self.line_y = state.read_u8()?;
+        self.line_high_byte = state.read_u8()?;
+        self.line_low_byte = state.read_u8()?;
+        self.tile_line.load_state(state)
+    }
+}
@@ -1,17 +1,18 @@
 use std::collections::VecDeque;
 use crate::GB::bus::BusDevice;
 use crate::GB::memory::vram::VRAM;
-use crate::GB::ppu::tile::{GbColor, TileDataArea, TileMapArea};
+use crate::GB::ppu::tile::{GbColor, GbPaletteId, TileDataArea, TileMapArea};
 use crate::GB::ppu::lcd_control::{LCDCMasks, ObjSize, LCDC};
 use crate::GB::ppu::lcd_stat::{LCDStatMasks, LcdStat, LCD_STAT_WRITEABLE_MASK};
 use crate::GB::ppu::oam::OAM;
 use crate::GB::ppu::palette::GbPalette;
-use crate::GB::ppu::pixel::PixelFifo;
+use crate::GB::ppu::pixel::{PixelFifo, PixelFifoPaletteRegister};
 use crate::GB::types::address::{Address, AddressRangeInclusive};
 use crate::GB::types::Byte;
 use crate::GB::utils::write_masked_byte;
 use super::ppu_mode::PpuMode;
 use super::PPU;
+use crate::GB::save_state::{SaveState, SaveStateError, StateReader, StateWriter};
 
 pub struct PpuMmio {
     ppu_mode: PpuMode,
@@ -75,6 +76,12 @@ impl PpuMmio {
         }
     }
 
+    /// Raw STAT value, without masking read-only bits
+    #[inline]
+    pub fn set_stat(&mut self, value: Byte) {
+        self.stat = value;
+    }
+
     #[inline]
     pub fn ppu_mode(&self) -> PpuMode {
         self.ppu_mode
@@ -139,6 +146,7 @@ impl PpuMmio {
     }
 
     #[inline]
+    #[allow(dead_code)]
     pub fn clear_oam_buffer(&mut self) {
         self.oam_buffer.clear();
     }
@@ -179,6 +187,7 @@ impl PpuMmio {
     }
 
     #[inline]
+    #[allow(dead_code)]
     pub fn obj_fifo(&self) -> &VecDeque<PixelFifo> {
         &self.obj_fifo
     }
@@ -214,11 +223,13 @@ impl PpuMmio {
     }
 
     #[inline]
+    #[cfg(test)]
     pub fn lcdc(&self) -> Byte {
         self.lcdc
     }
 
     #[inline]
+    #[allow(dead_code)]
     pub fn stat(&self) -> Byte {
         self.stat
     }
@@ -239,21 +250,25 @@ impl PpuMmio {
     }
 
     #[inline]
+    #[allow(dead_code)]
     pub fn lyc(&self) -> Byte {
         self.ly
     }
 
     #[inline]
+    #[allow(dead_code)]
     pub fn bgp(&self) -> Byte {
         self.bgp
     }
 
     #[inline]
+    #[allow(dead_code)]
     pub fn obp0(&self) -> Byte {
         self.obp0
     }
 
     #[inline]
+    #[allow(dead_code)]
     pub fn obp1(&self) -> Byte {
         self.obp1
     }
@@ -274,6 +289,7 @@ impl PpuMmio {
     }
 
     #[inline]
+    #[allow(dead_code)]
     pub fn vram_mut(&mut self) -> &mut VRAM {
         &mut self.vram
     }
@@ -366,3 +382,52 @@ impl BusDevice for PpuMmio {
         }
     }
 }
+
+impl SaveState for PpuMmio {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_u8(self.ppu_mode as u8);
+        state.write_u8(self.prev_ppu_mode as u8);
+        state.write_len(self.oam_buffer.len());
+        for oam in self.oam_buffer.iter() {
+            oam.save_state(state);
+        }
+        for fifo in [&self.obj_fifo, &self.background_fifo] {
+            state.write_len(fifo.len());
+            for pixel in fifo.iter() {
+                pixel.save_state(state);
+            }
+        }
+        state.write_option_u8(self.pixel_output.map(|color| color as u8));
+        self.vram.save_state(state);
+        for register in [self.lcdc, self.stat, self.scy, self.scx, self.lx, self.ly, self.lyc, self.bgp, self.obp0, self.obp1, self.wy, self.wx] {
+            state.write_u8(register);
+        }
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        self.ppu_mode = PpuMode::from(state.read_u8()? & 0b11);
+        self.prev_ppu_mode = PpuMode::from(state.read_u8()? & 0b11);
+        let oam_buffer_len = state.read_len(PPU::OAM_BUFFER as usize, "PPU OAM buffer")?;
+        self.oam_buffer.clear();
+        for _ in 0..oam_buffer_len {
+            let mut oam = OAM::new(0, 0, 0, 0, None);
+            oam.load_state(state)?;
+            self.oam_buffer.push(oam);
+        }
+        for fifo in [&mut self.obj_fifo, &mut self.background_fifo] {
+            let fifo_len = state.read_len(16, "PPU pixel FIFO")?;
+            fifo.clear();
+            for _ in 0..fifo_len {
+                let mut pixel = PixelFifo::new(GbPaletteId::Id0, PixelFifoPaletteRegister::Bgp, false);
+                pixel.load_state(state)?;
+                fifo.push_back(pixel);
+            }
+        }
+        self.pixel_output = state.read_option_u8()?.map(|color| GbColor::from(color & 0b11));
+        self.vram.load_state(state)?;
+        for register in [&mut self.lcdc, &mut self.stat, &mut self.scy, &mut self.scx, &mut self.lx, &mut self.ly, &mut self.lyc, &mut self.bgp, &mut self.obp0, &mut self.obp1, &mut self.wy, &mut self.wx] {
+            *register = state.read_u8()?;
+        }
+        Ok(())
+    }
+}
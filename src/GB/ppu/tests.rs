@@ -1,160 +1,103 @@
-use std::cell::RefCell;
-use std::rc::Rc;
-use crate::GB::joypad::Joypad;
-use crate::GB::memory;
-use crate::GB::memory::{RAM, VRAM_BLOCK_0_ADDRESS, VRAM_BLOCK_1_ADDRESS, VRAM_BLOCK_2_ADDRESS};
-use crate::GB::memory::registers::LCDC;
-use crate::GB::ppu::PPU;
-use crate::GB::ppu::tile::TILE_SIZE;
+use crate::GB::bus::BusDevice;
+use crate::GB::memory::vram::VRAM;
+use crate::GB::ppu::lcd_control::{ObjSize, LCDC};
+use crate::GB::ppu::ppu_mmio::PpuMmio;
+use crate::GB::ppu::tile::{Tile, TileDataArea, TileMapArea};
+use crate::GB::types::address::Address;
 
-macro_rules! create_memory {
-    () => {
-        Rc::new(RefCell::new(RAM::new(Rc::new(RefCell::new(Joypad::default())))))
-    };
-}
-
-macro_rules! test_get_bit_flag_function {
-    ($func: ident, $get_func: ident, $register: ident, $mask: expr) => {
+macro_rules! test_lcdc_flag {
+    ($func: ident, $field: ident, $mask: expr, $set: expr, $unset: expr) => {
         #[test]
         fn $func() {
-            let mem = create_memory!();
-            mem.borrow_mut().write($register, $mask as u8);
-            let ppu = PPU::new(Rc::clone(&mem));
-            assert_eq!(ppu.$get_func(), true);
-            mem.borrow_mut().write($register, 0);
-            assert_eq!(ppu.$get_func(), false);
+            let mut ppu_mmio = PpuMmio::new();
+            ppu_mmio.write(PpuMmio::LCDC_ADDRESS, $mask);
+            assert_eq!(ppu_mmio.lcdc_view().$field, $set);
+            assert_eq!(LCDC::from_byte(!$mask).$field, $unset);
+            ppu_mmio.write(PpuMmio::LCDC_ADDRESS, 0);
+            assert_eq!(ppu_mmio.lcdc_view().$field, $unset);
         }
     };
 }
 
-macro_rules! test_set_bit_flag_function {
-    ($func: ident, $set_func: ident, $register: ident, $mask: expr) => {
-        #[test]
-        fn $func() {
-            let mem = create_memory!();
-            mem.borrow_mut().write($register, $mask as u8);
-            let mut ppu = PPU::new(Rc::clone(&mem));
-            ppu.$set_func(false);
-            assert_eq!((mem.borrow().read($register) & $mask) == 0, true);
-            ppu.$set_func(true);
-            assert_eq!((mem.borrow().read($register) & $mask) != 0, true);
-        }
-    };
-}
+const TEST_SPRITE: [u8; Tile::TILE_SIZE as usize] = [
+    0x00, 0x3C,
+    0x3C, 0x66,
+    0x5A, 0xDB,
+    0x5A, 0x81,
+    0x7E, 0x99,
+    0x5A, 0xDB,
+    0x3C, 0x7E,
+    0x00, 0x3C,
+];
 
-macro_rules! test_get_set_bit_flag_function {
-    ($func_get: ident, $func_set: ident, $ppu_get_func: ident, $ppu_set_func: ident, $register: ident, $mask: expr) => {
-        test_get_bit_flag_function!($func_get, $ppu_get_func, $register, $mask);
-        test_set_bit_flag_function!($func_set, $ppu_set_func, $register, $mask);
-    };
+fn write_tile(ppu_mmio: &mut PpuMmio, address: Address, tile: &[u8; Tile::TILE_SIZE as usize]) {
+    for (i, byte) in tile.iter().enumerate() {
+        ppu_mmio.write(Address(address.as_u16() + i as u16), *byte);
+    }
 }
 
 #[test]
 fn test_new_ppu() {
-    let mut mem = create_memory!();
-    mem.borrow_mut().write(LCDC, 0xFE);
-    let ppu = PPU::new(Rc::clone(&mem));
-    assert_eq!(ppu.read_memory(LCDC), 0xFE);
-    mem.borrow_mut().write(LCDC, 0xFD);
-    assert_eq!(ppu.read_memory(LCDC), 0xFD);
+    let mut ppu_mmio = PpuMmio::new();
+    ppu_mmio.write(PpuMmio::LCDC_ADDRESS, 0xFE);
+    assert_eq!(ppu_mmio.read(PpuMmio::LCDC_ADDRESS), 0xFE);
+    assert_eq!(ppu_mmio.lcdc(), 0xFE);
+    ppu_mmio.write(PpuMmio::LCDC_ADDRESS, 0xFD);
+    assert_eq!(ppu_mmio.read(PpuMmio::LCDC_ADDRESS), 0xFD);
 }
 
-test_get_set_bit_flag_function!(test_ppu_get_bg_win_enabled_flag, test_ppu_set_bg_win_enabled_flag,
-    get_bg_win_enabled_flag, set_bg_win_enabled_flag, LCDC, 0b0000_0001);
-test_get_set_bit_flag_function!(test_ppu_get_obj_enabled_flag, test_ppu_set_obj_enabled_flag,
-    get_obj_enabled_flag, set_obj_enabled_flag, LCDC, 0b0000_0010);
-test_get_set_bit_flag_function!(test_ppu_get_obj_size_flag, test_ppu_set_obj_size_flag,
-    get_obj_size_flag, set_obj_size_flag, LCDC, 0b0000_0100);
-test_get_set_bit_flag_function!(test_ppu_get_bg_tile_map_area_flag, test_ppu_set_bg_tile_map_area_flag,
-    get_bg_tile_map_area_flag, set_bg_tile_map_area_flag, LCDC, 0b0000_1000);
-test_get_set_bit_flag_function!(test_ppu_get_bg_win_tiles_area_flag, test_ppu_set_bg_win_tiles_area_flag,
-    get_bg_win_tiles_area_flag, set_bg_win_tiles_area_flag, LCDC, 0b0001_0000);
-test_get_set_bit_flag_function!(test_ppu_get_win_enabled_flag, test_ppu_set_win_enabled_flag,
-    get_win_enabled_flag, set_win_enabled_flag, LCDC, 0b0010_0000);
-test_get_set_bit_flag_function!(test_ppu_get_win_tile_map_area_flag, test_ppu_set_win_tile_map_area_flag,
-    get_win_tile_map_area_flag, set_win_tile_map_area_flag, LCDC, 0b0100_0000);
-test_get_set_bit_flag_function!(test_ppu_get_lcd_enabled_flag, test_ppu_set_lcd_enabled_flag,
-    get_lcd_enabled_flag, set_lcd_enabled_flag, LCDC, 0b1000_0000);
+test_lcdc_flag!(test_lcdc_bg_win_enabled_flag, bg_win_enabled, 0b0000_0001, true, false);
+test_lcdc_flag!(test_lcdc_obj_enabled_flag, obj_enabled, 0b0000_0010, true, false);
+test_lcdc_flag!(test_lcdc_obj_size_flag, obj_size, 0b0000_0100, ObjSize::Double, ObjSize::Single);
+test_lcdc_flag!(test_lcdc_bg_tile_map_area_flag, bg_tile_map, 0b0000_1000, TileMapArea::MapBlock1, TileMapArea::MapBlock0);
+test_lcdc_flag!(test_lcdc_bg_win_tiles_area_flag, bg_window_tile_area, 0b0001_0000, TileDataArea::DataBlock01, TileDataArea::DataBlock12);
+test_lcdc_flag!(test_lcdc_win_enabled_flag, window_enabled, 0b0010_0000, true, false);
+test_lcdc_flag!(test_lcdc_win_tile_map_area_flag, window_tile_map, 0b0100_0000, TileMapArea::MapBlock1, TileMapArea::MapBlock0);
+test_lcdc_flag!(test_lcdc_lcd_enabled_flag, lcd_enabled, 0b1000_0000, true, false);
 
 #[test]
 fn test_ppu_get_tile() {
-    let mut mem = create_memory!();
-    let test_sprite: [u8; TILE_SIZE] = [
-        0x00, 0x3C,
-        0x3C, 0x66,
-        0x5A, 0xDB,
-        0x5A, 0x81,
-        0x7E, 0x99,
-        0x5A, 0xDB,
-        0x3C, 0x7E,
-        0x00, 0x3C,
-    ];
+    let mut ppu_mmio = PpuMmio::new();
     let tile_id: u8 = 3;
-    let tile_address = VRAM_BLOCK_0_ADDRESS + TILE_SIZE * tile_id as usize;
-    for i in 0..TILE_SIZE {
-        mem.borrow_mut().write((tile_address + i) as u16, test_sprite[i]);
-    }
-    let ppu = PPU::new(Rc::clone(&mem));
-    let result_tile = ppu.get_tile(tile_id, false);
-    assert_eq!(result_tile.data, test_sprite);
+    let tile_address = Address(VRAM::VRAM_TILE_BLOCK_0_START.as_u16() + Tile::TILE_SIZE as u16 * tile_id as u16);
+    write_tile(&mut ppu_mmio, tile_address, &TEST_SPRITE);
+    let result_tile = ppu_mmio.vram().tile(tile_id, TileDataArea::DataBlock01);
+    assert_eq!(result_tile.dots(), Tile::from_bytes(&TEST_SPRITE).dots());
 }
 
 #[test]
 fn test_ppu_get_bg_win_tile() {
-    let mut mem = create_memory!();
-    let test_sprite: [u8; TILE_SIZE] = [
-        0x00, 0x3C,
-        0x3C, 0x66,
-        0x5A, 0xDB,
-        0x5A, 0x81,
-        0x7E, 0x99,
-        0x5A, 0xDB,
-        0x3C, 0x7E,
-        0x00, 0x3C,
-    ];
+    let mut ppu_mmio = PpuMmio::new();
+    let expected = Tile::from_bytes(&TEST_SPRITE);
+
+    // Tiles 128-255 are in block 1 for both addressing modes
     let tile_id: u8 = 128;
-    let tile_address = VRAM_BLOCK_1_ADDRESS;
-    for i in 0..TILE_SIZE {
-        mem.borrow_mut().write((tile_address + i) as u16, test_sprite[i]);
-    }
-    mem.borrow_mut().write(LCDC, 0xFF);
-    let ppu = PPU::new(Rc::clone(&mem));
-    let result_tile = ppu.get_tile(tile_id, true);
-    assert_eq!(result_tile.data, test_sprite);
+    write_tile(&mut ppu_mmio, VRAM::VRAM_TILE_BLOCK_1_START, &TEST_SPRITE);
+    ppu_mmio.write(PpuMmio::LCDC_ADDRESS, 0xFF);
+    let data_area = ppu_mmio.lcdc_view().bg_window_tile_area;
+    assert_eq!(ppu_mmio.vram().tile(tile_id, data_area).dots(), expected.dots());
+    assert_eq!(ppu_mmio.vram().tile(tile_id, TileDataArea::DataBlock12).dots(), expected.dots());
 
-    mem.borrow_mut().write(LCDC, 0);
-    for i in 0..TILE_SIZE {
-        mem.borrow_mut().write((tile_address + i) as u16, 0);
-    }
-    let tile_id: u8 = 0;
-    let tile_address = VRAM_BLOCK_2_ADDRESS;
-    for i in 0..TILE_SIZE {
-        mem.borrow_mut().write((tile_address + i) as u16, test_sprite[i]);
-    }
-    let result_tile = ppu.get_tile(tile_id, true);
-    assert_eq!(result_tile.data, test_sprite);
+    // With signed addressing tiles 0-127 are in block 2
+    write_tile(&mut ppu_mmio, VRAM::VRAM_TILE_BLOCK_1_START, &[0; Tile::TILE_SIZE as usize]);
+    write_tile(&mut ppu_mmio, VRAM::VRAM_TILE_BLOCK_2_START, &TEST_SPRITE);
+    ppu_mmio.write(PpuMmio::LCDC_ADDRESS, 0);
+    let data_area = ppu_mmio.lcdc_view().bg_window_tile_area;
+    assert_eq!(ppu_mmio.vram().tile(0, data_area).dots(), expected.dots());
+    assert_ne!(ppu_mmio.vram().tile(0, TileDataArea::DataBlock01).dots(), expected.dots());
 }
 
 #[test]
-fn test_ppu_get_bg_chr_id() {
-    let mut x = 0;
-    let mut y = 0;
-    let mem = create_memory!();
-    mem.borrow_mut().write(memory::registers::SCX, 0);
-    mem.borrow_mut().write(memory::registers::SCY, 0);
-    let ppu = PPU::new(mem);
-    assert_eq!(ppu.get_bg_chr_id(x, y), 0);
-    x = 50;
-    y = 2;
-    assert_eq!(ppu.get_bg_chr_id(x, y), 6);
-    assert_eq!(ppu.get_bg_chr_id(50, 9), 38);
-    assert_eq!(ppu.get_bg_chr_id(255, 255), 1023);
-
-    let mem = create_memory!();
-    mem.borrow_mut().write(memory::registers::SCX, 32);
-    mem.borrow_mut().write(memory::registers::SCY, 64);
-    let ppu = PPU::new(mem);
-    assert_eq!(ppu.get_bg_chr_id(0, 0), 260);
-    assert_eq!(ppu.get_bg_chr_id(12, 1), 261);
-    assert_eq!(ppu.get_bg_chr_id(255, 255), 227);
+fn test_ppu_tile_map_id() {
+    let mut ppu_mmio = PpuMmio::new();
+    ppu_mmio.write(VRAM::VRAM_TILE_MAP_0_START, 1);
+    ppu_mmio.write(Address(VRAM::VRAM_TILE_MAP_0_START.as_u16() + 38), 2);
+    ppu_mmio.write(VRAM::VRAM_TILE_MAP_1_END, 3);
+    let vram = ppu_mmio.vram();
+    assert_eq!(vram.tile_id(0, TileMapArea::MapBlock0), 1);
+    assert_eq!(vram.tile_id(38, TileMapArea::MapBlock0), 2);
+    assert_eq!(vram.tile_id(1023, TileMapArea::MapBlock1), 3);
+    // Map position wraps around the 32x32 map
+    assert_eq!(vram.tile_id(1024 + 38, TileMapArea::MapBlock0), 2);
+    assert_eq!(vram.tile_id(0, TileMapArea::MapBlock1), 0);
 }
@@ -52,6 +52,7 @@ impl GbPaletteId {
 
 #[derive(Debug, Copy, Clone)]
 #[repr(u32)]
+#[allow(dead_code)]
 pub enum RGBPalette {
     White = 0xD1EE54,
     LightGray = 0x94B860,
@@ -97,6 +98,7 @@ impl Tile {
         Self { data: tile }
     }
 
+    #[allow(dead_code)]
     pub fn from_bytes(bytes: &[Byte; 8 * 2]) -> Self {
         let mut pixels = [GbPaletteId::Id0; Self::TILE_DOTS as usize];
         for row in 0_usize..8 {
@@ -112,14 +114,17 @@ impl Tile {
     }
 
     #[inline]
+    #[allow(dead_code)]
     pub fn dots(&self) -> &[GbPaletteId; Self::TILE_DOTS as usize] {
         &self.data
     }
 
+    #[allow(dead_code)]
     pub fn get_printable_id_map(&self, doubled: bool) -> String {
         Self::palette_id_map_to_printable_id_map(&self.dots(), doubled)
     }
 
+    #[allow(dead_code)]
     pub fn palette_id_map_to_printable_id_map(array_map: &[GbPaletteId; 8 * 8], doubled: bool) -> String {
         let mut to_print = "".to_string();
         for i in 0..8 {
@@ -135,6 +140,7 @@ impl Tile {
         to_print
     }
 
+    #[allow(dead_code)]
     pub fn append_tile_id_map_to_string(&self, s: &String, doubled: bool) -> Result<String, String> {
         let s_lines: Vec<&str> = s.lines().collect();
 
@@ -153,6 +159,7 @@ impl Tile {
     }
 
     #[inline]
+    #[allow(dead_code)]
     pub fn colored_tile(&self, palette: GbPalette) -> ColoredTile {
         ColoredTile::new(self, palette)
     }
@@ -181,12 +188,14 @@ impl fmt::Display for Tile {
     }
 }
 
+#[allow(dead_code)]
 pub struct ColoredTile {
     data: [GbColor; Tile::TILE_DOTS as usize],
     tile: Tile,
     palette: GbPalette,
 }
 
+#[allow(dead_code)]
 impl ColoredTile {
     pub fn new(tile: &Tile, palette: GbPalette) -> Self {
         let mut colored_tile = [GbColor::Black; Tile::TILE_DOTS as usize];
@@ -257,7 +266,7 @@ mod test {
             0x7C, 0x56,     // █▓▓▓▓▓██   █▒█▒█▒▒█   ██░░▓▓░░▓▓░░▒▒██
             0x38, 0x7C,     // ██▓▓▓███   █▒▒▒▒▒██   ██▒▒░░░░░░▒▒████
         ];
-        let tile = Tile { data: tile_data };
+        let tile = Tile::from_bytes(&tile_data);
         let (c0, c1, c2, c3) =
             (GbPaletteId::Id0, GbPaletteId::Id1, GbPaletteId::Id2,GbPaletteId::Id3);
         let expected_id_map = [
@@ -270,7 +279,7 @@ mod test {
           c0, c3, c1, c3, c1, c3, c2, c0,
           c0, c2, c3, c3, c3, c2, c0, c0,
         ];
-        let result = tile.to_picture_map();
+        let result = *tile.dots();
         let printable_test = Tile::palette_id_map_to_printable_id_map(&expected_id_map, true);
         let printable_result = Tile::palette_id_map_to_printable_id_map(&result, true);
         println!("{}", printable_test);
@@ -1,6 +1,7 @@
 use crate::GB::types::Byte;
 use crate::utils::expand_byte_bits;
 use super::tile::{GbPaletteId, Tile};
+use crate::GB::save_state::{SaveState, SaveStateError, StateReader, StateWriter};
 
 pub struct TileLine {
     line: [GbPaletteId; Tile::TILE_WIDTH as usize],
@@ -25,6 +26,7 @@ impl TileLine {
     }
 
     #[inline]
+    #[allow(dead_code)]
     pub fn line_mut(&mut self) -> &mut [GbPaletteId; Tile::TILE_WIDTH as usize] {
         &mut self.line
     }
@@ -51,3 +53,18 @@ impl Default for TileLine {
         Self::new(0, 0)
     }
 }
+
+impl SaveState for TileLine {
+    fn save_state(&self, state: &mut StateWriter) {
+        for color_id in self.line {
+            state.write_u8(color_id as u8);
+        }
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        for color_id in self.line.iter_mut() {
+            *color_id = GbPaletteId::half_nibble_to_palette_map(state.read_u8()?);
+        }
+        Ok(())
+    }
+}
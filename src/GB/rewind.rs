@@ -0,0 +1,289 @@
+use std::collections::VecDeque;
+use crate::GB::save_state::SaveStateError;
+use crate::GB::types::Byte;
+use crate::GB::GB;
+
+/// Snapshots sharing the same keyframe: the keyframe is a full save state, following snapshots are stored
+/// as XOR deltas against it, RLE compressed.
+struct RewindGroup {
+    keyframe: Vec<Byte>,
+    deltas: Vec<Vec<Byte>>,
+}
+
+impl RewindGroup {
+    fn len(&self) -> usize {
+        1 + self.deltas.len()
+    }
+
+    fn size(&self) -> usize {
+        self.keyframe.len() + self.deltas.iter().map(|delta| delta.len()).sum::<usize>()
+    }
+}
+
+/// Ring buffer of machine snapshots taken on frame boundaries, used to step the emulation backward.
+/// Snapshots are taken every `frame_interval` frames and a new keyframe every `keyframe_interval` snapshots.
+/// Memory is bounded by `capacity` snapshots: oldest keyframe and all its deltas are dropped together.
+pub struct RewindBuffer {
+    groups: VecDeque<RewindGroup>,
+    capacity: usize,
+    frame_interval: usize,
+    keyframe_interval: usize,
+    frames: usize, // Frames since the last snapshot
+    restored: bool, // A snapshot has just been restored, its frame boundary must not be captured again
+}
+
+impl RewindBuffer {
+    pub const DEFAULT_CAPACITY: usize = 600;
+    pub const DEFAULT_FRAME_INTERVAL: usize = 1;
+    pub const DEFAULT_KEYFRAME_INTERVAL: usize = 60;
+
+    pub fn new(capacity: usize, frame_interval: usize, keyframe_interval: usize) -> Self {
+        Self {
+            groups: VecDeque::new(),
+            capacity: capacity.max(1),
+            frame_interval: frame_interval.max(1),
+            keyframe_interval: keyframe_interval.max(1),
+            frames: 0,
+            restored: false,
+        }
+    }
+
+    /// Number of snapshots stored
+    pub fn len(&self) -> usize {
+        self.groups.iter().map(|group| group.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+
+    /// Bytes used by stored snapshots
+    pub fn size(&self) -> usize {
+        self.groups.iter().map(|group| group.size()).sum()
+    }
+
+    pub fn clear(&mut self) {
+        self.groups.clear();
+        self.frames = 0;
+        self.restored = false;
+    }
+
+    /// To be called on every frame boundary (see `GB::is_vblank_edge`): a snapshot is taken every `frame_interval` calls.
+    /// Return true if a snapshot has been taken.
+    pub fn on_frame(&mut self, gb: &GB) -> bool {
+        if std::mem::take(&mut self.restored) {
+            return false;
+        }
+        self.frames += 1;
+        if self.frames < self.frame_interval {
+            return false;
+        }
+        self.push(gb.save_state());
+        true
+    }
+
+    /// Restore the most recent snapshot, removing it from the buffer. Return false if there is nothing to rewind.
+    /// The snapshot is kept if it can't be restored.
+    pub fn rewind(&mut self, gb: &mut GB) -> Result<bool, SaveStateError> {
+        let Some(state) = self.last() else {
+            return Ok(false);
+        };
+        gb.load_state(&state?)?;
+        self.drop_last();
+        self.restored = true;
+        Ok(true)
+    }
+
+    fn push(&mut self, state: Vec<Byte>) {
+        self.frames = 0;
+        match self.groups.back_mut() {
+            Some(group) if group.len() < self.keyframe_interval => {
+                let delta = encode_delta(&group.keyframe, &state);
+                group.deltas.push(delta);
+            }
+            _ => self.groups.push_back(RewindGroup { keyframe: state, deltas: Vec::new() }),
+        }
+        while self.len() > self.capacity && self.groups.len() > 1 {
+            self.groups.pop_front();
+        }
+    }
+
+    fn last(&self) -> Option<Result<Vec<Byte>, SaveStateError>> {
+        let group = self.groups.back()?;
+        match group.deltas.last() {
+            Some(delta) => Some(decode_delta(&group.keyframe, delta)),
+            None => Some(Ok(group.keyframe.clone())),
+        }
+    }
+
+    fn drop_last(&mut self) {
+        self.frames = 0;
+        if let Some(group) = self.groups.back_mut() {
+            if group.deltas.pop().is_none() {
+                self.groups.pop_back();
+            }
+        }
+    }
+}
+
+impl Default for RewindBuffer {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_CAPACITY, Self::DEFAULT_FRAME_INTERVAL, Self::DEFAULT_KEYFRAME_INTERVAL)
+    }
+}
+
+/// XOR the state against the keyframe (missing bytes are 0) and RLE compress the result.
+/// Delta is the 32-bit state length followed by runs, each one a 16-bit count of zero bytes
+/// followed by a 16-bit count of literal bytes and the literals themselves.
+fn encode_delta(keyframe: &[Byte], state: &[Byte]) -> Vec<Byte> {
+    let mut delta = Vec::new();
+    delta.extend_from_slice(&(state.len() as u32).to_le_bytes());
+    let xor: Vec<Byte> = state.iter().enumerate()
+        .map(|(i, byte)| byte ^ keyframe.get(i).copied().unwrap_or(0))
+        .collect();
+
+    let mut i = 0;
+    while i < xor.len() {
+        let zeros = xor[i..].iter().take(u16::MAX as usize).take_while(|byte| **byte == 0).count();
+        i += zeros;
+        let literals = xor[i..].iter().take(u16::MAX as usize).take_while(|byte| **byte != 0).count();
+        delta.extend_from_slice(&(zeros as u16).to_le_bytes());
+        delta.extend_from_slice(&(literals as u16).to_le_bytes());
+        delta.extend_from_slice(&xor[i..i + literals]);
+        i += literals;
+    }
+    delta
+}
+
+/// Inverse of `encode_delta`. Fails on a truncated delta or on runs going past the state length.
+fn decode_delta(keyframe: &[Byte], delta: &[Byte]) -> Result<Vec<Byte>, SaveStateError> {
+    let len = delta.get(0..4).ok_or(SaveStateError::UnexpectedEnd)?;
+    let len = u32::from_le_bytes([len[0], len[1], len[2], len[3]]) as usize;
+    let mut state: Vec<Byte> = (0..len).map(|i| keyframe.get(i).copied().unwrap_or(0)).collect();
+
+    let mut i = 0;
+    let mut position = 4;
+    while position < delta.len() {
+        let run = delta.get(position..position + 4).ok_or(SaveStateError::UnexpectedEnd)?;
+        let zeros = u16::from_le_bytes([run[0], run[1]]) as usize;
+        let literals = u16::from_le_bytes([run[2], run[3]]) as usize;
+        position += 4;
+        i += zeros;
+        let bytes = delta.get(position..position + literals).ok_or(SaveStateError::UnexpectedEnd)?;
+        let target = state.get_mut(i..i + literals).ok_or(SaveStateError::InvalidValue("rewind delta run"))?;
+        for (byte, xor) in target.iter_mut().zip(bytes) {
+            *byte ^= xor;
+        }
+        i += literals;
+        position += literals;
+    }
+    Ok(state)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::GB::cartridge::Cartridge;
+    use crate::GB::rewind::{decode_delta, encode_delta, RewindBuffer};
+    use crate::GB::save_state::SaveStateError;
+    use crate::GB::GB;
+
+    /// ROM-only cartridge spinning on a `JR -2` with LCD on
+    fn gb_with_cartridge() -> GB {
+        let mut rom = vec![0u8; 2 * Cartridge::ROM_BANK_SIZE];
+        rom[0x100..0x102].copy_from_slice(&[0x18, 0xFE]);
+        let mut gb = GB::new(None).unwrap();
+        gb.cartridge = Some(Cartridge::new_from_vec(rom, String::new()).unwrap());
+        gb
+    }
+
+    fn run_frame(gb: &mut GB) {
+        gb.tick();
+        while !gb.is_vblank_edge() {
+            gb.tick();
+        }
+    }
+
+    #[test]
+    fn delta_round_trip() {
+        let keyframe: Vec<u8> = (0..1000).map(|i| (i % 251) as u8).collect();
+        let mut state = keyframe.clone();
+        state[10] ^= 0x55;
+        state[500..520].fill(0xAA);
+        state.extend_from_slice(&[1, 2, 3]);
+
+        let delta = encode_delta(&keyframe, &state);
+        assert!(delta.len() < 64);
+        assert_eq!(decode_delta(&keyframe, &delta), Ok(state));
+        assert_eq!(decode_delta(&keyframe, &encode_delta(&keyframe, &keyframe[..200])), Ok(keyframe[..200].to_vec()));
+        let long_run = vec![0xFF; 0x20000];
+        assert_eq!(decode_delta(&[], &encode_delta(&[], &long_run)), Ok(long_run));
+    }
+
+    #[test]
+    fn malformed_delta_is_an_error() {
+        let keyframe = vec![0u8; 16];
+        let delta = encode_delta(&keyframe, &[1, 2, 3, 4]);
+        assert_eq!(decode_delta(&keyframe, &delta[..2]), Err(SaveStateError::UnexpectedEnd));
+        assert_eq!(decode_delta(&keyframe, &delta[..6]), Err(SaveStateError::UnexpectedEnd));
+        assert_eq!(decode_delta(&keyframe, &delta[..delta.len() - 1]), Err(SaveStateError::UnexpectedEnd));
+
+        // 4-byte state, with a run of 2 literals after 3 zeros
+        let overflow = [4, 0, 0, 0, 3, 0, 2, 0, 0xAA, 0xBB];
+        assert!(matches!(decode_delta(&keyframe, &overflow), Err(SaveStateError::InvalidValue(_))));
+    }
+
+    #[test]
+    fn rewind_steps_back_frame_by_frame() {
+        let mut gb = gb_with_cartridge();
+        let mut rewind = RewindBuffer::new(100, 1, 4);
+        let mut states = Vec::new();
+        for _ in 0..10 {
+            run_frame(&mut gb);
+            assert!(rewind.on_frame(&gb));
+            states.push(gb.save_state());
+        }
+        assert_eq!(rewind.len(), 10);
+
+        run_frame(&mut gb);
+        while let Some(expected) = states.pop() {
+            assert_eq!(rewind.rewind(&mut gb), Ok(true));
+            assert_eq!(gb.save_state(), expected);
+            assert!(!rewind.on_frame(&gb));
+        }
+        assert!(rewind.is_empty());
+        assert_eq!(rewind.rewind(&mut gb), Ok(false));
+    }
+
+    #[test]
+    fn failed_rewind_keeps_snapshot() {
+        let mut gb = gb_with_cartridge();
+        let mut rewind = RewindBuffer::new(100, 1, 4);
+        for _ in 0..2 {
+            run_frame(&mut gb);
+            rewind.on_frame(&gb);
+        }
+        let expected = gb.save_state();
+
+        let mut no_cartridge = GB::new(None).unwrap();
+        assert_eq!(rewind.rewind(&mut no_cartridge), Err(SaveStateError::CartridgeMismatch));
+        assert_eq!(rewind.len(), 2);
+
+        run_frame(&mut gb);
+        assert_eq!(rewind.rewind(&mut gb), Ok(true));
+        assert_eq!(gb.save_state(), expected);
+        assert_eq!(rewind.len(), 1);
+    }
+
+    #[test]
+    fn capacity_drops_oldest_keyframe() {
+        let mut gb = gb_with_cartridge();
+        let mut rewind = RewindBuffer::new(8, 2, 4);
+        for frame in 1..=40 {
+            run_frame(&mut gb);
+            assert_eq!(rewind.on_frame(&gb), frame % 2 == 0);
+            assert!(rewind.len() <= 8);
+        }
+        assert_eq!(rewind.len(), 8);
+        assert!(rewind.size() < 2 * gb.save_state().len() + 6 * 1024);
+    }
+}
@@ -0,0 +1,271 @@
+use crate::GB::types::Byte;
+
+/// Errors on save state restoring
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SaveStateError {
+    /// Data doesn't start with the save state magic bytes
+    InvalidMagic,
+    /// Save state produced by an incompatible version
+    UnsupportedVersion(u16),
+    /// Data ends before the whole machine is restored
+    UnexpectedEnd,
+    /// A field holds a value not allowed for it (field name)
+    InvalidValue(&'static str),
+    /// Save state belongs to another cartridge (or to a system without cartridge)
+    CartridgeMismatch,
+}
+
+impl std::fmt::Display for SaveStateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SaveStateError::InvalidMagic => write!(f, "Not a save state"),
+            SaveStateError::UnsupportedVersion(version) => write!(f, "Unsupported save state version {}", version),
+            SaveStateError::UnexpectedEnd => write!(f, "Save state is truncated"),
+            SaveStateError::InvalidValue(field) => write!(f, "Invalid value for {} in save state", field),
+            SaveStateError::CartridgeMismatch => write!(f, "Save state belongs to another cartridge"),
+        }
+    }
+}
+
+impl std::error::Error for SaveStateError {}
+
+impl From<SaveStateError> for std::io::Error {
+    fn from(err: SaveStateError) -> Self {
+        std::io::Error::new(std::io::ErrorKind::InvalidData, err)
+    }
+}
+
+/// Binary little endian writer of a save state
+pub struct StateWriter {
+    data: Vec<Byte>,
+}
+
+impl StateWriter {
+    pub fn new() -> Self {
+        Self {
+            data: Vec::new(),
+        }
+    }
+
+    pub fn into_vec(self) -> Vec<Byte> {
+        self.data
+    }
+
+    pub fn write_u8(&mut self, value: u8) {
+        self.data.push(value);
+    }
+
+    pub fn write_bool(&mut self, value: bool) {
+        self.data.push(value as u8);
+    }
+
+    pub fn write_u16(&mut self, value: u16) {
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn write_u32(&mut self, value: u32) {
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn write_u64(&mut self, value: u64) {
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    /// Optional byte, stored as a presence flag followed by the value
+    pub fn write_option_u8(&mut self, value: Option<u8>) {
+        self.write_bool(value.is_some());
+        self.write_u8(value.unwrap_or(0));
+    }
+
+    /// Items count of a variable size collection
+    pub fn write_len(&mut self, len: usize) {
+        self.write_u32(len as u32);
+    }
+
+    /// Variable size block, stored as a 32-bit length followed by data
+    pub fn write_bytes(&mut self, bytes: &[Byte]) {
+        self.write_u32(bytes.len() as u32);
+        self.data.extend_from_slice(bytes);
+    }
+}
+
+impl Default for StateWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Binary little endian reader of a save state
+pub struct StateReader<'a> {
+    data: &'a [Byte],
+    position: usize,
+}
+
+impl<'a> StateReader<'a> {
+    pub fn new(data: &'a [Byte]) -> Self {
+        Self {
+            data,
+            position: 0,
+        }
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.position >= self.data.len()
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [Byte], SaveStateError> {
+        let end = self.position.checked_add(len).ok_or(SaveStateError::UnexpectedEnd)?;
+        let bytes = self.data.get(self.position..end).ok_or(SaveStateError::UnexpectedEnd)?;
+        self.position = end;
+        Ok(bytes)
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8, SaveStateError> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub fn read_bool(&mut self) -> Result<bool, SaveStateError> {
+        match self.read_u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(SaveStateError::InvalidValue("bool")),
+        }
+    }
+
+    pub fn read_u16(&mut self) -> Result<u16, SaveStateError> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    pub fn read_u32(&mut self) -> Result<u32, SaveStateError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    pub fn read_u64(&mut self) -> Result<u64, SaveStateError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    pub fn read_option_u8(&mut self) -> Result<Option<u8>, SaveStateError> {
+        let present = self.read_bool()?;
+        let value = self.read_u8()?;
+        Ok(if present { Some(value) } else { None })
+    }
+
+    /// Items count of a variable size collection, which can't be greater than the given one
+    pub fn read_len(&mut self, max: usize, field: &'static str) -> Result<usize, SaveStateError> {
+        let len = self.read_u32()? as usize;
+        if len > max {
+            return Err(SaveStateError::InvalidValue(field));
+        }
+        Ok(len)
+    }
+
+    pub fn read_bytes(&mut self) -> Result<&'a [Byte], SaveStateError> {
+        let len = self.read_u32()? as usize;
+        self.take(len)
+    }
+
+    /// Read a block that must fill exactly the given buffer
+    pub fn read_bytes_into(&mut self, buffer: &mut [Byte], field: &'static str) -> Result<(), SaveStateError> {
+        let bytes = self.read_bytes()?;
+        if bytes.len() != buffer.len() {
+            return Err(SaveStateError::InvalidValue(field));
+        }
+        buffer.copy_from_slice(bytes);
+        Ok(())
+    }
+}
+
+/// Components whose internal state is part of a save state.
+/// `load_state` must read back exactly what `save_state` wrote, in the same order.
+pub trait SaveState {
+    fn save_state(&self, state: &mut StateWriter);
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError>;
+}
+
+#[cfg(test)]
+mod test {
+    use crate::GB::cartridge::header::RomHeader;
+    use crate::GB::cartridge::Cartridge;
+    use crate::GB::save_state::SaveStateError;
+    use crate::GB::types::address::Address;
+    use crate::GB::GB;
+
+    /// MBC1+RAM+BATTERY cartridge looping on writes to WRAM and cartridge RAM
+    fn gb_with_cartridge(title: &[u8]) -> GB {
+        let mut rom = vec![0u8; 2 * Cartridge::ROM_BANK_SIZE];
+        rom[RomHeader::HEADER_CONTROLLER_TYPE_ADDRESS.as_usize()] = 0x03;
+        rom[RomHeader::HEADER_RAM_SIZE_ADDRESS.as_usize()] = 0x02;
+        rom[0x134..0x134 + title.len()].copy_from_slice(title);
+        let program = [
+            0x3E, 0x0A,       // LD A, $0A
+            0xEA, 0x00, 0x00, // LD ($0000), A - Enable RAM
+            0x3C,             // INC A
+            0xEA, 0x00, 0xC0, // LD ($C000), A
+            0xEA, 0x00, 0xA0, // LD ($A000), A
+            0x18, 0xF7,       // JR -9
+        ];
+        rom[0x100..0x100 + program.len()].copy_from_slice(&program);
+
+        let mut gb = GB::new(None).unwrap();
+        gb.cartridge = Some(Cartridge::new_from_vec(rom, String::new()).unwrap());
+        gb
+    }
+
+    fn run(gb: &mut GB, t_cycles: usize) {
+        for _ in 0..t_cycles {
+            gb.tick();
+        }
+    }
+
+    #[test]
+    fn restored_state_continues_identically() {
+        let mut gb = gb_with_cartridge(b"SAVESTATE");
+        run(&mut gb, 70_000);
+        let snapshot = gb.save_state();
+        run(&mut gb, 12_345);
+        let expected = gb.save_state();
+        let expected_ram = gb.read(Address(0xA000));
+        assert_ne!(snapshot, expected);
+        assert_ne!(expected_ram, 0);
+
+        gb.load_state(&snapshot).unwrap();
+        assert_eq!(gb.save_state(), snapshot);
+        run(&mut gb, 12_345);
+        assert_eq!(gb.save_state(), expected);
+        assert_eq!(gb.read(Address(0xA000)), expected_ram);
+        assert_eq!(gb.read(Address(0xC000)), expected_ram);
+
+        // A fresh system with the same cartridge restores the same machine
+        let mut other = gb_with_cartridge(b"SAVESTATE");
+        other.load_state(&snapshot).unwrap();
+        run(&mut other, 12_345);
+        assert_eq!(other.save_state(), expected);
+    }
+
+    #[test]
+    fn invalid_state_leaves_system_untouched() {
+        let mut gb = gb_with_cartridge(b"SAVESTATE");
+        run(&mut gb, 1_000);
+        let snapshot = gb.save_state();
+        run(&mut gb, 1_000);
+        let current = gb.save_state();
+
+        assert_eq!(gb.load_state(b"NOPE"), Err(SaveStateError::InvalidMagic));
+        assert_eq!(gb.load_state(&snapshot[..snapshot.len() - 1]), Err(SaveStateError::UnexpectedEnd));
+        let mut newer = snapshot.clone();
+        newer[8] = 0xFF; // Version follows the 4-byte length and 4-byte magic
+        assert_eq!(gb.load_state(&newer), Err(SaveStateError::UnsupportedVersion(0xFF)));
+        assert_eq!(gb.save_state(), current);
+    }
+
+    #[test]
+    fn state_is_bound_to_cartridge() {
+        let snapshot = gb_with_cartridge(b"SAVESTATE").save_state();
+
+        let mut other_game = gb_with_cartridge(b"OTHERGAME");
+        assert_eq!(other_game.load_state(&snapshot), Err(SaveStateError::CartridgeMismatch));
+        let mut no_cartridge = GB::new(None).unwrap();
+        assert_eq!(no_cartridge.load_state(&snapshot), Err(SaveStateError::CartridgeMismatch));
+    }
+}
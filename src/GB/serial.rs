@@ -0,0 +1,234 @@
+pub mod serial_mmio;
+
+use crate::GB::bus::{Bus, MmioContextWrite};
+use crate::GB::cpu::registers::interrupt_registers::InterruptFlagsMask;
+use crate::GB::serial::serial_mmio::SerialMmio;
+use crate::GB::traits::Tick;
+use crate::GB::types::address::{Address, AddressRangeInclusive};
+use crate::GB::types::Byte;
+use crate::GB::save_state::{SaveState, SaveStateError, StateReader, StateWriter};
+
+#[cfg(test)]
+mod test {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use crate::GB::cpu::registers::interrupt_registers::{InterruptFlagsMask, InterruptRegisters};
+    use crate::GB::ppu::ppu_mmio::PpuMmio;
+    use crate::GB::serial::{LinkCable, Serial};
+    use crate::GB::types::address::Address;
+    use crate::GB::types::Byte;
+    use crate::GB::GB;
+
+    struct TestLinkCable {
+        response: Byte,
+        sent: Rc<RefCell<Vec<Byte>>>,
+    }
+
+    impl LinkCable for TestLinkCable {
+        fn exchange(&mut self, data: Byte) -> Byte {
+            self.sent.borrow_mut().push(data);
+            self.response
+        }
+
+        fn external_transfer(&mut self, data: Byte) -> Option<Byte> {
+            self.exchange(data);
+            Some(self.response)
+        }
+    }
+
+    /// GB with CPU looping on a JR -2 in WRAM
+    fn gb_for_serial() -> GB {
+        let mut gb = GB::new(None).unwrap();
+        gb.write(PpuMmio::LCDC_ADDRESS, 0x00);
+        gb.write(InterruptRegisters::IF_ADDRESS, 0);
+        gb.write(Address(0xC000), 0x18);
+        gb.write(Address(0xC001), 0xFE);
+        gb.cpu_ctx.cpu.registers.set_pc(0xC000);
+        gb
+    }
+
+    fn serial_irq(gb: &GB) -> bool {
+        gb.read(InterruptRegisters::IF_ADDRESS) & InterruptFlagsMask::Serial as u8 != 0
+    }
+
+    #[test]
+    fn internal_clock_transfer_without_peer_receives_ff() {
+        let mut gb = gb_for_serial();
+        gb.write(Serial::SERIAL_SB_ADDRESS, 0x42);
+        gb.write(Serial::SERIAL_SC_ADDRESS, 0x81);
+        assert_eq!(gb.read(Serial::SERIAL_SC_ADDRESS), 0xFF);
+        for _ in 0..(7 * 512) {
+            gb.tick();
+        }
+        assert!(!serial_irq(&gb));
+        for _ in 0..(2 * 512) {
+            gb.tick();
+        }
+        assert!(serial_irq(&gb));
+        assert_eq!(gb.read(Serial::SERIAL_SB_ADDRESS), 0xFF);
+        assert_eq!(gb.read(Serial::SERIAL_SC_ADDRESS), 0x7F);
+    }
+
+    #[test]
+    fn internal_clock_transfer_exchange_with_peer() {
+        let sent = Rc::new(RefCell::new(Vec::new()));
+        let mut gb = gb_for_serial();
+        gb.connect_link_cable(Box::new(TestLinkCable { response: 0x5A, sent: sent.clone() }));
+        gb.write(Serial::SERIAL_SB_ADDRESS, 0x42);
+        gb.write(Serial::SERIAL_SC_ADDRESS, 0x81);
+        for _ in 0..(9 * 512) {
+            gb.tick();
+        }
+        assert!(serial_irq(&gb));
+        assert_eq!(gb.read(Serial::SERIAL_SB_ADDRESS), 0x5A);
+        assert_eq!(*sent.borrow(), vec![0x42]);
+    }
+
+    #[test]
+    fn external_clock_transfer_waits_for_peer() {
+        let mut gb = gb_for_serial();
+        gb.write(Serial::SERIAL_SB_ADDRESS, 0x42);
+        gb.write(Serial::SERIAL_SC_ADDRESS, 0x80);
+        for _ in 0..(16 * 512) {
+            gb.tick();
+        }
+        assert!(!serial_irq(&gb));
+        assert_eq!(gb.read(Serial::SERIAL_SC_ADDRESS), 0xFE);
+
+        let sent = Rc::new(RefCell::new(Vec::new()));
+        gb.connect_link_cable(Box::new(TestLinkCable { response: 0x24, sent: sent.clone() }));
+        gb.tick();
+        assert!(serial_irq(&gb));
+        assert_eq!(gb.read(Serial::SERIAL_SB_ADDRESS), 0x24);
+        assert_eq!(*sent.borrow(), vec![0x42]);
+    }
+}
+
+/// A peer plugged into the link port.
+pub trait LinkCable {
+    /// Send a byte to the peer with GB driving the clock (internal clock). Return the byte received from the peer.
+    fn exchange(&mut self, data: Byte) -> Byte;
+
+    /// Polled every T-Cycle while GB waits for the peer clock (external clock). Return the received byte
+    /// when peer has clocked a whole transfer, `data` is the byte sent by GB.
+    fn external_transfer(&mut self, data: Byte) -> Option<Byte>;
+}
+
+/// Nothing connected to the link port: input line is pulled up and no external clock is provided.
+pub struct DisconnectedLinkCable;
+
+impl LinkCable for DisconnectedLinkCable {
+    fn exchange(&mut self, _data: Byte) -> Byte {
+        0xFF
+    }
+
+    fn external_transfer(&mut self, _data: Byte) -> Option<Byte> {
+        None
+    }
+}
+
+pub struct Serial {
+    link_cable: Box<dyn LinkCable>,
+    transferring: bool,
+    incoming: Byte, // Byte received from peer, shifted into SB one bit at time
+    shifted_bits: u8,
+    clock_bit: bool, // Last level of the system counter bit used as internal serial clock
+}
+
+impl Serial {
+    pub const SERIAL_SB_ADDRESS: Address = Address(0xFF01);
+    pub const SERIAL_SC_ADDRESS: Address = Address(0xFF02);
+    pub const SERIAL_REGISTERS_RANGE: AddressRangeInclusive = Self::SERIAL_SB_ADDRESS..=Self::SERIAL_SC_ADDRESS;
+    const INTERNAL_CLOCK_BIT: u8 = 8; // 8192 Hz - 512 T-Cycles for each bit
+
+    pub fn new() -> Self {
+        Self {
+            link_cable: Box::new(DisconnectedLinkCable),
+            transferring: false,
+            incoming: 0xFF,
+            shifted_bits: 0,
+            clock_bit: false,
+        }
+    }
+
+    pub fn connect(&mut self, link_cable: Box<dyn LinkCable>) {
+        self.link_cable = link_cable;
+        self.transferring = false;
+    }
+
+    pub fn disconnect(&mut self) {
+        self.connect(Box::new(DisconnectedLinkCable));
+    }
+
+    fn complete_transfer(&mut self, ctx: &mut MmioContextWrite) {
+        self.transferring = false;
+        ctx.serial_mmio.end_transfer();
+        ctx.cpu_mmio.interrupt_registers_mut().set_if_bit(InterruptFlagsMask::Serial);
+    }
+}
+
+impl Tick for Serial {
+    fn tick(&mut self, _bus: &mut Bus, ctx: &mut MmioContextWrite) {
+        let clock_bit = (ctx.timer_mmio.div_counter() >> Self::INTERNAL_CLOCK_BIT) & 1 != 0;
+        let clock_falling_edge = self.clock_bit && !clock_bit;
+        self.clock_bit = clock_bit;
+
+        if !ctx.serial_mmio.transfer_enabled() {
+            // Transfer is aborted if SC bit 7 is reset
+            self.transferring = false;
+            return;
+        }
+
+        if ctx.serial_mmio.internal_clock() {
+            if !self.transferring {
+                self.transferring = true;
+                self.shifted_bits = 0;
+                self.incoming = self.link_cable.exchange(ctx.serial_mmio.sb());
+            }
+            if clock_falling_edge {
+                // MSB out first, received bit is shifted in from LSB
+                let in_bit = (self.incoming >> (7 - self.shifted_bits)) & 1;
+                ctx.serial_mmio.set_sb((ctx.serial_mmio.sb() << 1) | in_bit);
+                self.shifted_bits += 1;
+                if self.shifted_bits == 8 {
+                    self.complete_transfer(ctx);
+                }
+            }
+        } else {
+            self.transferring = true;
+            if let Some(byte) = self.link_cable.external_transfer(ctx.serial_mmio.sb()) {
+                ctx.serial_mmio.set_sb(byte);
+                self.complete_transfer(ctx);
+            }
+        }
+    }
+}
+
+impl Default for Serial {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SaveState for Serial {
+    /// Link cable is a host resource and it's not part of the state
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_bool(self.transferring);
+        state.write_u8(self.incoming);
+        state.write_u8(self.shifted_bits);
+        state.write_bool(self.clock_bit);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        self.transferring = state.read_bool()?;
+        self.incoming = state.read_u8()?;
+        self.shifted_bits = state.read_u8()?;
+        self.clock_bit = state.read_bool()?;
+        Ok(())
+    }
+}
+
+pub struct SerialCtx {
+    pub serial: Serial,
+    pub mmio: SerialMmio,
+}
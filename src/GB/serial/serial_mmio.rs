@@ -0,0 +1,87 @@
+use crate::GB::bus::BusDevice;
+use crate::GB::types::address::Address;
+use crate::GB::types::Byte;
+use crate::GB::save_state::{SaveState, SaveStateError, StateReader, StateWriter};
+use super::Serial;
+
+pub struct SerialMmio {
+    sb: Byte,
+    sc: Byte,
+}
+
+impl SerialMmio {
+    const SC_TRANSFER_ENABLE: Byte = 0b1000_0000;
+    const SC_CLOCK_SELECT: Byte = 0b0000_0001;
+    const SC_UNUSED_BITS: Byte = 0b0111_1110;
+
+    pub fn new() -> Self {
+        Self {
+            sb: 0,
+            sc: 0,
+        }
+    }
+
+    #[inline]
+    pub fn sb(&self) -> Byte {
+        self.sb
+    }
+
+    #[inline]
+    pub fn set_sb(&mut self, value: Byte) {
+        self.sb = value;
+    }
+
+    /// True while a transfer is requested or in progress (SC bit 7)
+    #[inline]
+    pub fn transfer_enabled(&self) -> bool {
+        self.sc & Self::SC_TRANSFER_ENABLE != 0
+    }
+
+    /// True if GB is the master and drives the serial clock (SC bit 0)
+    #[inline]
+    pub fn internal_clock(&self) -> bool {
+        self.sc & Self::SC_CLOCK_SELECT != 0
+    }
+
+    #[inline]
+    pub fn end_transfer(&mut self) {
+        self.sc &= !Self::SC_TRANSFER_ENABLE;
+    }
+}
+
+impl BusDevice for SerialMmio {
+    fn read(&self, address: Address) -> Byte {
+        match address {
+            Serial::SERIAL_SB_ADDRESS => self.sb,
+            Serial::SERIAL_SC_ADDRESS => self.sc | Self::SC_UNUSED_BITS,
+            _ => 0xFF,
+        }
+    }
+
+    fn write(&mut self, address: Address, data: Byte) {
+        match address {
+            Serial::SERIAL_SB_ADDRESS => self.sb = data,
+            Serial::SERIAL_SC_ADDRESS => self.sc = data & !Self::SC_UNUSED_BITS,
+            _ => {}
+        }
+    }
+}
+
+impl Default for SerialMmio {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SaveState for SerialMmio {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_u8(self.sb);
+        state.write_u8(self.sc);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        self.sb = state.read_u8()?;
+        self.sc = state.read_u8()?;
+        Ok(())
+    }
+}
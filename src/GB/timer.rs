@@ -1,14 +1,10 @@
 use crate::{define_enum_u8, default_enum_u8_bit_ops, mask_flag_enum_default_impl};
 use crate::GB::bus::{Bus, BusDevice, MmioContextWrite};
+use crate::GB::cpu::registers::interrupt_registers::InterruptFlagsMask;
 use crate::GB::traits::Tick;
 use crate::GB::types::address::{Address, AddressRangeInclusive};
 use crate::GB::types::Byte;
-use crate::utils::{falling_edge as utils, falling_edge};
-
-pub const M256_CLOCK_CYCLES: u64 = 256;
-pub const M4_CLOCK_CYCLES: u64 = 4;
-pub const M16_CLOCK_CYCLES: u64 = 16;
-pub const M64_CLOCK_CYCLES: u64 = 64;
+use crate::GB::save_state::{SaveState, SaveStateError, StateReader, StateWriter};
 
 pub const M256_CLOCK_MODE: u8 = 0b00;
 pub const M4_CLOCK_MODE: u8 = 0b01;
@@ -55,12 +51,128 @@ impl TACClock {
     }
 }
 
+#[cfg(test)]
+mod test {
+    use crate::GB::bus::BusDevice;
+    use crate::GB::cpu::registers::interrupt_registers::{InterruptFlagsMask, InterruptRegisters};
+    use crate::GB::ppu::ppu_mmio::PpuMmio;
+    use crate::GB::timer::{TACClock, TimerRegisters};
+    use crate::GB::GB;
+
+    fn tick_t_cycles(timer: &mut TimerRegisters, t_cycles: usize) -> bool {
+        let mut reloaded = false;
+        for _ in 0..t_cycles {
+            reloaded |= timer.tick_counter();
+        }
+        reloaded
+    }
+
+    #[test]
+    fn div_increments_every_256_t_cycles_and_resets_on_write() {
+        let mut timer = TimerRegisters::new();
+        tick_t_cycles(&mut timer, 256 * 3 + 10);
+        assert_eq!(timer.read(TimerRegisters::TIMER_DIV_REGISTER_ADDRESS), 3);
+        timer.write(TimerRegisters::TIMER_DIV_REGISTER_ADDRESS, 0x5A);
+        assert_eq!(timer.div_counter(), 0);
+    }
+
+    #[test]
+    fn tima_increments_with_selected_clock() {
+        let mut timer = TimerRegisters::new();
+        timer.write(TimerRegisters::TIMER_TAC_REGISTER_ADDRESS, 0b101); // Enabled, 16 T-Cycles
+        tick_t_cycles(&mut timer, 16 * 5);
+        assert_eq!(timer.tima(), 5);
+        assert_eq!(timer.read(TimerRegisters::TIMER_TAC_REGISTER_ADDRESS), 0xFD);
+    }
+
+    #[test]
+    fn div_write_glitch_increments_tima() {
+        let mut timer = TimerRegisters::new();
+        timer.set_tac_info(true, TACClock::M64); // Bit 7
+        tick_t_cycles(&mut timer, 0x80);
+        assert_eq!(timer.tima(), 0);
+        timer.write(TimerRegisters::TIMER_DIV_REGISTER_ADDRESS, 0);
+        assert_eq!(timer.tima(), 1);
+    }
+
+    #[test]
+    fn tac_write_glitch_increments_tima() {
+        let mut timer = TimerRegisters::new();
+        timer.set_tac_info(true, TACClock::M16); // Bit 5
+        tick_t_cycles(&mut timer, 0x20);
+        // Disabling timer with selected bit high
+        timer.write(TimerRegisters::TIMER_TAC_REGISTER_ADDRESS, 0b001);
+        assert_eq!(timer.tima(), 1);
+
+        // Changing clock select from an high bit (5) to a low one (3)
+        timer.write(TimerRegisters::TIMER_TAC_REGISTER_ADDRESS, 0b110);
+        timer.write(TimerRegisters::TIMER_TAC_REGISTER_ADDRESS, 0b101);
+        assert_eq!(timer.tima(), 2);
+    }
+
+    #[test]
+    fn tima_overflow_reload_is_delayed() {
+        let mut timer = TimerRegisters::new();
+        timer.set_tac_info(true, TACClock::M4);
+        timer.set_tma(0xAB);
+        timer.set_tima(0xFF);
+        assert!(!tick_t_cycles(&mut timer, 16));
+        assert_eq!(timer.tima(), 0x00);
+        assert!(!tick_t_cycles(&mut timer, 3));
+        assert_eq!(timer.tima(), 0x00);
+        assert!(tick_t_cycles(&mut timer, 1));
+        assert_eq!(timer.tima(), 0xAB);
+    }
+
+    #[test]
+    fn tima_write_during_overflow_cancels_reload() {
+        let mut timer = TimerRegisters::new();
+        timer.set_tac_info(true, TACClock::M4);
+        timer.set_tma(0xAB);
+        timer.set_tima(0xFF);
+        tick_t_cycles(&mut timer, 16);
+        timer.write(TimerRegisters::TIMER_TIMA_REGISTER_ADDRESS, 0x10);
+        assert!(!tick_t_cycles(&mut timer, 3));
+        assert_eq!(timer.tima(), 0x10);
+    }
+
+    #[test]
+    fn writes_during_reload_cycle() {
+        let mut timer = TimerRegisters::new();
+        timer.set_tac_info(true, TACClock::M4);
+        timer.set_tma(0xAB);
+        timer.set_tima(0xFF);
+        assert!(tick_t_cycles(&mut timer, 20));
+        // TIMA write is ignored, TMA write is propagated to TIMA
+        timer.write(TimerRegisters::TIMER_TIMA_REGISTER_ADDRESS, 0x10);
+        assert_eq!(timer.tima(), 0xAB);
+        timer.write(TimerRegisters::TIMER_TMA_REGISTER_ADDRESS, 0x20);
+        assert_eq!(timer.tima(), 0x20);
+    }
+
+    #[test]
+    fn timer_overflow_requests_interrupt() {
+        let mut gb = GB::new(None).unwrap();
+        gb.write(PpuMmio::LCDC_ADDRESS, 0x00);
+        gb.write(InterruptRegisters::IF_ADDRESS, 0);
+        gb.write(TimerRegisters::TIMER_TMA_REGISTER_ADDRESS, 0xF0);
+        gb.write(TimerRegisters::TIMER_TIMA_REGISTER_ADDRESS, 0xFE);
+        gb.write(TimerRegisters::TIMER_TAC_REGISTER_ADDRESS, 0b101);
+        for _ in 0..64 {
+            gb.tick();
+        }
+        assert_eq!(gb.read(InterruptRegisters::IF_ADDRESS) & InterruptFlagsMask::Timer as u8, InterruptFlagsMask::Timer as u8);
+        assert!(gb.read(TimerRegisters::TIMER_TIMA_REGISTER_ADDRESS) >= 0xF0);
+    }
+}
+
 pub struct TimerRegisters {
-    cycles: u16,
     div_counter: u16, // DIV is composed of 2 8bit-subregister, the lower one is not visible to dev while the upper one is the one commonly named DIV timer
     tima: u8,
     tma: u8,
     tac: u8,
+    overflow_t_cycles: u8, // T-Cycles left before reloading TIMA with TMA after an overflow (TIMA reads $00 meanwhile)
+    reload_t_cycles: u8, // T-Cycles left of the M-Cycle in which TIMA has been reloaded (TIMA writes are ignored)
 }
 
 impl TimerRegisters {
@@ -71,16 +183,20 @@ impl TimerRegisters {
     pub const TIMER_START_ADDRESS: Address = Self::TIMER_DIV_REGISTER_ADDRESS;
     pub const TIMER_END_ADDRESS: Address = Self::TIMER_TAC_REGISTER_ADDRESS;
     pub const TIMER_REGISTERS_RANGE: AddressRangeInclusive = Self::TIMER_START_ADDRESS..=Self::TIMER_END_ADDRESS;
+
+    const TAC_UNUSED_BITS: u8 = 0b1111_1000;
+    const TIMA_RELOAD_DELAY: u8 = 4; // T-Cycles
 }
 
 impl TimerRegisters {
     pub fn new() -> Self {
         Self {
-            cycles: 0,
             div_counter: 0,
             tima: 0,
             tma: 0,
             tac: 0,
+            overflow_t_cycles: 0,
+            reload_t_cycles: 0,
         }
     }
 
@@ -88,8 +204,20 @@ impl TimerRegisters {
         (self.div_counter >> 8) as u8
     }
 
+    pub fn div_counter(&self) -> u16 {
+        self.div_counter
+    }
+
+    /// Set the whole system counter, without any TIMA side effect
+    pub fn set_div_counter(&mut self, value: u16) {
+        self.div_counter = value;
+    }
+
+    /// Reset the whole system counter (any write on DIV). TIMA is incremented if the selected bit was high.
     pub fn reset_div(&mut self) {
+        let old_signal = self.timer_signal();
         self.div_counter = 0;
+        self.check_signal_falling_edge(old_signal);
     }
 
     pub fn is_tma_active(&self) -> bool {
@@ -108,41 +236,73 @@ impl TimerRegisters {
         self.tac
     }
 
+    #[cfg(test)]
     pub fn set_tma(&mut self, val: u8) {
         self.tma = val;
     }
 
+    #[cfg(test)]
     pub fn set_tima(&mut self, val: u8) {
         self.tima = val;
     }
 
+    #[cfg(test)]
     pub fn set_tac_info(&mut self, enabled: bool, clock_mode: TACClock) {
-        self.tac = clock_mode as u8 | (if enabled {0x01} else {0x00} << 2);
+        self.tac = clock_mode as u8 | ((if enabled {0x01} else {0x00}) << 2);
     }
 
+    #[allow(dead_code)]
     pub fn set_tac(&mut self, val: u8) {
         self.tac = val;
     }
 
+    #[allow(dead_code)]
     pub fn set_tac_mode(&mut self, mode: TACClock) {
         self.tac |= mode as u8;
     }
-}
 
-impl Tick for TimerRegisters {
-    fn tick(&mut self, bus: &mut Bus, ctx: &mut MmioContextWrite) {
-        self.cycles = self.cycles.wrapping_add(1);
-        let old_div_counter = self.div_counter;
-        let old_tma = self.tma;
-        self.div_counter = self.div_counter.wrapping_add(1); // Increment DIV
-        if falling_edge(old_div_counter, self.div_counter, TACClock::get_timer_bit_from_u8(self.tac & TACMask::TimerClock)) {
-            // Change/Increment TMA as needed
-            if (self.tima == 0xFF) {
+    /// Signal feeding TIMA: selected system counter bit AND timer enable. TIMA increments on its falling edge.
+    fn timer_signal(&self) -> bool {
+        let bit = TACClock::get_timer_bit_from_u8(self.tac & TACMask::TimerClock);
+        self.is_tma_active() && (self.div_counter >> bit) & 1 != 0
+    }
+
+    fn check_signal_falling_edge(&mut self, old_signal: bool) {
+        if old_signal && !self.timer_signal() {
+            self.increment_tima();
+        }
+    }
+
+    fn increment_tima(&mut self) {
+        if self.tima == 0xFF {
+            // TIMA stays $00 for a M-Cycle before being reloaded
+            self.tima = 0;
+            self.overflow_t_cycles = Self::TIMA_RELOAD_DELAY;
+        } else {
+            self.tima += 1;
+        }
+    }
+
+    /// Advance the system counter by 1 T-Cycle. Return true when TIMA has been reloaded with TMA
+    /// after an overflow (a Timer interrupt must be requested).
+    pub fn tick_counter(&mut self) -> bool {
+        let mut reloaded = false;
+        if self.reload_t_cycles > 0 {
+            self.reload_t_cycles -= 1;
+        }
+        if self.overflow_t_cycles > 0 {
+            self.overflow_t_cycles -= 1;
+            if self.overflow_t_cycles == 0 {
                 self.tima = self.tma;
-            } else {
-                self.tima += 1;
+                self.reload_t_cycles = Self::TIMA_RELOAD_DELAY;
+                reloaded = true;
             }
         }
+
+        let old_signal = self.timer_signal();
+        self.div_counter = self.div_counter.wrapping_add(1); // Increment DIV
+        self.check_signal_falling_edge(old_signal);
+        reloaded
     }
 }
 
@@ -152,12 +312,86 @@ impl BusDevice for TimerRegisters {
             Self::TIMER_DIV_REGISTER_ADDRESS => self.div(),
             Self::TIMER_TIMA_REGISTER_ADDRESS => self.tima(),
             Self::TIMER_TMA_REGISTER_ADDRESS => self.tma(),
-            Self::TIMER_TAC_REGISTER_ADDRESS => self.tac(),
+            Self::TIMER_TAC_REGISTER_ADDRESS => self.tac() | Self::TAC_UNUSED_BITS,
             _ => unreachable!(),
         }
     }
 
     fn write(&mut self, address: Address, data: Byte) {
-        todo!()
+        match address {
+            Self::TIMER_DIV_REGISTER_ADDRESS => self.reset_div(),
+            Self::TIMER_TIMA_REGISTER_ADDRESS => {
+                if self.reload_t_cycles > 0 {
+                    // TMA reload wins over writes in the same M-Cycle
+                    return;
+                }
+                // Writing during the overflow delay cancels both reload and interrupt
+                self.overflow_t_cycles = 0;
+                self.tima = data;
+            }
+            Self::TIMER_TMA_REGISTER_ADDRESS => {
+                self.tma = data;
+                if self.reload_t_cycles > 0 {
+                    self.tima = data;
+                }
+            }
+            Self::TIMER_TAC_REGISTER_ADDRESS => {
+                // Disabling timer or changing clock select can produce a falling edge (and a TIMA increment)
+                let old_signal = self.timer_signal();
+                self.tac = data & !Self::TAC_UNUSED_BITS;
+                self.check_signal_falling_edge(old_signal);
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl Default for TimerRegisters {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SaveState for TimerRegisters {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_u16(self.div_counter);
+        for register in [self.tima, self.tma, self.tac, self.overflow_t_cycles, self.reload_t_cycles] {
+            state.write_u8(register);
+        }
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        self.div_counter = state.read_u16()?;
+        for register in [&mut self.tima, &mut self.tma, &mut self.tac, &mut self.overflow_t_cycles, &mut self.reload_t_cycles] {
+            *register = state.read_u8()?;
+        }
+        Ok(())
+    }
+}
+
+pub struct Timer {}
+
+impl Timer {
+    pub fn new() -> Self {
+        Self {}
     }
 }
+
+impl Tick for Timer {
+    fn tick(&mut self, _bus: &mut Bus, ctx: &mut MmioContextWrite) {
+        if ctx.timer_mmio.tick_counter() {
+            ctx.cpu_mmio.interrupt_registers_mut().set_if_bit(InterruptFlagsMask::Timer);
+        }
+    }
+}
+
+impl Default for Timer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct TimerCtx {
+    pub timer: Timer,
+    pub mmio: TimerRegisters,
+}
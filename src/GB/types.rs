@@ -1,4 +1,3 @@
 pub mod address;
 
 pub type Byte = u8;
-pub type Word = u16;
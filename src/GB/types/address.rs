@@ -1,4 +1,4 @@
-use std::ops::{Add, BitAnd, BitOr, BitXor, Range, RangeInclusive, Shl, Shr, Sub};
+use std::ops::{Add, BitAnd, BitOr, BitXor, RangeInclusive, Shl, Shr, Sub};
 
 #[repr(transparent)]
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
@@ -211,6 +211,4 @@ impl Shr<Address> for u16 {
 // Other Addres-related types
 // --------------------------
 
-pub type AddressSize = u16;
-pub type AddressRange = Range<Address>;
 pub type AddressRangeInclusive = RangeInclusive<Address>;
@@ -0,0 +1,53 @@
+//! Game Boy (DMG) emulator core.
+//!
+//! [`GameBoy`] is the whole system: insert a [`Cartridge`], feed joypad inputs and run it by
+//! T-Cycle ([`GameBoy::tick`]), instruction ([`GameBoy::step_instruction`]) or frame ([`GameBoy::run_frame`]),
+//! reading back the screen with [`GameBoy::frame`] and the sound with [`GameBoy::take_audio_samples`].
+//! Tools working on SM83 code find the disassembler in [`disasm`] and the assembler in [`asm`].
+
+#[macro_use]
+extern crate lazy_static;
+
+#[macro_use]
+mod utils;
+#[allow(non_snake_case)]
+pub(crate) mod GB;
+#[cfg(test)]
+mod tests;
+
+pub use crate::GB::GB as GameBoy;
+pub use crate::GB::RunSummary;
+pub use crate::GB::apu::AudioSample;
+pub use crate::GB::cpu::{CpuLockup, CpuRegisters, InterruptType, RetiredInstruction};
+pub use crate::GB::cpu::bus_trace::{BusCycle, BusOperation, BusTrace};
+pub use crate::GB::cartridge::Cartridge;
+pub use crate::GB::cartridge::controller::CartridgeControllerType;
+pub use crate::GB::cartridge::header::{CgbSupport, HeaderError, RomHeader};
+pub use crate::GB::cartridge::header::new_license_code::NewLicenseCode;
+pub use crate::GB::cartridge::header::old_license_code::OldLicenseCode;
+pub use crate::GB::joypad::{JoypadButtonsBits, JoypadDPadBits, JoypadInputs};
+pub use crate::GB::model::GbModel;
+pub use crate::GB::ppu::tile::GbColor;
+pub use crate::GB::rewind::RewindBuffer;
+pub use crate::GB::save_state::SaveStateError;
+pub use crate::GB::serial::LinkCable;
+pub use crate::GB::types::address::Address;
+
+/// Static SM83 disassembler
+pub mod disasm {
+    pub use crate::GB::disasm::{decode, DisassembledInstruction, Flow, Operand};
+    pub use crate::GB::disasm::rom::{Location, RomDisassembler};
+}
+
+/// SM83 micro-ops, as recorded in [`BusCycle::micro_op`]
+pub mod microcode {
+    pub use crate::GB::cpu::instructions::microcode::{
+        AluOp, ByteBit, CheckCondition, IduOp, MCycleOp, MicroOp, SetFlag, VectorAddress,
+    };
+    pub use crate::GB::cpu::registers::core_registers::{Registers16Bit, Registers8Bit};
+}
+
+/// SM83 assembler, taking RGBDS syntax
+pub mod asm {
+    pub use crate::GB::asm::{assemble, AsmError, AsmErrorKind, Program};
+}
@@ -1,48 +1,40 @@
 #[macro_use]
 extern crate lazy_static;
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
 use std::{env, thread};
 use std::collections::HashMap;
 use std::fs;
 use std::fs::{File, OpenOptions};
 use std::io::{Read, stdout, Write};
+use std::path::Path;
 use std::sync::mpsc;
 use std::time;
 use std::time::{Duration, Instant};
 use winit;
 
-mod GB;
-#[macro_use]
-mod utils;
-#[cfg(test)]
-mod tests;
-
-use crate::GB::cpu::instructions::Instruction;
-use crate::GB::memory::Length;
-use crate::GB::ppu::tile::{ColoredTile, GbColor, Tile, TileDataArea, TileMapArea};
-use GB::cpu::{CPU};
-use crate::GB::addresses;
-use crate::GB::cpu::{InterruptType, CPU_INTERRUPT_CYCLES};
-use crate::GB::joypad::{JoypadButtonsBits, JoypadDPadBits};
-use crate::GB::memory::oam_memory::OamTable;
-use crate::GB::memory::vram::VRAM;
-use crate::GB::ppu::palette::GbPalette;
-use crate::GB::ppu::PPU;
-use crate::GB::ppu::ppu_mode::PpuMode;
-use crate::GB::types::address::Address;
+use yaemulator::disasm::RomDisassembler;
+use yaemulator::{Address, GameBoy, GbColor, InterruptType, JoypadButtonsBits, JoypadDPadBits, RewindBuffer};
+
+const LCDC_ADDRESS: Address = Address(0xFF40);
+const STAT_ADDRESS: Address = Address(0xFF41);
+const IF_ADDRESS: Address = Address(0xFF0F);
+const IE_ADDRESS: Address = Address(0xFFFF);
 
 #[derive(Parser, Debug)]
-#[command(version, about, long_about = None)]
+#[command(version, about, long_about = None, subcommand_negates_reqs = true)]
 struct Args {
-    /// Name of the person to greet
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Boot ROM to run before the cartridge (boot is skipped if not given)
     #[arg(short, long)]
-    bios: String,
+    bios: Option<String>,
 
     /// Name of the person to greet
-    #[arg(short, long)]
-    rom: String,
+    #[arg(short, long, required = true)]
+    rom: Option<String>,
 
     /// Number of times to greet
     #[arg(short, long, default_value_t = 1)]
@@ -52,6 +44,19 @@ struct Args {
     log_file: String,
 }
 
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Disassemble a ROM into RGBDS sources, one `bank_XXX.asm` per bank (build with `rgbasm` and `rgblink`)
+    Disasm {
+        /// ROM to disassemble
+        rom: String,
+
+        /// Directory where bank sources are written
+        #[arg(short, long, default_value = "disasm")]
+        output: String,
+    },
+}
+
 lazy_static! {
     pub static ref CONSOLE_PALETTE: HashMap<GbColor, char> = HashMap::from([
         (GbColor::White, '█'),
@@ -61,11 +66,11 @@ lazy_static! {
     ]);
 }
 
-fn frame_string(frame: &[GbColor; PPU::SCREEN_PIXELS as usize], doubled: bool) -> String {
+fn frame_string(frame: &[GbColor], doubled: bool) -> String {
     let mut s = "".to_string();
-    for i in 0..PPU::SCREEN_LINES {
-        for j in 0..PPU::SCREEN_COLUMNS {
-            let frame_char = CONSOLE_PALETTE[&frame[j as usize + i as usize * PPU::SCREEN_COLUMNS as usize]];
+    for i in 0..GameBoy::SCREEN_HEIGHT {
+        for j in 0..GameBoy::SCREEN_WIDTH {
+            let frame_char = CONSOLE_PALETTE[&frame[j + i * GameBoy::SCREEN_WIDTH]];
             s.push(frame_char);
             if doubled {
                 s.push(frame_char);
@@ -76,68 +81,35 @@ fn frame_string(frame: &[GbColor; PPU::SCREEN_PIXELS as usize], doubled: bool) -
     s
 }
 
-fn tile_map_string(tile_map: &[Tile; VRAM::VRAM_TILES_PER_MAP as usize], palette: GbPalette, doubled: bool) -> String {
-    let mut vec: Vec<String> = Vec::with_capacity(256);
-    for i in 0..32_usize {
-        let colored_tile_line: Vec<[GbColor; 64]> = tile_map[(i*32)..(i*32+32)]
-            .iter()
-            .map(|t| *t.colored_tile(palette).dots()).collect();
-        for j in 0..8_usize {
-            let mut str_tile_line = "".to_string();
-            for k in 0..32_usize {
-                let colored_tile = &colored_tile_line[k];
-                for l in j*8..(j*8+8) {
-                    str_tile_line.push(CONSOLE_PALETTE[&colored_tile[l]]);
-                    if doubled {
-                        str_tile_line.push(CONSOLE_PALETTE[&colored_tile[l]]);
-                    }
-                }
-            }
-            vec.push(str_tile_line);
-        }
+fn main() {
+    let args = Args::parse();
+    if let Some(Command::Disasm { rom, output }) = &args.command {
+        disassemble_rom(rom, output);
+        return;
     }
-    vec.join("\n")
-}
+    let rom = args.rom.clone().expect("ROM is required without a subcommand");
 
-fn oam_table_string(oam_table: OamTable, vram: &VRAM, obp0: GbPalette, obp1: GbPalette, doubled: bool) -> String {
-    let mut vec: Vec<String> = Vec::with_capacity(8 * 5);
-    let oam_per_line: usize = 8;
-    let oam_per_column: usize = 5;
-    for i in 0..oam_per_column {
-        let line_oam = &oam_table[(i*oam_per_line)..(i*oam_per_line+oam_per_line)];
-        let line_oam_dots: Vec<[GbColor; 64]> = line_oam
-            .iter()
-            .map(|oam| *oam.colored_tile(vram, obp0, obp1).dots())
-            .collect::<Vec<[GbColor; 64]>>();
-        for oam_row in 0..Tile::TILE_HEIGHT {
-            let mut str_tile_line = "".to_string();
-            for oam_dots in line_oam_dots.iter() {
-                for dot_idx in (oam_row *Tile::TILE_WIDTH)..(oam_row *Tile::TILE_WIDTH+Tile::TILE_WIDTH) {
-                    let dot = &oam_dots[dot_idx as usize];
-                    str_tile_line.push(CONSOLE_PALETTE[&dot]);
-                    if doubled {
-                        str_tile_line.push(CONSOLE_PALETTE[&dot]);
-                    }
-                }
-            }
-            vec.push(str_tile_line);
+    let mut gb = match GameBoy::new(args.bios.clone()) {
+        Ok(gb) => gb,
+        Err(err) => {
+            println!("Unable to load BIOS \"{}\": {}", args.bios.as_deref().unwrap_or_default(), err);
+            return;
         }
+    };
+    if let Err(err) = gb.insert_cartridge(&rom) {
+        println!("Unable to load ROM \"{}\": {}", rom, err);
+        return;
     }
-    vec.join("\n")
-}
-
-fn main() {
-    let args = Args::parse();
-
-    let mut gb = GB::GB::new(Option::from(args.bios.clone()));
-    gb.insert_cartridge(&args.rom);
     println!("{}", gb.cartridge().as_ref().unwrap());
+    if !gb.is_booting() {
+        // Header checksum affects post-boot flags
+        gb.set_use_boot(false);
+    }
 
     let mut ended = false;
     let mut i: u16 = 0;
     let mut cb = false;
 
-    gb.set_use_boot(false);
     let mut file_result = OpenOptions::new()
         .write(true)
         .truncate(true)
@@ -148,11 +120,12 @@ fn main() {
     // ------------------------------------------------------------------------
 
     // Frame time
-    let frame_duration = Duration::from_millis(33);
-    let cycle_duration = Duration::from_nanos(238);
     let mut cycles: u64 = 0;
     let mut time = Instant::now();
 
+    // Snapshots to step back frame by frame
+    let mut rewind = RewindBuffer::default();
+
     // Input TX/RX Channels
     let (tx, rx) = mpsc::channel();
     thread::spawn(move || {
@@ -167,43 +140,28 @@ fn main() {
 
     // Running in loop Game Boy execution
     'running: loop {
-        let start = Instant::now();
+        if gb.is_vblank_edge() {
+            rewind.on_frame(&gb);
 
-        // if (cycles % (GB::CYCLES_PER_FRAME)) == 0  {
-        if gb.ppu().mmio.ppu_mode() == PpuMode::VBlank && (gb.ppu().mmio.prev_ppu_mode() != gb.ppu().mmio.ppu_mode()) {
             // Frame
             let frame = gb.frame();
             let frame_str = frame_string(frame, true);
             println!("\x1B[2J\x1B[H{}", frame_str);
 
-            // OAM Memory
-            // let ppu = gb.ppu();
-            // let vram = ppu.mmio.vram();
-            // let obp0 = ppu.mmio.obp0_view();
-            // let obp1 = ppu.mmio.obp1_view();
-            // let oam_table = gb.oam_memory().oam_table();
-            // let oam_table_str = oam_table_string(oam_table, vram, obp0, obp1, true);
-            // println!("\x1B[2J\x1B[H{}", oam_table_str);
-
-            // BG Memory
-            // let palette = GbPalette::new(
-            //     GbColor::White,
-            //     GbColor::LightGray,
-            //     GbColor::DarkGray,
-            //     GbColor::Black
-            // );
-            // let map_str = tile_map_string(&gb.vram().tile_map(TileMapArea::MapBlock0, TileDataArea::DataBlock12), palette, true);
-            // println!("\x1B[2J\x1B[H{}", map_str);
-
             println!("S/f: {:?}", (Instant::now() - time).as_secs_f64());
             println!("C/s: {:?}", cycles as f64/(Instant::now() - time).as_secs_f64());
 
-            let joypad = gb.joypad();
-            println!("{}", joypad);
-            println!("{}", joypad.symbolic_display());
+            println!("{}", gb.joypad_view().symbolic_display());
+            if let Some(lockup) = gb.cpu_lockup() {
+                println!("{}", lockup);
+            }
             stdout().flush().unwrap();
             time = Instant::now();
             cycles = 0;
+
+            if let Err(err) = gb.autosave() {
+                println!("Autosave of cartridge RAM failed: {}", err);
+            }
         }
 
         // Polling input
@@ -211,6 +169,12 @@ fn main() {
             if key_event.kind == KeyEventKind::Press && key_event.code == KeyCode::Esc {
                 break;
             }
+            if key_event.kind == KeyEventKind::Press && key_event.code == KeyCode::Char('r') {
+                if let Err(err) = rewind.rewind(&mut gb) {
+                    println!("Unable to rewind: {}", err);
+                }
+                continue;
+            }
             manage_gb_input_event(&mut gb, key_event);
         }
 
@@ -225,38 +189,24 @@ fn main() {
 
         gb.tick();
         cycles += 1;
-        let elapsed = start.elapsed();
-        // if elapsed < cycle_duration {
-        //     thread::sleep(cycle_duration - elapsed);
-        // }
     }
 
-    {
-        // println!("{}\n\n", gb.ppu.get_frame_string(true));
-        // let map = gb.ppu.get_bg_map();
-        // for i in 0..16 {
-        //     for j in 0..16 {
-        //         print!("{}", gb.ppu.get_tile(i * 16 + j, false).get_printable_id_map(true));
-        //     }
-        //     println!()
-        // }
-        // println!("{}", gb.ppu.get_tile(0, true));
-        // println!("{}", gb.ppu.get_tile_map(0));
-        // let lines_to_print = 10;
-        // println!("{}\n", &(gb.ppu.get_bg_map())[..256*2*3*lines_to_print+lines_to_print]); // x3 because characters used in UTF-8 occupy 3 bytes + 1 byte per line for carriage return
-        // println!("SCX {} | SCY {}", gb.ppu.get_scx(), gb.ppu.get_scy())
+    if let Err(err) = gb.eject_cartridge() {
+        println!("Unable to save cartridge RAM: {}", err);
     }
 
-    if fs::metadata(&args.rom).is_ok() {
-        println!("La ROM \"{}\" esiste!", args.rom);
+    if fs::metadata(&rom).is_ok() {
+        println!("La ROM \"{}\" esiste!", rom);
     } else {
         println!("La ROM non esiste.");
     }
 
-    if fs::metadata(&args.bios).is_ok() {
-        println!("Il BIOS \"{}\" esiste!", &args.bios);
-    } else {
-        println!("Il file non esiste.");
+    if let Some(bios) = &args.bios {
+        if fs::metadata(bios).is_ok() {
+            println!("Il BIOS \"{}\" esiste!", bios);
+        } else {
+            println!("Il file non esiste.");
+        }
     }
 
     if let Ok(current_dir) = env::current_dir() {
@@ -266,123 +216,62 @@ fn main() {
     }
 }
 
-fn log(log_channel: &mut File, gb: &GB::GB, log_line: u64) {
-    let mut i: u32 = 0;
-    let mut cb = false;
-    let mut cycles = 0;
-    let mut debug_i = 142268;
-
-    if gb.cpu().instruction_m_cycle() == 0 && gb.cpu().instruction_t_cycle() == 0 {
-        let mut s = "".to_string();
-        let mut pc = gb.cpu().registers().get_pc() - 1;
-        let addr = pc;
-        let mut read_bytes: usize = 0;
-        let mut opcode = gb.cpu().opcode();
-        let mut s_ins = "UNKNOWN".to_string();
-        let mut opt_ins = gb.cpu().instruction();
-
-        pc += 1;
-        read_bytes += 1;
-
-        match gb.cpu().maneging_interrupt() {
-            Some(interrupt_type) => {
-                match interrupt_type {
-                    InterruptType::Joypad => { s_ins = "JoyPad int.".to_string(); }
-                    InterruptType::Serial => { s_ins = "Serial int.".to_string(); }
-                    InterruptType::Timer => { s_ins = "Timer int.".to_string(); }
-                    InterruptType::LCD => { s_ins = "LCD int.".to_string(); }
-                    InterruptType::VBlank => { s_ins = "VBlank int.".to_string(); }
-                }
-            }
-            _ => {
-                match opt_ins {
-                    None => {
-                        s += format!("{:02X} ", opcode).as_str();
-                    }
-                    Some(mut ins) => {
-                        s += format!("{:02X} ", opcode).as_str();
-                        cb = opcode == 0xCB;
-                        if cb {
-                            opcode = gb.read(Address(pc));
-                            ins = CPU::decode(opcode, cb).unwrap();
-                            s += format!("{:02X} ", opcode).as_str();
-                            s_ins = ins.name.to_string();
-                            pc += 1;
-                            read_bytes += 1;
-                        }
-                        let mut shift: u16 = 0;
-                        let mut immediate_val: u16 = 0;
-                        for j in read_bytes as u8..ins.size {
-                            let val = gb.read(Address(pc)) as u16;
-                            s += format!("{:02X} ", val).as_str();
-                            immediate_val |= val << shift;
-                            pc += 1;
-                            read_bytes += 1;
-                            shift += 8;
-                        }
-
-                        s_ins = ins.name.to_string();
-                        match ins.size {
-                            2 => {
-                                let fmt = format!("${:02X}", immediate_val);
-                                let new_s_ins = s_ins.replace("imm8", fmt.as_str());
-                                s_ins = new_s_ins;
-                                let fmt = format!("{}", immediate_val as i8);
-                                let new_s_ins = s_ins.replace("e8", fmt.as_str());
-                                s_ins = new_s_ins;
-                            }
-                            3 => {
-                                let fmt = format!("${:04X}", immediate_val);
-                                let new_s_ins = s_ins.replace("imm16", fmt.as_str());
-                                s_ins = new_s_ins;
-                            }
-                            _ => {}
-                        }
-
-                        // Extra data
-                        if !cb {
-                            match ins.opcode {
-                                // 0xD9  => {
-                                //     let b1 = gb.read(Address(gb.cpu().registers.get_sp() + 1));
-                                //     let b2 = gb.read(Address(gb.cpu().registers.get_sp() + 2));
-                                //     s_ins += format!(" (${:02X}{:02X})", b2, b1).as_str();
-                                // }
-                                _ => {}
-                            }
-                        }
-                    }
-                }
-            }
-        }
-
-        for j in read_bytes as u8..3 {
-            s += "   ";
-            read_bytes += 1;
-        }
-
-        let cartridge = gb.cartridge().unwrap();
-        {
-            let formatted = format!("| {:04} |  {:#06X} |  {} |  {}{}|  {} {{}} |  RxM B: {}/{}  |  {{AF: {:04X}, BC: {:04X}, DE: {:04X}, HL: {:04X}, SP: {:04X}}} | IE: {:02X} | IF: {:02X} | IME: {} | STAT: {:02X} | LCDC: {:02X}",
-                                    log_line, addr, s, s_ins, " ".repeat(16 - s_ins.len()), gb.ppu().ppu,
-                                    // mem_registers,
-                                    cartridge.rom_bank(),
-                                    cartridge.ram_bank(),
-                                    gb.cpu().registers().get_af(), gb.cpu().registers().get_bc(),
-                                    gb.cpu().registers().get_de(), gb.cpu().registers().get_hl(),
-                                    gb.cpu().registers().get_sp(),
-                                    gb.read(addresses::cpu::INTERRUPT_ENABLED_REGISTER),
-                                    gb.read(addresses::cpu::INTERRUPT_FLAGS_REGISTER),
-                                    if gb.cpu().ime() { "T" } else { "F" },
-                                    gb.read(addresses::ppu::STAT_REGISTER),
-                                    gb.read(addresses::ppu::LCDC_REGISTER),
-            );
-            writeln!(log_channel, "{}", formatted).expect("TODO: panic message");
+fn disassemble_rom(rom: &str, output: &str) {
+    let data = match fs::read(rom) {
+        Ok(data) => data,
+        Err(err) => {
+            println!("Unable to load ROM \"{}\": {}", rom, err);
+            return;
         }
+    };
+    let disassembler = RomDisassembler::new(&data);
+    match disassembler.write_banks(Path::new(output)) {
+        Ok(files) => println!("Disassembled {} banks into \"{}\"", files.len(), output),
+        Err(err) => println!("Unable to write disassembly into \"{}\": {}", output, err),
     }
+}
 
+fn log(log_channel: &mut File, gb: &GameBoy, log_line: u64) {
+    if !gb.is_instruction_start() {
+        return;
+    }
+    let registers = gb.cpu_registers();
+    let addr = registers.pc;
+    let mut s = "".to_string();
+    let s_ins = match gb.dispatching_interrupt() {
+        Some(InterruptType::Joypad) => "JoyPad int.".to_string(),
+        Some(InterruptType::Serial) => "Serial int.".to_string(),
+        Some(InterruptType::Timer) => "Timer int.".to_string(),
+        Some(InterruptType::LCD) => "LCD int.".to_string(),
+        Some(InterruptType::VBlank) => "VBlank int.".to_string(),
+        None => {
+            let instruction = gb.disassemble(Address(addr));
+            for byte in &instruction.bytes {
+                s += format!("{:02X} ", byte).as_str();
+            }
+            instruction.to_string()
+        }
+    };
+    s += &"   ".repeat(3usize.saturating_sub(s.len() / 3));
+
+    let cartridge = gb.cartridge().unwrap();
+    let formatted = format!("| {:04} |  {:#06X} |  {} |  {}{}|  RxM B: {}/{}  |  {{AF: {:04X}, BC: {:04X}, DE: {:04X}, HL: {:04X}, SP: {:04X}}} | IE: {:02X} | IF: {:02X} | IME: {} | STAT: {:02X} | LCDC: {:02X}",
+                            log_line, addr, s, s_ins, " ".repeat(16usize.saturating_sub(s_ins.len())),
+                            cartridge.rom_bank(),
+                            cartridge.ram_bank(),
+                            registers.af(), registers.bc(),
+                            registers.de(), registers.hl(),
+                            registers.sp,
+                            gb.read(IE_ADDRESS),
+                            gb.read(IF_ADDRESS),
+                            if registers.ime { "T" } else { "F" },
+                            gb.read(STAT_ADDRESS),
+                            gb.read(LCDC_ADDRESS),
+    );
+    writeln!(log_channel, "{}", formatted).expect("TODO: panic message");
 }
 
-fn manage_gb_input_event(gb: &mut GB::GB, key_event: KeyEvent) {
+fn manage_gb_input_event(gb: &mut GameBoy, key_event: KeyEvent) {
     match key_event.kind {
         KeyEventKind::Press => {
             match key_event.code {
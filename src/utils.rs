@@ -3,6 +3,7 @@ use std::ops::{BitAnd, Shl};
 #[macro_use]
 pub mod macros;
 
+#[allow(dead_code)]
 pub fn falling_edge<T, B>(old_value: T, new_value: T, bit: B) -> bool
 where
     T: Shl<B, Output = T> + From<u8> + std::ops::BitAnd<Output = T> + PartialEq + Copy,